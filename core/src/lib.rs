@@ -0,0 +1,544 @@
+//! Parser of the `LiME` memory dump format that does not depend on `std`.
+//!
+//! The dump is read from a byte slice, so that the parser can run where there are no files,
+//! like unikernels or kernel-mode components. Only `core` and `alloc` are used unless the
+//! `std` feature is enabled, which implements `std::error::Error` for [`ParseError`].
+//!
+//! source: [LiME Memory Range Header Version 1 Specification](https://github.com/504ensicsLabs/LiME/blob/master/doc/README.md#Spec)
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Size in bytes of a `LiME` header
+pub const HEADER_SIZE: usize = 32;
+
+/// Magic number identifying a `LiME` header
+pub const MAGIC: u32 = 0x4C69_4D45;
+
+/// Header version described by the specification
+pub const VERSION: u32 = 1;
+
+/// Byte order of the header fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    /// Little-endian headers, written by little-endian targets
+    Little,
+    /// Big-endian headers, written by big-endian targets
+    Big,
+}
+
+impl Endian {
+    /// Byte order of the header starting `buf`, from its magic number.
+    ///
+    /// Returns `None` if `buf` does not start with a `LiME` magic number.
+    pub fn detect(buf: &[u8]) -> Option<Self> {
+        match buf.get(0..4)? {
+            magic if magic == MAGIC.to_le_bytes() => Some(Self::Little),
+            magic if magic == MAGIC.to_be_bytes() => Some(Self::Big),
+            _ => None,
+        }
+    }
+
+    fn u32(self, bytes: [u8; 4]) -> u32 {
+        match self {
+            Self::Little => u32::from_le_bytes(bytes),
+            Self::Big => u32::from_be_bytes(bytes),
+        }
+    }
+
+    fn u64(self, bytes: [u8; 8]) -> u64 {
+        match self {
+            Self::Little => u64::from_le_bytes(bytes),
+            Self::Big => u64::from_be_bytes(bytes),
+        }
+    }
+}
+
+/// Why a `LiME` dump could not be parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// Fewer bytes than a header are left
+    TruncatedHeader,
+    /// The header does not start with the `LiME` magic number
+    BadMagic([u8; 4]),
+    /// The header version is not [`VERSION`]
+    UnsupportedVersion(u32),
+    /// The reserved fields of the header are not zero
+    NonZeroReserved([u8; 8]),
+    /// The end address is lower than the start address, or the size of the range does not
+    /// fit in a `u64`
+    ImpossibleRange {
+        /// Starting address of the range
+        s_addr: u64,
+        /// Ending address of the range, inclusive
+        e_addr: u64,
+    },
+    /// The dump ends before the data of the range
+    TruncatedRange {
+        /// Starting address of the range
+        s_addr: u64,
+        /// Ending address of the range, inclusive
+        e_addr: u64,
+    },
+    /// Two ranges of the dump overlap
+    OverlappingRanges {
+        /// Starting address of the range coming later in the file
+        s_addr: u64,
+        /// Ending address of the range coming later in the file, inclusive
+        e_addr: u64,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TruncatedHeader => write!(f, "truncated LiME header"),
+            Self::BadMagic(magic) => write!(f, "bad LiME magic number: {:02x?}", magic),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported LiME version: {}", version)
+            }
+            Self::NonZeroReserved(reserved) => {
+                write!(
+                    f,
+                    "unsupported LiME reserved fields values: {:02x?}",
+                    reserved
+                )
+            }
+            Self::ImpossibleRange { s_addr, e_addr } => write!(
+                f,
+                "LiME range {:#x}-{:#x} declares an impossible size",
+                s_addr, e_addr
+            ),
+            Self::TruncatedRange { s_addr, e_addr } => write!(
+                f,
+                "LiME range {:#x}-{:#x} is cut off by the end of the dump",
+                s_addr, e_addr
+            ),
+            Self::OverlappingRanges { s_addr, e_addr } => write!(
+                f,
+                "LiME range {:#x}-{:#x} overlaps a previous range",
+                s_addr, e_addr
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Header preceding every range of a `LiME` dump
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeHeader {
+    /// Header version number
+    pub version: u32,
+    /// Starting address of physical RAM range
+    pub s_addr: u64,
+    /// Ending address of physical RAM range, inclusive
+    pub e_addr: u64,
+    /// Currently all zeros
+    pub reserved: [u8; 8],
+}
+
+impl RangeHeader {
+    /// Create a version 1 header describing the physical range `[s_addr, e_addr]`.
+    pub const fn new(s_addr: u64, e_addr: u64) -> Self {
+        Self {
+            version: VERSION,
+            s_addr,
+            e_addr,
+            reserved: [0; 8],
+        }
+    }
+
+    /// Parse the header starting `buf`, whose fields are in `endian` byte order.
+    ///
+    /// Only the magic number is checked, see [`Self::check`] for the other fields.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if `buf` is shorter than a header or the magic number does not match
+    ///
+    pub fn parse(buf: &[u8], endian: Endian) -> Result<Self, ParseError> {
+        let buf: &[u8; HEADER_SIZE] = buf
+            .get(..HEADER_SIZE)
+            .and_then(|buf| buf.try_into().ok())
+            .ok_or(ParseError::TruncatedHeader)?;
+        let field = |start: usize| -> [u8; 8] { buf[start..start + 8].try_into().unwrap() };
+
+        let magic = buf[0..4].try_into().unwrap();
+        if endian.u32(magic) != MAGIC {
+            return Err(ParseError::BadMagic(magic));
+        }
+        Ok(Self {
+            version: endian.u32(buf[4..8].try_into().unwrap()),
+            s_addr: endian.u64(field(8)),
+            e_addr: endian.u64(field(16)),
+            reserved: field(24),
+        })
+    }
+
+    /// Check the fields the specification fixes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the range is impossible, or if the version or the reserved fields
+    /// have unsupported values
+    ///
+    pub fn check(&self) -> Result<(), ParseError> {
+        if self.size().is_none() {
+            return Err(ParseError::ImpossibleRange {
+                s_addr: self.s_addr,
+                e_addr: self.e_addr,
+            });
+        }
+        if self.version != VERSION {
+            return Err(ParseError::UnsupportedVersion(self.version));
+        }
+        if self.reserved != [0; 8] {
+            return Err(ParseError::NonZeroReserved(self.reserved));
+        }
+        Ok(())
+    }
+
+    /// Serialize the header in its on-disk representation, in `endian` byte order.
+    pub fn to_bytes(&self, endian: Endian) -> [u8; HEADER_SIZE] {
+        let mut buff = [0u8; HEADER_SIZE];
+        let (magic, version, s_addr, e_addr) = match endian {
+            Endian::Little => (
+                MAGIC.to_le_bytes(),
+                self.version.to_le_bytes(),
+                self.s_addr.to_le_bytes(),
+                self.e_addr.to_le_bytes(),
+            ),
+            Endian::Big => (
+                MAGIC.to_be_bytes(),
+                self.version.to_be_bytes(),
+                self.s_addr.to_be_bytes(),
+                self.e_addr.to_be_bytes(),
+            ),
+        };
+        buff[0..4].copy_from_slice(&magic);
+        buff[4..8].copy_from_slice(&version);
+        buff[8..16].copy_from_slice(&s_addr);
+        buff[16..24].copy_from_slice(&e_addr);
+        buff[24..32].copy_from_slice(&self.reserved);
+        buff
+    }
+
+    /// Size in bytes of the memory represented by this header
+    ///
+    /// Returns `None` if the end address is lower than the start address or if the range
+    /// covers the whole 64-bit address space, whose size does not fit in a `u64`.
+    pub const fn size(&self) -> Option<u64> {
+        match self.e_addr.checked_sub(self.s_addr) {
+            Some(diff) => diff.checked_add(1),
+            None => None,
+        }
+    }
+}
+
+/// Physical range held by a `LiME` dump
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    /// Starting address of the range
+    pub s_addr: u64,
+    /// Ending address of the range, inclusive
+    pub e_addr: u64,
+    /// Offset of the data of the range in the dump
+    pub offset: u64,
+}
+
+impl Range {
+    /// Size in bytes of the range
+    pub const fn size(&self) -> u64 {
+        self.e_addr - self.s_addr + 1
+    }
+
+    /// Data of the range, `dump` being the dump it was parsed from.
+    pub fn data<'a>(&self, dump: &'a [u8]) -> &'a [u8] {
+        &dump[self.offset as usize..(self.offset + self.size()) as usize]
+    }
+}
+
+/// Iterator over the ranges of a `LiME` dump held in a byte slice, see [`ranges`]
+///
+/// The headers are checked strictly, the iteration stopping after the first error.
+#[derive(Debug, Clone)]
+pub struct Ranges<'a> {
+    dump: &'a [u8],
+    offset: usize,
+    endian: Option<Endian>,
+    failed: bool,
+}
+
+impl<'a> Ranges<'a> {
+    /// Iterate over the ranges of `dump`, whose headers are in `endian` byte order or, if
+    /// `None`, in the byte order of the magic number of the first header.
+    pub const fn new(dump: &'a [u8], endian: Option<Endian>) -> Self {
+        Self {
+            dump,
+            offset: 0,
+            endian,
+            failed: false,
+        }
+    }
+
+    /// Offset of the next header, or of the header that failed to parse.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Byte order of the headers, `None` until it is detected from the first header.
+    pub const fn endian(&self) -> Option<Endian> {
+        self.endian
+    }
+
+    fn parse_next(&mut self) -> Result<Range, ParseError> {
+        let buf = &self.dump[self.offset..];
+        let endian = match self.endian {
+            Some(endian) => endian,
+            None => *self
+                .endian
+                .insert(Endian::detect(buf).unwrap_or(Endian::Little)),
+        };
+        let header = RangeHeader::parse(buf, endian)?;
+        header.check()?;
+
+        let offset = self.offset + HEADER_SIZE;
+        let truncated = ParseError::TruncatedRange {
+            s_addr: header.s_addr,
+            e_addr: header.e_addr,
+        };
+        let end = usize::try_from(header.e_addr - header.s_addr)
+            .ok()
+            .and_then(|len| offset.checked_add(len))
+            .filter(|last| *last < self.dump.len())
+            .ok_or(truncated)?;
+        self.offset = end + 1;
+        Ok(Range {
+            s_addr: header.s_addr,
+            e_addr: header.e_addr,
+            offset: offset as u64,
+        })
+    }
+}
+
+impl Iterator for Ranges<'_> {
+    type Item = Result<Range, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.offset >= self.dump.len() {
+            return None;
+        }
+        let next = self.parse_next();
+        self.failed = next.is_err();
+        Some(next)
+    }
+}
+
+/// Iterate over the ranges of the `LiME` dump `dump`, detecting the byte order of the
+/// headers.
+pub const fn ranges(dump: &[u8]) -> Ranges<'_> {
+    Ranges::new(dump, None)
+}
+
+/// Parse every range of the `LiME` dump `dump`, in file order.
+///
+/// # Errors
+///
+/// Returns `Err` if a header is corrupt or a range is cut off by the end of the dump
+///
+pub fn parse_ranges(dump: &[u8]) -> Result<Vec<Range>, ParseError> {
+    ranges(dump).collect()
+}
+
+/// Physical memory of a `LiME` dump held in a byte slice
+///
+/// Meant for the environments the memflow connector does not build for, like
+/// `wasm32-wasi` viewers handed a dump as a byte stream.
+#[derive(Debug, Clone)]
+pub struct LimeImage<'a> {
+    dump: &'a [u8],
+    /// Ranges sorted by physical address
+    ranges: Vec<Range>,
+}
+
+impl<'a> LimeImage<'a> {
+    /// Parse the ranges of `dump`, detecting the byte order of the headers.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if a header is corrupt, a range is cut off by the end of the dump or two
+    /// ranges overlap
+    ///
+    pub fn parse(dump: &'a [u8]) -> Result<Self, ParseError> {
+        let mut ranges = parse_ranges(dump)?;
+        ranges.sort_by_key(|range| range.s_addr);
+        if let Some(pair) = ranges
+            .windows(2)
+            .find(|pair| pair[1].s_addr <= pair[0].e_addr)
+        {
+            let later = pair.iter().max_by_key(|range| range.offset).unwrap();
+            return Err(ParseError::OverlappingRanges {
+                s_addr: later.s_addr,
+                e_addr: later.e_addr,
+            });
+        }
+        Ok(Self { dump, ranges })
+    }
+
+    /// Ranges of the dump, sorted by physical address.
+    pub fn ranges(&self) -> &[Range] {
+        &self.ranges
+    }
+
+    /// Highest physical address held by the dump, `None` if the dump is empty.
+    pub fn max_address(&self) -> Option<u64> {
+        self.ranges.last().map(|range| range.e_addr)
+    }
+
+    /// Read the physical memory starting at `addr` into `buf`, stopping at the first byte
+    /// the dump does not hold.
+    ///
+    /// Returns the number of bytes read, `buf.len()` if the whole buffer is backed by the
+    /// dump.
+    pub fn read(&self, addr: u64, buf: &mut [u8]) -> usize {
+        let mut read = 0;
+        let index = self.ranges.partition_point(|range| range.e_addr < addr);
+        for range in &self.ranges[index..] {
+            let Some(pos) = addr.checked_add(read as u64) else {
+                break;
+            };
+            if read == buf.len() || range.s_addr > pos {
+                break;
+            }
+            let data = &range.data(self.dump)[(pos - range.s_addr) as usize..];
+            let len = data.len().min(buf.len() - read);
+            buf[read..read + len].copy_from_slice(&data[..len]);
+            read += len;
+        }
+        read
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn dump(endian: Endian, ranges: &[(u64, &[u8])]) -> Vec<u8> {
+        let mut dump = Vec::new();
+        for (s_addr, data) in ranges {
+            let header = RangeHeader::new(*s_addr, s_addr + data.len() as u64 - 1);
+            dump.extend_from_slice(&header.to_bytes(endian));
+            dump.extend_from_slice(data);
+        }
+        dump
+    }
+
+    #[test]
+    fn ranges_are_parsed_in_both_byte_orders() {
+        for endian in [Endian::Little, Endian::Big] {
+            let dump = dump(endian, &[(0x1000, &[0x11; 0x10]), (0x4000, &[0x44; 0x20])]);
+            let ranges = parse_ranges(&dump).unwrap();
+
+            assert_eq!(
+                ranges,
+                vec![
+                    Range {
+                        s_addr: 0x1000,
+                        e_addr: 0x100f,
+                        offset: 0x20,
+                    },
+                    Range {
+                        s_addr: 0x4000,
+                        e_addr: 0x401f,
+                        offset: 0x50,
+                    },
+                ]
+            );
+            assert_eq!(ranges[1].data(&dump), [0x44; 0x20]);
+        }
+    }
+
+    #[test]
+    fn corrupt_dumps_are_refused() {
+        let whole = dump(Endian::Little, &[(0x1000, &[0x11; 0x10])]);
+        let mut inverted = whole.clone();
+        inverted[16..24].copy_from_slice(&0x0fffu64.to_le_bytes());
+        let mut bad_magic = whole.clone();
+        bad_magic[0] = 0;
+
+        assert_eq!(
+            parse_ranges(&whole[..0x28]),
+            Err(ParseError::TruncatedRange {
+                s_addr: 0x1000,
+                e_addr: 0x100f
+            })
+        );
+        assert_eq!(
+            parse_ranges(&whole[..0x10]),
+            Err(ParseError::TruncatedHeader)
+        );
+        assert_eq!(
+            parse_ranges(&inverted),
+            Err(ParseError::ImpossibleRange {
+                s_addr: 0x1000,
+                e_addr: 0x0fff
+            })
+        );
+        assert!(matches!(
+            parse_ranges(&bad_magic),
+            Err(ParseError::BadMagic(_))
+        ));
+    }
+
+    #[test]
+    fn image_reads_stop_at_gaps() {
+        let bytes = dump(
+            Endian::Little,
+            &[
+                (0x2000, &[0x22; 0x10]),
+                (0x1000, &[0x11; 0x1000]),
+                (0x3000, &[0x33; 0x10]),
+            ],
+        );
+        let image = LimeImage::parse(&bytes).unwrap();
+        let mut buff = [0u8; 0x20];
+
+        assert_eq!(image.max_address(), Some(0x300f));
+        assert_eq!(image.read(0x1ff0, &mut buff), 0x20);
+        assert_eq!(buff[..0x10], [0x11; 0x10]);
+        assert_eq!(buff[0x10..], [0x22; 0x10]);
+        assert_eq!(image.read(0x2008, &mut buff), 0x8);
+        assert_eq!(image.read(0x2800, &mut buff), 0);
+
+        let overlapping = dump(
+            Endian::Little,
+            &[(0x1000, &[0x11; 0x10]), (0x1008, &[0x22; 0x10])],
+        );
+        assert_eq!(
+            LimeImage::parse(&overlapping).unwrap_err(),
+            ParseError::OverlappingRanges {
+                s_addr: 0x1008,
+                e_addr: 0x1017
+            }
+        );
+    }
+
+    #[test]
+    fn iteration_stops_after_an_error() {
+        let mut dump = dump(Endian::Little, &[(0x1000, &[0x11; 0x10])]);
+        dump.extend_from_slice(&[0; HEADER_SIZE]);
+        let mut ranges = ranges(&dump);
+
+        assert!(ranges.next().unwrap().is_ok());
+        assert!(ranges.next().unwrap().is_err());
+        assert_eq!(ranges.offset(), 0x30);
+        assert!(ranges.next().is_none());
+    }
+}
@@ -0,0 +1,72 @@
+#ifndef MEMFLOW_LIME_H
+#define MEMFLOW_LIME_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The call succeeded
+#define LIME_OK 0
+
+// A pointer argument is null or a string is not valid UTF-8
+#define LIME_ERR_INVALID_ARGUMENT -1
+
+// Part of the requested memory is not held by the dump, or the dump could not be read
+#define LIME_ERR_READ -2
+
+// Opaque handle of a `LiME` dump opened by [`lime_open`]
+typedef struct LimeHandle LimeHandle;
+
+// Physical range held by a `LiME` dump
+typedef struct LimeRange {
+  // Starting address of the range
+  uint64_t s_addr;
+  // Ending address of the range, inclusive
+  uint64_t e_addr;
+} LimeRange;
+
+// Open the `LiME` file at `path`.
+//
+// `args` holds the connector arguments as a `key=value,...` string, it may be null.
+// Returns null if the file can not be opened or parsed, the cause being logged.
+//
+// # Safety
+//
+// `path` must be a nul-terminated string, `args` a nul-terminated string or null.
+struct LimeHandle *lime_open(const char *path, const char *args);
+
+// Read `len` bytes of physical memory at `addr` into `buf`.
+//
+// Returns [`LIME_OK`] on success, [`LIME_ERR_READ`] if part of the memory is not held by
+// the dump and [`LIME_ERR_INVALID_ARGUMENT`] if a pointer is null.
+//
+// # Safety
+//
+// `handle` must come from [`lime_open`] and not be closed, `buf` must be valid for writes
+// of `len` bytes.
+int32_t lime_read_phys(struct LimeHandle *handle, uint64_t addr, uint8_t *buf, size_t len);
+
+// Copy the physical ranges held by the dump, sorted by address, into `ranges`.
+//
+// At most `capacity` ranges are copied, `ranges` may be null if `capacity` is zero.
+// Returns the number of ranges of the dump, which may be more than `capacity`, so that the
+// call can be repeated with a large enough array.
+//
+// # Safety
+//
+// `handle` must come from [`lime_open`] and not be closed, `ranges` must be valid for
+// writes of `capacity` ranges.
+size_t lime_ranges(const struct LimeHandle *handle, struct LimeRange *ranges, size_t capacity);
+
+// Close a dump opened by [`lime_open`], null being ignored.
+//
+// # Safety
+//
+// `handle` must come from [`lime_open`] and not be closed already.
+void lime_close(struct LimeHandle *handle);
+
+#endif  /* MEMFLOW_LIME_H */
@@ -0,0 +1,201 @@
+use crate::writer::LimeWriter;
+
+use memflow::prelude::v1::*;
+
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Listing of the physical address space of the running Linux kernel
+pub const IOMEM_PATH: &str = "/proc/iomem";
+/// Devices exposing the physical memory of the running machine, in the order they are tried
+pub const MEMORY_DEVICES: [&str; 2] = ["/dev/crash", "/dev/mem"];
+/// Name of the ranges of `/proc/iomem` backed by RAM
+const SYSTEM_RAM: &str = "System RAM";
+
+/// Read the physical ranges backed by RAM of the `iomem` listing, the `/proc/iomem` of the
+/// running kernel being [`IOMEM_PATH`], as sorted pairs of their first and last address.
+///
+/// Only the top-level `System RAM` entries are kept, the ones nested in them (kernel code,
+/// crash kernel reservation...) being part of them.
+///
+/// # Errors
+///
+/// Returns `Err` if the listing can not be read, holds a malformed `System RAM` entry or
+/// hides its addresses, as the kernel does for readers lacking `CAP_SYS_ADMIN`
+///
+pub fn system_ram_ranges<P: AsRef<Path>>(iomem: P) -> Result<Vec<(u64, u64)>> {
+    let iomem = iomem.as_ref();
+    let listing = fs::read_to_string(iomem).map_err(|e| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+            "Unable to read {}: {}",
+            iomem.display(),
+            e
+        ))
+    })?;
+    parse_system_ram(&listing, iomem)
+}
+
+/// Parse the `System RAM` entries of the `/proc/iomem` listing `listing` read from `iomem`.
+fn parse_system_ram(listing: &str, iomem: &Path) -> Result<Vec<(u64, u64)>> {
+    let malformed = |line: &str| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+            "Entry '{}' of {} is malformed",
+            line,
+            iomem.display()
+        ))
+    };
+
+    let mut ranges = Vec::new();
+    for line in listing.lines() {
+        // Nested entries are indented.
+        if line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        let Some((range, name)) = line.split_once(" : ") else {
+            continue;
+        };
+        if name.trim() != SYSTEM_RAM {
+            continue;
+        }
+        let (start, end) = range.split_once('-').ok_or_else(|| malformed(line))?;
+        let start = u64::from_str_radix(start.trim(), 16).map_err(|_| malformed(line))?;
+        let end = u64::from_str_radix(end.trim(), 16).map_err(|_| malformed(line))?;
+        if end < start {
+            return Err(malformed(line));
+        }
+        ranges.push((start, end));
+    }
+
+    if !ranges.is_empty() && ranges.iter().all(|&range| range == (0, 0)) {
+        return Err(
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+                "The addresses of {} are hidden, acquiring memory requires root",
+                iomem.display()
+            )),
+        );
+    }
+    ranges.sort_unstable();
+    Ok(ranges)
+}
+
+/// Reader of the physical memory exposed by a device, from a physical address
+struct DeviceReader<'a, F: FnMut(u64)> {
+    device: &'a mut File,
+    path: &'a Path,
+    addr: u64,
+    progress: F,
+}
+
+impl<F: FnMut(u64)> Read for DeviceReader<'_, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self
+            .device
+            .seek(SeekFrom::Start(self.addr))
+            .and_then(|_| self.device.read(buf))
+            .inspect_err(|e| {
+                log::error!(
+                    "Unable to read {} at {:#x}: {}{}",
+                    self.path.display(),
+                    self.addr,
+                    e,
+                    match e.kind() {
+                        io::ErrorKind::PermissionDenied => {
+                            ", the kernel may restrict it with CONFIG_STRICT_DEVMEM"
+                        }
+                        _ => "",
+                    }
+                )
+            })?;
+        self.addr += read as u64;
+        (self.progress)(read as u64);
+        Ok(read)
+    }
+}
+
+impl<W: Write> LimeWriter<W> {
+    /// Write the physical ranges `ranges`, pairs of their first and last address, reading
+    /// them from `device`, a device like `/dev/mem` whose byte at offset `n` is the byte at
+    /// physical address `n`.
+    ///
+    /// `progress` is called with the number of bytes of memory copied so far and the total
+    /// number of bytes of the ranges.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the device can not be opened, a range can not be read from it or an
+    /// error occurred while writing
+    ///
+    pub fn write_device_ranges<P, F>(
+        &mut self,
+        device: P,
+        ranges: &[(u64, u64)],
+        mut progress: F,
+    ) -> Result<()>
+    where
+        P: AsRef<Path>,
+        F: FnMut(u64, u64),
+    {
+        let path = device.as_ref();
+        let mut device = File::open(path).map_err(|e| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+                "Unable to open {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let total = ranges.iter().map(|(s, e)| e - s + 1).sum::<u64>();
+        let mut done = 0;
+        for &(s_addr, e_addr) in ranges {
+            let mut reader = DeviceReader {
+                device: &mut device,
+                path,
+                addr: s_addr,
+                progress: |read| {
+                    done += read;
+                    progress(done, total);
+                },
+            };
+            self.write_range_from(s_addr, e_addr - s_addr + 1, &mut reader)?;
+        }
+        Ok(())
+    }
+
+    /// Acquire the physical memory of the running Linux machine, writing the `System RAM`
+    /// ranges of [`IOMEM_PATH`] read from the first of [`MEMORY_DEVICES`] that can be opened,
+    /// for when loading the `LiME` kernel module is not possible.
+    ///
+    /// `/dev/crash`, the driver of the `crash` utility on Red Hat kernels, reads all the RAM.
+    /// `/dev/mem` only does so on kernels built without `CONFIG_STRICT_DEVMEM`, reads past
+    /// the first megabyte failing otherwise. Both require root.
+    ///
+    /// Returns the path of the device read. `progress` is called like in
+    /// [`write_device_ranges`](Self::write_device_ranges).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the ranges can not be listed, no device can be opened, a range can not
+    /// be read or an error occurred while writing
+    ///
+    pub fn acquire_local<F: FnMut(u64, u64)>(&mut self, progress: F) -> Result<PathBuf> {
+        let ranges = system_ram_ranges(IOMEM_PATH)?;
+        let device = MEMORY_DEVICES
+            .iter()
+            .map(Path::new)
+            .find(|device| File::open(device).is_ok())
+            .ok_or_else(|| {
+                Error(ErrorOrigin::Connector, ErrorKind::NotSupported).log_error(format!(
+                    "None of {} can be opened, reading physical memory requires root and a \
+                    kernel exposing one of them",
+                    MEMORY_DEVICES.join(", ")
+                ))
+            })?;
+        log::info!(
+            "Acquiring {} ranges of physical memory from {}",
+            ranges.len(),
+            device.display()
+        );
+        self.write_device_ranges(device, &ranges, progress)?;
+        Ok(device.to_path_buf())
+    }
+}
@@ -0,0 +1,651 @@
+use crate::dumpfile::{DecodedStream, DumpFile};
+use crate::segment::LimeSegment;
+use crate::uri::percent_decode;
+
+use memflow::prelude::v1::*;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use zip::{CompressionMethod, ZipArchive};
+
+const AFF4: &str = "http://aff4.org/Schema#";
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// Chunk size of the image streams not stating one
+const DEFAULT_CHUNK_SIZE: u64 = 0x8000;
+/// Chunks per bevy of the image streams not stating it
+const DEFAULT_CHUNKS_IN_SEGMENT: u64 = 1024;
+/// Size of an entry of a map stream: mapped offset, length, target offset and target index
+const MAP_ENTRY_SIZE: usize = 28;
+/// Size of an entry of a bevy index: offset and length of a chunk
+const BEVY_INDEX_ENTRY_SIZE: usize = 12;
+
+/// Open the physical memory image of the AFF4 container at `path`.
+///
+/// The image is the first `aff4:Map` of the container, its ranges pointing into image
+/// streams made of compressed chunks grouped in bevies. A container with a single image
+/// stream and no map is mapped from address zero. The returned dump is the concatenation of
+/// the image streams the map points to, the file offsets of the returned segments being
+/// relative to it.
+///
+/// # Errors
+///
+/// Returns `Err` if the container can not be read, has no image, or uses a compression
+/// method that is not supported
+///
+pub(crate) fn open_image(path: &Path) -> Result<(DumpFile, Vec<LimeSegment>)> {
+    let mut volume = Volume::open(path)?;
+    let graph = Graph::parse(&volume.read_member("information.turtle")?);
+
+    let maps = graph.subjects_of_type(&format!("{}Map", AFF4));
+    let Some(map) = maps.first() else {
+        let streams = graph.subjects_of_type(&format!("{}ImageStream", AFF4));
+        let [stream] = streams.as_slice() else {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+                .log_error("AFF4 container has no map and not exactly one image stream"));
+        };
+        let stream = ImageStream::open(&mut volume, &graph, stream)?;
+        let size = stream.len();
+        let segments = (size > 0)
+            .then_some(LimeSegment {
+                s_addr: 0,
+                e_addr: size - 1,
+                file_offset: 0,
+            })
+            .into_iter()
+            .collect();
+        return Ok((DumpFile::decoded(vec![Arc::new(stream)]), segments));
+    };
+    if maps.len() > 1 {
+        log::warn!("AFF4 container has {} maps, using {}", maps.len(), map);
+    }
+
+    let targets = volume.read_member(&format!("{}/idx", map))?;
+    let targets = String::from_utf8_lossy(&targets);
+    let entries = volume.read_member(&format!("{}/map", map))?;
+
+    let mut streams: Vec<Arc<dyn DecodedStream>> = Vec::new();
+    let mut starts = HashMap::new();
+    let mut start = 0;
+    let mut segments = Vec::new();
+    for entry in entries.chunks_exact(MAP_ENTRY_SIZE) {
+        let field = |at: usize| u64::from_le_bytes(entry[at..at + 8].try_into().unwrap());
+        let (s_addr, len, target_offset) = (field(0), field(8), field(16));
+        let target_id = u32::from_le_bytes(entry[24..28].try_into().unwrap());
+        let target = targets.lines().nth(target_id as usize).unwrap_or_default();
+        if len == 0 {
+            continue;
+        }
+        if target.starts_with(AFF4) {
+            // aff4:Zero, aff4:UnknownData and the like have no data to map.
+            log::debug!(
+                "Skipping AFF4 range {:#x}+{:#x} backed by {}",
+                s_addr,
+                len,
+                target
+            );
+            continue;
+        }
+
+        let target_start = match starts.get(target) {
+            Some(target_start) => *target_start,
+            None => {
+                let stream = ImageStream::open(&mut volume, &graph, target)?;
+                let target_start = start;
+                start += stream.len();
+                streams.push(Arc::new(stream));
+                starts.insert(target, target_start);
+                target_start
+            }
+        };
+        segments.push(LimeSegment {
+            s_addr,
+            e_addr: s_addr + len - 1,
+            file_offset: target_start + target_offset,
+        });
+    }
+
+    if streams.is_empty() {
+        return Err(Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+            .log_error(format!("AFF4 map {} points to no image stream", map)));
+    }
+    Ok((DumpFile::decoded(streams), segments))
+}
+
+/// Zip archive holding an AFF4 volume
+struct Volume {
+    file: File,
+    archive: ZipArchive<File>,
+    /// URN of the volume, stored as the comment of the archive
+    urn: String,
+}
+
+impl Volume {
+    fn open(path: &Path) -> Result<Self> {
+        let corrupt = || {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+                "Unable to read the AFF4 container {}",
+                path.display()
+            ))
+        };
+        let file = File::open(path).map_err(|_| corrupt())?;
+        let archive =
+            ZipArchive::new(file.try_clone().map_err(|_| corrupt())?).map_err(|_| corrupt())?;
+        let urn = String::from_utf8_lossy(archive.comment())
+            .trim_end_matches('\0')
+            .trim()
+            .to_owned();
+        Ok(Self { file, archive, urn })
+    }
+
+    /// Name of the member of the archive storing the object `urn`, members being named
+    /// after their URN relative to the volume, percent-encoded.
+    fn member_name(&self, urn: &str) -> Option<String> {
+        self.archive
+            .file_names()
+            .find(|name| {
+                let name = percent_decode(name).unwrap_or((*name).into());
+                name == urn
+                    || (!self.urn.is_empty()
+                        && urn
+                            .strip_prefix(self.urn.as_str())
+                            .is_some_and(|relative| relative.trim_start_matches('/') == name))
+            })
+            .map(str::to_owned)
+    }
+
+    fn missing(urn: &str) -> Error {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+            .log_error(format!("AFF4 object {} is missing", urn))
+    }
+
+    /// Read the whole member storing the object `urn`.
+    fn read_member(&mut self, urn: &str) -> Result<Vec<u8>> {
+        let name = self.member_name(urn).ok_or_else(|| Self::missing(urn))?;
+        let mut member = self
+            .archive
+            .by_name(&name)
+            .map_err(|_| Self::missing(urn))?;
+        let mut data = Vec::new();
+        member
+            .read_to_end(&mut data)
+            .map_err(|_| Self::missing(urn))?;
+        Ok(data)
+    }
+
+    /// Get the data of the member storing the object `urn`, read in place if stored.
+    fn open_member(&mut self, urn: &str) -> Result<BevyData> {
+        let name = self.member_name(urn).ok_or_else(|| Self::missing(urn))?;
+        let member = self
+            .archive
+            .by_name(&name)
+            .map_err(|_| Self::missing(urn))?;
+        if member.compression() == CompressionMethod::Stored {
+            return Ok(BevyData::InPlace {
+                base: member.data_start(),
+            });
+        }
+        drop(member);
+        self.read_member(urn).map(BevyData::Memory)
+    }
+}
+
+/// Compression of the chunks of an image stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    Stored,
+    Deflate,
+    Zlib,
+    Snappy,
+    Lz4,
+}
+
+impl Compression {
+    fn from_iri(iri: &str) -> Option<Self> {
+        let iri = iri.to_lowercase();
+        if iri.contains("nullcompressor") || iri.ends_with("#stored") {
+            Some(Self::Stored)
+        } else if iri.contains("rfc1951") || iri.contains("deflate") {
+            Some(Self::Deflate)
+        } else if iri.contains("rfc1950") || iri.contains("zlib") || iri.contains("gzip") {
+            Some(Self::Zlib)
+        } else if iri.contains("snappy") {
+            Some(Self::Snappy)
+        } else if iri.contains("lz4") {
+            Some(Self::Lz4)
+        } else {
+            None
+        }
+    }
+
+    /// Decompress `chunk` into at most `chunk_size` bytes.
+    fn decompress(self, chunk: &[u8], chunk_size: usize) -> io::Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(chunk_size);
+        match self {
+            Self::Stored => data.extend_from_slice(chunk),
+            Self::Deflate => {
+                flate2::read::DeflateDecoder::new(chunk)
+                    .take(chunk_size as u64)
+                    .read_to_end(&mut data)?;
+            }
+            Self::Zlib => {
+                flate2::read::ZlibDecoder::new(chunk)
+                    .take(chunk_size as u64)
+                    .read_to_end(&mut data)?;
+            }
+            Self::Snappy => {
+                data = snap::raw::Decoder::new()
+                    .decompress_vec(chunk)
+                    .map_err(io::Error::other)?;
+            }
+            Self::Lz4 => {
+                data = lz4_flex::block::decompress(chunk, chunk_size).map_err(io::Error::other)?;
+            }
+        }
+        Ok(data)
+    }
+}
+
+/// Bytes of a bevy, the chunks of a segment of an image stream
+enum BevyData {
+    /// Stored in the container from offset `base`
+    InPlace { base: u64 },
+    /// Inflated from a compressed member of the container
+    Memory(Vec<u8>),
+}
+
+struct Bevy {
+    data: BevyData,
+    /// Offset and length of each chunk in the bevy
+    index: Vec<(u64, u32)>,
+}
+
+/// AFF4 image stream, decoded a chunk at a time
+struct ImageStream {
+    file: Mutex<File>,
+    bevies: Vec<Bevy>,
+    size: u64,
+    chunk_size: u64,
+    chunks_in_segment: u64,
+    compression: Compression,
+    /// Last chunk decoded
+    cache: Mutex<Option<(u64, Arc<[u8]>)>>,
+}
+
+impl ImageStream {
+    fn open(volume: &mut Volume, graph: &Graph, urn: &str) -> Result<Self> {
+        let number = |predicate: &str, default: u64| {
+            graph
+                .object(urn, &format!("{}{}", AFF4, predicate))
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or(default)
+        };
+        let size = number("size", 0);
+        let chunk_size = number("chunkSize", DEFAULT_CHUNK_SIZE).max(1);
+        let chunks_in_segment = number("chunksInSegment", DEFAULT_CHUNKS_IN_SEGMENT).max(1);
+        let compression = match graph.object(urn, &format!("{}compressionMethod", AFF4)) {
+            None => Compression::Stored,
+            Some(iri) => Compression::from_iri(iri).ok_or_else(|| {
+                Error(ErrorOrigin::Connector, ErrorKind::NotSupported).log_error(format!(
+                    "AFF4 stream {} uses the unsupported compression {}",
+                    urn, iri
+                ))
+            })?,
+        };
+
+        let chunks = size.div_ceil(chunk_size);
+        let mut bevies = Vec::new();
+        for bevy in 0..chunks.div_ceil(chunks_in_segment) {
+            let bevy_urn = format!("{}/{:08}", urn, bevy);
+            let index = volume
+                .read_member(&format!("{}.index", bevy_urn))?
+                .chunks_exact(BEVY_INDEX_ENTRY_SIZE)
+                .map(|entry| {
+                    (
+                        u64::from_le_bytes(entry[..8].try_into().unwrap()),
+                        u32::from_le_bytes(entry[8..].try_into().unwrap()),
+                    )
+                })
+                .collect();
+            bevies.push(Bevy {
+                data: volume.open_member(&bevy_urn)?,
+                index,
+            });
+        }
+
+        let file = volume.file.try_clone().map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+                .log_error("Unable to reopen the AFF4 container")
+        })?;
+        Ok(Self {
+            file: Mutex::new(file),
+            bevies,
+            size,
+            chunk_size,
+            chunks_in_segment,
+            compression,
+            cache: Mutex::new(None),
+        })
+    }
+
+    /// Decode the chunk number `chunk`.
+    fn chunk(&self, chunk: u64) -> io::Result<Arc<[u8]>> {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((cached, data)) = cache.as_ref() {
+            if *cached == chunk {
+                return Ok(data.clone());
+            }
+        }
+
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "AFF4 chunk out of its bevy");
+        let bevy = usize::try_from(chunk / self.chunks_in_segment)
+            .ok()
+            .and_then(|bevy| self.bevies.get(bevy))
+            .ok_or_else(invalid)?;
+        let (offset, len) = usize::try_from(chunk % self.chunks_in_segment)
+            .ok()
+            .and_then(|entry| bevy.index.get(entry))
+            .copied()
+            .ok_or_else(invalid)?;
+
+        let raw = match &bevy.data {
+            BevyData::InPlace { base } => {
+                let mut raw = vec![0; len as usize];
+                let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+                file.seek(SeekFrom::Start(base + offset))?;
+                file.read_exact(&mut raw)?;
+                raw
+            }
+            BevyData::Memory(data) => usize::try_from(offset)
+                .ok()
+                .and_then(|offset| data.get(offset..offset + len as usize))
+                .ok_or_else(invalid)?
+                .to_vec(),
+        };
+        // Chunks that do not shrink are stored as they are.
+        let data: Arc<[u8]> = match u64::from(len) == self.chunk_size {
+            true => raw.into(),
+            false => self
+                .compression
+                .decompress(&raw, self.chunk_size as usize)?
+                .into(),
+        };
+        *cache = Some((chunk, data.clone()));
+        Ok(data)
+    }
+}
+
+impl DecodedStream for ImageStream {
+    fn len(&self) -> u64 {
+        self.size
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        if offset >= self.size || buf.is_empty() {
+            return Ok(0);
+        }
+        let data = self.chunk(offset / self.chunk_size)?;
+        let within = (offset % self.chunk_size) as usize;
+        let available = data
+            .len()
+            .saturating_sub(within)
+            .min(usize::try_from(self.size - offset).unwrap_or(usize::MAX));
+        if available == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let read = available.min(buf.len());
+        buf[..read].copy_from_slice(&data[within..within + read]);
+        Ok(read)
+    }
+}
+
+/// Triples of an RDF graph in Turtle, the subset written by AFF4 tools
+struct Graph {
+    triples: Vec<(String, String, String)>,
+}
+
+impl Graph {
+    /// Parse `turtle`, skipping what it can not make sense of.
+    fn parse(turtle: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(turtle);
+        let mut prefixes = HashMap::new();
+        let mut triples = Vec::new();
+        let mut tokens = tokenize(&text).into_iter().peekable();
+        let mut statement = Vec::new();
+
+        while let Some(token) = tokens.next() {
+            match token {
+                Token::Word(word) if word == "@prefix" || word.eq_ignore_ascii_case("prefix") => {
+                    if let (Some(Token::Word(name)), Some(Token::Iri(iri))) =
+                        (tokens.next(), tokens.next())
+                    {
+                        prefixes.insert(name.trim_end_matches(':').to_owned(), iri);
+                    }
+                    tokens.next_if(|token| *token == Token::Punct('.'));
+                }
+                Token::Word(word) if word == "@base" || word.eq_ignore_ascii_case("base") => {
+                    tokens.next();
+                    tokens.next_if(|token| *token == Token::Punct('.'));
+                }
+                Token::Punct('.') => {
+                    collect_statement(&statement, &mut triples);
+                    statement.clear();
+                }
+                token => statement.push(match token {
+                    Token::Word(word) => Token::Word(expand(&word, &prefixes)),
+                    token => token,
+                }),
+            }
+        }
+        collect_statement(&statement, &mut triples);
+        Self { triples }
+    }
+
+    /// Subjects of type `class`, sorted.
+    fn subjects_of_type(&self, class: &str) -> Vec<&str> {
+        let mut subjects = self
+            .triples
+            .iter()
+            .filter(|(_, predicate, object)| predicate == RDF_TYPE && object == class)
+            .map(|(subject, _, _)| subject.as_str())
+            .collect::<Vec<_>>();
+        subjects.sort_unstable();
+        subjects.dedup();
+        subjects
+    }
+
+    /// First object of `predicate` for `subject`.
+    fn object(&self, subject: &str, predicate: &str) -> Option<&str> {
+        self.triples
+            .iter()
+            .find(|(s, p, _)| s == subject && p == predicate)
+            .map(|(_, _, object)| object.as_str())
+    }
+}
+
+/// Expand a prefixed name, `a` standing for `rdf:type`.
+fn expand(word: &str, prefixes: &HashMap<String, String>) -> String {
+    if word == "a" {
+        return RDF_TYPE.to_owned();
+    }
+    match word.split_once(':') {
+        Some((prefix, local)) if prefixes.contains_key(prefix) => {
+            format!("{}{}", prefixes[prefix], local)
+        }
+        _ => word.to_owned(),
+    }
+}
+
+/// Turn the tokens of a statement, `subject predicate object (, object)* (; predicate ...)*`,
+/// into triples.
+fn collect_statement(statement: &[Token], triples: &mut Vec<(String, String, String)>) {
+    let mut tokens = statement.iter();
+    let Some(subject) = tokens.next().and_then(Token::term) else {
+        return;
+    };
+    let mut predicate = None;
+    for token in tokens {
+        match token {
+            Token::Punct(';') => predicate = None,
+            Token::Punct(',') => {}
+            token => match (&predicate, token.term()) {
+                (None, Some(term)) => predicate = Some(term),
+                (Some(predicate), Some(object)) => {
+                    triples.push((subject.clone(), predicate.clone(), object))
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Iri(String),
+    Literal(String),
+    Word(String),
+    Punct(char),
+}
+
+impl Token {
+    fn term(&self) -> Option<String> {
+        match self {
+            Self::Iri(term) | Self::Literal(term) | Self::Word(term) => Some(term.clone()),
+            Self::Punct(_) => None,
+        }
+    }
+}
+
+/// Split Turtle text into IRIs, literals, words and punctuation, dropping comments and the
+/// datatype or language of literals.
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '#' => {
+                chars.by_ref().find(|c| *c == '\n');
+            }
+            '<' => tokens.push(Token::Iri(
+                chars.by_ref().take_while(|c| *c != '>').collect(),
+            )),
+            '"' | '\'' => {
+                let long = chars.next_if_eq(&c).is_some();
+                let mut literal = String::new();
+                if long && chars.next_if_eq(&c).is_none() {
+                    // `""` is an empty literal.
+                    tokens.push(Token::Literal(literal));
+                    continue;
+                }
+                while let Some(next) = chars.next() {
+                    match next {
+                        '\\' => literal.extend(chars.next()),
+                        next if next == c && !long => break,
+                        next if next == c && chars.peek() == Some(&c) => {
+                            chars.next();
+                            if chars.next_if_eq(&c).is_some() {
+                                break;
+                            }
+                            literal.extend([c, c]);
+                        }
+                        next => literal.push(next),
+                    }
+                }
+                tokens.push(Token::Literal(literal));
+                if chars.next_if_eq(&'^').is_some() && chars.next_if_eq(&'^').is_some() {
+                    // Drop the datatype, an IRI or a prefixed name.
+                    if chars.next_if_eq(&'<').is_some() {
+                        chars.by_ref().find(|c| *c == '>');
+                    } else {
+                        while chars.next_if(|c| !is_delimiter(*c)).is_some() {}
+                    }
+                } else if chars.next_if_eq(&'@').is_some() {
+                    while chars.next_if(|c| !is_delimiter(*c)).is_some() {}
+                }
+            }
+            ';' | ',' | '.' | '[' | ']' | '(' | ')' => tokens.push(Token::Punct(c)),
+            c => {
+                let mut word = String::from(c);
+                while let Some(next) = chars.next_if(|c| !is_delimiter(*c)) {
+                    word.push(next);
+                }
+                // A statement may end right after a word.
+                let end = word.ends_with('.') && word.len() > 1;
+                let word = word.strip_suffix('.').filter(|_| end).unwrap_or(&word);
+                tokens.push(Token::Word(word.to_owned()));
+                if end {
+                    tokens.push(Token::Punct('.'));
+                }
+            }
+        }
+    }
+    tokens
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ';' | ',' | '<' | '>' | '"' | '[' | ']' | '(' | ')' | '#')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turtle_is_parsed() {
+        let graph = Graph::parse(
+            br#"@prefix aff4: <http://aff4.org/Schema#> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+
+# A map over one stream.
+<aff4://c0ffee/map>
+    a aff4:Map, aff4:Image ;
+    aff4:size "8192"^^xsd:long ;
+    aff4:description """multi
+line"""@en .
+
+<aff4://c0ffee/stream> a aff4:ImageStream;
+    aff4:chunkSize 4096.
+"#,
+        );
+
+        assert_eq!(
+            graph.subjects_of_type(&format!("{}Map", AFF4)),
+            ["aff4://c0ffee/map"]
+        );
+        assert_eq!(
+            graph.subjects_of_type(&format!("{}ImageStream", AFF4)),
+            ["aff4://c0ffee/stream"]
+        );
+        assert_eq!(
+            graph.object("aff4://c0ffee/map", &format!("{}size", AFF4)),
+            Some("8192")
+        );
+        assert_eq!(
+            graph.object("aff4://c0ffee/map", &format!("{}description", AFF4)),
+            Some("multi\nline")
+        );
+        assert_eq!(
+            graph.object("aff4://c0ffee/stream", &format!("{}chunkSize", AFF4)),
+            Some("4096")
+        );
+    }
+
+    #[test]
+    fn compression_methods_are_recognized() {
+        for (iri, compression) in [
+            ("http://code.google.com/p/snappy/", Compression::Snappy),
+            ("https://code.google.com/p/lz4/", Compression::Lz4),
+            ("https://www.ietf.org/rfc/rfc1950.txt", Compression::Zlib),
+            ("https://tools.ietf.org/html/rfc1951", Compression::Deflate),
+            ("http://aff4.org/Schema#NullCompressor", Compression::Stored),
+        ] {
+            assert_eq!(Compression::from_iri(iri), Some(compression));
+        }
+        assert_eq!(Compression::from_iri("http://example.com/xz"), None);
+    }
+}
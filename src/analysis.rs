@@ -0,0 +1,149 @@
+use crate::memory::LimeMemory;
+
+use memflow::prelude::v1::*;
+
+/// Bytes read from the dump at a time while analyzing it, rounded down to whole pages
+const ANALYSIS_CHUNK_SIZE: u64 = 1 << 20;
+
+/// Entropy in bits per byte from which a page is considered packed or encrypted
+pub const HIGH_ENTROPY: f64 = 7.5;
+
+/// Page classification and entropy summary of the mapped ranges, returned by
+/// [`LimeMemory::page_stats`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LimePageStats {
+    /// Size in bytes of the pages, aligned on their size
+    pub page_size: u64,
+    /// Pages holding mapped data, partially mapped pages included
+    pub pages: u64,
+    /// Pages made only of zeros
+    pub zero_pages: u64,
+    /// Pages repeating a single non-zero byte
+    pub constant_pages: u64,
+    /// Number of pages per whole bit of entropy per byte, `histogram[n]` counting the pages
+    /// with an entropy in `[n, n + 1)`, the last bucket including 8
+    pub histogram: [u64; 8],
+    /// Inclusive physical windows of consecutive pages with an entropy of at least
+    /// [`HIGH_ENTROPY`], likely packed or encrypted data
+    pub high_entropy: Vec<(u64, u64)>,
+}
+
+impl LimePageStats {
+    /// Share of the pages that are neither zero nor constant, a rough estimate of how much
+    /// of the dump survives compression.
+    pub fn populated_ratio(&self) -> f64 {
+        if self.pages == 0 {
+            return 0.0;
+        }
+        (self.pages - self.zero_pages - self.constant_pages) as f64 / self.pages as f64
+    }
+
+    fn add_page(&mut self, addr: u64, data: &[u8]) {
+        let mut counts = [0u64; 256];
+        for byte in data {
+            counts[*byte as usize] += 1;
+        }
+
+        self.pages += 1;
+        if counts[0] == data.len() as u64 {
+            self.zero_pages += 1;
+        } else if counts.contains(&(data.len() as u64)) {
+            self.constant_pages += 1;
+        }
+
+        let entropy = entropy(&counts, data.len() as u64);
+        self.histogram[(entropy as usize).min(7)] += 1;
+        if entropy >= HIGH_ENTROPY {
+            let e_addr = addr + data.len() as u64 - 1;
+            match self.high_entropy.last_mut() {
+                Some(window) if window.1.checked_add(1) == Some(addr) => window.1 = e_addr,
+                _ => self.high_entropy.push((addr, e_addr)),
+            }
+        }
+    }
+}
+
+/// Shannon entropy, in bits per byte, of `len` bytes whose values are counted in `counts`.
+fn entropy(counts: &[u64; 256], len: u64) -> f64 {
+    counts
+        .iter()
+        .filter(|count| **count != 0)
+        .map(|count| {
+            let p = *count as f64 / len as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Classify the pages of the ranges mapped by `mem` and measure their entropy, reading the
+/// ranges in address order.
+pub(crate) fn page_stats(mem: &mut LimeMemory, page_size: u64) -> Result<LimePageStats> {
+    if page_size == 0 {
+        return Err(Error(ErrorOrigin::Connector, ErrorKind::InvalidArgument)
+            .log_error("the page size can not be zero"));
+    }
+
+    // Contiguous mappings are merged so that a page they share is analyzed once.
+    let mut ranges: Vec<(u64, u64)> = Vec::new();
+    for mapping in mem.mem_map().iter() {
+        let s_addr = mapping.base().to_umem();
+        let e_addr = s_addr + mapping.output().1 - 1;
+        match ranges.last_mut() {
+            Some(range) if range.1 + 1 == s_addr => range.1 = e_addr,
+            _ => ranges.push((s_addr, e_addr)),
+        }
+    }
+
+    let mut stats = LimePageStats {
+        page_size,
+        pages: 0,
+        zero_pages: 0,
+        constant_pages: 0,
+        histogram: [0; 8],
+        high_entropy: Vec::new(),
+    };
+    let chunk_size = (ANALYSIS_CHUNK_SIZE / page_size).max(1) * page_size;
+    let mut buf = Vec::new();
+    for (s_addr, e_addr) in ranges {
+        let mut addr = s_addr;
+        while addr <= e_addr {
+            // Chunks end on a page boundary so that pages are never split between reads.
+            let chunk_end = (addr - addr % page_size)
+                .saturating_add(chunk_size - 1)
+                .min(e_addr);
+            buf.resize((chunk_end - addr + 1) as usize, 0);
+            mem.phys_view().read_raw_into(addr.into(), &mut buf)?;
+
+            // A range starting or ending mid-page only holds part of its first or last page.
+            let mut offset = 0;
+            while offset < buf.len() {
+                let page_addr = addr + offset as u64;
+                let len = ((page_size - page_addr % page_size) as usize).min(buf.len() - offset);
+                stats.add_page(page_addr, &buf[offset..offset + len]);
+                offset += len;
+            }
+            match chunk_end.checked_add(1) {
+                Some(next) => addr = next,
+                None => break,
+            }
+        }
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entropy_is_measured_in_bits_per_byte() {
+        let mut counts = [0u64; 256];
+        counts[0] = 16;
+        assert_eq!(entropy(&counts, 16), 0.0);
+        counts[0] = 8;
+        counts[1] = 8;
+        assert_eq!(entropy(&counts, 16), 1.0);
+        assert_eq!(entropy(&[1; 256], 256), 8.0);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::dumpfile::DumpFile;
+
+use memflow::prelude::v1::*;
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use zip::CompressionMethod;
+
+/// Open `member` of the zip or tar archive at `path`, told apart by the extension.
+///
+/// Members stored uncompressed are read in place from the archive. Members of a zip archive
+/// compressed with deflate are inflated into an unnamed spool file of the temporary directory,
+/// their size being the one inflated and not the one the archive declares.
+///
+/// # Errors
+///
+/// Returns `Err` if the archive can not be read, has no such member, or if the member is
+/// encrypted, compressed with another method or is not a regular file
+///
+pub(crate) fn open_member(path: &Path, member: &str) -> Result<DumpFile> {
+    let file = File::open(path).map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+            .log_error(format!("Unable to open the archive {}", path.display()))
+    })?;
+    let is_zip = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"));
+    match is_zip {
+        true => open_zip_member(file, path, member),
+        false => open_tar_member(file, path, member),
+    }
+}
+
+fn open_zip_member(file: File, path: &Path, member: &str) -> Result<DumpFile> {
+    let corrupt = || {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+            .log_error(format!("Unable to read the zip archive {}", path.display()))
+    };
+    let mut archive =
+        zip::ZipArchive::new(file.try_clone().map_err(|_| corrupt())?).map_err(|_| corrupt())?;
+    let mut entry = archive.by_name(member).map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::InvalidPath).log_error(format!(
+            "{} has no readable member {}",
+            path.display(),
+            member
+        ))
+    })?;
+    if entry.encrypted() || !entry.is_file() {
+        return Err(
+            Error(ErrorOrigin::Connector, ErrorKind::NotSupported).log_error(format!(
+                "{} in {} is encrypted or is not a file",
+                member,
+                path.display()
+            )),
+        );
+    }
+
+    match entry.compression() {
+        CompressionMethod::Stored => Ok(DumpFile::window(file, entry.data_start(), entry.size())),
+        CompressionMethod::Deflated => {
+            log::info!(
+                "Inflating {} of {} to a spool file, {} bytes declared",
+                member,
+                path.display(),
+                entry.size()
+            );
+            let (mut spool, spool_path) = create_spool()?;
+            let len = io::copy(&mut entry, &mut spool).map_err(|e| {
+                Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile).log_error(format!(
+                    "Unable to inflate {} of {} to {}: {}",
+                    member,
+                    path.display(),
+                    spool_path.display(),
+                    e
+                ))
+            })?;
+            Ok(DumpFile::window(spool, 0, len))
+        }
+        method => Err(
+            Error(ErrorOrigin::Connector, ErrorKind::NotSupported).log_error(format!(
+                "{} in {} is compressed with the unsupported method {}",
+                member,
+                path.display(),
+                method
+            )),
+        ),
+    }
+}
+
+/// Create a spool file in the temporary directory, removed at once so that it disappears with
+/// the last handle to it where open files can be unlinked.
+fn create_spool() -> Result<(File, PathBuf)> {
+    static NEXT_SPOOL: AtomicU64 = AtomicU64::new(0);
+    let spool_path = env::temp_dir().join(format!(
+        "memflow-lime-{}-{}.inflated",
+        process::id(),
+        NEXT_SPOOL.fetch_add(1, Ordering::Relaxed)
+    ));
+    let spool = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&spool_path)
+        .map_err(|e| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile).log_error(format!(
+                "Unable to create the spool file {}: {}",
+                spool_path.display(),
+                e
+            ))
+        })?;
+    if let Err(e) = fs::remove_file(&spool_path) {
+        log::warn!(
+            "Unable to remove the spool file {}, it is left behind: {}",
+            spool_path.display(),
+            e
+        );
+    }
+    Ok((spool, spool_path))
+}
+
+fn open_tar_member(file: File, path: &Path, member: &str) -> Result<DumpFile> {
+    let corrupt = || {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+            .log_error(format!("Unable to read the tar archive {}", path.display()))
+    };
+    let member = member.trim_start_matches("./");
+    let mut archive = tar::Archive::new(file.try_clone().map_err(|_| corrupt())?);
+    for entry in archive.entries_with_seek().map_err(|_| corrupt())? {
+        let entry = entry.map_err(|_| corrupt())?;
+        if entry
+            .path()
+            .map_err(|_| corrupt())?
+            .to_str()
+            .map(|name| name.trim_start_matches("./"))
+            != Some(member)
+        {
+            continue;
+        }
+
+        // Sparse members are not stored as one run of bytes.
+        if !entry.header().entry_type().is_file() {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::NotSupported).log_error(format!(
+                    "{} in {} is not a regular file",
+                    member,
+                    path.display()
+                )),
+            );
+        }
+        return Ok(DumpFile::window(
+            file,
+            entry.raw_file_position(),
+            entry.size(),
+        ));
+    }
+
+    Err(
+        Error(ErrorOrigin::Connector, ErrorKind::InvalidPath).log_error(format!(
+            "{} has no member {}",
+            path.display(),
+            member
+        )),
+    )
+}
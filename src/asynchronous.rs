@@ -0,0 +1,106 @@
+use crate::memory::LimeMemory;
+
+use memflow::prelude::v1::*;
+
+use std::sync::{Arc, Mutex};
+
+/// Asynchronous handle on a `LiME` connector, for tokio services reading many dumps at once
+///
+/// Reads run on tokio's blocking thread pool, like the file IO of tokio itself, each on a
+/// clone of the connector taken from a pool of idle ones. Concurrent reads of one handle
+/// therefore proceed in parallel, and no thread is held while a connector is idle. Cloning
+/// the handle shares the pool.
+#[derive(Clone)]
+pub struct AsyncLimeMemory {
+    pool: Arc<Mutex<Pool>>,
+    /// Inclusive physical ranges mapped, sorted by address
+    ranges: Arc<[(u64, u64)]>,
+}
+
+/// Connectors of an [`AsyncLimeMemory`]
+struct Pool {
+    /// Connector cloned when every other one is reading
+    template: LimeMemory,
+    /// Connectors done reading
+    idle: Vec<LimeMemory>,
+}
+
+impl AsyncLimeMemory {
+    /// Open the dump described by `args` like [`create_connector`](crate::create_connector),
+    /// parsing it on the blocking thread pool.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the connector can not be created or if the runtime is shutting down
+    ///
+    pub async fn open(args: ConnectorArgs) -> Result<Self> {
+        tokio::task::spawn_blocking(move || crate::create_connector(&args))
+            .await
+            .map_err(|_| {
+                Error(ErrorOrigin::Connector, ErrorKind::Unknown)
+                    .log_error("The task opening the LiME dump was cancelled")
+            })?
+            .map(Self::new)
+    }
+
+    /// Read `mem` asynchronously.
+    pub fn new(mem: LimeMemory) -> Self {
+        Self {
+            ranges: mem
+                .mem_map()
+                .iter()
+                .map(|mapping| {
+                    let s_addr = mapping.base().to_umem();
+                    (s_addr, s_addr + mapping.output().1 - 1)
+                })
+                .collect(),
+            pool: Arc::new(Mutex::new(Pool {
+                template: mem,
+                idle: Vec::new(),
+            })),
+        }
+    }
+
+    /// Inclusive physical ranges backed by the dump, sorted by address, see
+    /// [`LimeMemory::mem_map`]. A lazily parsed dump only reports the ranges walked when the
+    /// handle was created.
+    pub fn ranges(&self) -> &[(u64, u64)] {
+        &self.ranges
+    }
+
+    /// Read the `len` bytes of physical memory following `addr`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if part of the memory is not backed by the dump, unless gaps are
+    /// zero-filled (`fill_gaps`), if an error occurred while reading or if the runtime is
+    /// shutting down
+    ///
+    pub async fn read_phys(&self, addr: PhysicalAddress, len: usize) -> Result<Vec<u8>> {
+        let mut mem = self.take();
+        let pool = self.pool.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut buf = vec![0u8; len];
+            let read = mem
+                .phys_view()
+                .read_raw_into(addr.address(), &mut buf)
+                .map_err(Error::from);
+            pool.lock().unwrap().idle.push(mem);
+            read.map(|_| buf)
+        })
+        .await
+        .map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::Unknown)
+                .log_error("The task reading the LiME dump was cancelled")
+        })?
+    }
+
+    /// Take an idle connector, cloning one if they are all reading.
+    fn take(&self) -> LimeMemory {
+        let mut pool = self.pool.lock().unwrap();
+        match pool.idle.pop() {
+            Some(mem) => mem,
+            None => pool.template.clone(),
+        }
+    }
+}
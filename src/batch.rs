@@ -0,0 +1,212 @@
+use crate::direct::DirectFile;
+use crate::dumpfile::DumpFile;
+use crate::stats::LimeStats;
+
+use memflow::prelude::v1::*;
+
+use std::io::{self, IoSliceMut};
+use std::sync::Arc;
+
+/// Dump file mapped into physical memory, serving batches of reads with vectored IO
+///
+/// memflow's `FileIoMemory` seeks and reads once per entry of a batch. Here the entries are
+/// sorted by file offset first, and the ones that are contiguous in the file are read together
+/// with a single positional vectored read, a `preadv` on Linux and a read split into the
+/// entries elsewhere. Clones of the file never move a shared file cursor. A run crossing two
+/// parts of a split dump, longer than `IOV_MAX` entries or cut short by the end of the file
+/// takes more than one syscall.
+#[derive(Clone)]
+pub(crate) struct BatchedFile {
+    file: DumpFile,
+    direct: Option<DirectFile>,
+    mem_map: MemoryMap<(Address, umem)>,
+    stats: Arc<LimeStats>,
+    /// Whether the reads are page loads of a page cache
+    cached: bool,
+}
+
+impl BatchedFile {
+    /// Map `file` into physical memory according to `mem_map`, reading through `direct` if set.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the memory map can not be built
+    ///
+    pub(crate) fn new(
+        file: DumpFile,
+        direct: Option<DirectFile>,
+        mem_map: MemoryMap<(Address, umem)>,
+    ) -> Result<Self> {
+        Ok(Self {
+            file,
+            direct,
+            mem_map,
+            stats: Arc::default(),
+            cached: false,
+        })
+    }
+
+    /// Map of the physical ranges to the offsets of the file holding them.
+    pub(crate) const fn mem_map(&self) -> &MemoryMap<(Address, umem)> {
+        &self.mem_map
+    }
+
+    /// Get back the mapped file.
+    pub(crate) fn into_file(self) -> DumpFile {
+        self.file
+    }
+
+    /// Record the reads in `stats`, as page cache misses too if `cached` is set.
+    pub(crate) fn with_stats(self, stats: Arc<LimeStats>, cached: bool) -> Self {
+        Self {
+            stats,
+            cached,
+            ..self
+        }
+    }
+}
+
+/// Fill `bufs` with the bytes following file offset `start`, through `direct` if set.
+///
+/// If the filesystem refuses the `O_DIRECT` reads, `direct` is dropped and buffered reads of
+/// `file` are used from then on.
+fn read_run(
+    file: &mut DumpFile,
+    direct: &mut Option<DirectFile>,
+    start: u64,
+    bufs: &mut [IoSliceMut],
+) -> io::Result<()> {
+    if let Some(handle) = direct {
+        match handle.read_exact_at(start, bufs) {
+            Err(e) if e.kind() == io::ErrorKind::InvalidInput => {
+                log::warn!(
+                    "O_DIRECT read refused, falling back to buffered reads: {}",
+                    e
+                );
+                *direct = None;
+            }
+            read => return read,
+        }
+    }
+    file.read_exact_at(start, bufs)
+}
+
+impl PhysicalMemory for BatchedFile {
+    fn phys_read_raw_iter(
+        &mut self,
+        MemOps {
+            inp,
+            mut out,
+            out_fail,
+        }: PhysicalReadMemOps,
+    ) -> Result<()> {
+        let Self {
+            file,
+            direct,
+            mem_map,
+            stats,
+            cached,
+            ..
+        } = self;
+        let cached = *cached;
+        let misses = stats.clone();
+        let inp = inp.inspect(move |_| {
+            if cached {
+                misses.record_cache_misses(1);
+            }
+        });
+        let mut iter = mem_map.map_iter(inp, out_fail);
+        let mut chunks = iter
+            .by_ref()
+            .map(|CTup3((file_off, _), meta_addr, buf)| (file_off.to_umem(), meta_addr, buf))
+            .collect::<Vec<_>>();
+        chunks.sort_by_key(|(file_off, _, _)| *file_off);
+
+        let mut chunks = chunks.into_iter().peekable();
+        while let Some(first) = chunks.next() {
+            let start = first.0;
+            let mut end = start + first.2.len() as u64;
+            let mut run = vec![first];
+            while let Some(next) = chunks.next_if(|(file_off, _, _)| *file_off == end) {
+                end += next.2.len() as u64;
+                run.push(next);
+            }
+
+            let mut bufs = run
+                .iter_mut()
+                .map(|(_, _, buf)| IoSliceMut::new(buf))
+                .collect::<Vec<_>>();
+            #[cfg(feature = "tracing")]
+            tracing::trace!(
+                file_offset = start,
+                len = end - start,
+                entries = bufs.len(),
+                direct = direct.is_some(),
+                "reading from the LiME file"
+            );
+            let read = read_run(file, direct, start, &mut bufs);
+            drop(bufs);
+            stats.record_file_read(end - start);
+            match read {
+                Ok(()) => {
+                    for (_, meta_addr, buf) in run {
+                        opt_call(out.as_deref_mut(), CTup2(meta_addr, buf));
+                    }
+                }
+                Err(e) => {
+                    log::debug!(
+                        "Vectored read of {} bytes at file offset {:#x} failed: {}",
+                        end - start,
+                        start,
+                        e
+                    );
+                    for (_, meta_addr, buf) in run {
+                        opt_call(iter.fail_out(), CTup2(meta_addr, buf));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn phys_write_raw_iter(
+        &mut self,
+        MemOps {
+            inp,
+            mut out,
+            out_fail,
+        }: PhysicalWriteMemOps,
+    ) -> Result<()> {
+        let mut iter = self.mem_map.map_iter(inp, out_fail);
+        while let Some(CTup3((file_off, _), meta_addr, buf)) = iter.next() {
+            match self.file.write_all_at(file_off.to_umem(), &buf) {
+                Ok(()) => {
+                    opt_call(out.as_deref_mut(), CTup2(meta_addr, buf));
+                }
+                Err(e) => {
+                    log::debug!(
+                        "Write of {} bytes at file offset {:#x} failed: {}",
+                        buf.len(),
+                        file_off.to_umem(),
+                        e
+                    );
+                    opt_call(iter.fail_out(), CTup2(meta_addr, buf));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn metadata(&self) -> PhysicalMemoryMetadata {
+        PhysicalMemoryMetadata {
+            max_address: self.mem_map.max_address(),
+            real_size: self.mem_map.real_size(),
+            readonly: false,
+            ideal_batch_size: u32::MAX,
+        }
+    }
+
+    fn set_mem_map(&mut self, mem_map: &[PhysicalMemoryMapping]) {
+        self.mem_map = MemoryMap::from_vec(mem_map.to_vec());
+    }
+}
@@ -0,0 +1,239 @@
+//! Generator of synthetic `LiME` files, for reproducible fixtures of any size.
+
+use memflow_lime::LimeWriter;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Read};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: memflow-lime-gen <out> --range <start>:<size> [--range ...] [options]
+
+Write a deterministic LiME file holding the given physical ranges, every run with the same
+arguments writing the same bytes.
+
+Options:
+    --range <start>:<size>      physical range written, as many times as needed, sizes taking
+                                a k, m or g suffix (powers of 1024, e.g. 0x100000:2g)
+    --fill <pattern>            bytes of the ranges:
+                                  address (default)  every 8-byte word holds its own physical
+                                                     address, little-endian
+                                  zero               zero bytes
+                                  byte:<value>       the same byte everywhere
+                                  random:<seed>      pseudo-random words, the word at address a
+                                                     being splitmix64(seed ^ a)
+    --string <addr>:<text>      text written at a physical address over the fill, as many times
+                                as needed
+    --segment-size <size>       split the ranges in sections of at most size bytes, aligned on
+                                their size
+";
+
+/// Bytes the ranges are filled with
+#[derive(Debug, Clone, Copy)]
+enum Fill {
+    Address,
+    Byte(u8),
+    Random(u64),
+}
+
+impl Fill {
+    /// Value of the 8-byte word at the aligned physical address `addr`.
+    fn word(self, addr: u64) -> [u8; 8] {
+        match self {
+            Self::Address => addr.to_le_bytes(),
+            Self::Byte(byte) => [byte; 8],
+            Self::Random(seed) => splitmix64(seed ^ addr).to_le_bytes(),
+        }
+    }
+}
+
+/// Output of the splitmix64 generator for the state `x`.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Arguments of the generator
+struct Options {
+    out: String,
+    ranges: Vec<(u64, u64)>,
+    fill: Fill,
+    strings: Vec<(u64, Vec<u8>)>,
+    segment_size: Option<u64>,
+}
+
+/// Bytes of the physical memory from an address on, the fill overwritten by the strings
+struct Pattern<'a> {
+    addr: u64,
+    fill: Fill,
+    strings: &'a [(u64, Vec<u8>)],
+}
+
+impl Read for Pattern<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut word = self.fill.word(self.addr & !7);
+        for (addr, byte) in (self.addr..).zip(buf.iter_mut()) {
+            if addr & 7 == 0 {
+                word = self.fill.word(addr);
+            }
+            *byte = word[(addr & 7) as usize];
+        }
+        let end = self.addr + buf.len() as u64;
+        for (s_addr, text) in self.strings {
+            let e_addr = s_addr + text.len() as u64;
+            if *s_addr >= end || e_addr <= self.addr {
+                continue;
+            }
+            let from = s_addr.max(&self.addr);
+            let to = e_addr.min(end);
+            buf[(from - self.addr) as usize..(to - self.addr) as usize]
+                .copy_from_slice(&text[(from - s_addr) as usize..(to - s_addr) as usize]);
+        }
+        self.addr = end;
+        Ok(buf.len())
+    }
+}
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let options = match parse_options(&args) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("memflow-lime-gen: {}", msg);
+            eprint!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match generate(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(msg) => {
+            eprintln!("memflow-lime-gen: {}", msg);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Write the `LiME` file described by `options`.
+fn generate(options: &Options) -> Result<(), String> {
+    let file = File::create(&options.out)
+        .map_err(|e| format!("unable to create {}: {}", options.out, e))?;
+    let mut writer = LimeWriter::new(BufWriter::new(file));
+    if let Some(segment_size) = options.segment_size {
+        writer = writer
+            .with_segment_size(segment_size)
+            .map_err(|e| format!("invalid segment size: {}", e))?;
+    }
+
+    for &(s_addr, size) in &options.ranges {
+        let mut pattern = Pattern {
+            addr: s_addr,
+            fill: options.fill,
+            strings: &options.strings,
+        };
+        writer
+            .write_range_from(s_addr, size, &mut pattern)
+            .map_err(|e| format!("unable to write the range at {:#x}: {}", s_addr, e))?;
+    }
+    let ranges = writer.ranges_written();
+    let bytes = writer.bytes_written();
+    writer
+        .finish()
+        .map_err(|e| format!("unable to write {}: {}", options.out, e))?;
+
+    println!(
+        "{}: {} sections, {} bytes of memory",
+        options.out, ranges, bytes
+    );
+    for (addr, text) in &options.strings {
+        println!("{:#x}: {}", addr, String::from_utf8_lossy(text));
+    }
+    Ok(())
+}
+
+/// Parse the command line of the generator.
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut args = args.iter().map(String::as_str);
+    let out = args
+        .next()
+        .filter(|out| !out.starts_with("--"))
+        .ok_or("missing output file")?;
+    let mut options = Options {
+        out: out.to_owned(),
+        ranges: Vec::new(),
+        fill: Fill::Address,
+        strings: Vec::new(),
+        segment_size: None,
+    };
+
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{} needs a value", flag))?;
+        match flag {
+            "--range" => {
+                let (start, size) = value
+                    .split_once(':')
+                    .ok_or_else(|| format!("invalid range: {}", value))?;
+                let (start, size) = (parse_number(start)?, parse_size(size)?);
+                if size == 0 || start.checked_add(size).is_none() {
+                    return Err(format!("invalid range: {}", value));
+                }
+                options.ranges.push((start, size));
+            }
+            "--fill" => {
+                options.fill = match value.split_once(':') {
+                    None if value == "address" => Fill::Address,
+                    None if value == "zero" => Fill::Byte(0),
+                    Some(("byte", byte)) => Fill::Byte(
+                        u8::try_from(parse_number(byte)?)
+                            .map_err(|_| format!("invalid byte: {}", byte))?,
+                    ),
+                    Some(("random", seed)) => Fill::Random(parse_number(seed)?),
+                    _ => return Err(format!("unknown fill pattern: {}", value)),
+                }
+            }
+            "--string" => {
+                let (addr, text) = value
+                    .split_once(':')
+                    .ok_or_else(|| format!("invalid string: {}", value))?;
+                let addr = parse_number(addr)?;
+                if text.is_empty() || addr.checked_add(text.len() as u64).is_none() {
+                    return Err(format!("invalid string: {}", value));
+                }
+                options.strings.push((addr, text.as_bytes().to_vec()));
+            }
+            "--segment-size" => options.segment_size = Some(parse_size(value)?),
+            _ => return Err(format!("unknown option: {}", flag)),
+        }
+    }
+
+    if options.ranges.is_empty() {
+        return Err("at least one --range is needed".to_owned());
+    }
+    Ok(options)
+}
+
+/// Parse a decimal or `0x` prefixed hexadecimal number.
+fn parse_number(s: &str) -> Result<u64, String> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+    .map_err(|_| format!("invalid number: {}", s))
+}
+
+/// Parse a number optionally followed by a `k`, `m` or `g` unit, powers of 1024.
+fn parse_size(s: &str) -> Result<u64, String> {
+    let lowercase = s.to_lowercase();
+    let (digits, unit) = [('k', 1u64 << 10), ('m', 1 << 20), ('g', 1 << 30)]
+        .into_iter()
+        .find_map(|(suffix, unit)| lowercase.strip_suffix(suffix).map(|digits| (digits, unit)))
+        .unwrap_or((&lowercase, 1));
+    parse_number(digits)?
+        .checked_mul(unit)
+        .ok_or_else(|| format!("invalid size: {}", s))
+}
@@ -0,0 +1,314 @@
+//! Command line companion of the `memflow-lime` connector.
+
+use memflow_lime::{
+    carve_layout, convert_to_memprocfs, convert_to_raw_with_options, default_blocks_sidecar,
+    default_sha256_sidecar, extract_range, hash_segments, lime_diff, read_layout_detailed,
+    recover_layout, verify_block_checksums, verify_sha256, volatility_layer, write_hash_manifest,
+    HashAlgorithm, LimeLayout, LimeSegment, LimeWriter, ParseMode, RawOutput,
+};
+
+use std::env;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: memflow-lime <command> [arguments]
+
+Commands:
+    info <dump> [--recover|--carve]      list the memory ranges, their sizes and the gaps between them,
+                                         skipping corrupt headers up to the next valid one if --recover,
+                                         searching the whole file for headers if --carve
+    verify <dump>                        check that all the headers are valid and the file is complete,
+                                         the SHA-256 digest if a <dump>.sha256 file exists and the
+                                         block checksums if a <dump>.blocks file exists
+    convert <dump> <raw> [--dense|--memprocfs]
+                                         write a raw physical image of the dump, its gaps being holes
+                                         of a sparse file unless --dense zero-fills them, and with
+                                         --memprocfs the <raw>.memmap memory map MemProcFS opens it with
+    extract <dump> <out> <start> <end>   write the physical window [start, end] to a new LiME file,
+                                         compressed if out ends with .gz or .zst
+    diff <dump_a> <dump_b> [granularity] list the ranges that differ between two dumps, compared in
+                                         blocks of granularity bytes (default 0x1000)
+    hash <dump> [manifest]               print the SHA-256 digest of every range, and write them to
+                                         manifest if given
+    volatility <dump>                    print the Volatility3 layer definition of the dump as JSON,
+                                         and the vol arguments opening it
+    acquire <out>                        write the RAM of this machine, listed by /proc/iomem, to a new
+                                         LiME file, reading it from /dev/crash or /dev/mem as root
+";
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let result = match args.as_slice() {
+        ["info", dump] => layout(dump).and_then(info),
+        ["info", dump, "--recover"] => recover_layout(dump, ParseMode::Strict)
+            .map_err(|e| format!("unable to parse {}: {}", dump, e))
+            .and_then(info),
+        ["info", dump, "--carve"] => carve_layout(dump, ParseMode::Strict)
+            .map_err(|e| format!("unable to carve {}: {}", dump, e))
+            .and_then(info),
+        ["verify", dump] => verify(dump),
+        ["convert", dump, raw] => convert(dump, raw, RawOutput::Sparse),
+        ["convert", dump, raw, "--dense"] => convert(dump, raw, RawOutput::Dense),
+        ["convert", dump, raw, "--memprocfs"] => convert_to_memprocfs(dump, raw, |_, _| ())
+            .map_err(|e| format!("conversion failed: {}", e)),
+        ["extract", dump, out, start, end] => parse_address(start)
+            .and_then(|start| Ok((start, parse_address(end)?)))
+            .and_then(|(start, end)| extract(dump, out, start, end)),
+        ["diff", dump_a, dump_b] => diff(dump_a, dump_b, 0x1000),
+        ["diff", dump_a, dump_b, granularity] => {
+            parse_address(granularity).and_then(|granularity| diff(dump_a, dump_b, granularity))
+        }
+        ["hash", dump] => hash(dump, None),
+        ["hash", dump, manifest] => hash(dump, Some(manifest)),
+        ["volatility", dump] => volatility(dump),
+        ["acquire", out] => acquire(out),
+        _ => {
+            eprint!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(msg) => {
+            eprintln!("memflow-lime: {}", msg);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Print the segments of a dump and the gaps between them.
+fn info(layout: LimeLayout) -> Result<(), String> {
+    let mut segments = layout.segments.clone();
+    segments.sort_by_key(|segment| segment.s_addr);
+
+    println!(
+        "{:>18}  {:>18}  {:>18}  {:>14}",
+        "start", "end", "file offset", "size"
+    );
+    let mut prev_end: Option<u64> = None;
+    for segment in &segments {
+        if let Some(prev_end) = prev_end {
+            if segment.s_addr > prev_end + 1 {
+                println!(
+                    "{:>18}  {:>18}  {:>18}  {:>14}",
+                    format!("{:#x}", prev_end + 1),
+                    format!("{:#x}", segment.s_addr - 1),
+                    "(gap)",
+                    segment.s_addr - prev_end - 1
+                );
+            }
+        }
+        println!(
+            "{:>18}  {:>18}  {:>18}  {:>14}",
+            format!("{:#x}", segment.s_addr),
+            format!("{:#x}", segment.e_addr),
+            format!("{:#x}", segment.file_offset),
+            segment.size()
+        );
+        prev_end = Some(prev_end.map_or(segment.e_addr, |e| e.max(segment.e_addr)));
+    }
+
+    let total = segments.iter().map(LimeSegment::size).sum::<u64>();
+    println!("{} ranges, {} bytes of memory", segments.len(), total);
+
+    if let Some(truncation) = layout.truncation {
+        println!(
+            "truncated file, range {:#x}-{:#x} is missing",
+            truncation.s_addr, truncation.e_addr
+        );
+    }
+    for skipped in &layout.skipped {
+        println!(
+            "{} bytes skipped at offset {:#x}",
+            skipped.len, skipped.offset
+        );
+    }
+    if let Some(digest) = layout.digest {
+        let hex = digest
+            .value
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        println!(
+            "{} digest trailer: {}",
+            digest.algorithm_hint().unwrap_or("unknown"),
+            hex
+        );
+    }
+    Ok(())
+}
+
+/// Check that `dump` parses, that its size matches the declared ranges and, if a sidecar
+/// file is present, that its SHA-256 digest matches.
+fn verify(dump: &str) -> Result<(), String> {
+    let layout = layout(dump)?;
+    if let Some(truncation) = layout.truncation {
+        return Err(format!(
+            "{} is truncated, range {:#x}-{:#x} is missing",
+            dump, truncation.s_addr, truncation.e_addr
+        ));
+    }
+
+    let file_size = fs::metadata(dump)
+        .map_err(|e| format!("unable to stat {}: {}", dump, e))?
+        .len();
+    let expected_size = match &layout.digest {
+        Some(digest) => file_size.min(digest.covered_len),
+        None => file_size,
+    };
+    let declared_size = layout
+        .segments
+        .last()
+        .map_or(0, |segment| segment.file_offset + segment.size());
+
+    if declared_size != expected_size {
+        return Err(format!(
+            "{} holds {} bytes of ranges but its headers describe {} bytes",
+            dump, expected_size, declared_size
+        ));
+    }
+
+    let sidecar = default_sha256_sidecar(dump);
+    if sidecar.exists() {
+        verify_sha256(dump, &sidecar).map_err(|_| {
+            format!(
+                "{} does not match the digest in {}",
+                dump,
+                sidecar.display()
+            )
+        })?;
+        println!("{}: SHA-256 digest matches {}", dump, sidecar.display());
+    }
+
+    let manifest = default_blocks_sidecar(dump);
+    if manifest.exists() {
+        let corrupt = verify_block_checksums(dump, &manifest)
+            .map_err(|e| format!("unable to check the blocks of {}: {}", dump, e))?;
+        if !corrupt.is_empty() {
+            for block in &corrupt {
+                eprintln!(
+                    "{}: block {:#x}-{:#x} does not match its checksum",
+                    dump, block.s_addr, block.e_addr
+                );
+            }
+            return Err(format!(
+                "{} has {} corrupt blocks, see {}",
+                dump,
+                corrupt.len(),
+                manifest.display()
+            ));
+        }
+        println!("{}: every block matches {}", dump, manifest.display());
+    }
+
+    println!("{}: OK ({} ranges)", dump, layout.segments.len());
+    Ok(())
+}
+
+/// Print the ranges that differ between `dump_a` and `dump_b`.
+fn diff(dump_a: &str, dump_b: &str, granularity: u64) -> Result<(), String> {
+    let changes = lime_diff(dump_a, dump_b, granularity)
+        .map_err(|e| format!("unable to compare {} and {}: {}", dump_a, dump_b, e))?;
+
+    println!("{:>18}  {:>18}  {:>14}  change", "start", "end", "size");
+    for change in &changes {
+        println!(
+            "{:>18}  {:>18}  {:>14}  {:?}",
+            format!("{:#x}", change.s_addr),
+            format!("{:#x}", change.e_addr),
+            change.size(),
+            change.kind
+        );
+    }
+    let total = changes.iter().map(|change| change.size()).sum::<u64>();
+    println!("{} changed ranges, {} bytes", changes.len(), total);
+    Ok(())
+}
+
+/// Print the SHA-256 digest of every range of `dump`, writing them to `manifest` too.
+fn hash(dump: &str, manifest: Option<&str>) -> Result<(), String> {
+    let digests = hash_segments(dump, HashAlgorithm::Sha256)
+        .map_err(|e| format!("unable to hash {}: {}", dump, e))?;
+
+    for digest in &digests {
+        let hex = digest
+            .digest
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        println!(
+            "{}  {:#x}-{:#x}",
+            hex, digest.segment.s_addr, digest.segment.e_addr
+        );
+    }
+    if let Some(manifest) = manifest {
+        write_hash_manifest(manifest, HashAlgorithm::Sha256, &digests)
+            .map_err(|e| format!("unable to write {}: {}", manifest, e))?;
+    }
+    Ok(())
+}
+
+/// Write the raw physical image of `dump` to `raw`.
+fn convert(dump: &str, raw: &str, output: RawOutput) -> Result<(), String> {
+    convert_to_raw_with_options(dump, raw, output, |_, _| ())
+        .map_err(|e| format!("conversion failed: {}", e))
+}
+
+/// Write the physical window `[start, end]` of `dump` to `out`, compressed if `out` ends with
+/// `.gz` or `.zst` and the `compression` feature is enabled.
+fn extract(dump: &str, out: &str, start: u64, end: u64) -> Result<(), String> {
+    #[cfg(feature = "compression")]
+    if let Some(compression) = memflow_lime::LimeCompression::from_path(out) {
+        return memflow_lime::extract_range_compressed(dump, out, start, end, compression)
+            .map_err(|e| format!("extraction failed: {}", e));
+    }
+    extract_range(dump, out, start, end).map_err(|e| format!("extraction failed: {}", e))
+}
+
+/// Print the Volatility3 layer definition of `dump` and the `vol` arguments opening it.
+fn volatility(dump: &str) -> Result<(), String> {
+    let layer = volatility_layer(dump, ParseMode::Strict)
+        .map_err(|e| format!("unable to export {}: {}", dump, e))?;
+    print!("{}", layer.to_json());
+    eprintln!("vol {} <plugin>", layer.as_volatility_args().join(" "));
+    Ok(())
+}
+
+/// Acquire the physical memory of this machine into the new `LiME` file `out`.
+fn acquire(out: &str) -> Result<(), String> {
+    let file = File::create_new(out).map_err(|e| format!("unable to create {}: {}", out, e))?;
+    let mut writer = LimeWriter::new(BufWriter::new(file));
+    let device = writer
+        .acquire_local(|_, _| ())
+        .map_err(|e| format!("acquisition failed: {}", e))?;
+    let (ranges, bytes) = (writer.ranges_written(), writer.bytes_written());
+    writer
+        .finish()
+        .map_err(|e| format!("unable to write {}: {}", out, e))?;
+    eprintln!(
+        "{} ranges, {} bytes of memory read from {}",
+        ranges,
+        bytes,
+        device.display()
+    );
+    Ok(())
+}
+
+fn layout(dump: &str) -> Result<LimeLayout, String> {
+    read_layout_detailed(dump, ParseMode::Strict)
+        .map_err(|e| format!("unable to parse {}: {}", dump, e))
+}
+
+/// Parse a decimal or `0x` prefixed hexadecimal address.
+fn parse_address(s: &str) -> Result<u64, String> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+    .map_err(|_| format!("invalid address: {}", s))
+}
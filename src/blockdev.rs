@@ -0,0 +1,98 @@
+use std::fs::{self, File, Metadata};
+use std::io::{self, Seek, SeekFrom};
+use std::path::Path;
+
+/// `BLKGETSIZE64` request of the generic Linux ioctl encoding, `_IOR(0x12, 114, u64)`
+#[cfg(all(
+    target_os = "linux",
+    feature = "blockdev",
+    any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        target_arch = "arm",
+        target_arch = "aarch64",
+        target_arch = "riscv64"
+    )
+))]
+const BLKGETSIZE64: u64 = 0x8008_1272;
+
+/// Whether `metadata` describes a block device, e.g. a partition an acquisition was written to.
+pub(crate) fn is_block_device(metadata: &Metadata) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        metadata.file_type().is_block_device()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        false
+    }
+}
+
+/// Whether the file at `path` is a block device.
+pub(crate) fn is_block_device_path<P: AsRef<Path>>(path: P) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| is_block_device(&metadata))
+}
+
+/// Size of `file` in bytes.
+///
+/// The metadata of a block device reports no size, so the size of the device is queried
+/// instead, with the `BLKGETSIZE64` ioctl if the `blockdev` feature is enabled on Linux and by
+/// seeking to its end otherwise, the seek of the file being restored.
+pub(crate) fn file_size(file: &File) -> io::Result<u64> {
+    let metadata = file.metadata()?;
+    if !is_block_device(&metadata) {
+        return Ok(metadata.len());
+    }
+
+    #[cfg(all(
+        target_os = "linux",
+        feature = "blockdev",
+        any(
+            target_arch = "x86",
+            target_arch = "x86_64",
+            target_arch = "arm",
+            target_arch = "aarch64",
+            target_arch = "riscv64"
+        )
+    ))]
+    {
+        use std::os::unix::io::AsRawFd;
+        let mut size = 0u64;
+        // SAFETY: BLKGETSIZE64 writes the size of the device to the u64 it is given.
+        if unsafe { libc::ioctl(file.as_raw_fd(), BLKGETSIZE64 as _, &mut size) } == 0 {
+            return Ok(size);
+        }
+        log::debug!(
+            "BLKGETSIZE64 failed, measuring the device by seeking: {}",
+            io::Error::last_os_error()
+        );
+    }
+
+    let mut file = file;
+    let pos = file.stream_position()?;
+    let size = file.seek(SeekFrom::End(0))?;
+    file.seek(SeekFrom::Start(pos))?;
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn regular_files_report_their_length() {
+        let path = std::env::temp_dir().join("memflow_lime_blockdev.tmp");
+        let mut file = File::create(&path).unwrap();
+        file.write_all(&[0; 0x1234]).unwrap();
+        let size = file_size(&file).unwrap();
+        let device = is_block_device_path(&path);
+
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(size, 0x1234);
+        assert!(!device);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::batch::BatchedFile;
+use crate::config::PageCacheConfig;
+use crate::stats::LimeStats;
+
+use memflow::prelude::v1::*;
+use memflow::types::cache::{CacheValidator, TimedCacheValidator};
+
+use std::sync::Arc;
+
+/// Cache validator keeping pages valid until they are evicted, or for a fixed time.
+///
+/// The content of a dump does not change behind the connector's back, so unlike the time based
+/// validator of memflow there is no reason to expire a cached page unless `cache_time` asks for
+/// it.
+#[derive(Clone)]
+pub(crate) enum DumpCacheValidator {
+    Evicted(Vec<bool>),
+    Timed(TimedCacheValidator),
+}
+
+impl DumpCacheValidator {
+    /// Validator for the cache validity of `cache`
+    pub(crate) fn new(cache: &PageCacheConfig) -> Self {
+        match cache.validity {
+            None => Self::default(),
+            Some(validity) => Self::Timed(TimedCacheValidator::new(validity.into())),
+        }
+    }
+}
+
+impl Default for DumpCacheValidator {
+    fn default() -> Self {
+        Self::Evicted(Vec::new())
+    }
+}
+
+impl CacheValidator for DumpCacheValidator {
+    fn allocate_slots(&mut self, slot_count: usize) {
+        match self {
+            Self::Evicted(valid) => *valid = vec![false; slot_count],
+            Self::Timed(validator) => validator.allocate_slots(slot_count),
+        }
+    }
+
+    fn update_validity(&mut self) {
+        if let Self::Timed(validator) = self {
+            validator.update_validity();
+        }
+    }
+
+    fn is_slot_valid(&self, slot_id: usize) -> bool {
+        match self {
+            Self::Evicted(valid) => valid[slot_id],
+            Self::Timed(validator) => validator.is_slot_valid(slot_id),
+        }
+    }
+
+    fn validate_slot(&mut self, slot_id: usize) {
+        match self {
+            Self::Evicted(valid) => valid[slot_id] = true,
+            Self::Timed(validator) => validator.validate_slot(slot_id),
+        }
+    }
+
+    fn invalidate_slot(&mut self, slot_id: usize) {
+        match self {
+            Self::Evicted(valid) => valid[slot_id] = false,
+            Self::Timed(validator) => validator.invalidate_slot(slot_id),
+        }
+    }
+}
+
+/// Dump file mapped according to the `LiME` headers, optionally behind a page cache
+#[derive(Clone)]
+pub(crate) enum MappedFile {
+    Direct(BatchedFile),
+    Cached {
+        mem: CachedPhysicalMemory<'static, BatchedFile, DumpCacheValidator>,
+        page_size: u64,
+        stats: Arc<LimeStats>,
+    },
+}
+
+impl MappedFile {
+    /// Put `mem` behind a page cache if `cache` is set, recording the IO in `stats`.
+    ///
+    /// Unlike the memflow cache middleware, which only keeps page tables and read-only pages,
+    /// every page is cached since none of them can change unless `cache_time` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the cache can not be built
+    ///
+    pub(crate) fn new(
+        mem: BatchedFile,
+        cache: Option<PageCacheConfig>,
+        stats: Arc<LimeStats>,
+    ) -> Result<Self> {
+        let mem = mem.with_stats(stats.clone(), cache.is_some());
+        match cache {
+            None => Ok(Self::Direct(mem)),
+            Some(cache) => CachedPhysicalMemory::builder(mem)
+                .validator(DumpCacheValidator::new(&cache))
+                .page_size(cache.page_size)
+                .cache_size(cache.size)
+                .page_type_mask(PageType::all())
+                .build()
+                .map(|mem| Self::Cached {
+                    mem,
+                    page_size: cache.page_size as u64,
+                    stats,
+                }),
+        }
+    }
+
+    /// Map the file according to the memory map of `mem`, the pages cached so far being kept.
+    pub(crate) fn remap(&mut self, mem: BatchedFile) {
+        match self {
+            Self::Direct(direct) => direct.set_mem_map(&mem.mem_map().clone().into_vec()),
+            Self::Cached { mem: cached, .. } => {
+                cached.set_mem_map(&mem.mem_map().clone().into_vec())
+            }
+        }
+    }
+
+    /// Drop the page cache, if any, and get back the mapped file.
+    pub(crate) fn into_inner(self) -> BatchedFile {
+        match self {
+            Self::Direct(mem) => mem,
+            Self::Cached { mem, .. } => mem.into_inner(),
+        }
+    }
+}
+
+impl PhysicalMemory for MappedFile {
+    fn phys_read_raw_iter(&mut self, data: PhysicalReadMemOps) -> Result<()> {
+        match self {
+            Self::Direct(mem) => mem.phys_read_raw_iter(data),
+            Self::Cached {
+                mem,
+                page_size,
+                stats,
+            } => {
+                let MemOps { inp, out, out_fail } = data;
+                let page_size = *page_size;
+                let inp = inp.inspect(|CTup3(addr, _, buf)| {
+                    if !buf.is_empty() {
+                        let skew = addr.to_umem() % page_size;
+                        stats.record_cache_lookups((skew + buf.len() as u64).div_ceil(page_size));
+                    }
+                });
+                MemOps::with_raw(inp, out, out_fail, |data| mem.phys_read_raw_iter(data))
+            }
+        }
+    }
+
+    fn phys_write_raw_iter(&mut self, data: PhysicalWriteMemOps) -> Result<()> {
+        match self {
+            Self::Direct(mem) => mem.phys_write_raw_iter(data),
+            Self::Cached { mem, .. } => mem.phys_write_raw_iter(data),
+        }
+    }
+
+    fn metadata(&self) -> PhysicalMemoryMetadata {
+        match self {
+            Self::Direct(mem) => mem.metadata(),
+            Self::Cached { mem, .. } => mem.metadata(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validator_keeps_pages_until_invalidated() {
+        let mut validator = DumpCacheValidator::default();
+        validator.allocate_slots(2);
+        assert!(!validator.is_slot_valid(1));
+
+        validator.validate_slot(1);
+        validator.update_validity();
+        assert!(validator.is_slot_valid(1));
+
+        validator.invalidate_slot(1);
+        assert!(!validator.is_slot_valid(1));
+    }
+}
@@ -0,0 +1,147 @@
+use crate::config::CaptureConfig;
+
+use memflow::prelude::v1::*;
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Size of the chunks copied from the socket to the spool file
+const CHUNK_SIZE: usize = 0x10_0000;
+
+/// Progress of a dump received over TCP, see
+/// [`LimeMemory::capture_status`](crate::LimeMemory::capture_status)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LimeCaptureStatus {
+    /// Bytes of the stream written to the spool file so far
+    pub bytes_received: u64,
+    /// Whether the stream is over, the spool file holding the whole dump unless `error` is set
+    pub finished: bool,
+    /// Why the capture stopped before the end of the stream, `None` if it did not
+    pub error: Option<String>,
+}
+
+/// Capture of a dump, shared by the thread receiving it and the connectors reading the spool
+/// file
+#[derive(Debug, Default)]
+pub(crate) struct Capture {
+    received: AtomicU64,
+    finished: AtomicBool,
+    error: Mutex<Option<String>>,
+}
+
+impl Capture {
+    /// Whether data may still be appended to the spool file. Once it returns `false`, every
+    /// byte of the dump has been written.
+    pub(crate) fn is_receiving(&self) -> bool {
+        !self.finished.load(Ordering::Acquire)
+    }
+
+    /// Progress of the capture so far.
+    pub(crate) fn status(&self) -> LimeCaptureStatus {
+        LimeCaptureStatus {
+            finished: !self.is_receiving(),
+            bytes_received: self.received.load(Ordering::Acquire),
+            error: self.error.lock().map_or(None, |error| error.clone()),
+        }
+    }
+}
+
+/// Connect to `LiME` listening at the address of `capture` and receive the dump it streams
+/// into the new file `spool` from a background thread, the connector reading the spool file
+/// as it grows.
+///
+/// The socket is only read once the previous chunk reached the spool file, so a disk slower
+/// than the network holds `LiME` back through TCP flow control rather than piling the dump
+/// up in memory, and the reads of the connector never wait for the network. The capture goes
+/// on until the stream ends, even after the connectors are dropped.
+///
+/// # Errors
+///
+/// Returns `Err` if the spool file already exists or can not be created, or if the
+/// connection fails
+///
+pub(crate) fn start(capture: &CaptureConfig, spool: &Path) -> Result<Arc<Capture>> {
+    // An existing spool file may hold an earlier acquisition, never overwrite it.
+    let mut out = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(spool)
+        .map_err(|e| {
+            let kind = match e.kind() {
+                io::ErrorKind::AlreadyExists => ErrorKind::AlreadyExists,
+                _ => ErrorKind::UnableToWriteFile,
+            };
+            Error(ErrorOrigin::Connector, kind).log_error(format!(
+                "Unable to create the spool file {}: {}",
+                spool.display(),
+                e
+            ))
+        })?;
+    let mut stream = TcpStream::connect(&capture.address).map_err(|e| {
+        let _ = fs::remove_file(spool);
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+            "Unable to connect to LiME at {}: {}",
+            capture.address, e
+        ))
+    })?;
+    log::info!(
+        "Receiving the dump streamed from {} into {}",
+        capture.address,
+        spool.display()
+    );
+
+    let state = Arc::new(Capture::default());
+    let shared = state.clone();
+    let address = capture.address.clone();
+    let spool = spool.to_path_buf();
+    thread::Builder::new()
+        .name("lime-capture".into())
+        .spawn(move || {
+            match receive(&mut stream, &mut out, &shared.received) {
+                Ok(()) => log::info!(
+                    "Received the dump streamed from {}, {:#x} bytes",
+                    address,
+                    shared.received.load(Ordering::Acquire)
+                ),
+                Err(e) => {
+                    log::error!(
+                        "Capture of the dump streamed from {} into {} failed: {}",
+                        address,
+                        spool.display(),
+                        e
+                    );
+                    if let Ok(mut error) = shared.error.lock() {
+                        *error = Some(e.to_string());
+                    }
+                }
+            }
+            shared.finished.store(true, Ordering::Release);
+        })
+        .map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::Unknown)
+                .log_error("Unable to start the capture thread")
+        })?;
+    Ok(state)
+}
+
+/// Copy `stream` to `out` until it ends, adding the bytes written to `received`.
+fn receive<R: Read>(stream: &mut R, out: &mut File, received: &AtomicU64) -> io::Result<()> {
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    loop {
+        let len = match stream.read(&mut buf) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        out.write_all(&buf[..len])?;
+        received.fetch_add(len as u64, Ordering::Release);
+    }
+    out.sync_all()
+}
@@ -0,0 +1,426 @@
+use crate::config::ParseMode;
+use crate::dumpfile::DumpFile;
+use crate::segment::read_layout;
+
+use memflow::prelude::v1::*;
+
+use crc32fast::Hasher;
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// CRC-32 of a block of a `LiME` section, as recorded in a [`LimeBlockManifest`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LimeBlockChecksum {
+    /// Physical address of the first byte of the block
+    pub s_addr: u64,
+    /// Physical address of the last byte of the block
+    pub e_addr: u64,
+    /// Offset of the block data in the file
+    pub file_offset: u64,
+    /// CRC-32 (IEEE) of the block data
+    pub crc32: u32,
+}
+
+impl LimeBlockChecksum {
+    /// Size in bytes of the block.
+    pub const fn size(&self) -> u64 {
+        self.e_addr - self.s_addr + 1
+    }
+}
+
+/// Per-block checksums of a `LiME` file, stored next to it in a sidecar (`<dump>.blocks`)
+///
+/// The data of every section is cut into blocks at the physical addresses multiple of
+/// `block_size`, a block never spanning two sections, and the CRC-32 of each block is
+/// recorded with its physical window and file offset. Unlike a digest of the whole file,
+/// a mismatch tells which blocks rotted. The dump itself is left untouched, so readers
+/// unaware of the sidecar are not affected.
+///
+/// The sidecar starts with a `# crc32 <block_size>` line, followed by one
+/// `<crc32>  <s_addr>-<e_addr>  <file offset>` line per block, in file order, the checksum
+/// being 8 hexadecimal digits and the addresses inclusive and in hexadecimal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimeBlockManifest {
+    /// Size in bytes of the blocks, blocks being aligned on it
+    pub block_size: u64,
+    /// Checksums of the blocks, in file order
+    pub blocks: Vec<LimeBlockChecksum>,
+}
+
+impl LimeBlockManifest {
+    /// Read the block manifest at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the manifest can not be read or is malformed
+    ///
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let manifest = fs::read_to_string(path).map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+                .log_error("Unable to read the block manifest")
+        })?;
+        let malformed = |line: &str| {
+            Error(ErrorOrigin::Connector, ErrorKind::Encoding)
+                .log_error(format!("Malformed block manifest line `{}`", line))
+        };
+
+        let mut lines = manifest.lines();
+        let first = lines.next().unwrap_or_default();
+        let block_size = first
+            .strip_prefix("# crc32 ")
+            .and_then(|size| parse_hex(size.trim()))
+            .filter(|size| *size != 0)
+            .ok_or_else(|| malformed(first))?;
+
+        let mut blocks = Vec::new();
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            let mut fields = line.split_whitespace();
+            let block = (|| {
+                let crc32 = fields.next().filter(|crc| crc.len() == 8)?;
+                let (s_addr, e_addr) = fields.next()?.split_once('-')?;
+                let block = LimeBlockChecksum {
+                    crc32: u32::from_str_radix(crc32, 16).ok()?,
+                    s_addr: parse_hex(s_addr)?,
+                    e_addr: parse_hex(e_addr)?,
+                    file_offset: parse_hex(fields.next()?)?,
+                };
+                // A block fits in one aligned window of the block size.
+                (fields.next().is_none()
+                    && block.s_addr <= block.e_addr
+                    && block.e_addr - block.s_addr < block_size
+                    && block.file_offset.checked_add(block.size()).is_some())
+                .then_some(block)
+            })()
+            .ok_or_else(|| malformed(line))?;
+            blocks.push(block);
+        }
+        Ok(Self { block_size, blocks })
+    }
+
+    /// Write the manifest to `path`, creating or truncating it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the manifest can not be written
+    ///
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let write = || -> io::Result<()> {
+            let mut out = BufWriter::new(File::create(path)?);
+            writeln!(out, "# crc32 {:#x}", self.block_size)?;
+            for block in &self.blocks {
+                writeln!(
+                    out,
+                    "{:08x}  {:#x}-{:#x}  {:#x}",
+                    block.crc32, block.s_addr, block.e_addr, block.file_offset
+                )?;
+            }
+            out.flush()
+        };
+        write().map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+                .log_error("Unable to write the block manifest")
+        })
+    }
+}
+
+/// Parse a hexadecimal number, with or without its `0x` prefix.
+pub(crate) fn parse_hex(hex: &str) -> Option<u64> {
+    u64::from_str_radix(hex.strip_prefix("0x").unwrap_or(hex), 16).ok()
+}
+
+/// Default location of the block manifest of `lime_path`, i.e. `<lime_path>.blocks`.
+pub fn default_blocks_sidecar<P: AsRef<Path>>(lime_path: P) -> PathBuf {
+    let mut path = OsString::from(lime_path.as_ref());
+    path.push(".blocks");
+    path.into()
+}
+
+/// Computes the block checksums of sections as their data goes by
+#[derive(Debug, Clone)]
+pub(crate) struct BlockHasher {
+    manifest: LimeBlockManifest,
+    /// Block being hashed
+    current: Option<(LimeBlockChecksum, Hasher)>,
+}
+
+impl BlockHasher {
+    /// Hash blocks of `block_size` bytes, which is not zero.
+    pub(crate) fn new(block_size: u64) -> Self {
+        Self {
+            manifest: LimeBlockManifest {
+                block_size,
+                blocks: Vec::new(),
+            },
+            current: None,
+        }
+    }
+
+    /// Hash `data`, the bytes of the current section following the physical address
+    /// `s_addr`, stored at `file_offset`.
+    pub(crate) fn update(&mut self, s_addr: u64, file_offset: u64, data: &[u8]) {
+        let block_size = self.manifest.block_size;
+        let mut start = 0;
+        while start < data.len() {
+            let addr = s_addr + start as u64;
+            if addr.is_multiple_of(block_size) {
+                self.end_block();
+            }
+            let block_end = (addr - addr % block_size).saturating_add(block_size - 1);
+            let len = usize::try_from((block_end - addr).saturating_add(1))
+                .map_or(data.len() - start, |len| (data.len() - start).min(len));
+            let chunk = &data[start..start + len];
+
+            let (block, hasher) = self.current.get_or_insert_with(|| {
+                let block = LimeBlockChecksum {
+                    s_addr: addr,
+                    e_addr: addr,
+                    file_offset: file_offset + start as u64,
+                    crc32: 0,
+                };
+                (block, Hasher::new())
+            });
+            block.e_addr = addr + (len as u64 - 1);
+            hasher.update(chunk);
+            start += len;
+        }
+    }
+
+    /// Complete the block being hashed, the current section being over.
+    pub(crate) fn end_block(&mut self) {
+        if let Some((mut block, hasher)) = self.current.take() {
+            block.crc32 = hasher.finalize();
+            self.manifest.blocks.push(block);
+        }
+    }
+
+    /// Checksums of the blocks completed so far.
+    pub(crate) fn manifest(&self) -> &LimeBlockManifest {
+        &self.manifest
+    }
+}
+
+/// Compute the block checksums of the `LiME` file at `path`, e.g. to add a block manifest to
+/// a dump written without one.
+///
+/// # Errors
+///
+/// Returns `Err` if `block_size` is zero or an error occurred while reading or parsing the
+/// file
+///
+pub fn block_checksums<P: AsRef<Path>>(path: P, block_size: u64) -> Result<LimeBlockManifest> {
+    if block_size == 0 {
+        return Err(Error(ErrorOrigin::Connector, ErrorKind::InvalidArgument)
+            .log_error("The block size can not be zero"));
+    }
+    let segments = read_layout(&path, ParseMode::Strict)?.segments;
+    let mut file =
+        File::open(path).map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+    let mut hasher = BlockHasher::new(block_size);
+    let mut buff = vec![0u8; 1 << 20];
+
+    for segment in segments {
+        file.seek(SeekFrom::Start(segment.file_offset))
+            .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToSeekFile))?;
+        let mut done = 0;
+        while done < segment.size() {
+            let len = (segment.size() - done).min(buff.len() as u64) as usize;
+            file.read_exact(&mut buff[..len]).map_err(|_| {
+                Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+                    "Unable to read range {:#x}-{:#x} while hashing it",
+                    segment.s_addr, segment.e_addr
+                ))
+            })?;
+            hasher.update(
+                segment.s_addr + done,
+                segment.file_offset + done,
+                &buff[..len],
+            );
+            done += len as u64;
+        }
+        hasher.end_block();
+    }
+    Ok(hasher.manifest)
+}
+
+/// Check every block of the `LiME` file at `lime_path` against the block manifest at
+/// `manifest_path`, returning the blocks whose data does not match, e.g. to locate the
+/// damage after a whole-file digest failed.
+///
+/// # Errors
+///
+/// Returns `Err` if the manifest can not be read or is malformed, or if an error occurred
+/// while reading the file, blocks cut short by a truncation being reported as corrupt
+///
+pub fn verify_block_checksums<P: AsRef<Path>, Q: AsRef<Path>>(
+    lime_path: P,
+    manifest_path: Q,
+) -> Result<Vec<LimeBlockChecksum>> {
+    let manifest = LimeBlockManifest::read(manifest_path)?;
+    let mut file = DumpFile::from(
+        File::open(lime_path)
+            .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?,
+    );
+    let mut corrupt = Vec::new();
+    let mut buff = Vec::new();
+    for block in manifest.blocks {
+        if !block_matches(&mut file, &block, &mut buff)? {
+            corrupt.push(block);
+        }
+    }
+    Ok(corrupt)
+}
+
+/// Whether the data of `block` in `file` matches its checksum, a block cut short by the end
+/// of the file not matching.
+fn block_matches(
+    file: &mut DumpFile,
+    block: &LimeBlockChecksum,
+    buff: &mut Vec<u8>,
+) -> Result<bool> {
+    buff.resize(block.size() as usize, 0);
+    file.seek(SeekFrom::Start(block.file_offset))
+        .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToSeekFile))?;
+    match file.read_exact(buff) {
+        Ok(()) => Ok(crc32fast::hash(buff) == block.crc32),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(_) => Err(
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+                "Unable to read block {:#x}-{:#x}",
+                block.s_addr, block.e_addr
+            )),
+        ),
+    }
+}
+
+/// Whether a block has been checked yet, and how it went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockState {
+    Unchecked,
+    Valid,
+    Corrupt,
+}
+
+/// Checks the blocks of a dump against their manifest the first time a read touches them,
+/// shared by the clones of a connector
+#[derive(Clone)]
+pub(crate) struct BlockVerifier {
+    file: DumpFile,
+    /// Blocks of the manifest, sorted by file offset
+    blocks: Arc<[LimeBlockChecksum]>,
+    states: Arc<Mutex<Vec<BlockState>>>,
+}
+
+impl BlockVerifier {
+    /// Check the blocks of `file` against the manifest at `manifest_path` as they are read.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the manifest can not be read or is malformed
+    ///
+    pub(crate) fn new(file: DumpFile, manifest_path: &Path) -> Result<Self> {
+        let mut blocks = LimeBlockManifest::read(manifest_path)?.blocks;
+        blocks.sort_by_key(|block| block.file_offset);
+        Ok(Self {
+            file,
+            states: Arc::new(Mutex::new(vec![BlockState::Unchecked; blocks.len()])),
+            blocks: blocks.into(),
+        })
+    }
+
+    /// File windows `[offset, offset + len)` of corrupt blocks intersecting
+    /// `[offset, offset + len)`, clipped to it and sorted, checking the blocks not checked
+    /// yet.
+    ///
+    /// Blocks that can not be read are left unchecked, the read of their data failing on
+    /// its own.
+    pub(crate) fn corrupt_windows(&mut self, offset: u64, len: u64) -> Vec<(u64, u64)> {
+        let end = offset.saturating_add(len);
+        let first = self
+            .blocks
+            .partition_point(|block| block.file_offset + block.size() <= offset);
+        let mut windows = Vec::new();
+        let mut buff = Vec::new();
+        for (index, block) in self.blocks.iter().enumerate().skip(first) {
+            if block.file_offset >= end {
+                break;
+            }
+            let state = self.states.lock().unwrap()[index];
+            let state = match state {
+                BlockState::Unchecked => {
+                    let state = match block_matches(&mut self.file, block, &mut buff) {
+                        Ok(true) => BlockState::Valid,
+                        Ok(false) => {
+                            log::warn!(
+                                "LiME block {:#x}-{:#x} does not match its checksum",
+                                block.s_addr,
+                                block.e_addr
+                            );
+                            BlockState::Corrupt
+                        }
+                        Err(_) => BlockState::Unchecked,
+                    };
+                    self.states.lock().unwrap()[index] = state;
+                    state
+                }
+                state => state,
+            };
+            if state == BlockState::Corrupt {
+                let s_offset = block.file_offset.max(offset);
+                let e_offset = (block.file_offset + block.size()).min(end);
+                windows.push((s_offset, e_offset - s_offset));
+            }
+        }
+        windows
+    }
+
+    /// Blocks found corrupt so far, in file order.
+    pub(crate) fn corrupt_blocks(&self) -> Vec<LimeBlockChecksum> {
+        let states = self.states.lock().unwrap();
+        self.blocks
+            .iter()
+            .zip(states.iter())
+            .filter(|(_, state)| **state == BlockState::Corrupt)
+            .map(|(block, _)| *block)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_are_aligned_within_sections() {
+        let mut hasher = BlockHasher::new(0x1000);
+        hasher.update(0x800, 0x20, &[1; 0x400]);
+        hasher.update(0xc00, 0x420, &[1; 0x1800]);
+        hasher.end_block();
+        hasher.update(0x3000, 0x1c40, &[2; 0x10]);
+        hasher.end_block();
+
+        let windows = hasher
+            .manifest()
+            .blocks
+            .iter()
+            .map(|block| (block.s_addr, block.e_addr, block.file_offset))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            windows,
+            [
+                (0x800, 0xfff, 0x20),
+                (0x1000, 0x1fff, 0x820),
+                (0x2000, 0x23ff, 0x1820),
+                (0x3000, 0x300f, 0x1c40),
+            ]
+        );
+        assert_eq!(
+            hasher.manifest().blocks[0].crc32,
+            crc32fast::hash(&[1; 0x800])
+        );
+    }
+}
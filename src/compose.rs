@@ -0,0 +1,73 @@
+use crate::config::{OverlapPolicy, ParseOptions};
+use crate::dumpfile::DumpFile;
+use crate::segment::{
+    coalesce, open_dump, parse_dump_with_progress, resolve_overlaps, LimeSegment,
+};
+
+use memflow::prelude::v1::*;
+
+use std::path::PathBuf;
+
+/// Open the `parts` as one physical address space, the addresses of each part being moved
+/// up by its offset.
+///
+/// Parts are `LiME` files acquired separately from the same machine, e.g. per NUMA node or
+/// RAM and device memory. Unlike overlays, no part shadows another: ranges overlapping
+/// within a part or across parts are resolved according to `overlap`, the parts coming first
+/// in the list counting as coming first in the file.
+///
+/// Returns the parts read as one file and the segments mapping them, sorted and free of
+/// overlaps.
+///
+/// # Errors
+///
+/// Returns `Err` if a part can not be read or parsed, if an offset moves a range past the
+/// end of the address space, or if ranges overlap and `overlap` is [`OverlapPolicy::Error`]
+///
+pub(crate) fn open_composed(
+    parts: &[(PathBuf, u64)],
+    options: ParseOptions,
+    overlap: OverlapPolicy,
+    progress: &mut dyn FnMut(u64, u64),
+) -> Result<(DumpFile, Vec<LimeSegment>)> {
+    let mut composed = Vec::new();
+    let mut start = 0;
+    for (path, rebase) in parts {
+        let mut part = open_dump(path)?;
+        let len = part
+            .metadata()
+            .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?
+            .len();
+        for segment in parse_dump_with_progress(&mut part, options, progress)?.segments {
+            let (Some(s_addr), Some(e_addr)) = (
+                segment.s_addr.checked_add(*rebase),
+                segment.e_addr.checked_add(*rebase),
+            ) else {
+                return Err(
+                    Error(ErrorOrigin::Connector, ErrorKind::OutOfBounds).log_error(format!(
+                        "Offset {:#x} moves range {:#x}-{:#x} of {} past the end of the \
+                        address space",
+                        rebase,
+                        segment.s_addr,
+                        segment.e_addr,
+                        path.display()
+                    )),
+                );
+            };
+            composed.push(LimeSegment {
+                s_addr,
+                e_addr,
+                file_offset: start + segment.file_offset,
+            });
+        }
+        start += len;
+    }
+    log::info!("Composed {} dumps into one address space", parts.len());
+
+    let mapped = coalesce(resolve_overlaps(&composed, overlap)?);
+    let paths = parts
+        .iter()
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    Ok((DumpFile::open_parts(&paths)?, mapped))
+}
@@ -0,0 +1,176 @@
+use crate::export::{clip_dump, write_sections};
+use crate::writer::LimeWriter;
+
+use memflow::prelude::v1::*;
+
+use flate2::write::GzEncoder;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Compression applied to a `LiME` file as it is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimeCompression {
+    /// gzip, `.lime.gz` files
+    Gzip,
+    /// Zstandard, `.lime.zst` files
+    Zstd,
+}
+
+impl LimeCompression {
+    /// Compression matching the extension of `path`, `None` if it is neither `.gz` nor
+    /// `.zst`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "gz" => Some(Self::Gzip),
+            "zst" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// Stream compressing the bytes written to it before passing them to the inner writer
+///
+/// The compressed stream is only complete once [`finish`](Self::finish) has written its
+/// trailer.
+pub struct CompressedWriter<W: Write> {
+    encoder: Encoder<W>,
+}
+
+enum Encoder<W: Write> {
+    Gzip(GzEncoder<W>),
+    Zstd(zstd::Encoder<'static, W>),
+}
+
+impl<W: Write> CompressedWriter<W> {
+    /// Compress the bytes written with `compression`, at its default level, into `out`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the compressor can not be initialized
+    ///
+    pub fn new(out: W, compression: LimeCompression) -> Result<Self> {
+        let encoder = match compression {
+            LimeCompression::Gzip => {
+                Encoder::Gzip(GzEncoder::new(out, flate2::Compression::default()))
+            }
+            LimeCompression::Zstd => zstd::Encoder::new(out, zstd::DEFAULT_COMPRESSION_LEVEL)
+                .map(Encoder::Zstd)
+                .map_err(|_| {
+                    Error(ErrorOrigin::Connector, ErrorKind::Unknown)
+                        .log_error("Unable to initialize the zstd compressor")
+                })?,
+        };
+        Ok(Self { encoder })
+    }
+
+    /// Write the end of the compressed stream and return the inner writer, flushed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an error occurred while writing
+    ///
+    pub fn finish(self) -> Result<W> {
+        let out = match self.encoder {
+            Encoder::Gzip(encoder) => encoder.finish(),
+            Encoder::Zstd(encoder) => encoder.finish(),
+        };
+        out.and_then(|mut out| out.flush().map(|_| out))
+            .map_err(|_| {
+                Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+                    .log_error("Unable to finish the compressed stream")
+            })
+    }
+}
+
+impl<W: Write> Write for CompressedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.encoder {
+            Encoder::Gzip(encoder) => encoder.write(buf),
+            Encoder::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.encoder {
+            Encoder::Gzip(encoder) => encoder.flush(),
+            Encoder::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+impl<W: Write> std::fmt::Debug for CompressedWriter<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let compression = match self.encoder {
+            Encoder::Gzip(_) => LimeCompression::Gzip,
+            Encoder::Zstd(_) => LimeCompression::Zstd,
+        };
+        f.debug_struct("CompressedWriter")
+            .field("compression", &compression)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Extract the physical window `[s_addr, e_addr]` of a `LiME` file into a new `LiME` file
+/// compressed with `compression`, like [`extract_range`](crate::extract_range).
+///
+/// # Errors
+///
+/// Returns `Err` if the window is empty or not backed by the source file, or if an error
+/// occurred while reading the source file or writing the new one
+///
+pub fn extract_range_compressed<P: AsRef<Path>, Q: AsRef<Path>>(
+    lime_path: P,
+    out_path: Q,
+    s_addr: u64,
+    e_addr: u64,
+    compression: LimeCompression,
+) -> Result<()> {
+    let (mut lime_dump, clipped) = clip_dump(lime_path.as_ref(), s_addr, e_addr)?;
+    let mut out = create_compressed(out_path.as_ref(), compression)?;
+    write_sections(&mut lime_dump, &clipped, &mut out)?;
+    out.finish().map(|_| ())
+}
+
+/// Create the file at `path`, or truncate it, to write a compressed stream to it.
+pub(crate) fn create_compressed(
+    path: &Path,
+    compression: LimeCompression,
+) -> Result<CompressedWriter<BufWriter<File>>> {
+    let file = File::create(path).map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+            .log_error("Unable to create the output file")
+    })?;
+    CompressedWriter::new(BufWriter::new(file), compression)
+}
+
+impl LimeWriter<CompressedWriter<BufWriter<File>>> {
+    /// Write a `LiME` file compressed with `compression` to `path`, creating or truncating
+    /// it, so that no uncompressed copy of the dump reaches the disk. Complete the file with
+    /// [`finish_compressed`](Self::finish_compressed).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file can not be created or the compressor initialized
+    ///
+    pub fn create_compressed<P: AsRef<Path>>(
+        path: P,
+        compression: LimeCompression,
+    ) -> Result<Self> {
+        create_compressed(path.as_ref(), compression).map(Self::new)
+    }
+}
+
+impl<W: Write> LimeWriter<CompressedWriter<W>> {
+    /// Write the end of the compressed stream and return the inner writer, see
+    /// [`finish`](Self::finish).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an error occurred while writing
+    ///
+    pub fn finish_compressed(self) -> Result<W> {
+        self.finish()?.finish()
+    }
+}
@@ -0,0 +1,2397 @@
+use crate::blockdev::{is_block_device, is_block_device_path};
+use crate::checksum::default_blocks_sidecar;
+use crate::index::default_index_path;
+use crate::integrity::default_sha256_sidecar;
+use crate::spec::{find_arg, suggest_arg};
+use crate::split::expand_parts;
+use crate::uri::DumpTarget;
+
+use memflow::plugins::args::split_str_args;
+use memflow::prelude::v1::*;
+
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Connector arguments, parsed and validated
+///
+/// Every argument documented by [`help`](crate::help) and [`args_spec`](crate::args_spec)
+/// has a typed field here, with `auto` values resolved to the path they stand for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LimeConfig {
+    /// Path of the `LiME` file, or of the first part of a split dump (`target`)
+    pub path: PathBuf,
+    /// Paths of the parts of a split dump in order, empty if the dump is a single file
+    /// (`target`)
+    pub split: Vec<PathBuf>,
+    /// Member of the archive at `path` holding the dump, `None` if the dump is not in an
+    /// archive (`target`)
+    pub member: Option<String>,
+    /// Paths of the dumps merged into one physical address space, each with the offset added
+    /// to its addresses, empty if a single dump is opened (`target`)
+    pub composed: Vec<(PathBuf, u64)>,
+    /// Container the memory image is stored in (`target`)
+    pub format: DumpFormat,
+    /// Remote host the dump is acquired from into the spool file at `path`, `None` if the
+    /// dump is local (`target`, `ssh_cmd`, `ssh_bin`, `spool`)
+    pub ssh: Option<SshConfig>,
+    /// Host whose `LiME` stream is received into the spool file at `path` while the dump is
+    /// read, `None` if the dump is not received over TCP (`target`, `spool`)
+    pub capture: Option<CaptureConfig>,
+    /// Key the dump is decrypted with, `None` if it is not encrypted (`key`, `keyfile`)
+    pub key: Option<DecryptionKey>,
+    /// How strictly the file is checked (`mode`)
+    pub mode: ParseMode,
+    /// Map headers with a version unknown to the parser (`allow_unknown_version`)
+    pub allow_unknown_version: bool,
+    /// Byte order of the header fields (`endian`)
+    pub byte_order: ByteOrder,
+    /// Mask applied to the physical addresses of the headers (`addr_mask`)
+    pub addr_mask: Option<u64>,
+    /// Bounds on the ranges of the dump (`max_segments`, `max_segment_size`,
+    /// `max_total_size`)
+    pub limits: ParseLimits,
+    /// Skip corrupt headers up to the next plausible one instead of refusing the dump
+    /// (`recover`)
+    pub recover: bool,
+    /// Search the whole file for plausible headers, mapping the segments found in arbitrary
+    /// data (`carve`)
+    pub carve: bool,
+    /// When the headers are walked (`parse`)
+    pub parse: ParseStrategy,
+    /// How the headers are located (`scan`)
+    pub scan: ScanStrategy,
+    /// How overlapping ranges are mapped (`overlap`)
+    pub overlap: OverlapPolicy,
+    /// How ranges not aligned to page boundaries are mapped (`align`)
+    pub align: AlignPolicy,
+    /// Shifts applied to the addresses of the headers before they are mapped, sorted by
+    /// address (`rebase`)
+    pub rebase: Vec<RebaseRule>,
+    /// Physical windows mapped, sorted and merged, the rest of the ranges being skipped,
+    /// `None` if every range is mapped (`ranges`)
+    pub ranges: Option<Vec<(u64, u64)>>,
+    /// How reads between the ranges are handled (`fill_gaps`)
+    pub fill_gaps: GapFill,
+    /// Page cache put in front of the dump (`cached`, `cache_size`, `cache_page_size`,
+    /// `cache_time`)
+    pub cache: Option<PageCacheConfig>,
+    /// Window prefetched after sequential reads (`readahead`)
+    pub readahead: Option<ReadAheadConfig>,
+    /// How the data of the dump is read (`odirect`)
+    pub io_mode: IoMode,
+    /// Path of the SHA-256 sidecar the dump is verified against (`verify`)
+    pub verify: Option<PathBuf>,
+    /// Path of the block manifest the blocks read are checked against (`verify_blocks`)
+    pub verify_blocks: Option<PathBuf>,
+    /// Detached signature the dump is verified against (`signature`, `pubkey`)
+    pub signature: Option<SignatureConfig>,
+    /// Path of the index cache of the range table (`index`)
+    pub index: Option<PathBuf>,
+    /// Pick up the ranges appended to the dump while it is read (`reload`)
+    pub reload: bool,
+    /// Wait for the data of a dump still being written (`follow`)
+    pub follow: Option<FollowConfig>,
+    /// Artificial latency and bandwidth limit of the reads (`throttle`, `bandwidth`)
+    pub throttle: Option<ThrottleConfig>,
+    /// Read failures injected for robustness testing (`faults`, `fault_rate`, `fault_seed`)
+    pub faults: Option<FaultConfig>,
+    /// Whether writes to the physical memory modify the dump (`write`)
+    pub write: WriteMode,
+    /// Paths of the `LiME` files stacked on top of the dump in order, each shadowing the
+    /// layers below it (`overlay`)
+    pub overlays: Vec<PathBuf>,
+}
+
+impl LimeConfig {
+    /// Parse and validate the connector arguments.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the target is missing or not supported, if an argument is unknown
+    /// (suggesting the closest known one) or has an invalid value, or if arguments that can
+    /// not be combined are given together
+    ///
+    pub fn from_args(args: &ConnectorArgs) -> Result<Self> {
+        let target: &str = args
+            .target
+            .as_ref()
+            .ok_or(
+                Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+                    .log_error("LiME file path not specified"),
+            )?
+            .as_ref();
+        let mut composed = Vec::new();
+        let mut ssh = None;
+        let mut capture = None;
+        let (path, split, member) = match DumpTarget::parse(target)? {
+            DumpTarget::File(path) => (PathBuf::from(path.as_ref()), Vec::new(), None),
+            DumpTarget::Ssh {
+                destination,
+                port,
+                path,
+            } => {
+                let remote = SshConfig::from_args(&args.extra_args, destination, port, &path)?;
+                let spool = match args.extra_args.get("spool") {
+                    Some(spool) => PathBuf::from(spool),
+                    None => remote.default_spool_path(),
+                };
+                ssh = Some(remote);
+                (spool, Vec::new(), None)
+            }
+            DumpTarget::Tcp { address } => {
+                let spool = args.extra_args.get("spool").ok_or_else(|| {
+                    Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                        .log_error("a tcp:// target needs a spool file to receive the dump into")
+                })?;
+                capture = Some(CaptureConfig {
+                    address: address.to_owned(),
+                });
+                (PathBuf::from(spool), Vec::new(), None)
+            }
+            DumpTarget::Composed(parts) => {
+                composed = parts
+                    .into_iter()
+                    .map(|(path, rebase)| (PathBuf::from(path.as_ref()), rebase))
+                    .collect::<Vec<_>>();
+                (composed[0].0.clone(), Vec::new(), None)
+            }
+            DumpTarget::Split(paths) => (
+                PathBuf::from(paths[0].as_ref()),
+                paths.iter().map(|path| path.as_ref().into()).collect(),
+                None,
+            ),
+            DumpTarget::Pattern(pattern) => {
+                let split = expand_parts(Path::new(pattern.as_ref()))?;
+                (split[0].clone(), split, None)
+            }
+            DumpTarget::Archive { archive, member } if cfg!(feature = "archive") => (
+                PathBuf::from(archive.as_ref()),
+                Vec::new(),
+                Some(member.to_owned()),
+            ),
+            DumpTarget::Archive { .. } => {
+                return Err(Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+                    .log_error("archive targets require the `archive` feature"))
+            }
+        };
+
+        let format = match member {
+            None if split.is_empty() && composed.is_empty() && capture.is_none() => {
+                DumpFormat::from_args(&args.extra_args, &path)?
+            }
+            _ if args.extra_args.get("format").is_some_and(|format| format != "lime") => {
+                return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                    "format can only be `lime` for split, composed, archived and streamed dumps",
+                ))
+            }
+            _ => DumpFormat::Lime,
+        };
+
+        Self::with_target(
+            path,
+            split,
+            member,
+            composed,
+            format,
+            ssh,
+            capture,
+            &args.extra_args,
+        )
+    }
+
+    /// Parse and validate the connector arguments of a `LiME` file opened by the caller, see
+    /// [`create_connector_from_file`](crate::create_connector_from_file).
+    ///
+    /// The `path` of the returned configuration is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if a target is given, if an argument is unknown or has an invalid value,
+    /// if arguments that can not be combined are given together, or if an argument needs the
+    /// path of the dump
+    ///
+    pub fn from_file_args(args: &ConnectorArgs) -> Result<Self> {
+        if args.target.is_some() {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("a target can not be given along with an open LiME file"));
+        }
+        let config = Self::with_target(
+            PathBuf::new(),
+            Vec::new(),
+            None,
+            Vec::new(),
+            DumpFormat::Lime,
+            None,
+            None,
+            &args.extra_args,
+        )?;
+        if config.verify.is_some()
+            || config.verify_blocks.is_some()
+            || config.signature.is_some()
+            || config.index.is_some()
+            || config.scan != ScanStrategy::Serial
+            || config.readahead.is_some()
+            || config.io_mode != IoMode::Buffered
+            || !config.overlays.is_empty()
+        {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                    "an open LiME file can not be combined with verify, verify_blocks, \
+                    signature, index, scan=parallel, readahead, odirect or overlay",
+                ),
+            );
+        }
+        Ok(config)
+    }
+
+    /// Parse and validate the extra arguments of the dump at `path`.
+    #[allow(clippy::too_many_arguments)]
+    fn with_target(
+        path: PathBuf,
+        split: Vec<PathBuf>,
+        member: Option<String>,
+        composed: Vec<(PathBuf, u64)>,
+        format: DumpFormat,
+        ssh: Option<SshConfig>,
+        capture: Option<CaptureConfig>,
+        extra_args: &Args,
+    ) -> Result<Self> {
+        check_known_args(extra_args)?;
+        if ssh.is_none()
+            && ["ssh_cmd", "ssh_bin"]
+                .iter()
+                .any(|key| extra_args.get(key).is_some())
+        {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("ssh_cmd and ssh_bin require an ssh:// target"));
+        }
+        if ssh.is_none() && capture.is_none() && extra_args.get("spool").is_some() {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("spool requires an ssh:// or tcp:// target"));
+        }
+        // A dump being received is followed, as long as the capture runs by default.
+        let follow = match FollowConfig::from_args(extra_args)? {
+            None if capture.is_some() => Some(FollowConfig { timeout: None }),
+            follow => follow,
+        };
+        let options = ParseOptions::from_args(extra_args)?;
+        let config = Self {
+            mode: options.mode,
+            allow_unknown_version: options.allow_unknown_version,
+            byte_order: options.byte_order,
+            addr_mask: options.addr_mask,
+            limits: options.limits,
+            recover: options.recover,
+            carve: options.carve,
+            parse: ParseStrategy::from_args(extra_args)?,
+            scan: ScanStrategy::from_args(extra_args)?,
+            overlap: OverlapPolicy::from_args(extra_args)?,
+            align: AlignPolicy::from_args(extra_args)?,
+            rebase: RebaseRule::from_args(extra_args)?,
+            ranges: parse_ranges(extra_args)?,
+            fill_gaps: GapFill::from_args(extra_args)?,
+            cache: PageCacheConfig::from_args(extra_args)?,
+            readahead: ReadAheadConfig::from_args(extra_args)?,
+            io_mode: IoMode::from_args(extra_args)?,
+            verify: match extra_args.get("verify") {
+                None => None,
+                Some("auto") => Some(default_sha256_sidecar(&path)),
+                Some(sidecar_path) => Some(sidecar_path.into()),
+            },
+            verify_blocks: match extra_args.get("verify_blocks") {
+                None => None,
+                Some("auto") => Some(default_blocks_sidecar(&path)),
+                Some(manifest_path) => Some(manifest_path.into()),
+            },
+            signature: SignatureConfig::from_args(extra_args, &path)?,
+            index: match extra_args.get("index") {
+                None => None,
+                Some("auto") => Some(default_index_path(&path)),
+                Some(index_path) => Some(index_path.into()),
+            },
+            reload: parse_bool(extra_args, "reload")?,
+            follow,
+            throttle: ThrottleConfig::from_args(extra_args)?,
+            faults: FaultConfig::from_args(extra_args)?,
+            write: WriteMode::from_args(extra_args)?,
+            key: DecryptionKey::from_args(extra_args)?,
+            overlays: extra_args
+                .get("overlay")
+                .map(|overlays| overlays.split(',').map(PathBuf::from).collect())
+                .unwrap_or_default(),
+            path,
+            split,
+            member,
+            composed,
+            format,
+            ssh,
+            capture,
+        };
+
+        if config.capture.is_some()
+            && (config.key.is_some()
+                || !config.overlays.is_empty()
+                || config.verify.is_some()
+                || config.verify_blocks.is_some()
+                || config.signature.is_some()
+                || config.io_mode != IoMode::Buffered
+                || config.write != WriteMode::ReadOnly)
+        {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                    "tcp:// targets can not be combined with key, overlay, verify, \
+                    verify_blocks, signature, odirect or write",
+                ),
+            );
+        }
+        if config.addr_mask.is_some() && config.index.is_some() {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("addr_mask can not be combined with index"));
+        }
+        if config.parse == ParseStrategy::Lazy
+            && (config.index.is_some()
+                || config.scan != ScanStrategy::Serial
+                || config.readahead.is_some())
+        {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                    "parse=lazy can not be combined with index, scan=parallel or readahead",
+                ),
+            );
+        }
+        if (config.reload || config.follow.is_some())
+            && (config.index.is_some()
+                || config.scan != ScanStrategy::Serial
+                || config.readahead.is_some())
+        {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                    "reload and follow can not be combined with index, scan=parallel or readahead",
+                ),
+            );
+        }
+        if config.align != AlignPolicy::Keep
+            && (config.parse == ParseStrategy::Lazy || config.reload || config.follow.is_some())
+        {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("align can not be combined with parse=lazy, reload or follow"));
+        }
+        if (config.ranges.is_some() || !config.rebase.is_empty())
+            && (config.parse == ParseStrategy::Lazy || config.reload || config.follow.is_some())
+        {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                    "ranges and rebase can not be combined with parse=lazy, reload or follow",
+                ),
+            );
+        }
+        if !config.composed.is_empty()
+            && (!config.overlays.is_empty()
+                || config.key.is_some()
+                || config.parse == ParseStrategy::Lazy
+                || config.reload
+                || config.follow.is_some()
+                || config.verify.is_some()
+                || config.signature.is_some()
+                || config.index.is_some()
+                || config.scan != ScanStrategy::Serial
+                || config.readahead.is_some()
+                || config.io_mode != IoMode::Buffered
+                || config.write != WriteMode::ReadOnly)
+        {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                    "composed dumps can not be combined with overlay, key, parse=lazy, reload, \
+                    follow, verify, signature, index, scan=parallel, readahead, odirect or write",
+                ),
+            );
+        }
+        if config.verify_blocks.is_some()
+            && (config.member.is_some()
+                || config.format != DumpFormat::Lime
+                || !config.composed.is_empty()
+                || !config.overlays.is_empty()
+                || config.key.is_some()
+                || config.write != WriteMode::ReadOnly)
+        {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                    "verify_blocks can only check a LiME file or split dump, without overlay, \
+                    key or write",
+                ),
+            );
+        }
+        if config.key.is_some()
+            && (!config.split.is_empty()
+                || config.member.is_some()
+                || config.format != DumpFormat::Lime
+                || !config.overlays.is_empty()
+                || config.write != WriteMode::ReadOnly)
+        {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                    "key and keyfile can only decrypt a single LiME file, without overlay or \
+                    write",
+                ),
+            );
+        }
+        if (!config.split.is_empty() || config.member.is_some() || config.key.is_some())
+            && (config.verify.is_some()
+                || config.signature.is_some()
+                || config.index.is_some()
+                || config.scan != ScanStrategy::Serial
+                || config.readahead.is_some()
+                || config.io_mode != IoMode::Buffered)
+        {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                    "split dumps, archive members and encrypted dumps can not be combined with \
+                    verify, signature, index, scan=parallel, readahead or odirect",
+                ),
+            );
+        }
+        if !config.overlays.is_empty()
+            && (!config.split.is_empty()
+                || config.member.is_some()
+                || config.format != DumpFormat::Lime
+                || config.parse == ParseStrategy::Lazy
+                || config.reload
+                || config.follow.is_some()
+                || config.index.is_some()
+                || config.scan != ScanStrategy::Serial
+                || config.readahead.is_some()
+                || config.io_mode != IoMode::Buffered
+                || config.write != WriteMode::ReadOnly)
+        {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                    "overlay can only be stacked on a single LiME file, without parse=lazy, \
+                    reload, follow, index, scan=parallel, readahead, odirect or write",
+                ),
+            );
+        }
+        if config.write == WriteMode::InPlace
+            && (!config.split.is_empty()
+                || config.member.is_some()
+                || config.format != DumpFormat::Lime)
+        {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                    "write=inplace can not be combined with split dumps, archive members, \
+                    AFF4 containers, raw images, hibernation files, /proc/kcore or VirtualBox \
+                    cores",
+                ),
+            );
+        }
+        if config.format != DumpFormat::Lime
+            && (config.parse == ParseStrategy::Lazy
+                || config.reload
+                || config.follow.is_some()
+                || config.verify.is_some()
+                || config.signature.is_some()
+                || config.index.is_some()
+                || config.scan != ScanStrategy::Serial
+                || config.readahead.is_some()
+                || config.io_mode != IoMode::Buffered)
+        {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                    "AFF4 containers, raw images, hibernation files, /proc/kcore and VirtualBox \
+                    cores can not be combined with parse=lazy, reload, follow, verify, \
+                    signature, index, scan=parallel, readahead or odirect",
+                ),
+            );
+        }
+        Ok(config)
+    }
+
+    /// Options the headers are parsed with, the dump being a block device if `path` is one.
+    pub(crate) fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            mode: self.mode,
+            allow_unknown_version: self.allow_unknown_version,
+            byte_order: self.byte_order,
+            addr_mask: self.addr_mask,
+            limits: self.limits,
+            recover: self.recover,
+            carve: self.carve,
+            device: is_block_device_path(&self.path),
+        }
+    }
+}
+
+/// Get the boolean connector argument `key`, defaults to `false`.
+fn parse_bool(args: &Args, key: &str) -> Result<bool> {
+    match args.get(key) {
+        None | Some("false") => Ok(false),
+        Some("true") => Ok(true),
+        Some(_) => Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+            .log_error(format!("{} must be either `true` or `false`", key))),
+    }
+}
+
+/// Refuse the arguments the connector does not know, suggesting the closest known one.
+fn check_known_args(args: &Args) -> Result<()> {
+    // `Args` has no accessor for its keys, its string form is parsed back instead.
+    let args = args.to_string();
+    for key in split_str_args(&args, ',').filter_map(|arg| {
+        let mut parts = split_str_args(arg, '=');
+        parts.next().filter(|_| parts.next().is_some())
+    }) {
+        if find_arg(key).is_none() {
+            let msg = match suggest_arg(key) {
+                Some(known) => format!("unknown argument `{}`, did you mean `{}`?", key, known),
+                None => format!("unknown argument `{}`, see the help of the connector", key),
+            };
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgNotExists).log_error(msg));
+        }
+    }
+    Ok(())
+}
+
+/// How strictly the `LiME` file is checked while parsing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Refuse any deviation from the `LiME` specification
+    #[default]
+    Strict,
+    /// Log recoverable deviations from the `LiME` specification and carry on
+    Lenient,
+}
+
+impl ParseMode {
+    /// Get the parse mode from the `mode` connector argument, defaults to [`ParseMode::Strict`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the argument has an unknown value
+    ///
+    pub fn from_args(args: &Args) -> Result<Self> {
+        match args.get("mode") {
+            None | Some("strict") => Ok(Self::Strict),
+            Some("lenient") => Ok(Self::Lenient),
+            Some(_) => Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("mode must be either `strict` or `lenient`")),
+        }
+    }
+
+    /// Handle a recoverable deviation from the specification described by `msg`.
+    ///
+    /// Returns `Err` in strict mode, logs a warning and returns `Ok` in lenient mode.
+    pub(crate) fn tolerate(self, msg: &str) -> Result<()> {
+        match self {
+            Self::Strict => {
+                Err(Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(msg))
+            }
+            Self::Lenient => {
+                log::warn!("{}", msg);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Options controlling how the headers of a `LiME` file are parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct ParseOptions {
+    /// How strictly the file is checked
+    pub(crate) mode: ParseMode,
+    /// Map headers with a version unknown to the parser, assuming the version 1 layout
+    pub(crate) allow_unknown_version: bool,
+    /// Byte order of the header fields
+    pub(crate) byte_order: ByteOrder,
+    /// Mask applied to the physical addresses of the headers, its bits being the low ones
+    pub(crate) addr_mask: Option<u64>,
+    /// Bounds on the ranges of the dump
+    pub(crate) limits: ParseLimits,
+    /// Skip corrupt headers up to the next plausible one
+    pub(crate) recover: bool,
+    /// Search the whole file for plausible headers
+    pub(crate) carve: bool,
+    /// The dump is a block device, the first position not holding a header ending the `LiME`
+    /// data instead of the end of the device
+    pub(crate) device: bool,
+}
+
+impl ParseOptions {
+    /// Get the parse options from the `mode`, `allow_unknown_version`, `endian`,
+    /// `addr_mask`, `max_segments`, `max_segment_size`, `max_total_size`, `recover` and
+    /// `carve` connector arguments.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an argument has an unknown value
+    ///
+    pub(crate) fn from_args(args: &Args) -> Result<Self> {
+        Ok(Self {
+            mode: ParseMode::from_args(args)?,
+            allow_unknown_version: parse_bool(args, "allow_unknown_version")?,
+            byte_order: ByteOrder::from_args(args)?,
+            addr_mask: parse_addr_mask(args)?,
+            limits: ParseLimits::from_args(args)?,
+            recover: parse_bool(args, "recover")?,
+            carve: parse_bool(args, "carve")?,
+            device: false,
+        })
+    }
+
+    /// Options for parsing `lime_dump`, detecting whether it is a block device.
+    pub(crate) fn for_file(self, lime_dump: &File) -> Self {
+        Self {
+            device: lime_dump
+                .metadata()
+                .is_ok_and(|metadata| is_block_device(&metadata)),
+            ..self
+        }
+    }
+}
+
+/// Parse a decimal or `0x` prefixed hexadecimal address.
+fn parse_address(s: &str) -> Option<u64> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+/// Shift of the physical addresses of the headers falling in a window, e.g. to turn the bus
+/// addresses recorded by an embedded capture into CPU physical addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RebaseRule {
+    /// Starting address of the window
+    pub s_addr: u64,
+    /// Ending address of the window, inclusive
+    pub e_addr: u64,
+    /// Signed offset added to the addresses in the window
+    pub offset: i64,
+}
+
+impl RebaseRule {
+    /// Get the rebase rules from the `rebase` connector argument, sorted by address, empty if
+    /// it is not given.
+    ///
+    /// The argument is a comma-separated list of `[<start>-<end>:]<offset>` rules, the
+    /// window being inclusive and the offset a decimal or `0x` prefixed hexadecimal number
+    /// optionally preceded by `+` or `-` (e.g. `+0x80000000` or
+    /// `0x40000000-0x7fffffff:-0x40000000`). A rule without window covers every address.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if a rule can not be parsed or if the windows of two rules overlap
+    ///
+    pub fn from_args(args: &Args) -> Result<Vec<Self>> {
+        let Some(rebase) = args.get("rebase") else {
+            return Ok(Vec::new());
+        };
+        let mut rules = rebase
+            .split(',')
+            .map(|rule| {
+                parse_rebase_rule(rule.trim()).ok_or_else(|| {
+                    Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(format!(
+                        "invalid rebase rule `{}`, expected `[<start>-<end>:]<+|-><offset>`",
+                        rule
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        rules.sort_unstable_by_key(|rule| rule.s_addr);
+        if rules
+            .windows(2)
+            .any(|pair| pair[1].s_addr <= pair[0].e_addr)
+        {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("the windows of the rebase rules overlap"));
+        }
+        Ok(rules)
+    }
+}
+
+/// Parse a `[<start>-<end>:]<offset>` rebase rule.
+fn parse_rebase_rule(rule: &str) -> Option<RebaseRule> {
+    let (s_addr, e_addr, offset) = match rule.split_once(':') {
+        Some((window, offset)) => {
+            let (s_addr, e_addr) = window.split_once('-')?;
+            (parse_address(s_addr)?, parse_address(e_addr)?, offset)
+        }
+        None => (0, u64::MAX, rule),
+    };
+    let offset = match offset.strip_prefix('-') {
+        Some(offset) => i64::try_from(parse_address(offset)?).ok()?.checked_neg()?,
+        None => i64::try_from(parse_address(offset.strip_prefix('+').unwrap_or(offset))?).ok()?,
+    };
+    (s_addr <= e_addr).then_some(RebaseRule {
+        s_addr,
+        e_addr,
+        offset,
+    })
+}
+
+/// Get the physical windows to map from the `ranges` connector argument, sorted and merged,
+/// returns `None` if it is not given.
+fn parse_ranges(args: &Args) -> Result<Option<Vec<(u64, u64)>>> {
+    let Some(ranges) = args.get("ranges") else {
+        return Ok(None);
+    };
+    let mut windows = ranges
+        .split(',')
+        .map(|range| {
+            range
+                .trim()
+                .split_once('-')
+                .and_then(|(s_addr, e_addr)| {
+                    let s_addr = parse_address(s_addr)?;
+                    let e_addr = match e_addr {
+                        "" => u64::MAX,
+                        e_addr => parse_address(e_addr)?,
+                    };
+                    (s_addr <= e_addr).then_some((s_addr, e_addr))
+                })
+                .ok_or_else(|| {
+                    Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(format!(
+                        "invalid range `{}`, expected `<start>-<end>` or `<start>-`",
+                        range
+                    ))
+                })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    windows.sort_unstable();
+    let mut merged: Vec<(u64, u64)> = Vec::with_capacity(windows.len());
+    for (s_addr, e_addr) in windows {
+        match merged.last_mut() {
+            Some(last) if s_addr <= last.1.saturating_add(1) => last.1 = last.1.max(e_addr),
+            _ => merged.push((s_addr, e_addr)),
+        }
+    }
+    Ok(Some(merged))
+}
+
+/// Get the `addr_mask` connector argument, a decimal or `0x` prefixed hexadecimal mask made
+/// of the low bits of an address (e.g. `0xFFFFFFFF` for 32-bit addresses).
+fn parse_addr_mask(args: &Args) -> Result<Option<u64>> {
+    let Some(mask) = args.get("addr_mask") else {
+        return Ok(None);
+    };
+    match parse_address(mask) {
+        Some(mask) if mask != 0 && mask & mask.wrapping_add(1) == 0 => Ok(Some(mask)),
+        _ => Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+            .log_error("addr_mask must be a non-zero mask of the low bits, like `0xFFFFFFFF`")),
+    }
+}
+
+/// Bounds on the ranges of a dump, for services opening untrusted dumps
+///
+/// A crafted dump can declare millions of tiny ranges or ranges spanning the whole address
+/// space, the walk over its headers stops with an error as soon as a bound is exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseLimits {
+    /// Most ranges the dump may hold, `None` if unbounded (`max_segments`)
+    pub max_segments: Option<usize>,
+    /// Largest range in bytes, `None` if unbounded (`max_segment_size`)
+    pub max_segment_size: Option<u64>,
+    /// Most bytes of memory the ranges may hold together, `None` if unbounded
+    /// (`max_total_size`)
+    pub max_total_size: Option<u64>,
+}
+
+impl ParseLimits {
+    /// Get the limits from the `max_segments`, `max_segment_size` and `max_total_size`
+    /// connector arguments, every bound being unset if its argument is not given.
+    ///
+    /// `max_segments` is a decimal count, the sizes are hex numbers followed by a `k`, `m` or
+    /// `g` unit, optionally followed by `b` (e.g. `400m`).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an argument can not be parsed or is zero
+    ///
+    pub fn from_args(args: &Args) -> Result<Self> {
+        let max_size = |key: &str| match args.get(key) {
+            None => Ok(None),
+            Some(size) => parse_hex_size(size)
+                .filter(|size| *size > 0)
+                .map(|size| Some(size as u64))
+                .ok_or_else(|| {
+                    Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(format!(
+                        "{} must be a non-zero hex number followed by `k`, `m` or `g`",
+                        key
+                    ))
+                }),
+        };
+        Ok(Self {
+            max_segments: match args.get("max_segments") {
+                None => None,
+                Some(count) => Some(
+                    count
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|count| *count > 0)
+                        .ok_or_else(|| {
+                            Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                                .log_error("max_segments must be a non-zero decimal count")
+                        })?,
+                ),
+            },
+            max_segment_size: max_size("max_segment_size")?,
+            max_total_size: max_size("max_total_size")?,
+        })
+    }
+}
+
+impl From<ParseMode> for ParseOptions {
+    fn from(mode: ParseMode) -> Self {
+        Self {
+            mode,
+            ..Self::default()
+        }
+    }
+}
+
+/// Byte order of the header fields of a `LiME` file
+///
+/// `LiME` writes the headers in the byte order of the target, so dumps of big-endian systems
+/// (older MIPS or PowerPC devices) have big-endian headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ByteOrder {
+    /// Detect the byte order from the magic number of the first header
+    #[default]
+    Auto,
+    /// Little-endian headers
+    Little,
+    /// Big-endian headers
+    Big,
+}
+
+impl ByteOrder {
+    /// Get the byte order from the `endian` connector argument, defaults to
+    /// [`ByteOrder::Auto`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the argument has an unknown value
+    ///
+    pub fn from_args(args: &Args) -> Result<Self> {
+        match args.get("endian") {
+            None | Some("auto") => Ok(Self::Auto),
+            Some("le") => Ok(Self::Little),
+            Some("be") => Ok(Self::Big),
+            Some(_) => Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("endian must be one of `le`, `be` or `auto`")),
+        }
+    }
+}
+
+/// How the headers of a `LiME` file are located on open
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScanStrategy {
+    /// Hop from each header to the next one
+    #[default]
+    Serial,
+    /// Search partitions of the file for headers concurrently, then validate the chain,
+    /// falling back to the serial scan if the result is ambiguous
+    Parallel,
+}
+
+impl ScanStrategy {
+    /// Get the scan strategy from the `scan` connector argument, defaults to
+    /// [`ScanStrategy::Serial`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the argument has an unknown value
+    ///
+    pub fn from_args(args: &Args) -> Result<Self> {
+        match args.get("scan") {
+            None | Some("serial") => Ok(Self::Serial),
+            Some("parallel") => Ok(Self::Parallel),
+            Some(_) => Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("scan must be either `serial` or `parallel`")),
+        }
+    }
+}
+
+/// When the headers of a `LiME` file are parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseStrategy {
+    /// Walk every header when the connector is created
+    #[default]
+    Eager,
+    /// Walk the headers only as far as needed to serve each read
+    Lazy,
+}
+
+impl ParseStrategy {
+    /// Get the parse strategy from the `parse` connector argument, defaults to
+    /// [`ParseStrategy::Eager`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the argument has an unknown value
+    ///
+    pub fn from_args(args: &Args) -> Result<Self> {
+        match args.get("parse") {
+            None | Some("eager") => Ok(Self::Eager),
+            Some("lazy") => Ok(Self::Lazy),
+            Some(_) => Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("parse must be either `eager` or `lazy`")),
+        }
+    }
+}
+
+/// Whether writes to the physical memory reach the dump
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WriteMode {
+    /// Refuse writes, the dump is opened read-only
+    #[default]
+    ReadOnly,
+    /// Write to the dump itself, at the file offsets the written addresses map to
+    InPlace,
+}
+
+impl WriteMode {
+    /// Get the write mode from the `write` connector argument, defaults to
+    /// [`WriteMode::ReadOnly`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the argument has an unknown value
+    ///
+    pub fn from_args(args: &Args) -> Result<Self> {
+        match args.get("write") {
+            None | Some("none") => Ok(Self::ReadOnly),
+            Some("inplace") => Ok(Self::InPlace),
+            Some(_) => Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("write must be either `none` or `inplace`")),
+        }
+    }
+}
+
+/// Size of the page cache put in front of the dump
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageCacheConfig {
+    /// Size of the cache in bytes
+    pub size: usize,
+    /// Size of a cached page in bytes
+    pub page_size: usize,
+    /// Time after which a cached page is read again from the dump, `None` to keep pages until
+    /// they are evicted
+    pub validity: Option<Duration>,
+}
+
+impl PageCacheConfig {
+    /// Cache size used when `cached=true` is given without `cache_size`, the default of the
+    /// memflow cache middleware
+    pub const DEFAULT_SIZE: usize = 0x20_0000;
+    /// Page size used when `cache_page_size` is not given
+    pub const DEFAULT_PAGE_SIZE: usize = 0x1000;
+
+    /// Get the cache configuration from the `cached`, `cache_size`, `cache_page_size` and
+    /// `cache_time` connector arguments, returns `None` if neither `cached=true` nor
+    /// `cache_size` is given.
+    ///
+    /// Like for the memflow cache middleware, `cache_size` is a hex number followed by a `kb`,
+    /// `mb` or `gb` unit (e.g. `10mb`), `cache_page_size` a hex number (e.g. `1000`) and
+    /// `cache_time` a number of milliseconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an argument can not be parsed, if the cache can not hold a page or if
+    /// `cached=false` is given along with cache options
+    ///
+    pub fn from_args(args: &Args) -> Result<Option<Self>> {
+        let cached = match args.get("cached") {
+            None => None,
+            Some(_) => Some(parse_bool(args, "cached")?),
+        };
+        let size = match (cached, args.get("cache_size")) {
+            (Some(false), _)
+                if ["cache_size", "cache_page_size", "cache_time"]
+                    .iter()
+                    .any(|option| args.get(option).is_some()) =>
+            {
+                return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                    .log_error("cache options can not be used with cached=false"));
+            }
+            (None | Some(false), None) => return Ok(None),
+            (Some(true), None) => Self::DEFAULT_SIZE,
+            (_, Some(size)) => parse_hex_size(size).ok_or_else(|| {
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                    .log_error("cache_size must be a hex number followed by `kb`, `mb` or `gb`")
+            })?,
+        };
+
+        let page_size = match args.get("cache_page_size") {
+            None => Self::DEFAULT_PAGE_SIZE,
+            Some(page_size) => usize::from_str_radix(page_size, 16)
+                .ok()
+                .filter(|page_size| page_size.is_power_of_two())
+                .ok_or_else(|| {
+                    Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                        .log_error("cache_page_size must be a hex power of two")
+                })?,
+        };
+
+        let validity = match args.get("cache_time") {
+            None => None,
+            Some(time) => Some(time.parse().map(Duration::from_millis).map_err(|_| {
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                    .log_error("cache_time must be a number of milliseconds")
+            })?),
+        };
+
+        if size < page_size {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("cache_size must hold at least one page"));
+        }
+        Ok(Some(Self {
+            size,
+            page_size,
+            validity,
+        }))
+    }
+}
+
+/// Size of the window prefetched after sequential reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadAheadConfig {
+    /// Bytes loaded ahead of the last read
+    pub window: u64,
+}
+
+impl ReadAheadConfig {
+    /// Get the read-ahead configuration from the `readahead` connector argument, returns
+    /// `None` if it is not given.
+    ///
+    /// The window is a hex number followed by a `k`, `m` or `g` unit, optionally followed by
+    /// `b` (e.g. `8M` or `8mb`).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the argument can not be parsed or is zero
+    ///
+    pub fn from_args(args: &Args) -> Result<Option<Self>> {
+        let Some(window) = args.get("readahead") else {
+            return Ok(None);
+        };
+        parse_hex_size(window)
+            .filter(|window| *window > 0)
+            .map(|window| {
+                Some(Self {
+                    window: window as u64,
+                })
+            })
+            .ok_or_else(|| {
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                    "readahead must be a non-zero hex number followed by `k`, `m` or `g`",
+                )
+            })
+    }
+}
+
+/// How long reads wait for the data of a dump still being written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FollowConfig {
+    /// Longest wait of a read, `None` waiting as long as it takes
+    pub timeout: Option<Duration>,
+}
+
+impl FollowConfig {
+    /// Get the follow configuration from the `follow` connector argument, returns `None` if
+    /// it is not given or `false`.
+    ///
+    /// The argument is either `true`, waiting without limit, or a timeout in milliseconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the argument can not be parsed or is zero
+    ///
+    pub fn from_args(args: &Args) -> Result<Option<Self>> {
+        match args.get("follow") {
+            None | Some("false") => Ok(None),
+            Some("true") => Ok(Some(Self { timeout: None })),
+            Some(timeout) => timeout
+                .parse::<u64>()
+                .ok()
+                .filter(|timeout| *timeout > 0)
+                .map(|timeout| {
+                    Some(Self {
+                        timeout: Some(Duration::from_millis(timeout)),
+                    })
+                })
+                .ok_or_else(|| {
+                    Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                        "follow must be `true`, `false` or a non-zero timeout in milliseconds",
+                    )
+                }),
+        }
+    }
+}
+
+/// Slowdown of the reads, simulating slow evidence storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThrottleConfig {
+    /// Time every read takes at least
+    pub latency: Duration,
+    /// Most bytes read per second, `None` if the throughput is not limited
+    pub bandwidth: Option<u64>,
+}
+
+impl ThrottleConfig {
+    /// Get the throttling configuration from the `throttle` and `bandwidth` connector
+    /// arguments, returns `None` if neither is given.
+    ///
+    /// `throttle` is a decimal duration followed by `us`, `ms` or `s` (e.g. `10ms`) and
+    /// `bandwidth` a decimal number of bytes per second followed by `B`, `kB`, `MB` or `GB`,
+    /// optionally followed by `ps` or `/s` (e.g. `50MBps`), units being powers of 1000.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an argument can not be parsed or the bandwidth is zero
+    ///
+    pub fn from_args(args: &Args) -> Result<Option<Self>> {
+        let latency = args.get("throttle");
+        let bandwidth = args.get("bandwidth");
+        if latency.is_none() && bandwidth.is_none() {
+            return Ok(None);
+        }
+
+        let latency = match latency {
+            None => Duration::ZERO,
+            Some(latency) => parse_duration(latency).ok_or_else(|| {
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                    .log_error("throttle must be a decimal number followed by `us`, `ms` or `s`")
+            })?,
+        };
+        let bandwidth = match bandwidth {
+            None => None,
+            Some(bandwidth) => Some(
+                parse_bandwidth(bandwidth)
+                    .filter(|bandwidth| *bandwidth > 0)
+                    .ok_or_else(|| {
+                        Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(
+                            "bandwidth must be a non-zero decimal number followed by `B`, `kB`, \
+                            `MB` or `GB` per second",
+                        )
+                    })?,
+            ),
+        };
+        Ok(Some(Self { latency, bandwidth }))
+    }
+
+    /// Time a read of `len` bytes takes at least.
+    pub(crate) fn min_duration(&self, len: u64) -> Duration {
+        let transfer = self.bandwidth.map_or(Duration::ZERO, |bandwidth| {
+            Duration::from_secs_f64(len as f64 / bandwidth as f64)
+        });
+        self.latency + transfer
+    }
+}
+
+/// Detached minisign signature of a dump and the public key it is checked with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureConfig {
+    /// Path of the signature file
+    pub signature: PathBuf,
+    /// Public key, either in base64 (`RW...`) or as the path of a minisign public key file
+    pub pubkey: String,
+}
+
+impl SignatureConfig {
+    /// Get the signature configuration from the `signature` and `pubkey` connector
+    /// arguments, returns `None` if neither is given.
+    ///
+    /// `signature` is the path of the signature or `auto` to use `<lime_path>.minisig`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if only one of the arguments is given, or if they are given on a build
+    /// without the `signature` feature
+    ///
+    pub fn from_args(args: &Args, lime_path: &Path) -> Result<Option<Self>> {
+        let (signature, pubkey) = match (args.get("signature"), args.get("pubkey")) {
+            (None, None) => return Ok(None),
+            (Some(signature), Some(pubkey)) => (signature, pubkey),
+            _ => {
+                return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                    .log_error("signature and pubkey must be given together"))
+            }
+        };
+        if !cfg!(feature = "signature") {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+                .log_error("signature verification requires the `signature` feature"));
+        }
+        let signature = match signature {
+            "auto" => default_signature_path(lime_path),
+            signature => signature.into(),
+        };
+        Ok(Some(Self {
+            signature,
+            pubkey: pubkey.to_owned(),
+        }))
+    }
+}
+
+/// Default location of the signature of `lime_path`, i.e. `<lime_path>.minisig`.
+fn default_signature_path(lime_path: &Path) -> PathBuf {
+    let mut path = lime_path.as_os_str().to_owned();
+    path.push(".minisig");
+    path.into()
+}
+
+/// Remote host a dump is acquired from over SSH
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshConfig {
+    /// Host the SSH client connects to, as `[user@]host`
+    pub destination: String,
+    /// Port of the SSH server, `None` for the default of the client
+    pub port: Option<u16>,
+    /// Path of the dump on the remote host, `None` if `command` produces it
+    pub remote_path: Option<String>,
+    /// Remote command writing the dump to its standard output, `{offset}` being replaced with
+    /// the number of bytes already spooled, `None` to read `remote_path` (`ssh_cmd`)
+    pub command: Option<String>,
+    /// SSH client run, OpenSSH compatible (`ssh_bin`)
+    pub program: PathBuf,
+}
+
+impl SshConfig {
+    /// Get the acquisition of the dump at `path` on `destination` from the `ssh_cmd` and
+    /// `ssh_bin` connector arguments, `path` being empty if `ssh_cmd` produces the dump.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if neither or both of `path` and `ssh_cmd` are given, or on a build
+    /// without the `ssh` feature
+    ///
+    pub fn from_args(
+        args: &Args,
+        destination: &str,
+        port: Option<u16>,
+        path: &str,
+    ) -> Result<Self> {
+        if !cfg!(feature = "ssh") {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+                .log_error("ssh:// targets require the `ssh` feature"));
+        }
+        let (remote_path, command) = match (path, args.get("ssh_cmd")) {
+            ("", Some(command)) => (None, Some(command.to_owned())),
+            (path, None) if !path.is_empty() => (Some(path.to_owned()), None),
+            _ => {
+                return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                    .log_error("an ssh:// target needs either a remote path or ssh_cmd, not both"))
+            }
+        };
+        Ok(Self {
+            destination: destination.to_owned(),
+            port,
+            remote_path,
+            command,
+            program: args.get("ssh_bin").unwrap_or("ssh").into(),
+        })
+    }
+
+    /// Default spool file of the dump, named after the host and the remote file in the
+    /// temporary directory, so that an interrupted acquisition resumes on the next open.
+    pub fn default_spool_path(&self) -> PathBuf {
+        let host = match self.port {
+            Some(port) => format!("{}-{}", self.destination, port),
+            None => self.destination.clone(),
+        };
+        let host = host.replace(|c: char| !c.is_ascii_alphanumeric() && c != '.', "_");
+        let name = self
+            .remote_path
+            .as_deref()
+            .and_then(|path| Path::new(path).file_name())
+            .map_or_else(|| "acquisition.lime".into(), |name| name.to_owned());
+        std::env::temp_dir()
+            .join("memflow-lime-ssh")
+            .join(host)
+            .join(name)
+    }
+}
+
+/// `LiME` acquisition received over TCP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureConfig {
+    /// Address `LiME` listens at in its TCP mode (`path=tcp:<port>`), as `host:port`
+    pub address: String,
+}
+
+/// age identities an encrypted dump is decrypted with
+///
+/// The secret key is redacted from the `Debug` output.
+#[derive(Clone, PartialEq, Eq)]
+pub enum DecryptionKey {
+    /// Identity given inline, e.g. `AGE-SECRET-KEY-1...` (`key`)
+    Key(String),
+    /// Path of an age identity file (`keyfile`)
+    KeyFile(PathBuf),
+}
+
+impl fmt::Debug for DecryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(_) => f.write_str("Key(<redacted>)"),
+            Self::KeyFile(path) => f.debug_tuple("KeyFile").field(path).finish(),
+        }
+    }
+}
+
+impl DecryptionKey {
+    /// Get the decryption key from the `key` or `keyfile` connector argument, returns `None`
+    /// if neither is given.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if both are given, or if one is given on a build without the `age`
+    /// feature
+    ///
+    pub fn from_args(args: &Args) -> Result<Option<Self>> {
+        let key = match (args.get("key"), args.get("keyfile")) {
+            (None, None) => return Ok(None),
+            (Some(key), None) => Self::Key(key.to_owned()),
+            (None, Some(keyfile)) => Self::KeyFile(keyfile.into()),
+            (Some(_), Some(_)) => {
+                return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                    .log_error("key and keyfile can not be given together"))
+            }
+        };
+        match cfg!(feature = "age") {
+            true => Ok(Some(key)),
+            false => Err(Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+                .log_error("encrypted dumps require the `age` feature")),
+        }
+    }
+}
+
+/// Failure injected into the reads of a [`FaultRange`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultKind {
+    /// The read fails as a whole (`io_error`)
+    IoError,
+    /// Only the first half of the read succeeds, the rest fails (`short_read`)
+    ShortRead,
+}
+
+/// Physical range whose reads fail on purpose
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaultRange {
+    /// Starting address of the range
+    pub s_addr: u64,
+    /// Ending address of the range, inclusive
+    pub e_addr: u64,
+    /// How the reads touching the range fail
+    pub kind: FaultKind,
+}
+
+/// Read failures injected to exercise the error paths of the tools built on the connector
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultConfig {
+    /// Ranges whose reads fail
+    pub ranges: Vec<FaultRange>,
+    /// Probability, between 0 and 1, that a read touching a range fails
+    pub rate: f64,
+    /// Seed of the pseudo-random draws, the same seed failing the same reads
+    pub seed: u64,
+}
+
+// `rate` is checked to be a number between 0 and 1 on parse, never NaN.
+impl Eq for FaultConfig {}
+
+impl FaultConfig {
+    /// Get the fault injection configuration from the `faults`, `fault_rate` and
+    /// `fault_seed` connector arguments, returns `None` if `faults` is not given.
+    ///
+    /// `faults` is a comma-separated list of `<start>-<end>:<kind>` entries, the addresses
+    /// being inclusive and either decimal or `0x` prefixed hexadecimal numbers, and `kind`
+    /// either `io_error` or `short_read`. `fault_rate` defaults to `1`, failing every read
+    /// touching a range, and `fault_seed` is a decimal number defaulting to `0`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an argument can not be parsed, or if `fault_rate` or `fault_seed` is
+    /// given without `faults`
+    ///
+    pub fn from_args(args: &Args) -> Result<Option<Self>> {
+        let Some(faults) = args.get("faults") else {
+            if args.get("fault_rate").is_some() || args.get("fault_seed").is_some() {
+                return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                    .log_error("fault_rate and fault_seed require faults"));
+            }
+            return Ok(None);
+        };
+
+        let ranges = faults
+            .split(',')
+            .map(|fault| {
+                parse_fault_range(fault).ok_or_else(|| {
+                    Error(ErrorOrigin::Connector, ErrorKind::ArgValidation).log_error(format!(
+                        "invalid fault `{}`, expected `<start>-<end>:io_error|short_read`",
+                        fault
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let rate = match args.get("fault_rate") {
+            None => 1.0,
+            Some(rate) => rate
+                .parse::<f64>()
+                .ok()
+                .filter(|rate| (0.0..=1.0).contains(rate))
+                .ok_or_else(|| {
+                    Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                        .log_error("fault_rate must be a number between 0 and 1")
+                })?,
+        };
+        let seed = match args.get("fault_seed") {
+            None => 0,
+            Some(seed) => seed.parse().map_err(|_| {
+                Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                    .log_error("fault_seed must be a decimal number")
+            })?,
+        };
+        Ok(Some(Self { ranges, rate, seed }))
+    }
+}
+
+/// Parse a `<start>-<end>:<kind>` fault entry.
+fn parse_fault_range(fault: &str) -> Option<FaultRange> {
+    let (range, kind) = fault.trim().split_once(':')?;
+    let (s_addr, e_addr) = range.split_once('-')?;
+    let kind = match kind {
+        "io_error" => FaultKind::IoError,
+        "short_read" => FaultKind::ShortRead,
+        _ => return None,
+    };
+    Some(FaultRange {
+        s_addr: parse_address(s_addr)?,
+        e_addr: parse_address(e_addr)?,
+        kind,
+    })
+    .filter(|range| range.s_addr <= range.e_addr)
+}
+
+/// Parse a decimal duration followed by a `us`, `ms` or `s` unit.
+fn parse_duration(duration: &str) -> Option<Duration> {
+    let duration = duration.to_lowercase();
+    if let Some(us) = duration.strip_suffix("us") {
+        us.parse().ok().map(Duration::from_micros)
+    } else if let Some(ms) = duration.strip_suffix("ms") {
+        ms.parse().ok().map(Duration::from_millis)
+    } else {
+        duration
+            .strip_suffix('s')?
+            .parse()
+            .ok()
+            .map(Duration::from_secs)
+    }
+}
+
+/// Parse a decimal number of bytes per second followed by a `b`, `kb`, `mb` or `gb` unit,
+/// optionally followed by `ps` or `/s`.
+fn parse_bandwidth(bandwidth: &str) -> Option<u64> {
+    let bandwidth = bandwidth.to_lowercase();
+    let bandwidth = bandwidth
+        .strip_suffix("ps")
+        .or_else(|| bandwidth.strip_suffix("/s"))
+        .unwrap_or(&bandwidth)
+        .strip_suffix('b')?;
+    let (digits, unit) = [('k', 1_000), ('m', 1_000_000), ('g', 1_000_000_000)]
+        .into_iter()
+        .find_map(|(suffix, unit)| bandwidth.strip_suffix(suffix).map(|digits| (digits, unit)))
+        .unwrap_or((bandwidth, 1));
+    digits.parse::<u64>().ok()?.checked_mul(unit)
+}
+
+/// Parse a hex size followed by a `kb`, `mb` or `gb` unit, the trailing `b` being optional.
+fn parse_hex_size(size: &str) -> Option<usize> {
+    let size = size.to_lowercase();
+    let size = size.strip_suffix('b').unwrap_or(&size);
+    let (digits, unit) = [('k', 1 << 10), ('m', 1 << 20), ('g', 1 << 30)]
+        .into_iter()
+        .find_map(|(suffix, unit)| size.strip_suffix(suffix).map(|digits| (digits, unit)))?;
+    usize::from_str_radix(digits, 16).ok()?.checked_mul(unit)
+}
+
+/// Container a memory image is stored in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DumpFormat {
+    /// `LiME` dump
+    #[default]
+    Lime,
+    /// AFF4 container, its physical memory image being mapped (`format=aff4`, files
+    /// starting like zip files)
+    Aff4,
+    /// Raw memory image described by a JSON run list, the target being the run list
+    /// (`format=rawjson`, files starting with a JSON object or array)
+    RawJson,
+    /// Windows hibernation file, its compressed pages being mapped (`format=hiberfil`, files
+    /// starting with a hibernation signature)
+    Hiberfil,
+    /// ELF core file of the running Linux kernel, its RAM segments being mapped
+    /// (`format=kcore`, ELF files like `/proc/kcore`)
+    Kcore,
+    /// ELF core file of a VirtualBox guest, its guest-physical ranges being mapped
+    /// (`format=vbcore`, ELF files carrying the `VBCORE` note)
+    VBoxCore,
+}
+
+impl DumpFormat {
+    /// Get the format of the file at `path` from the `format` connector argument, or from the
+    /// magic number starting the file if the argument is not given, defaults to
+    /// [`DumpFormat::Lime`].
+    ///
+    /// The name of the file is never looked at, a `LiME` dump being opened whatever its
+    /// extension. A file that can not be read is taken for a `LiME` dump, opening it reporting
+    /// the error.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the argument has an unknown value, or if the format needs a feature
+    /// the build lacks: `aff4`, `rawjson`, `hiberfil`, `kcore` or `vbcore`
+    ///
+    pub fn from_args(args: &Args, path: &Path) -> Result<Self> {
+        let format =
+            match args.get("format") {
+                None => Self::detect(path),
+                Some("lime") => Self::Lime,
+                Some("aff4") => Self::Aff4,
+                Some("rawjson") => Self::RawJson,
+                Some("hiberfil") => Self::Hiberfil,
+                Some("kcore") => Self::Kcore,
+                Some("vbcore") => Self::VBoxCore,
+                Some(_) => return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                    .log_error(
+                        "format must be one of `lime`, `aff4`, `rawjson`, `hiberfil`, `kcore` or \
+                    `vbcore`",
+                    )),
+            };
+        let feature = match format {
+            Self::Lime => return Ok(format),
+            Self::Aff4 if cfg!(feature = "aff4") => return Ok(format),
+            Self::RawJson if cfg!(feature = "rawjson") => return Ok(format),
+            Self::Hiberfil if cfg!(feature = "hiberfil") => return Ok(format),
+            Self::Kcore if cfg!(feature = "kcore") => return Ok(format),
+            Self::VBoxCore if cfg!(feature = "vbcore") => return Ok(format),
+            Self::Aff4 => "AFF4 containers require the `aff4` feature",
+            Self::RawJson => "JSON run lists require the `rawjson` feature",
+            Self::Hiberfil => "hibernation files require the `hiberfil` feature",
+            Self::Kcore => "/proc/kcore requires the `kcore` feature",
+            Self::VBoxCore => "VirtualBox core files require the `vbcore` feature",
+        };
+        Err(Error(ErrorOrigin::Connector, ErrorKind::NotSupported).log_error(feature))
+    }
+
+    /// Get the format of the file at `path` from the magic number starting it.
+    fn detect(path: &Path) -> Self {
+        let mut magic = Vec::with_capacity(MAGIC_SIZE);
+        let read =
+            File::open(path).and_then(|file| file.take(MAGIC_SIZE as u64).read_to_end(&mut magic));
+        if read.is_err() || magic.starts_with(LIME_MAGIC) {
+            return Self::Lime;
+        }
+        let text = magic.strip_prefix(UTF8_BOM).unwrap_or(&magic);
+        if magic.starts_with(ZIP_MAGIC) {
+            Self::Aff4
+        } else if HIBERFIL_SIGNATURES
+            .iter()
+            .any(|signature| magic.starts_with(*signature))
+        {
+            Self::Hiberfil
+        } else if magic.starts_with(ELF_MAGIC) {
+            Self::detect_elf(path)
+        } else if matches!(
+            text.iter().find(|byte| !byte.is_ascii_whitespace()),
+            Some(b'{' | b'[')
+        ) {
+            Self::RawJson
+        } else {
+            Self::Lime
+        }
+    }
+
+    /// Get the format of the ELF core file at `path`, a VirtualBox core if it carries the
+    /// `VBCORE` note, a kernel core file like `/proc/kcore` otherwise.
+    fn detect_elf(path: &Path) -> Self {
+        #[cfg(feature = "vbcore")]
+        if crate::vbcore::is_vbox_core(path) {
+            return Self::VBoxCore;
+        }
+        #[cfg(not(feature = "vbcore"))]
+        let _ = path;
+        Self::Kcore
+    }
+}
+
+/// Bytes of a file read to detect its format, enough for the whitespace indenting a JSON document
+const MAGIC_SIZE: usize = 0x100;
+/// Magic number starting `LiME` files, as the first header stores it
+const LIME_MAGIC: &[u8; 4] = b"EMiL";
+/// Magic number starting zip files, like AFF4 containers
+const ZIP_MAGIC: &[u8; 4] = b"PK\x03\x04";
+/// Magic number starting ELF files
+const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+/// Byte order mark some tools start JSON documents with
+const UTF8_BOM: &[u8; 3] = b"\xef\xbb\xbf";
+/// Signatures of a hibernation file, before and after the machine resumed from it
+pub(crate) const HIBERFIL_SIGNATURES: [&[u8; 4]; 4] = [b"hibr", b"HIBR", b"wake", b"WAKE"];
+
+/// How the data of the dump is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IoMode {
+    /// Read through the page cache of the operating system
+    #[default]
+    Buffered,
+    /// Read with `O_DIRECT`, bypassing the page cache
+    Direct,
+}
+
+impl IoMode {
+    /// Get the IO mode from the `odirect` connector argument, defaults to
+    /// [`IoMode::Buffered`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the argument has an unknown value, or is `true` on a build without
+    /// `O_DIRECT` support (Linux with the `odirect` feature)
+    ///
+    pub fn from_args(args: &Args) -> Result<Self> {
+        match args.get("odirect") {
+            None | Some("false") => Ok(Self::Buffered),
+            Some("true") if cfg!(all(target_os = "linux", feature = "odirect")) => Ok(Self::Direct),
+            Some("true") => Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("odirect requires Linux and the `odirect` feature")),
+            Some(_) => Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("odirect must be either `true` or `false`")),
+        }
+    }
+}
+
+/// How memory ranges overlapping each other are mapped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverlapPolicy {
+    /// Refuse dumps containing overlapping ranges
+    Error,
+    /// Keep the data of the range that comes first in the file
+    First,
+    /// Keep the data of the range that comes last in the file, as the memory map of memflow
+    /// does
+    #[default]
+    Last,
+}
+
+impl OverlapPolicy {
+    /// Get the policy from the `overlap` connector argument, defaults to
+    /// [`OverlapPolicy::Last`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the argument has an unknown value
+    ///
+    pub fn from_args(args: &Args) -> Result<Self> {
+        match args.get("overlap") {
+            Some("error") => Ok(Self::Error),
+            Some("first") => Ok(Self::First),
+            None | Some("last") => Ok(Self::Last),
+            Some(_) => Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("overlap must be one of `error`, `first` or `last`")),
+        }
+    }
+}
+
+/// How the ranges that do not start or end on a page boundary are mapped
+///
+/// Ranges contiguous in physical memory are checked as a whole, only the outer edges of the
+/// block they form have to be aligned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlignPolicy {
+    /// Log misaligned ranges and map them as they are
+    #[default]
+    Keep,
+    /// Shrink misaligned ranges to the whole pages they hold
+    Trim,
+    /// Pad misaligned ranges to whole pages with zeros, the data surrounding them in the file
+    /// being left unmapped
+    Extend,
+    /// Refuse dumps containing misaligned ranges
+    Error,
+}
+
+impl AlignPolicy {
+    /// Size in bytes of the pages the ranges are aligned to
+    pub const PAGE_SIZE: u64 = 0x1000;
+
+    /// Get the policy from the `align` connector argument, defaults to [`AlignPolicy::Keep`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the argument has an unknown value
+    ///
+    pub fn from_args(args: &Args) -> Result<Self> {
+        match args.get("align") {
+            None | Some("none") => Ok(Self::Keep),
+            Some("trim") => Ok(Self::Trim),
+            Some("extend") => Ok(Self::Extend),
+            Some("error") => Ok(Self::Error),
+            Some(_) => Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("align must be one of `none`, `trim`, `extend` or `error`")),
+        }
+    }
+}
+
+/// How reads of physical addresses not backed by the dump are served
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GapFill {
+    /// Reads in gaps between ranges fail
+    #[default]
+    None,
+    /// Reads in gaps between ranges return zeros
+    Zero,
+}
+
+impl GapFill {
+    /// Get the gap filling strategy from the `fill_gaps` connector argument, defaults to
+    /// [`GapFill::None`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the argument has an unknown value
+    ///
+    pub fn from_args(args: &Args) -> Result<Self> {
+        match args.get("fill_gaps") {
+            None | Some("none") => Ok(Self::None),
+            Some("zero") => Ok(Self::Zero),
+            Some(_) => Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error("fill_gaps must be either `none` or `zero`")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connector_args(extra_args: Args) -> ConnectorArgs {
+        ConnectorArgs::new(Some("dump.lime"), extra_args, None)
+    }
+
+    #[test]
+    fn lime_config_from_args() {
+        let config = LimeConfig::from_args(&connector_args(
+            Args::new()
+                .insert("mode", "lenient")
+                .insert("fill_gaps", "zero")
+                .insert("index", "auto")
+                .insert("verify", "dump.sum")
+                .insert("verify_blocks", "auto"),
+        ))
+        .unwrap();
+        assert_eq!(config.path, PathBuf::from("dump.lime"));
+        assert_eq!(config.mode, ParseMode::Lenient);
+        assert_eq!(config.fill_gaps, GapFill::Zero);
+        assert_eq!(config.index, Some(PathBuf::from("dump.lime.limeidx")));
+        assert_eq!(config.verify, Some(PathBuf::from("dump.sum")));
+        assert_eq!(
+            config.verify_blocks,
+            Some(PathBuf::from("dump.lime.blocks"))
+        );
+        assert_eq!(config.cache, None);
+        assert_eq!(config.write, WriteMode::ReadOnly);
+        assert_eq!(config.addr_mask, None);
+        for (mask, parsed) in [("0xFFFFFFFF", Some(0xffff_ffff)), ("4095", Some(0xfff))] {
+            let config =
+                LimeConfig::from_args(&connector_args(Args::new().insert("addr_mask", mask)));
+            assert_eq!(config.unwrap().addr_mask, parsed);
+        }
+        for mask in ["0", "0xFF00", "32"] {
+            assert!(
+                LimeConfig::from_args(&connector_args(Args::new().insert("addr_mask", mask)))
+                    .is_err()
+            );
+        }
+        assert_eq!(config.rebase, []);
+        let config = LimeConfig::from_args(&connector_args(Args::new().insert(
+            "rebase",
+            "0x80000000-0x8fffffff:-0x80000000,0x40000000-0x4fffffff:+4096",
+        )))
+        .unwrap();
+        assert_eq!(
+            config.rebase,
+            [
+                RebaseRule {
+                    s_addr: 0x4000_0000,
+                    e_addr: 0x4fff_ffff,
+                    offset: 0x1000
+                },
+                RebaseRule {
+                    s_addr: 0x8000_0000,
+                    e_addr: 0x8fff_ffff,
+                    offset: -0x8000_0000
+                }
+            ]
+        );
+        for rebase in [
+            "+0x1000,0x0-0xfff:+1",
+            "0x2000-0x1000:+1",
+            "~0x1000",
+            "+0x8000000000000000",
+        ] {
+            assert!(
+                LimeConfig::from_args(&connector_args(Args::new().insert("rebase", rebase)))
+                    .is_err()
+            );
+        }
+        assert_eq!(config.ranges, None);
+        let config = LimeConfig::from_args(&connector_args(Args::new().insert(
+            "ranges",
+            "0x100000000-,0x100000-0x3fffffff,0-0xfffff,0x2000-0x2fff",
+        )))
+        .unwrap();
+        assert_eq!(
+            config.ranges,
+            Some(vec![(0, 0x3fff_ffff), (0x1_0000_0000, u64::MAX)])
+        );
+        for ranges in ["0x2000", "0x2000-0x1000", "low-high"] {
+            assert!(
+                LimeConfig::from_args(&connector_args(Args::new().insert("ranges", ranges)))
+                    .is_err()
+            );
+        }
+        assert_eq!(config.limits, ParseLimits::default());
+        let config = LimeConfig::from_args(&connector_args(
+            Args::new()
+                .insert("max_segments", "1000")
+                .insert("max_segment_size", "400m")
+                .insert("max_total_size", "10gb"),
+        ))
+        .unwrap();
+        assert_eq!(
+            config.limits,
+            ParseLimits {
+                max_segments: Some(1000),
+                max_segment_size: Some(0x400 << 20),
+                max_total_size: Some(0x10 << 30),
+            }
+        );
+        for (key, value) in [
+            ("max_segments", "0"),
+            ("max_segments", "0x10"),
+            ("max_segment_size", "0m"),
+            ("max_total_size", "10"),
+        ] {
+            assert!(
+                LimeConfig::from_args(&connector_args(Args::new().insert(key, value))).is_err()
+            );
+        }
+
+        assert!(LimeConfig::from_args(&ConnectorArgs::default()).is_err());
+        assert!(LimeConfig::from_args(&connector_args(
+            Args::new()
+                .insert("parse", "lazy")
+                .insert("scan", "parallel")
+        ))
+        .is_err());
+        assert!(LimeConfig::from_args(&connector_args(
+            Args::new().insert("reload", "true").insert("index", "auto")
+        ))
+        .is_err());
+        assert!(
+            LimeConfig::from_args(&connector_args(Args::new().insert("reload", "yes"))).is_err()
+        );
+        assert!(LimeConfig::from_args(&connector_args(
+            Args::new()
+                .insert("follow", "true")
+                .insert("readahead", "8m")
+        ))
+        .is_err());
+
+        let split = LimeConfig::from_args(&ConnectorArgs::new(
+            Some("dump.lime.000,dump.lime.001"),
+            Args::new(),
+            None,
+        ))
+        .unwrap();
+        assert_eq!(split.path, PathBuf::from("dump.lime.000"));
+        assert_eq!(
+            split.split,
+            [PathBuf::from("dump.lime.000"), "dump.lime.001".into()]
+        );
+        assert!(config.split.is_empty());
+        assert!(LimeConfig::from_args(&ConnectorArgs::new(
+            Some("dump.lime.000,dump.lime.001"),
+            Args::new().insert("index", "auto"),
+            None,
+        ))
+        .is_err());
+
+        assert_eq!(config.format, DumpFormat::Lime);
+        let format = |format: &str| {
+            LimeConfig::from_args(&ConnectorArgs::new(
+                Some("dump"),
+                Args::new().insert("format", format),
+                None,
+            ))
+        };
+        let aff4 = format("aff4");
+        match cfg!(feature = "aff4") {
+            true => assert_eq!(aff4.unwrap().format, DumpFormat::Aff4),
+            false => assert_eq!(
+                aff4.unwrap_err(),
+                Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+            ),
+        }
+        assert!(LimeConfig::from_args(&ConnectorArgs::new(
+            Some("dump.lime.000,dump.lime.001"),
+            Args::new().insert("write", "inplace"),
+            None,
+        ))
+        .is_err());
+        let overlays = LimeConfig::from_args(&connector_args(
+            Args::new().insert("overlay", "delta1.lime,delta2.lime"),
+        ))
+        .unwrap();
+        assert_eq!(
+            overlays.overlays,
+            [PathBuf::from("delta1.lime"), "delta2.lime".into()]
+        );
+        assert!(LimeConfig::from_args(&connector_args(
+            Args::new()
+                .insert("overlay", "delta1.lime")
+                .insert("parse", "lazy")
+        ))
+        .is_err());
+        assert!(LimeConfig::from_args(&ConnectorArgs::new(
+            Some("dump"),
+            Args::new().insert("format", "aff4").insert("parse", "lazy"),
+            None,
+        ))
+        .is_err());
+        let hiberfil = format("hiberfil");
+        match cfg!(feature = "hiberfil") {
+            true => assert_eq!(hiberfil.unwrap().format, DumpFormat::Hiberfil),
+            false => assert_eq!(
+                hiberfil.unwrap_err(),
+                Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+            ),
+        }
+        let kcore = format("kcore");
+        match cfg!(feature = "kcore") {
+            true => assert_eq!(kcore.unwrap().format, DumpFormat::Kcore),
+            false => assert_eq!(
+                kcore.unwrap_err(),
+                Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+            ),
+        }
+        let vbcore = format("vbcore");
+        match cfg!(feature = "vbcore") {
+            true => assert_eq!(vbcore.unwrap().format, DumpFormat::VBoxCore),
+            false => assert_eq!(
+                vbcore.unwrap_err(),
+                Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+            ),
+        }
+        let raw = format("rawjson");
+        match cfg!(feature = "rawjson") {
+            true => assert_eq!(raw.unwrap().format, DumpFormat::RawJson),
+            false => assert_eq!(
+                raw.unwrap_err(),
+                Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+            ),
+        }
+        // Names are not looked at, the files not existing being taken for LiME dumps.
+        for name in [
+            "dump.aff4",
+            "dump.json",
+            "hiberfil.sys",
+            "/proc/kcore",
+            "guest.elf",
+        ] {
+            let config =
+                LimeConfig::from_args(&ConnectorArgs::new(Some(name), Args::new(), None)).unwrap();
+            assert_eq!(config.format, DumpFormat::Lime);
+        }
+        assert_eq!(
+            format("raw").unwrap_err(),
+            Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+        );
+        assert!(LimeConfig::from_args(&ConnectorArgs::new(
+            Some("dump.lime.000,dump.lime.001"),
+            Args::new().insert("format", "aff4"),
+            None,
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn unknown_args_are_refused() {
+        assert_eq!(
+            LimeConfig::from_args(&connector_args(Args::new().insert("cache_sise", "10mb")))
+                .unwrap_err(),
+            Error(ErrorOrigin::Connector, ErrorKind::ArgNotExists)
+        );
+        assert!(LimeConfig::from_args(&connector_args(
+            Args::new().insert("verify", "sums,v2=x.sha256")
+        ))
+        .is_ok());
+    }
+
+    #[test]
+    fn parse_mode_from_args() {
+        assert_eq!(
+            ParseMode::from_args(&Args::new()).unwrap(),
+            ParseMode::Strict
+        );
+        assert_eq!(
+            ParseMode::from_args(&Args::new().insert("mode", "lenient")).unwrap(),
+            ParseMode::Lenient
+        );
+        assert!(ParseMode::from_args(&Args::new().insert("mode", "lax")).is_err());
+        assert!(ParseMode::Strict.tolerate("test").is_err());
+        assert!(ParseMode::Lenient.tolerate("test").is_ok());
+    }
+
+    #[test]
+    fn parse_options_from_args() {
+        assert_eq!(
+            ParseOptions::from_args(&Args::new()).unwrap(),
+            ParseOptions::default()
+        );
+        let options = ParseOptions::from_args(
+            &Args::new()
+                .insert("mode", "lenient")
+                .insert("allow_unknown_version", "true"),
+        )
+        .unwrap();
+        assert_eq!(options.mode, ParseMode::Lenient);
+        assert!(options.allow_unknown_version);
+        assert!(
+            ParseOptions::from_args(&Args::new().insert("allow_unknown_version", "yes")).is_err()
+        );
+    }
+
+    #[test]
+    fn byte_order_from_args() {
+        assert_eq!(ByteOrder::from_args(&Args::new()).unwrap(), ByteOrder::Auto);
+        assert_eq!(
+            ByteOrder::from_args(&Args::new().insert("endian", "be")).unwrap(),
+            ByteOrder::Big
+        );
+        assert!(ByteOrder::from_args(&Args::new().insert("endian", "middle")).is_err());
+    }
+
+    #[test]
+    fn scan_strategy_from_args() {
+        assert_eq!(
+            ScanStrategy::from_args(&Args::new()).unwrap(),
+            ScanStrategy::Serial
+        );
+        assert_eq!(
+            ScanStrategy::from_args(&Args::new().insert("scan", "parallel")).unwrap(),
+            ScanStrategy::Parallel
+        );
+        assert!(ScanStrategy::from_args(&Args::new().insert("scan", "fast")).is_err());
+    }
+
+    #[test]
+    fn parse_strategy_from_args() {
+        assert_eq!(
+            ParseStrategy::from_args(&Args::new()).unwrap(),
+            ParseStrategy::Eager
+        );
+        assert_eq!(
+            ParseStrategy::from_args(&Args::new().insert("parse", "lazy")).unwrap(),
+            ParseStrategy::Lazy
+        );
+        assert!(ParseStrategy::from_args(&Args::new().insert("parse", "later")).is_err());
+    }
+
+    #[test]
+    fn page_cache_config_from_args() {
+        assert_eq!(PageCacheConfig::from_args(&Args::new()).unwrap(), None);
+        assert_eq!(
+            PageCacheConfig::from_args(&Args::new().insert("cache_size", "10mb")).unwrap(),
+            Some(PageCacheConfig {
+                size: 0x10 << 20,
+                page_size: 0x1000,
+                validity: None,
+            })
+        );
+        assert_eq!(
+            PageCacheConfig::from_args(
+                &Args::new()
+                    .insert("cache_size", "8kb")
+                    .insert("cache_page_size", "2000")
+            )
+            .unwrap(),
+            Some(PageCacheConfig {
+                size: 0x2000,
+                page_size: 0x2000,
+                validity: None,
+            })
+        );
+        assert_eq!(
+            PageCacheConfig::from_args(
+                &Args::new()
+                    .insert("cached", "true")
+                    .insert("cache_time", "500")
+            )
+            .unwrap(),
+            Some(PageCacheConfig {
+                size: PageCacheConfig::DEFAULT_SIZE,
+                page_size: 0x1000,
+                validity: Some(Duration::from_millis(500)),
+            })
+        );
+        assert_eq!(
+            PageCacheConfig::from_args(&Args::new().insert("cached", "false")).unwrap(),
+            None
+        );
+        assert!(PageCacheConfig::from_args(
+            &Args::new()
+                .insert("cached", "false")
+                .insert("cache_size", "10mb")
+        )
+        .is_err());
+        assert!(PageCacheConfig::from_args(&Args::new().insert("cached", "yes")).is_err());
+        assert!(PageCacheConfig::from_args(
+            &Args::new()
+                .insert("cached", "true")
+                .insert("cache_time", "1s")
+        )
+        .is_err());
+        assert!(PageCacheConfig::from_args(&Args::new().insert("cache_size", "10")).is_err());
+        assert!(PageCacheConfig::from_args(
+            &Args::new()
+                .insert("cache_size", "1kb")
+                .insert("cache_page_size", "1000")
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn read_ahead_config_from_args() {
+        assert_eq!(ReadAheadConfig::from_args(&Args::new()).unwrap(), None);
+        assert_eq!(
+            ReadAheadConfig::from_args(&Args::new().insert("readahead", "8M")).unwrap(),
+            Some(ReadAheadConfig { window: 8 << 20 })
+        );
+        assert_eq!(
+            ReadAheadConfig::from_args(&Args::new().insert("readahead", "40kb")).unwrap(),
+            Some(ReadAheadConfig { window: 0x40 << 10 })
+        );
+        assert!(ReadAheadConfig::from_args(&Args::new().insert("readahead", "0m")).is_err());
+        assert!(ReadAheadConfig::from_args(&Args::new().insert("readahead", "8")).is_err());
+    }
+
+    #[test]
+    fn follow_config_from_args() {
+        assert_eq!(FollowConfig::from_args(&Args::new()).unwrap(), None);
+        assert_eq!(
+            FollowConfig::from_args(&Args::new().insert("follow", "false")).unwrap(),
+            None
+        );
+        assert_eq!(
+            FollowConfig::from_args(&Args::new().insert("follow", "true")).unwrap(),
+            Some(FollowConfig { timeout: None })
+        );
+        assert_eq!(
+            FollowConfig::from_args(&Args::new().insert("follow", "1500")).unwrap(),
+            Some(FollowConfig {
+                timeout: Some(Duration::from_millis(1500))
+            })
+        );
+        assert!(FollowConfig::from_args(&Args::new().insert("follow", "0")).is_err());
+        assert!(FollowConfig::from_args(&Args::new().insert("follow", "1s")).is_err());
+    }
+
+    #[test]
+    fn signature_config_from_args() {
+        let path = Path::new("dump.lime");
+        assert_eq!(
+            SignatureConfig::from_args(&Args::new(), path).unwrap(),
+            None
+        );
+        let config = SignatureConfig::from_args(
+            &Args::new()
+                .insert("signature", "auto")
+                .insert("pubkey", "agent.pub"),
+            path,
+        );
+        match cfg!(feature = "signature") {
+            true => assert_eq!(
+                config.unwrap(),
+                Some(SignatureConfig {
+                    signature: "dump.lime.minisig".into(),
+                    pubkey: "agent.pub".into(),
+                })
+            ),
+            false => assert!(config.is_err()),
+        }
+        assert!(
+            SignatureConfig::from_args(&Args::new().insert("signature", "auto"), path).is_err()
+        );
+    }
+
+    #[test]
+    fn decryption_key_from_args() {
+        assert_eq!(DecryptionKey::from_args(&Args::new()).unwrap(), None);
+        let keyfile = DecryptionKey::from_args(&Args::new().insert("keyfile", "case.key"));
+        match cfg!(feature = "age") {
+            true => assert_eq!(
+                keyfile.unwrap(),
+                Some(DecryptionKey::KeyFile("case.key".into()))
+            ),
+            false => assert!(keyfile.is_err()),
+        }
+        assert!(DecryptionKey::from_args(
+            &Args::new()
+                .insert("key", "AGE-SECRET-KEY-1")
+                .insert("keyfile", "case.key")
+        )
+        .is_err());
+        assert_eq!(
+            format!("{:?}", DecryptionKey::Key("AGE-SECRET-KEY-1".into())),
+            "Key(<redacted>)"
+        );
+    }
+
+    #[test]
+    fn fault_config_from_args() {
+        assert_eq!(FaultConfig::from_args(&Args::new()).unwrap(), None);
+        assert_eq!(
+            FaultConfig::from_args(
+                &Args::new()
+                    .insert("faults", "0x1000-0x1fff:io_error,8192-12287:short_read")
+                    .insert("fault_rate", "0.01")
+                    .insert("fault_seed", "42")
+            )
+            .unwrap(),
+            Some(FaultConfig {
+                ranges: vec![
+                    FaultRange {
+                        s_addr: 0x1000,
+                        e_addr: 0x1fff,
+                        kind: FaultKind::IoError
+                    },
+                    FaultRange {
+                        s_addr: 0x2000,
+                        e_addr: 0x2fff,
+                        kind: FaultKind::ShortRead
+                    }
+                ],
+                rate: 0.01,
+                seed: 42,
+            })
+        );
+        for args in [
+            Args::new().insert("faults", "0x1000-0x1fff"),
+            Args::new().insert("faults", "0x2000-0x1000:io_error"),
+            Args::new().insert("faults", "0x1000-0x1fff:timeout"),
+            Args::new()
+                .insert("faults", "0x1000-0x1fff:io_error")
+                .insert("fault_rate", "1.5"),
+            Args::new().insert("fault_rate", "0.5"),
+        ] {
+            assert!(FaultConfig::from_args(&args).is_err());
+        }
+    }
+
+    #[test]
+    fn throttle_config_from_args() {
+        assert_eq!(ThrottleConfig::from_args(&Args::new()).unwrap(), None);
+        assert_eq!(
+            ThrottleConfig::from_args(&Args::new().insert("throttle", "10ms")).unwrap(),
+            Some(ThrottleConfig {
+                latency: Duration::from_millis(10),
+                bandwidth: None
+            })
+        );
+        for (bandwidth, parsed) in [
+            ("50MBps", 50_000_000),
+            ("512kB/s", 512_000),
+            ("1gb", 1_000_000_000),
+            ("4096B", 4096),
+        ] {
+            assert_eq!(
+                ThrottleConfig::from_args(&Args::new().insert("bandwidth", bandwidth)).unwrap(),
+                Some(ThrottleConfig {
+                    latency: Duration::ZERO,
+                    bandwidth: Some(parsed)
+                })
+            );
+        }
+        for (key, value) in [
+            ("throttle", "10"),
+            ("throttle", "1.5s"),
+            ("bandwidth", "0MBps"),
+            ("bandwidth", "50M"),
+        ] {
+            assert!(ThrottleConfig::from_args(&Args::new().insert(key, value)).is_err());
+        }
+
+        let throttle = ThrottleConfig {
+            latency: Duration::from_millis(10),
+            bandwidth: Some(1_000_000),
+        };
+        assert_eq!(throttle.min_duration(500_000), Duration::from_millis(510));
+    }
+
+    #[test]
+    fn io_mode_from_args() {
+        assert_eq!(IoMode::from_args(&Args::new()).unwrap(), IoMode::Buffered);
+        assert_eq!(
+            IoMode::from_args(&Args::new().insert("odirect", "false")).unwrap(),
+            IoMode::Buffered
+        );
+        assert_eq!(
+            IoMode::from_args(&Args::new().insert("odirect", "true")).is_ok(),
+            cfg!(all(target_os = "linux", feature = "odirect"))
+        );
+        assert!(IoMode::from_args(&Args::new().insert("odirect", "yes")).is_err());
+    }
+
+    #[test]
+    fn overlap_policy_from_args() {
+        assert_eq!(
+            OverlapPolicy::from_args(&Args::new()).unwrap(),
+            OverlapPolicy::Last
+        );
+        assert_eq!(
+            OverlapPolicy::from_args(&Args::new().insert("overlap", "error")).unwrap(),
+            OverlapPolicy::Error
+        );
+        assert!(OverlapPolicy::from_args(&Args::new().insert("overlap", "both")).is_err());
+    }
+
+    #[test]
+    fn align_policy_from_args() {
+        assert_eq!(
+            AlignPolicy::from_args(&Args::new()).unwrap(),
+            AlignPolicy::Keep
+        );
+        assert_eq!(
+            AlignPolicy::from_args(&Args::new().insert("align", "extend")).unwrap(),
+            AlignPolicy::Extend
+        );
+        assert!(AlignPolicy::from_args(&Args::new().insert("align", "round")).is_err());
+        assert!(LimeConfig::from_args(&connector_args(
+            Args::new().insert("align", "trim").insert("follow", "true")
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn gap_fill_from_args() {
+        assert_eq!(GapFill::from_args(&Args::new()).unwrap(), GapFill::None);
+        assert_eq!(
+            GapFill::from_args(&Args::new().insert("fill_gaps", "zero")).unwrap(),
+            GapFill::Zero
+        );
+        assert!(GapFill::from_args(&Args::new().insert("fill_gaps", "ones")).is_err());
+    }
+}
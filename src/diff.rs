@@ -0,0 +1,200 @@
+use crate::config::{OverlapPolicy, ParseMode};
+use crate::segment::{open_dump, parse_dump, resolve_overlaps, LimeSegment};
+
+use memflow::prelude::v1::*;
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Bytes compared at a time, rounded down to a multiple of the granularity
+const COMPARE_CHUNK_SIZE: u64 = 1 << 20;
+
+/// How a physical range differs between two dumps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum LimeChangeKind {
+    /// Held by both dumps with different content
+    Modified,
+    /// Held by the first dump only
+    Removed,
+    /// Held by the second dump only
+    Added,
+}
+
+/// Physical range that differs between two dumps, returned by [`lime_diff`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LimeChange {
+    /// Starting address of the range
+    pub s_addr: u64,
+    /// Ending address of the range, inclusive
+    pub e_addr: u64,
+    /// How the range differs
+    pub kind: LimeChangeKind,
+}
+
+impl LimeChange {
+    /// Size in bytes of the range
+    pub const fn size(&self) -> u64 {
+        self.e_addr - self.s_addr + 1
+    }
+}
+
+/// Compare two `LiME` files of the same machine and list the physical ranges that differ.
+///
+/// Memory held by both dumps is compared in blocks of `granularity` bytes aligned to their
+/// size (e.g. `0x1000` for pages), a block whose content differs being reported whole,
+/// clipped to the memory held by both dumps. Memory held by only one of the dumps is
+/// reported byte-exact. Adjacent changes of the same kind are merged, and the changes are
+/// sorted by address. When the ranges of a dump overlap, the data of the range that comes
+/// last in the file is compared, like [`convert_to_raw`](crate::convert_to_raw) does.
+///
+/// # Arguments
+///
+/// * `dump_a` - path of the first `LiME` file, e.g. captured before an event
+/// * `dump_b` - path of the second `LiME` file, e.g. captured after it
+/// * `granularity` - size in bytes of the blocks compared
+///
+/// # Errors
+///
+/// Returns `Err` if `granularity` is zero, or if an error occurred while reading or parsing
+/// the files
+///
+pub fn lime_diff<P: AsRef<Path>, Q: AsRef<Path>>(
+    dump_a: P,
+    dump_b: Q,
+    granularity: u64,
+) -> Result<Vec<LimeChange>> {
+    if granularity == 0 {
+        return Err(Error(ErrorOrigin::Connector, ErrorKind::InvalidArgument)
+            .log_error("The diff granularity can not be zero"));
+    }
+
+    let mut file_a = open_dump(dump_a)?;
+    let mut file_b = open_dump(dump_b)?;
+    let sorted_a = sorted_segments(&mut file_a)?;
+    let sorted_b = sorted_segments(&mut file_b)?;
+
+    // Addresses where the coverage of either dump may change, one past the end of the
+    // ranges being kept in 128 bits not to overflow.
+    let mut bounds = sorted_a
+        .iter()
+        .chain(&sorted_b)
+        .flat_map(|segment| [segment.s_addr as u128, segment.e_addr as u128 + 1])
+        .collect::<Vec<_>>();
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    let mut changes = Vec::new();
+    let mut buffs = (Vec::new(), Vec::new());
+    for window in bounds.windows(2) {
+        let (s_addr, e_addr) = (window[0] as u64, (window[1] - 1) as u64);
+        match (covering(&sorted_a, s_addr), covering(&sorted_b, s_addr)) {
+            (None, None) => {}
+            (Some(_), None) => push_change(&mut changes, s_addr, e_addr, LimeChangeKind::Removed),
+            (None, Some(_)) => push_change(&mut changes, s_addr, e_addr, LimeChangeKind::Added),
+            (Some(a), Some(b)) => {
+                let a = a.clip(s_addr, e_addr).unwrap();
+                let b = b.clip(s_addr, e_addr).unwrap();
+                compare(
+                    (&mut file_a, a.file_offset),
+                    (&mut file_b, b.file_offset),
+                    (s_addr, e_addr),
+                    granularity,
+                    &mut buffs,
+                    &mut changes,
+                )?;
+            }
+        }
+    }
+    Ok(changes)
+}
+
+/// Parse `lime_dump` and get its segments sorted by address, free of overlaps.
+fn sorted_segments(lime_dump: &mut File) -> Result<Vec<LimeSegment>> {
+    let segments = parse_dump(lime_dump, ParseMode::Strict.into())?.segments;
+    resolve_overlaps(&segments, OverlapPolicy::Last)
+}
+
+/// Segment of `sorted` holding `addr`, if any.
+fn covering(sorted: &[LimeSegment], addr: u64) -> Option<LimeSegment> {
+    let index = sorted.partition_point(|segment| segment.e_addr < addr);
+    sorted
+        .get(index)
+        .filter(|segment| segment.s_addr <= addr)
+        .copied()
+}
+
+/// Record the change of `[s_addr, e_addr]`, merging it with the previous one if they are
+/// adjacent and of the same kind.
+fn push_change(changes: &mut Vec<LimeChange>, s_addr: u64, e_addr: u64, kind: LimeChangeKind) {
+    match changes.last_mut() {
+        Some(last) if last.kind == kind && last.e_addr.checked_add(1) == Some(s_addr) => {
+            last.e_addr = e_addr;
+        }
+        _ => changes.push(LimeChange {
+            s_addr,
+            e_addr,
+            kind,
+        }),
+    }
+}
+
+/// Compare the physical range `[s_addr, e_addr]` held by both dumps from the given file
+/// offsets, block by block.
+fn compare(
+    (file_a, offset_a): (&mut File, u64),
+    (file_b, offset_b): (&mut File, u64),
+    (s_addr, e_addr): (u64, u64),
+    granularity: u64,
+    (buff_a, buff_b): &mut (Vec<u8>, Vec<u8>),
+    changes: &mut Vec<LimeChange>,
+) -> Result<()> {
+    let blocks_per_chunk = (COMPARE_CHUNK_SIZE / granularity).max(1);
+    let mut addr = s_addr;
+    loop {
+        // Chunks end on a block boundary so that no block is split across two of them.
+        let chunk_end = (addr / granularity)
+            .checked_add(blocks_per_chunk)
+            .and_then(|blocks| blocks.checked_mul(granularity))
+            .map_or(e_addr, |next| (next - 1).min(e_addr));
+        let len = (chunk_end - addr + 1) as usize;
+        let delta = addr - s_addr;
+        read_at(file_a, offset_a + delta, buff_a, len)?;
+        read_at(file_b, offset_b + delta, buff_b, len)?;
+
+        let mut block_start = addr;
+        while block_start <= chunk_end {
+            let block_end = (block_start / granularity)
+                .checked_add(1)
+                .and_then(|block| block.checked_mul(granularity))
+                .map_or(chunk_end, |next| (next - 1).min(chunk_end));
+            let range = (block_start - addr) as usize..=(block_end - addr) as usize;
+            if buff_a[range.clone()] != buff_b[range] {
+                push_change(changes, block_start, block_end, LimeChangeKind::Modified);
+            }
+            match block_end.checked_add(1) {
+                Some(next) => block_start = next,
+                None => break,
+            }
+        }
+
+        match chunk_end.checked_add(1) {
+            Some(next) if chunk_end < e_addr => addr = next,
+            _ => return Ok(()),
+        }
+    }
+}
+
+/// Read `len` bytes at `offset` of `lime_dump` into `buff`.
+fn read_at(lime_dump: &mut File, offset: u64, buff: &mut Vec<u8>, len: usize) -> Result<()> {
+    buff.resize(len, 0);
+    lime_dump
+        .seek(SeekFrom::Start(offset))
+        .and_then(|_| lime_dump.read_exact(buff))
+        .map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+                .log_error("Unable to read the memory section")
+        })
+}
@@ -0,0 +1,94 @@
+use crate::dumpfile::read_at;
+
+use std::fs::File;
+use std::io::{self, IoSliceMut};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Alignment of the offset, length and memory of `O_DIRECT` reads.
+///
+/// Every block device in use has a logical block size dividing the page size, so reads
+/// aligned to it are accepted everywhere `O_DIRECT` is.
+const DIRECT_IO_ALIGNMENT: usize = 0x1000;
+
+/// Handle of the dump opened with `O_DIRECT`, reading around the page cache
+///
+/// Reads are widened to aligned boundaries into an aligned bounce buffer, the requested bytes
+/// are then copied out of it.
+#[derive(Clone)]
+pub(crate) struct DirectFile {
+    file: Arc<File>,
+}
+
+impl DirectFile {
+    /// Open the dump at `path` with `O_DIRECT`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file can not be opened, e.g. because its filesystem does not
+    /// support `O_DIRECT`
+    ///
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        #[cfg(all(target_os = "linux", feature = "odirect"))]
+        {
+            use std::fs::OpenOptions;
+            use std::os::unix::fs::OpenOptionsExt;
+
+            let file = OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_DIRECT)
+                .open(path)?;
+            Ok(Self {
+                file: Arc::new(file),
+            })
+        }
+        #[cfg(not(all(target_os = "linux", feature = "odirect")))]
+        {
+            let _ = path;
+            Err(io::ErrorKind::Unsupported.into())
+        }
+    }
+
+    /// Fill every buffer of `bufs` with the bytes following file offset `offset`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the read fails or ends before the buffers are filled, the kind being
+    /// [`io::ErrorKind::InvalidInput`] if the filesystem refuses `O_DIRECT` reads
+    ///
+    pub(crate) fn read_exact_at(&self, offset: u64, bufs: &mut [IoSliceMut]) -> io::Result<()> {
+        let len = bufs.iter().map(|buf| buf.len() as u64).sum::<u64>();
+        let align = DIRECT_IO_ALIGNMENT as u64;
+        let aligned_start = offset - offset % align;
+        let aligned_len = usize::try_from((offset + len).next_multiple_of(align) - aligned_start)
+            .map_err(|_| io::Error::from(io::ErrorKind::OutOfMemory))?;
+
+        let mut bounce = vec![0u8; aligned_len + DIRECT_IO_ALIGNMENT];
+        let skew = bounce.as_ptr().align_offset(DIRECT_IO_ALIGNMENT);
+        let bounce = &mut bounce[skew..skew + aligned_len];
+
+        // Only the read reaching the end of the file may come back short.
+        let needed = (offset - aligned_start + len) as usize;
+        let mut filled = 0;
+        while filled < needed {
+            match read_at(
+                &self.file,
+                &mut bounce[filled..],
+                aligned_start + filled as u64,
+            ) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(read) => filled += read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut data = &bounce[(offset - aligned_start) as usize..needed];
+        for buf in bufs {
+            let (head, tail) = data.split_at(buf.len());
+            buf.copy_from_slice(head);
+            data = tail;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,543 @@
+use crate::blockdev::file_size;
+
+use memflow::connector::fileio::CloneFile;
+use memflow::prelude::v1::*;
+
+use std::fs::File;
+use std::io::{self, IoSliceMut, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Bytes of a `LiME` dump, stored in one or more parts read as their concatenation
+///
+/// A dump is usually a single file. Dumps split in numbered parts to fit filesystems limiting
+/// the size of a file (FAT32) may have a header or a range cut across two parts, and dumps
+/// inside an archive are a window of the archive or, once inflated, a buffer in memory. The
+/// offsets of the parts are computed on open, only the last part may keep growing.
+///
+/// Files are read and written with positional IO, every clone keeping its own handle and
+/// offset, so that clones used from different threads never race on a shared file cursor.
+#[derive(Clone)]
+pub(crate) struct DumpFile {
+    parts: Vec<Part>,
+    /// Offset of each part in the dump
+    starts: Arc<[u64]>,
+    pos: u64,
+}
+
+/// Read-only bytes decoded on demand, like the compressed chunks of a stream
+pub(crate) trait DecodedStream: Send + Sync {
+    /// Size of the decoded bytes.
+    fn len(&self) -> u64;
+
+    /// Decode the bytes following `offset` into `buf`, returns how many were decoded.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize>;
+}
+
+/// Part of a [`DumpFile`]
+#[derive(Clone)]
+enum Part {
+    /// Bytes of `file` from offset `base`, up to `len` bytes or the end of the file, read
+    /// from offset `pos` of the part on
+    File {
+        file: CloneFile,
+        base: u64,
+        len: Option<u64>,
+        pos: u64,
+    },
+    /// Read-only bytes of `stream`, from offset `pos` on
+    #[cfg_attr(not(any(feature = "aff4", feature = "hiberfil")), allow(dead_code))]
+    Decoded {
+        stream: Arc<dyn DecodedStream>,
+        pos: u64,
+    },
+}
+
+impl Part {
+    /// Size of the part, a part running to the end of its file being measured again.
+    fn len(&self) -> io::Result<u64> {
+        match self {
+            Self::File { len: Some(len), .. } => Ok(*len),
+            Self::File {
+                file,
+                base,
+                len: None,
+                ..
+            } => Ok(file_size(file)?.saturating_sub(*base)),
+            Self::Decoded { stream, .. } => Ok(stream.len()),
+        }
+    }
+
+    /// Size of the part if it can not grow.
+    fn fixed_len(&self) -> Option<u64> {
+        match self {
+            Self::File { len, .. } => *len,
+            Self::Decoded { stream, .. } => Some(stream.len()),
+        }
+    }
+
+    /// Move the seek of the part to `offset`, relative to the start of the part.
+    fn seek_to(&mut self, offset: u64) -> io::Result<()> {
+        match self {
+            Self::File { pos, .. } | Self::Decoded { pos, .. } => {
+                *pos = offset;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Read for Part {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::File {
+                file, base, pos, ..
+            } => {
+                let read = read_at(file, buf, *base + *pos)?;
+                *pos += read as u64;
+                Ok(read)
+            }
+            Self::Decoded { stream, pos } => {
+                let read = stream.read_at(*pos, buf)?;
+                *pos += read as u64;
+                Ok(read)
+            }
+        }
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        match self {
+            Self::File {
+                file, base, pos, ..
+            } => {
+                let read = read_vectored_at(file, bufs, *base + *pos)?;
+                *pos += read as u64;
+                Ok(read)
+            }
+            Self::Decoded { .. } => match bufs.iter_mut().find(|buf| !buf.is_empty()) {
+                Some(buf) => self.read(buf),
+                None => Ok(0),
+            },
+        }
+    }
+}
+
+impl Write for Part {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::File {
+                file, base, pos, ..
+            } => {
+                let written = write_at(file, buf, *base + *pos)?;
+                *pos += written as u64;
+                Ok(written)
+            }
+            Self::Decoded { .. } => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "the decoded dump is read-only",
+            )),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::File { file, .. } => file.flush(),
+            Self::Decoded { .. } => Ok(()),
+        }
+    }
+}
+
+impl DumpFile {
+    /// Open the parts of a split dump, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if `paths` is empty or a part can not be opened
+    ///
+    pub(crate) fn open_parts(paths: &[PathBuf]) -> Result<Self> {
+        let parts = paths
+            .iter()
+            .map(|path| {
+                File::open(path)
+                    .map(|file| Part::File {
+                        file: file.into(),
+                        base: 0,
+                        len: None,
+                        pos: 0,
+                    })
+                    .map_err(|_| {
+                        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+                            .log_error(format!("Unable to open the LiME part {}", path.display()))
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if parts.is_empty() {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+                .log_error("LiME split dump has no part"));
+        }
+        Self::from_parts(parts)
+            .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))
+    }
+
+    /// Dump made of the `len` bytes of `file` from offset `base`, like a member stored in an
+    /// archive.
+    #[cfg_attr(not(feature = "archive"), allow(dead_code))]
+    pub(crate) fn window(file: File, base: u64, len: u64) -> Self {
+        Self::single(Part::File {
+            file: file.into(),
+            base,
+            len: Some(len),
+            pos: 0,
+        })
+    }
+
+    /// Read-only dump made of the concatenation of `streams`, like the image streams of a
+    /// container, `streams` not being empty.
+    #[cfg_attr(not(any(feature = "aff4", feature = "hiberfil")), allow(dead_code))]
+    pub(crate) fn decoded(streams: Vec<Arc<dyn DecodedStream>>) -> Self {
+        let mut starts = Vec::with_capacity(streams.len());
+        let mut start = 0;
+        for stream in &streams {
+            starts.push(start);
+            start += stream.len();
+        }
+        Self {
+            parts: streams
+                .into_iter()
+                .map(|stream| Part::Decoded { stream, pos: 0 })
+                .collect(),
+            starts: starts.into(),
+            pos: 0,
+        }
+    }
+
+    fn single(part: Part) -> Self {
+        Self {
+            parts: vec![part],
+            starts: [0].into(),
+            pos: 0,
+        }
+    }
+
+    fn from_parts(parts: Vec<Part>) -> io::Result<Self> {
+        let mut starts = Vec::with_capacity(parts.len());
+        let mut start = 0;
+        for part in &parts {
+            starts.push(start);
+            start += part.len()?;
+        }
+        Ok(Self {
+            parts,
+            starts: starts.into(),
+            pos: 0,
+        })
+    }
+
+    /// Duplicate the handle of the file the dump is read from, `None` unless the dump is a
+    /// whole single file.
+    pub(crate) fn into_file(self) -> Option<io::Result<File>> {
+        match <[Part; 1]>::try_from(self.parts) {
+            Ok(
+                [Part::File {
+                    file,
+                    base: 0,
+                    len: None,
+                    ..
+                }],
+            ) => Some(file.try_clone()),
+            _ => None,
+        }
+    }
+
+    /// Size of the dump, the last part being measured again.
+    pub(crate) fn len(&self) -> io::Result<u64> {
+        let last = self.parts.len() - 1;
+        Ok(self.starts[last] + self.parts[last].len()?)
+    }
+
+    /// Fill `bufs` with the bytes of the dump following `offset`, leaving the offset of the
+    /// handle unchanged.
+    pub(crate) fn read_exact_at(
+        &mut self,
+        offset: u64,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> io::Result<()> {
+        let pos = std::mem::replace(&mut self.pos, offset);
+        let read = read_exact_vectored(self, bufs);
+        self.pos = pos;
+        read
+    }
+
+    /// Write all of `buf` to the dump from `offset`, leaving the offset of the handle
+    /// unchanged.
+    pub(crate) fn write_all_at(&mut self, offset: u64, buf: &[u8]) -> io::Result<()> {
+        let pos = std::mem::replace(&mut self.pos, offset);
+        let written = self.write_all(buf);
+        self.pos = pos;
+        written
+    }
+
+    /// Part holding the current offset, its offset within the part and the bytes left in it,
+    /// `None` if the part runs to the end of a file that may grow.
+    fn locate(&self) -> (usize, u64, Option<u64>) {
+        let index = self.starts.partition_point(|start| *start <= self.pos) - 1;
+        let start = self.starts[index];
+        let end = match self.starts.get(index + 1) {
+            Some(end) => Some(*end),
+            None => self.parts[index].fixed_len().map(|len| start + len),
+        };
+        let left = end.map(|end| end.saturating_sub(self.pos));
+        (index, self.pos - start, left)
+    }
+}
+
+impl From<File> for DumpFile {
+    fn from(file: File) -> Self {
+        Self::single(Part::File {
+            file: file.into(),
+            base: 0,
+            len: None,
+            pos: 0,
+        })
+    }
+}
+
+/// Longest prefix of a request of `len` bytes that does not cross the end of the part.
+fn clamp(len: usize, left: Option<u64>) -> usize {
+    left.map_or(len, |left| {
+        len.min(usize::try_from(left).unwrap_or(usize::MAX))
+    })
+}
+
+impl Read for DumpFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (index, offset, left) = self.locate();
+        let len = clamp(buf.len(), left);
+        let part = &mut self.parts[index];
+        part.seek_to(offset)?;
+        let read = part.read(&mut buf[..len])?;
+        self.pos += read as u64;
+        Ok(read)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        let (index, offset, left) = self.locate();
+        let len = bufs.iter().map(|buf| buf.len()).sum::<usize>();
+        if clamp(len, left) < len {
+            // The request crosses the end of the part, only its first buffer is read.
+            return match bufs.iter_mut().find(|buf| !buf.is_empty()) {
+                Some(buf) => self.read(buf),
+                None => Ok(0),
+            };
+        }
+
+        let part = &mut self.parts[index];
+        part.seek_to(offset)?;
+        let read = part.read_vectored(bufs)?;
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl Write for DumpFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let (index, offset, left) = self.locate();
+        let len = clamp(buf.len(), left);
+        let part = &mut self.parts[index];
+        part.seek_to(offset)?;
+        let written = part.write(&buf[..len])?;
+        self.pos += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.parts.iter_mut().try_for_each(|part| part.flush())
+    }
+}
+
+impl Seek for DumpFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.len()?.checked_add_signed(delta),
+        };
+        self.pos = pos.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek before the start of the dump",
+            )
+        })?;
+        Ok(self.pos)
+    }
+}
+
+/// Fill every buffer of `bufs` from `reader`, like [`Read::read_exact`] does for one buffer.
+fn read_exact_vectored<R: Read>(reader: &mut R, mut bufs: &mut [IoSliceMut]) -> io::Result<()> {
+    IoSliceMut::advance_slices(&mut bufs, 0);
+    while !bufs.is_empty() {
+        match reader.read_vectored(bufs) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(read) => IoSliceMut::advance_slices(&mut bufs, read),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Read the bytes of `file` following `offset` into `buf`, without using the file cursor
+/// on Unix.
+#[cfg(unix)]
+pub(crate) fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+pub(crate) fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
+
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn read_at(mut file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    file.seek(SeekFrom::Start(offset))?;
+    file.read(buf)
+}
+
+/// Read the bytes of `file` following `offset` into `bufs` with a single `preadv`, the
+/// first `IOV_MAX` buffers being read if there are more.
+#[cfg(target_os = "linux")]
+fn read_vectored_at(file: &File, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+    use std::os::fd::AsRawFd;
+
+    let count = bufs.len().min(IOV_MAX);
+    let offset = libc::off_t::try_from(offset)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset out of range"))?;
+    // SAFETY: `IoSliceMut` is ABI compatible with `iovec` on Unix, and `count` of them are
+    // valid for writes.
+    let read = unsafe {
+        libc::preadv(
+            file.as_raw_fd(),
+            bufs.as_mut_ptr().cast::<libc::iovec>(),
+            count as libc::c_int,
+            offset,
+        )
+    };
+    usize::try_from(read).map_err(|_| io::Error::last_os_error())
+}
+
+/// Read the bytes of `file` following `offset` into `bufs` with a single read into a scratch
+/// buffer, split into `bufs` afterwards.
+#[cfg(not(target_os = "linux"))]
+fn read_vectored_at(file: &File, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+    let mut scratch = vec![0; bufs.iter().map(|buf| buf.len()).sum()];
+    let read = read_at(file, &mut scratch, offset)?;
+    let mut left = &scratch[..read];
+    for buf in bufs {
+        let (head, tail) = left.split_at(buf.len().min(left.len()));
+        buf[..head.len()].copy_from_slice(head);
+        left = tail;
+    }
+    Ok(read)
+}
+
+/// Most buffers a single `preadv` accepts
+#[cfg(target_os = "linux")]
+const IOV_MAX: usize = 1024;
+
+/// Write `buf` to `file` from `offset`, without using the file cursor on Unix.
+#[cfg(unix)]
+fn write_at(file: &File, buf: &[u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::write_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn write_at(file: &File, buf: &[u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_write(file, buf, offset)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn write_at(mut file: &File, buf: &[u8], offset: u64) -> io::Result<usize> {
+    file.seek(SeekFrom::Start(offset))?;
+    file.write(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn windows_and_buffers_are_bounded() {
+        let path = std::env::temp_dir().join("memflow_lime_dumpfile_window");
+        std::fs::write(&path, b"xx0123yy").unwrap();
+        let mut window = DumpFile::window(File::open(&path).unwrap(), 2, 4);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(window.len().unwrap(), 4);
+        let mut read = Vec::new();
+        window.seek(SeekFrom::Start(1)).unwrap();
+        window.read_to_end(&mut read).unwrap();
+        assert_eq!(read, b"123");
+    }
+
+    #[test]
+    fn positional_reads_keep_the_offset() {
+        let path = std::env::temp_dir().join("memflow_lime_dumpfile_positional");
+        std::fs::write(&path, b"0123456789").unwrap();
+        let mut file = DumpFile::from(File::open(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        file.seek(SeekFrom::Start(2)).unwrap();
+        let (mut a, mut b) = ([0u8; 2], [0u8; 3]);
+        file.read_exact_at(5, &mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+            .unwrap();
+        assert_eq!((&a, &b), (b"56", b"789"));
+        assert_eq!(file.stream_position().unwrap(), 2);
+        assert!(file
+            .read_exact_at(9, &mut [IoSliceMut::new(&mut a)])
+            .is_err());
+    }
+
+    #[test]
+    fn contiguous_buffers_take_a_single_read() {
+        let path = std::env::temp_dir().join("memflow_lime_dumpfile_preadv");
+        std::fs::write(&path, b"0123456789").unwrap();
+        let mut file = DumpFile::from(File::open(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        file.seek(SeekFrom::Start(1)).unwrap();
+        let (mut a, mut b, mut c) = ([0u8; 2], [0u8; 0], [0u8; 5]);
+        let read = file
+            .read_vectored(&mut [
+                IoSliceMut::new(&mut a),
+                IoSliceMut::new(&mut b),
+                IoSliceMut::new(&mut c),
+            ])
+            .unwrap();
+        assert_eq!(read, 7);
+        assert_eq!((&a, &c), (b"12", b"34567"));
+    }
+
+    #[test]
+    fn vectored_read_fills_every_buffer() {
+        let mut reader = Cursor::new((0..8u8).collect::<Vec<_>>());
+        let (mut a, mut b, mut c) = ([0u8; 3], [0u8; 0], [0u8; 4]);
+        read_exact_vectored(
+            &mut reader,
+            &mut [
+                IoSliceMut::new(&mut a),
+                IoSliceMut::new(&mut b),
+                IoSliceMut::new(&mut c),
+            ],
+        )
+        .unwrap();
+        assert_eq!((a, c), ([0, 1, 2], [3, 4, 5, 6]));
+
+        let mut d = [0u8; 2];
+        assert_eq!(
+            read_exact_vectored(&mut reader, &mut [IoSliceMut::new(&mut d)])
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+}
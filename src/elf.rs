@@ -0,0 +1,217 @@
+use memflow::prelude::v1::*;
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Magic number starting every ELF file
+const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+/// `EI_CLASS` of 64-bit ELF files
+const ELFCLASS64: u8 = 2;
+/// `EI_DATA` of little-endian ELF files
+const ELFDATA2LSB: u8 = 1;
+/// `EI_DATA` of big-endian ELF files
+const ELFDATA2MSB: u8 = 2;
+/// `e_type` of core files
+const ET_CORE: u16 = 4;
+/// Size of the ELF header of 64-bit files
+const EHDR_SIZE: usize = 0x40;
+/// Size of a program header of 64-bit files
+const PHDR_SIZE: usize = 0x38;
+/// Size of a section header of 64-bit files
+const SHDR_SIZE: usize = 0x40;
+/// `e_phnum` telling that the number of program headers is in `sh_info` of section 0
+const PN_XNUM: u16 = 0xffff;
+/// `p_type` of loadable segments
+pub(crate) const PT_LOAD: u32 = 1;
+/// `p_type` of note segments
+#[cfg(feature = "vbcore")]
+pub(crate) const PT_NOTE: u32 = 4;
+/// Most program headers read, well above the few hundred of large NUMA machines
+const MAX_PHDRS: usize = 0x10000;
+/// Largest note segment read
+#[cfg(feature = "vbcore")]
+const MAX_NOTES_SIZE: u64 = 0x100_0000;
+
+/// Program header of a 64-bit ELF core file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ProgramHeader {
+    pub(crate) p_type: u32,
+    pub(crate) offset: u64,
+    pub(crate) vaddr: u64,
+    pub(crate) paddr: u64,
+    pub(crate) filesz: u64,
+    pub(crate) memsz: u64,
+}
+
+/// Note of an ELF core file
+#[cfg(feature = "vbcore")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Note {
+    /// Owner of the note, without its terminating NUL
+    pub(crate) name: Vec<u8>,
+    pub(crate) n_type: u32,
+    pub(crate) desc: Vec<u8>,
+}
+
+/// Byte order of the fields of an ELF file
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Elf {
+    big_endian: bool,
+}
+
+impl Elf {
+    pub(crate) fn u16_at(&self, buf: &[u8], offset: usize) -> u16 {
+        let bytes = buf[offset..offset + 2].try_into().unwrap();
+        match self.big_endian {
+            true => u16::from_be_bytes(bytes),
+            false => u16::from_le_bytes(bytes),
+        }
+    }
+
+    pub(crate) fn u32_at(&self, buf: &[u8], offset: usize) -> u32 {
+        let bytes = buf[offset..offset + 4].try_into().unwrap();
+        match self.big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        }
+    }
+
+    pub(crate) fn u64_at(&self, buf: &[u8], offset: usize) -> u64 {
+        let bytes = buf[offset..offset + 8].try_into().unwrap();
+        match self.big_endian {
+            true => u64::from_be_bytes(bytes),
+            false => u64::from_le_bytes(bytes),
+        }
+    }
+}
+
+/// Read the byte order and the program headers of the 64-bit ELF core file `file`, in file
+/// order.
+///
+/// # Errors
+///
+/// Returns `Err` if the file can not be read or is not a 64-bit ELF core file
+///
+pub(crate) fn read_program_headers(
+    file: &mut File,
+    path: &Path,
+) -> Result<(Elf, Vec<ProgramHeader>)> {
+    let mut ehdr = [0u8; EHDR_SIZE];
+    read_exact_at(file, 0, &mut ehdr)?;
+    if &ehdr[..4] != ELF_MAGIC {
+        return Err(corrupt(format!("{} is not an ELF file", path.display())));
+    }
+    let big_endian = match ehdr[5] {
+        ELFDATA2LSB => false,
+        ELFDATA2MSB => true,
+        data => return Err(corrupt(format!("ELF byte order {} is unknown", data))),
+    };
+    let elf = Elf { big_endian };
+    if ehdr[4] != ELFCLASS64 || elf.u16_at(&ehdr, 0x10) != ET_CORE {
+        return Err(Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+            .log_error(format!("{} is not a 64-bit ELF core file", path.display())));
+    }
+
+    let phoff = elf.u64_at(&ehdr, 0x20);
+    let shoff = elf.u64_at(&ehdr, 0x28);
+    let phentsize = usize::from(elf.u16_at(&ehdr, 0x36));
+    let phnum = match elf.u16_at(&ehdr, 0x38) {
+        PN_XNUM => {
+            let mut shdr = [0u8; SHDR_SIZE];
+            read_exact_at(file, shoff, &mut shdr)?;
+            elf.u32_at(&shdr, 0x2c) as usize
+        }
+        phnum => usize::from(phnum),
+    };
+    if phentsize < PHDR_SIZE || phnum > MAX_PHDRS {
+        return Err(corrupt(format!(
+            "ELF file declares {} program headers of {} bytes",
+            phnum, phentsize
+        )));
+    }
+
+    let mut phdrs = vec![0u8; phnum * phentsize];
+    read_exact_at(file, phoff, &mut phdrs)?;
+    let phdrs = phdrs
+        .chunks_exact(phentsize)
+        .map(|phdr| ProgramHeader {
+            p_type: elf.u32_at(phdr, 0),
+            offset: elf.u64_at(phdr, 0x08),
+            vaddr: elf.u64_at(phdr, 0x10),
+            paddr: elf.u64_at(phdr, 0x18),
+            filesz: elf.u64_at(phdr, 0x20),
+            memsz: elf.u64_at(phdr, 0x28),
+        })
+        .collect();
+    Ok((elf, phdrs))
+}
+
+/// Read the notes of the note segments among `phdrs`, their names and descriptors being
+/// padded to `align` bytes.
+///
+/// # Errors
+///
+/// Returns `Err` if a note segment can not be read or a note overflows its segment
+///
+#[cfg(feature = "vbcore")]
+pub(crate) fn read_notes(
+    file: &mut File,
+    elf: Elf,
+    phdrs: &[ProgramHeader],
+    align: usize,
+) -> Result<Vec<Note>> {
+    let mut notes = Vec::new();
+    for phdr in phdrs.iter().filter(|phdr| phdr.p_type == PT_NOTE) {
+        if phdr.filesz > MAX_NOTES_SIZE {
+            return Err(corrupt(format!(
+                "ELF note segment of {:#x} bytes is too large",
+                phdr.filesz
+            )));
+        }
+        let mut segment = vec![0u8; phdr.filesz as usize];
+        read_exact_at(file, phdr.offset, &mut segment)?;
+
+        let mut rest = segment.as_slice();
+        while rest.len() >= 12 {
+            let namesz = elf.u32_at(rest, 0) as usize;
+            let descsz = elf.u32_at(rest, 4) as usize;
+            let n_type = elf.u32_at(rest, 8);
+            let name_end = 12 + namesz;
+            let desc_start = name_end.next_multiple_of(align);
+            let desc_end = desc_start + descsz;
+            if desc_end > rest.len() {
+                return Err(corrupt(format!(
+                    "ELF note of type {:#x} overflows its segment",
+                    n_type
+                )));
+            }
+            let name = &rest[12..name_end];
+            notes.push(Note {
+                name: name.strip_suffix(b"\0").unwrap_or(name).to_vec(),
+                n_type,
+                desc: rest[desc_start..desc_end].to_vec(),
+            });
+            rest = &rest[desc_end.next_multiple_of(align).min(rest.len())..];
+        }
+    }
+    Ok(notes)
+}
+
+/// Fill `buf` with the bytes of `file` following `offset`.
+fn read_exact_at(file: &mut File, offset: u64, buf: &mut [u8]) -> Result<()> {
+    file.seek(SeekFrom::Start(offset))
+        .and_then(|_| file.read_exact(buf))
+        .map_err(|e: io::Error| {
+            corrupt(format!(
+                "Unable to read {} bytes of the ELF file at offset {:#x}: {}",
+                buf.len(),
+                offset,
+                e
+            ))
+        })
+}
+
+pub(crate) fn corrupt(msg: String) -> Error {
+    Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(msg)
+}
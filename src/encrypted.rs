@@ -0,0 +1,100 @@
+use crate::config::DecryptionKey;
+use crate::dumpfile::{DecodedStream, DumpFile};
+
+use memflow::prelude::v1::*;
+
+use age::stream::StreamReader;
+use age::{Decryptor, Identity, IdentityFile};
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Open the `LiME` dump encrypted with age at `path`, decrypting it with `key`.
+///
+/// The payload of an age file is encrypted in independent 64 KiB chunks, only the chunks
+/// a read touches are decrypted, in memory. No plaintext reaches the disk.
+///
+/// # Errors
+///
+/// Returns `Err` if the file can not be read, is not an age file, or if none of the
+/// identities of `key` can decrypt it
+///
+pub(crate) fn open_encrypted(path: &Path, key: &DecryptionKey) -> Result<DumpFile> {
+    let identities = load_identities(key)?;
+    let file = File::open(path).map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+            "Unable to open the encrypted dump {}",
+            path.display()
+        ))
+    })?;
+    let mut reader = Decryptor::new(file)
+        .and_then(|decryptor| decryptor.decrypt(identities.iter().map(|identity| &**identity)))
+        .map_err(|e| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+                "Unable to decrypt {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+    let len = reader
+        .seek(SeekFrom::End(0))
+        .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToSeekFile))?;
+    log::info!(
+        "Decrypting {} on demand, {} bytes of plaintext",
+        path.display(),
+        len
+    );
+
+    let stream = AgeStream {
+        reader: Mutex::new(reader),
+        len,
+    };
+    Ok(DumpFile::decoded(vec![Arc::new(stream)]))
+}
+
+/// Parse the age identities `key` holds or points to.
+fn load_identities(key: &DecryptionKey) -> Result<Vec<Box<dyn Identity>>> {
+    let identities = match key {
+        DecryptionKey::Key(key) => IdentityFile::from_buffer(key.as_bytes()),
+        DecryptionKey::KeyFile(path) => {
+            IdentityFile::from_file(path.to_string_lossy().into_owned())
+        }
+    }
+    .map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+            .log_error("Unable to read the age identities")
+    })?
+    .into_identities()
+    .map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+            .log_error("The age identities are not supported or malformed")
+    })?;
+    if identities.is_empty() {
+        return Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+            .log_error("No age identity was given"));
+    }
+    Ok(identities)
+}
+
+/// Plaintext of an age file
+struct AgeStream {
+    reader: Mutex<StreamReader<File>>,
+    len: u64,
+}
+
+impl DecodedStream for AgeStream {
+    fn len(&self) -> u64 {
+        self.len
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        if offset >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+        let mut reader = self.reader.lock().unwrap_or_else(|e| e.into_inner());
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read(buf)
+    }
+}
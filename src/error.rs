@@ -0,0 +1,231 @@
+use crate::config::ByteOrder;
+use crate::header::{HeaderError, LimeHeader};
+
+use memflow::prelude::v1::*;
+use memflow_lime_core::ParseError;
+
+use std::fmt;
+use std::io;
+
+/// Why a `LiME` file could not be parsed, with the header affected
+///
+/// Converts into the memflow [`Error`] returned by the connector, logging its description.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LimeError {
+    /// The file could not be read
+    Io {
+        /// Index of the header being read
+        header: usize,
+        /// File offset of the header
+        offset: u64,
+        /// Underlying IO error
+        source: io::Error,
+    },
+    /// The header does not start with the `LiME` magic number
+    BadMagic {
+        /// Index of the header
+        header: usize,
+        /// File offset of the header
+        offset: u64,
+        /// Magic number expected, as stored in the file
+        expected: [u8; 4],
+        /// First four bytes of the header
+        found: [u8; 4],
+    },
+    /// The header version is not supported, refused in strict mode
+    UnsupportedVersion {
+        /// Index of the header
+        header: usize,
+        /// File offset of the header
+        offset: u64,
+        /// Version supported
+        expected: u32,
+        /// Version of the header
+        found: u32,
+    },
+    /// The reserved fields of the header are not zero, refused in strict mode
+    NonZeroReserved {
+        /// Index of the header
+        header: usize,
+        /// File offset of the header
+        offset: u64,
+        /// Reserved fields of the header
+        found: [u8; 8],
+    },
+    /// The end address is lower than the start address, or the size of the range does not
+    /// fit in a `u64`
+    ImpossibleRange {
+        /// Index of the header
+        header: usize,
+        /// File offset of the header
+        offset: u64,
+        /// Starting address of the range
+        s_addr: u64,
+        /// Ending address of the range, inclusive
+        e_addr: u64,
+    },
+    /// Any other error, already logged
+    Other(Error),
+}
+
+impl LimeError {
+    /// Map the failure to read header #`header`, at `offset` and in `byte_order`.
+    pub(crate) fn from_header(
+        err: HeaderError,
+        header: usize,
+        offset: u64,
+        byte_order: ByteOrder,
+    ) -> Self {
+        match err {
+            HeaderError::Io(source) => Self::Io {
+                header,
+                offset,
+                source,
+            },
+            HeaderError::Parse(ParseError::BadMagic(found)) => Self::BadMagic {
+                header,
+                offset,
+                expected: match byte_order {
+                    ByteOrder::Big => LimeHeader::MAGIC.to_be_bytes(),
+                    ByteOrder::Auto | ByteOrder::Little => LimeHeader::MAGIC.to_le_bytes(),
+                },
+                found,
+            },
+            HeaderError::Parse(err) => Self::Other(
+                Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+                    "LiME header #{} at offset {:#x} is corrupt: {}",
+                    header, offset, err
+                )),
+            ),
+        }
+    }
+
+    /// Whether the header itself is corrupt, as opposed to the file being unreadable.
+    pub(crate) const fn is_corrupt_header(&self) -> bool {
+        matches!(
+            self,
+            Self::BadMagic { .. }
+                | Self::UnsupportedVersion { .. }
+                | Self::NonZeroReserved { .. }
+                | Self::ImpossibleRange { .. }
+        )
+    }
+
+    /// Index of the header affected, `None` for errors not tied to a header.
+    pub const fn header(&self) -> Option<usize> {
+        match self {
+            Self::Io { header, .. }
+            | Self::BadMagic { header, .. }
+            | Self::UnsupportedVersion { header, .. }
+            | Self::NonZeroReserved { header, .. }
+            | Self::ImpossibleRange { header, .. } => Some(*header),
+            Self::Other(_) => None,
+        }
+    }
+
+    /// File offset of the header affected, `None` for errors not tied to a header.
+    pub const fn offset(&self) -> Option<u64> {
+        match self {
+            Self::Io { offset, .. }
+            | Self::BadMagic { offset, .. }
+            | Self::UnsupportedVersion { offset, .. }
+            | Self::NonZeroReserved { offset, .. }
+            | Self::ImpossibleRange { offset, .. } => Some(*offset),
+            Self::Other(_) => None,
+        }
+    }
+
+    /// Kind of the memflow [`Error`] the error converts into.
+    pub const fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Io { .. }
+            | Self::BadMagic { .. }
+            | Self::UnsupportedVersion { .. }
+            | Self::NonZeroReserved { .. } => ErrorKind::UnableToReadFile,
+            Self::ImpossibleRange { .. } => ErrorKind::InvalidMemorySize,
+            Self::Other(Error(_, kind)) => *kind,
+        }
+    }
+}
+
+impl fmt::Display for LimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io {
+                header,
+                offset,
+                source,
+            } => write!(
+                f,
+                "LiME header #{} at offset {:#x} can not be read: {}",
+                header, offset, source
+            ),
+            Self::BadMagic {
+                header,
+                offset,
+                expected,
+                found,
+            } => write!(
+                f,
+                "LiME header #{} at offset {:#x} is corrupt: expected magic {:02x?}, found {:02x?}",
+                header, offset, expected, found
+            ),
+            Self::UnsupportedVersion {
+                header,
+                offset,
+                expected,
+                found,
+            } => write!(
+                f,
+                "LiME header #{} at offset {:#x}: unsupported LiME version {}, expected {}",
+                header, offset, found, expected
+            ),
+            Self::NonZeroReserved {
+                header,
+                offset,
+                found,
+            } => write!(
+                f,
+                "LiME header #{} at offset {:#x}: unsupported LiME reserved fields values: {:02x?}",
+                header, offset, found
+            ),
+            Self::ImpossibleRange {
+                header,
+                offset,
+                s_addr,
+                e_addr,
+            } => write!(
+                f,
+                "LiME header #{} at offset {:#x}: range {:#x}-{:#x} declares an impossible size",
+                header, offset, s_addr, e_addr
+            ),
+            Self::Other(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for LimeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Other(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<Error> for LimeError {
+    fn from(err: Error) -> Self {
+        Self::Other(err)
+    }
+}
+
+impl From<LimeError> for Error {
+    fn from(err: LimeError) -> Self {
+        match err {
+            LimeError::Other(err) => err,
+            err => Error(ErrorOrigin::Connector, err.kind()).log_error(err.to_string()),
+        }
+    }
+}
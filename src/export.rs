@@ -0,0 +1,351 @@
+use crate::config::ParseMode;
+use crate::header::LimeHeader;
+use crate::segment::{open_dump, parse_dump, LimeSegment};
+
+use memflow::prelude::v1::*;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Bytes copied at a time from the dump to the output file
+const COPY_CHUNK_SIZE: u64 = 1 << 20;
+
+/// Extract the physical window `[s_addr, e_addr]` of a `LiME` file into a new `LiME` file.
+///
+/// Every segment of the source dump that intersects the window is clipped to it and written
+/// to `out_path` with a rewritten header, parts of the window not backed by the source dump
+/// are left out.
+///
+/// # Arguments
+///
+/// * `lime_path` - path of the source `LiME` file
+/// * `out_path` - path of the `LiME` file to create, it is overwritten if it already exists
+/// * `s_addr` - starting physical address of the window
+/// * `e_addr` - ending physical address of the window, inclusive
+///
+/// # Errors
+///
+/// Returns `Err` if the window is empty or not backed by the source file, or if an error
+/// occurred while reading the source file or writing the new one
+///
+pub fn extract_range<P: AsRef<Path>, Q: AsRef<Path>>(
+    lime_path: P,
+    out_path: Q,
+    s_addr: u64,
+    e_addr: u64,
+) -> Result<()> {
+    let (mut lime_dump, clipped) = clip_dump(lime_path.as_ref(), s_addr, e_addr)?;
+    let mut out = create_output(out_path)?;
+    write_sections(&mut lime_dump, &clipped, &mut out)?;
+    out.flush().map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+            .log_error("Unable to flush the output file")
+    })
+}
+
+/// Open the `LiME` file at `lime_path` and clip its segments to the physical window
+/// `[s_addr, e_addr]`, see [`extract_range`].
+pub(crate) fn clip_dump(
+    lime_path: &Path,
+    s_addr: u64,
+    e_addr: u64,
+) -> Result<(File, Vec<LimeSegment>)> {
+    if e_addr < s_addr {
+        return Err(Error(ErrorOrigin::Connector, ErrorKind::InvalidArgument)
+            .log_error("End address can not be lower than start address"));
+    }
+
+    let mut lime_dump = open_dump(lime_path)?;
+    let clipped = parse_dump(&mut lime_dump, ParseMode::Strict.into())?
+        .segments
+        .into_iter()
+        .filter_map(|segment| segment.clip(s_addr, e_addr))
+        .collect::<Vec<_>>();
+
+    if clipped.is_empty() {
+        return Err(Error(ErrorOrigin::Connector, ErrorKind::OutOfMemoryRange)
+            .log_error("The requested range is not backed by the LiME file"));
+    }
+    Ok((lime_dump, clipped))
+}
+
+/// Write `segments` of `lime_dump` to `out`, each with a rewritten header.
+pub(crate) fn write_sections<W: Write>(
+    lime_dump: &mut File,
+    segments: &[LimeSegment],
+    out: &mut W,
+) -> Result<()> {
+    for segment in segments {
+        LimeHeader::new(segment.s_addr, segment.e_addr).write_to(out)?;
+        copy_section(
+            lime_dump,
+            segment.file_offset,
+            segment.size(),
+            out,
+            &mut |_| (),
+        )?;
+    }
+    Ok(())
+}
+
+/// How the gaps between the ranges are stored in a raw image
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RawOutput {
+    /// Gaps are skipped with a seek, leaving holes in a sparse file that read as zeros
+    #[default]
+    Sparse,
+    /// Gaps are written as zeros, for filesystems without sparse file support
+    Dense,
+}
+
+/// Convert a `LiME` file into a flat raw physical memory image.
+///
+/// The byte at offset `n` of the raw image is the byte at physical address `n`, gaps between
+/// the ranges of the dump read as zeros and are left as holes of a sparse file, see
+/// [`convert_to_raw_with_options`] for a dense image. When ranges overlap, the data of the
+/// range that comes last in the `LiME` file is kept.
+///
+/// # Arguments
+///
+/// * `lime_path` - path of the source `LiME` file
+/// * `out_path` - path of the raw image to create, it is overwritten if it already exists
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading the source file or writing the raw image
+///
+pub fn convert_to_raw<P: AsRef<Path>, Q: AsRef<Path>>(lime_path: P, out_path: Q) -> Result<()> {
+    convert_to_raw_with_progress(lime_path, out_path, |_, _| ())
+}
+
+/// Convert a `LiME` file into a flat raw physical memory image like [`convert_to_raw`],
+/// reporting progress.
+///
+/// `progress` is called with the number of bytes of memory copied so far and the total number
+/// of bytes of memory held by the dump.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading the source file or writing the raw image
+///
+pub fn convert_to_raw_with_progress<P, Q, F>(lime_path: P, out_path: Q, progress: F) -> Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    F: FnMut(u64, u64),
+{
+    convert_to_raw_with_options(lime_path, out_path, RawOutput::Sparse, progress)
+}
+
+/// Convert a `LiME` file into a flat raw physical memory image like
+/// [`convert_to_raw_with_progress`], storing the gaps as `output` requests.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading the source file or writing the raw image
+///
+pub fn convert_to_raw_with_options<P, Q, F>(
+    lime_path: P,
+    out_path: Q,
+    output: RawOutput,
+    mut progress: F,
+) -> Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    F: FnMut(u64, u64),
+{
+    write_raw(lime_path.as_ref(), out_path.as_ref(), output, &mut progress).map(|_| ())
+}
+
+/// Convert a `LiME` file into the raw image and memory map MemProcFS opens, reporting
+/// progress like [`convert_to_raw_with_progress`].
+///
+/// The raw image at `raw_path` is the sparse image of [`convert_to_raw`], the byte at offset
+/// `n` being the byte at physical address `n`. The physical ranges it holds are written to
+/// [`default_memmap_path`], one line per range in the format of the `physmemmap.txt` file of
+/// MemProcFS, so that the gaps are not read as RAM:
+///
+/// ```text
+/// MemProcFS -device dump.raw -memmap dump.raw.memmap
+/// ```
+///
+/// Overlapping and adjacent ranges are merged into one line.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading the source file or writing the raw image
+/// or the memory map
+///
+pub fn convert_to_memprocfs<P, Q, F>(lime_path: P, raw_path: Q, mut progress: F) -> Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    F: FnMut(u64, u64),
+{
+    let raw_path = raw_path.as_ref();
+    let segments = write_raw(
+        lime_path.as_ref(),
+        raw_path,
+        RawOutput::Sparse,
+        &mut progress,
+    )?;
+    let memmap_path = default_memmap_path(raw_path);
+    std::fs::write(&memmap_path, memprocfs_memmap(&segments)).map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile).log_error(format!(
+            "Unable to write the memory map {}",
+            memmap_path.display()
+        ))
+    })
+}
+
+/// Default location of the MemProcFS memory map of the raw image at `raw_path`, i.e.
+/// `<raw_path>.memmap`.
+pub fn default_memmap_path<P: AsRef<Path>>(raw_path: P) -> PathBuf {
+    let mut path = raw_path.as_ref().as_os_str().to_owned();
+    path.push(".memmap");
+    path.into()
+}
+
+/// Memory map of `segments` in the format of MemProcFS, the index, first and last address of
+/// every merged range in hex.
+fn memprocfs_memmap(segments: &[LimeSegment]) -> String {
+    let mut sorted = segments.to_vec();
+    sorted.sort_by_key(|segment| segment.s_addr);
+    let mut ranges: Vec<(u64, u64)> = Vec::with_capacity(sorted.len());
+    for segment in sorted {
+        match ranges.last_mut() {
+            Some((_, e_addr)) if segment.s_addr <= e_addr.saturating_add(1) => {
+                *e_addr = (*e_addr).max(segment.e_addr);
+            }
+            _ => ranges.push((segment.s_addr, segment.e_addr)),
+        }
+    }
+    ranges
+        .iter()
+        .enumerate()
+        .map(|(index, (s_addr, e_addr))| {
+            format!("{:04x} {:>16x} - {:>16x}\n", index, s_addr, e_addr)
+        })
+        .collect()
+}
+
+/// Write the raw image of the `LiME` file at `lime_path` to `out_path`, returns the segments
+/// of the dump.
+fn write_raw(
+    lime_path: &Path,
+    out_path: &Path,
+    output: RawOutput,
+    progress: &mut dyn FnMut(u64, u64),
+) -> Result<Vec<LimeSegment>> {
+    let mut lime_dump = open_dump(lime_path)?;
+    let segments = parse_dump(&mut lime_dump, ParseMode::Strict.into())?.segments;
+    let mut out = create_output(out_path)?;
+    let total = segments.iter().map(LimeSegment::size).sum::<u64>();
+    let mut copied = 0;
+
+    let mut sorted = segments.clone();
+    sorted.sort_by_key(|segment| segment.s_addr);
+
+    // First fill the holes of a dense image, then lay the ranges down in file order so that
+    // later ranges overwrite earlier ones where they overlap. The holes of a sparse image are
+    // left by the seeks past them.
+    let mut next_uncovered = 0u64;
+    for segment in &sorted {
+        if output == RawOutput::Dense && segment.s_addr > next_uncovered {
+            seek_output(&mut out, next_uncovered)?;
+            write_zeros(&mut out, segment.s_addr - next_uncovered)?;
+        }
+        next_uncovered = next_uncovered.max(segment.e_addr.saturating_add(1));
+    }
+
+    for segment in &segments {
+        seek_output(&mut out, segment.s_addr)?;
+        copy_section(
+            &mut lime_dump,
+            segment.file_offset,
+            segment.size(),
+            &mut out,
+            &mut |len| {
+                copied += len;
+                progress(copied, total);
+            },
+        )?;
+    }
+
+    out.flush().map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+            .log_error("Unable to flush the output file")
+    })?;
+    Ok(segments)
+}
+
+/// Create (or truncate) the output file at `path`.
+fn create_output<P: AsRef<Path>>(path: P) -> Result<BufWriter<File>> {
+    File::create(path).map(BufWriter::new).map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+            .log_error("Unable to create the output file")
+    })
+}
+
+/// Move the write position of `out` to `offset`.
+fn seek_output<W: Seek>(out: &mut W, offset: u64) -> Result<()> {
+    out.seek(SeekFrom::Start(offset)).map(|_| ()).map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToSeekFile)
+            .log_error("Unable to seek the output file")
+    })
+}
+
+/// Write `len` zero bytes to `out`.
+fn write_zeros<W: Write>(out: &mut W, len: u64) -> Result<()> {
+    io::copy(&mut io::repeat(0).take(len), out)
+        .map(|_| ())
+        .map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+                .log_error("Unable to zero-fill the output file")
+        })
+}
+
+/// Copy `len` bytes starting at `offset` of `lime_dump` into `out`, calling `progress` with
+/// the size of every chunk copied.
+fn copy_section<W: Write>(
+    lime_dump: &mut File,
+    offset: u64,
+    len: u64,
+    out: &mut W,
+    progress: &mut dyn FnMut(u64),
+) -> Result<()> {
+    lime_dump.seek(SeekFrom::Start(offset)).map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToSeekFile)
+            .log_error("Unable to seek to the memory section")
+    })?;
+
+    let mut section = lime_dump.take(len);
+    let mut buff = vec![0u8; COPY_CHUNK_SIZE.min(len) as usize];
+    let mut copied = 0;
+    loop {
+        let n = match section.read(&mut buff) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => {
+                return Err(Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+                    .log_error("Unable to read the memory section"))
+            }
+        };
+        out.write_all(&buff[..n]).map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+                .log_error("Unable to copy the memory section")
+        })?;
+        copied += n as u64;
+        progress(n as u64);
+    }
+
+    if copied == len {
+        Ok(())
+    } else {
+        Err(Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+            .log_error("The memory section is shorter than declared by its header"))
+    }
+}
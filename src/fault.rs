@@ -0,0 +1,76 @@
+use crate::config::{FaultConfig, FaultKind};
+
+/// Decides which reads fail according to a [`FaultConfig`]
+///
+/// The draws come from a xorshift generator seeded with `fault_seed`, so that a given
+/// sequence of reads fails the same way on every run.
+#[derive(Debug, Clone)]
+pub(crate) struct FaultInjector {
+    config: FaultConfig,
+    state: u64,
+}
+
+impl FaultInjector {
+    pub(crate) fn new(config: FaultConfig) -> Self {
+        // xorshift is stuck at zero, the seed is mixed with a constant instead.
+        let state = config.seed ^ 0x9e37_79b9_7f4a_7c15;
+        Self { config, state }
+    }
+
+    /// How the read of `len` bytes at `addr` fails, `None` if it succeeds.
+    pub(crate) fn inject(&mut self, addr: u64, len: u64) -> Option<FaultKind> {
+        let e_addr = addr.saturating_add(len.saturating_sub(1));
+        let kind = self
+            .config
+            .ranges
+            .iter()
+            .find(|range| range.s_addr <= e_addr && addr <= range.e_addr)?
+            .kind;
+        (self.draw() < self.config.rate).then_some(kind)
+    }
+
+    /// Next pseudo-random number in `[0, 1)`.
+    fn draw(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FaultRange;
+
+    fn injector(rate: f64, seed: u64) -> FaultInjector {
+        FaultInjector::new(FaultConfig {
+            ranges: vec![FaultRange {
+                s_addr: 0x1000,
+                e_addr: 0x1fff,
+                kind: FaultKind::IoError,
+            }],
+            rate,
+            seed,
+        })
+    }
+
+    #[test]
+    fn faults_are_deterministic() {
+        let mut always = injector(1.0, 0);
+        assert_eq!(always.inject(0x1ff0, 0x20), Some(FaultKind::IoError));
+        assert_eq!(always.inject(0x2000, 0x1000), None);
+        assert_eq!(always.inject(0, 0x1000), None);
+
+        let draws = |seed| {
+            let mut injector = injector(0.5, seed);
+            (0..64)
+                .map(|_| injector.inject(0x1000, 8).is_some())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(draws(7), draws(7));
+        assert_ne!(draws(7), draws(8));
+        let failures = draws(7).into_iter().filter(|failed| *failed).count();
+        assert!((16..48).contains(&failures));
+    }
+}
@@ -0,0 +1,203 @@
+//! C API of the connector, declared in `include/memflow_lime.h`.
+//!
+//! The header is generated with
+//! `cbindgen --config cbindgen.toml --output include/memflow_lime.h src/ffi.rs`
+//! and must be regenerated whenever the functions below change.
+
+use crate::memory::LimeMemory;
+
+use memflow::prelude::v1::*;
+
+use std::ffi::{c_char, CStr};
+use std::ptr;
+use std::slice;
+
+/// Opaque handle of a `LiME` dump opened by [`lime_open`]
+pub struct LimeHandle {
+    mem: LimeMemory,
+}
+
+/// Physical range held by a `LiME` dump
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimeRange {
+    /// Starting address of the range
+    pub s_addr: u64,
+    /// Ending address of the range, inclusive
+    pub e_addr: u64,
+}
+
+/// The call succeeded
+pub const LIME_OK: i32 = 0;
+/// A pointer argument is null or a string is not valid UTF-8
+pub const LIME_ERR_INVALID_ARGUMENT: i32 = -1;
+/// Part of the requested memory is not held by the dump, or the dump could not be read
+pub const LIME_ERR_READ: i32 = -2;
+
+/// Open the `LiME` file at `path`.
+///
+/// `args` holds the connector arguments as a `key=value,...` string, it may be null.
+/// Returns null if the file can not be opened or parsed, the cause being logged.
+///
+/// # Safety
+///
+/// `path` must be a nul-terminated string, `args` a nul-terminated string or null.
+#[no_mangle]
+pub unsafe extern "C" fn lime_open(path: *const c_char, args: *const c_char) -> *mut LimeHandle {
+    let Some(path) = to_str(path) else {
+        return ptr::null_mut();
+    };
+    let extra_args = match args.is_null() {
+        true => Ok(Args::new()),
+        false => to_str(args).map_or(
+            Err(Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)),
+            str::parse,
+        ),
+    };
+    extra_args
+        .and_then(|extra_args| {
+            crate::create_connector(&ConnectorArgs::new(Some(path), extra_args, None))
+        })
+        .map_or(ptr::null_mut(), |mem| {
+            Box::into_raw(Box::new(LimeHandle { mem }))
+        })
+}
+
+/// Read `len` bytes of physical memory at `addr` into `buf`.
+///
+/// Returns [`LIME_OK`] on success, [`LIME_ERR_READ`] if part of the memory is not held by
+/// the dump and [`LIME_ERR_INVALID_ARGUMENT`] if a pointer is null.
+///
+/// # Safety
+///
+/// `handle` must come from [`lime_open`] and not be closed, `buf` must be valid for writes
+/// of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn lime_read_phys(
+    handle: *mut LimeHandle,
+    addr: u64,
+    buf: *mut u8,
+    len: usize,
+) -> i32 {
+    let Some(handle) = handle.as_mut() else {
+        return LIME_ERR_INVALID_ARGUMENT;
+    };
+    if buf.is_null() {
+        return LIME_ERR_INVALID_ARGUMENT;
+    }
+    let buf = slice::from_raw_parts_mut(buf, len);
+    match handle.mem.phys_view().read_raw_into(addr.into(), buf) {
+        Ok(()) => LIME_OK,
+        Err(_) => LIME_ERR_READ,
+    }
+}
+
+/// Copy the physical ranges held by the dump, sorted by address, into `ranges`.
+///
+/// At most `capacity` ranges are copied, `ranges` may be null if `capacity` is zero.
+/// Returns the number of ranges of the dump, which may be more than `capacity`, so that the
+/// call can be repeated with a large enough array.
+///
+/// # Safety
+///
+/// `handle` must come from [`lime_open`] and not be closed, `ranges` must be valid for
+/// writes of `capacity` ranges.
+#[no_mangle]
+pub unsafe extern "C" fn lime_ranges(
+    handle: *const LimeHandle,
+    ranges: *mut LimeRange,
+    capacity: usize,
+) -> usize {
+    let Some(handle) = handle.as_ref() else {
+        return 0;
+    };
+    let mem_map = handle.mem.mem_map();
+    if !ranges.is_null() {
+        let ranges = slice::from_raw_parts_mut(ranges, capacity);
+        for (range, mapping) in ranges.iter_mut().zip(mem_map.iter()) {
+            let s_addr = mapping.base().to_umem();
+            *range = LimeRange {
+                s_addr,
+                e_addr: s_addr + mapping.output().1 - 1,
+            };
+        }
+    }
+    mem_map.iter().count()
+}
+
+/// Close a dump opened by [`lime_open`], null being ignored.
+///
+/// # Safety
+///
+/// `handle` must come from [`lime_open`] and not be closed already.
+#[no_mangle]
+pub unsafe extern "C" fn lime_close(handle: *mut LimeHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Borrow the nul-terminated string `s`, `None` if it is null or not valid UTF-8.
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LimeWriter;
+    use std::ffi::CString;
+    use std::fs::{self, File};
+
+    #[test]
+    fn dump_is_read_through_the_c_api() {
+        let path = std::env::temp_dir().join("memflow_lime_ffi.lime");
+        let mut writer = LimeWriter::new(File::create(&path).unwrap());
+        writer.write_range(0x1000, &[0x11; 0x1000]).unwrap();
+        writer.write_range(0x4000, &[0x44; 0x1000]).unwrap();
+        writer.finish().unwrap();
+        let c_path = CString::new(path.to_str().unwrap()).unwrap();
+        let c_args = CString::new("fill_gaps=zero").unwrap();
+
+        let mut buff = [0u8; 0x10];
+        let mut ranges = [LimeRange {
+            s_addr: 0,
+            e_addr: 0,
+        }; 1];
+        unsafe {
+            let handle = lime_open(c_path.as_ptr(), ptr::null());
+            assert!(!handle.is_null());
+            assert_eq!(
+                lime_read_phys(handle, 0x4ff0, buff.as_mut_ptr(), buff.len()),
+                LIME_OK
+            );
+            assert_eq!(
+                lime_read_phys(handle, 0x2000, buff.as_mut_ptr(), 0x10),
+                LIME_ERR_READ
+            );
+            assert_eq!(lime_ranges(handle, ranges.as_mut_ptr(), ranges.len()), 2);
+            lime_close(handle);
+
+            let handle = lime_open(c_path.as_ptr(), c_args.as_ptr());
+            assert_eq!(
+                lime_read_phys(handle, 0x2000, buff.as_mut_ptr(), 0x10),
+                LIME_OK
+            );
+            lime_close(handle);
+            assert!(lime_open(ptr::null(), ptr::null()).is_null());
+        }
+
+        fs::remove_file(path).unwrap();
+        assert_eq!(buff, [0; 0x10]);
+        assert_eq!(
+            ranges,
+            [LimeRange {
+                s_addr: 0x1000,
+                e_addr: 0x1fff
+            }]
+        );
+    }
+}
@@ -0,0 +1,400 @@
+use crate::config::{ByteOrder, ParseMode, ParseOptions};
+use crate::error::LimeError;
+
+use memflow_lime_core::{Endian, ParseError, RangeHeader};
+
+use memflow::prelude::v1::*;
+
+use std::fmt;
+use std::io;
+use std::io::{Read, Write};
+
+/// Header defined by the `LiME` file format, version 1
+///
+/// source: [LiME Memory Range Header Version 1 Specification](https://github.com/504ensicsLabs/LiME/blob/master/doc/README.md#Spec)
+#[derive(Debug)]
+pub(crate) struct LimeHeader {
+    /// Header version number
+    pub(crate) version: u32,
+    /// Starting address of physical RAM range
+    pub(crate) s_addr: u64,
+    /// Ending address of physical RAM range
+    pub(crate) e_addr: u64,
+    /// Currently all zeros
+    pub(crate) reserved: [u8; 8],
+}
+
+/// Why a header could not be read
+#[derive(Debug)]
+pub(crate) enum HeaderError {
+    /// The file could not be read
+    Io(io::Error),
+    /// The header is corrupt
+    Parse(ParseError),
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "unable to read the header: {}", e),
+            Self::Parse(e) => e.fmt(f),
+        }
+    }
+}
+
+/// Header versions known to the parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LimeVersion {
+    /// Version 1, the only version published so far
+    V1,
+    /// Version 2, reserved for the next revision of the format. No specification exists yet,
+    /// so it is handled like an unknown version until its layout is known.
+    V2,
+}
+
+impl LimeVersion {
+    /// Map the raw `version` field of a header to a known version.
+    pub(crate) const fn from_raw(version: u32) -> Option<Self> {
+        match version {
+            1 => Some(Self::V1),
+            2 => Some(Self::V2),
+            _ => None,
+        }
+    }
+
+    /// Whether headers of this version are parsed according to their specification.
+    pub(crate) const fn is_supported(self) -> bool {
+        match self {
+            Self::V1 => true,
+            Self::V2 => false,
+        }
+    }
+}
+
+impl LimeHeader {
+    /// Size in bytes of `LimeHeader`
+    pub(crate) const HEADER_SIZE_IN_BYTES: usize = memflow_lime_core::HEADER_SIZE;
+
+    /// Magic number identifying a `LiME` header
+    pub(crate) const MAGIC: u32 = memflow_lime_core::MAGIC;
+
+    /// Create a version 1 header describing the physical range `[s_addr, e_addr]`.
+    pub(crate) const fn new(s_addr: u64, e_addr: u64) -> Self {
+        Self {
+            version: 1,
+            s_addr,
+            e_addr,
+            reserved: [0; 8],
+        }
+    }
+
+    /// Get the `LiME` header from file.
+    ///
+    /// Returns `Ok(None)` if the End Of File is reached\
+    /// Returns `Ok(Some(...))` if the `LimeHeader` is parsed correctly\
+    ///
+    /// # Arguments
+    ///
+    /// * `lime_dump` - file to read from, the seek of the file  must be already at the start of the header or at EOF.
+    /// * `byte_order` - byte order of the header fields, [`ByteOrder::Auto`] is replaced with
+    ///   the byte order matching the magic number of the header so that the following headers
+    ///   are read in the same order
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an error occurred while reading the file or parsing the header, the
+    /// error is not logged so that the caller can report which header is affected
+    ///
+    pub(crate) fn next_header_from_file<R: Read>(
+        lime_dump: &mut R,
+        byte_order: &mut ByteOrder,
+    ) -> std::result::Result<Option<LimeHeader>, HeaderError> {
+        let mut buff = [0u8; LimeHeader::HEADER_SIZE_IN_BYTES];
+
+        match lime_dump.read_exact(&mut buff) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(HeaderError::Io(e)),
+            Ok(()) => Self::from_bytes(&buff, byte_order)
+                .map(Some)
+                .map_err(HeaderError::Parse),
+        }
+    }
+
+    /// Parse a header from its on-disk representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `buff` - raw header
+    /// * `byte_order` - byte order of the header fields, [`ByteOrder::Auto`] is replaced with
+    ///   the byte order matching the magic number of the header
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseError::BadMagic`] if the magic number does not match
+    ///
+    pub(crate) fn from_bytes(
+        buff: &[u8; LimeHeader::HEADER_SIZE_IN_BYTES],
+        byte_order: &mut ByteOrder,
+    ) -> std::result::Result<LimeHeader, ParseError> {
+        if *byte_order == ByteOrder::Auto {
+            *byte_order = match Endian::detect(buff) {
+                Some(Endian::Big) => ByteOrder::Big,
+                Some(Endian::Little) | None => ByteOrder::Little,
+            };
+        }
+        let endian = match byte_order {
+            ByteOrder::Big => Endian::Big,
+            ByteOrder::Auto | ByteOrder::Little => Endian::Little,
+        };
+
+        let header = RangeHeader::parse(buff, endian)?;
+        Ok(Self {
+            version: header.version,
+            s_addr: header.s_addr,
+            e_addr: header.e_addr,
+            reserved: header.reserved,
+        })
+    }
+
+    /// Check the header fields the specification fixes.
+    ///
+    /// Inverted ranges and ranges whose size does not fit in a `u64` are always refused.
+    /// Unsupported versions are mapped with the version 1 layout and a warning if
+    /// `allow_unknown_version` is set. Otherwise they are refused in strict mode and only
+    /// logged in lenient mode, like non-zero reserved fields.
+    ///
+    /// Returns the messages of the problems tolerated, empty if the header is valid.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - parsing options
+    /// * `index` - position of the header in the file, used to identify it in the messages
+    /// * `offset` - file offset of the header
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the range is impossible, or if a field has an unsupported value and
+    /// the mode is strict
+    ///
+    pub(crate) fn check(
+        &self,
+        options: ParseOptions,
+        index: usize,
+        offset: u64,
+    ) -> std::result::Result<Vec<String>, LimeError> {
+        let strict = options.mode == ParseMode::Strict;
+        if self.mem_section_size().is_none() {
+            return Err(LimeError::ImpossibleRange {
+                header: index,
+                offset,
+                s_addr: self.s_addr,
+                e_addr: self.e_addr,
+            });
+        }
+        let mut anomalies = Vec::new();
+        match LimeVersion::from_raw(self.version) {
+            Some(version) if version.is_supported() => {}
+            _ if options.allow_unknown_version => {
+                let msg = format!(
+                    "LiME header #{}: unknown LiME version {}, assuming the version 1 layout",
+                    index, self.version
+                );
+                log::warn!("{}", msg);
+                anomalies.push(msg);
+            }
+            _ if strict => {
+                return Err(LimeError::UnsupportedVersion {
+                    header: index,
+                    offset,
+                    expected: memflow_lime_core::VERSION,
+                    found: self.version,
+                })
+            }
+            _ => {
+                let msg = format!(
+                    "LiME header #{}: unsupported LiME version: {}",
+                    index, self.version
+                );
+                log::warn!("{}", msg);
+                anomalies.push(msg);
+            }
+        }
+        if self.reserved != [0; 8] {
+            if strict {
+                return Err(LimeError::NonZeroReserved {
+                    header: index,
+                    offset,
+                    found: self.reserved,
+                });
+            }
+            let msg = format!(
+                "LiME header #{}: unsupported LiME reserved fields values: {:02x?}",
+                index, self.reserved
+            );
+            log::warn!("{}", msg);
+            anomalies.push(msg);
+        }
+        Ok(anomalies)
+    }
+
+    /// Serialize the header in its on-disk little endian representation.
+    pub(crate) fn to_bytes(&self) -> [u8; LimeHeader::HEADER_SIZE_IN_BYTES] {
+        RangeHeader {
+            version: self.version,
+            s_addr: self.s_addr,
+            e_addr: self.e_addr,
+            reserved: self.reserved,
+        }
+        .to_bytes(Endian::Little)
+    }
+
+    /// Write the header to `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an error occurred while writing
+    ///
+    pub(crate) fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.to_bytes()).map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+                .log_error("Unable to write the LiME header")
+        })
+    }
+
+    /// Size in bytes of the memory represented by this header
+    ///
+    /// Returns `None` if the end address is lower than the start address or if the range
+    /// covers the whole 64-bit address space, whose size does not fit in a `u64`.
+    pub(crate) const fn mem_section_size(&self) -> Option<u64> {
+        match self.e_addr.checked_sub(self.s_addr) {
+            Some(diff) => diff.checked_add(1),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ParseMode;
+    use std::fs;
+    use std::fs::OpenOptions;
+    use std::io::{Seek, SeekFrom, Write};
+
+    const RAW_HEADER: [u8; LimeHeader::HEADER_SIZE_IN_BYTES] = [
+        69, 77, 105, 76, 1, 0, 0, 0, 0, 0, 0, 64, 0, 0, 0, 0, 255, 255, 207, 251, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0,
+    ];
+
+    #[test]
+    fn header_parser_works() {
+        let tmp_file_path = "./test_header.tmp";
+        let mut tmp_file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(tmp_file_path)
+            .unwrap();
+
+        tmp_file.write_all(&RAW_HEADER).unwrap();
+        tmp_file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut byte_order = ByteOrder::Auto;
+        let header = LimeHeader::next_header_from_file(&mut tmp_file, &mut byte_order)
+            .unwrap()
+            .unwrap();
+
+        fs::remove_file(tmp_file_path).unwrap();
+
+        assert_eq!(header.version, 1);
+        assert_eq!(header.s_addr, 0x40000000);
+        assert_eq!(header.e_addr, 0xFBD00000 - 1);
+        assert_eq!(header.reserved, [0; 8]);
+        assert_eq!(byte_order, ByteOrder::Little);
+    }
+
+    #[test]
+    fn header_check_depends_on_mode() {
+        let mut header = LimeHeader::new(0x1000, 0x1fff);
+        assert!(header.check(ParseMode::Strict.into(), 0, 0).is_ok());
+
+        header.version = 2;
+        header.reserved = [1; 8];
+        assert!(header.check(ParseMode::Strict.into(), 0, 0).is_err());
+        assert_eq!(
+            header.check(ParseMode::Lenient.into(), 0, 0).unwrap().len(),
+            2
+        );
+    }
+
+    #[test]
+    fn unknown_versions_depend_on_options() {
+        let mut header = LimeHeader::new(0x1000, 0x1fff);
+        header.version = 7;
+        let allow_unknown = ParseOptions {
+            mode: ParseMode::Strict,
+            allow_unknown_version: true,
+            ..ParseOptions::default()
+        };
+
+        assert_eq!(LimeVersion::from_raw(1), Some(LimeVersion::V1));
+        assert_eq!(LimeVersion::from_raw(7), None);
+        assert!(header.check(ParseMode::Strict.into(), 0, 0).is_err());
+        assert!(header.check(allow_unknown, 0, 0).is_ok());
+
+        header.reserved = [1; 8];
+        assert!(header.check(allow_unknown, 0, 0).is_err());
+    }
+
+    #[test]
+    fn impossible_ranges_are_refused() {
+        assert_eq!(LimeHeader::new(0, u64::MAX).mem_section_size(), None);
+        assert_eq!(LimeHeader::new(0x2000, 0x1fff).mem_section_size(), None);
+        assert_eq!(
+            LimeHeader::new(0x1000, 0x1fff).mem_section_size(),
+            Some(0x1000)
+        );
+
+        let inverted = LimeHeader::new(0x2000, 0x1fff);
+        assert_eq!(
+            inverted
+                .check(ParseMode::Lenient.into(), 3, 0)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidMemorySize
+        );
+    }
+
+    #[test]
+    fn corrupt_headers_report_the_cause() {
+        let mut raw = RAW_HEADER;
+        raw[0] = 0;
+        assert_eq!(
+            LimeHeader::from_bytes(&raw, &mut ByteOrder::Auto).unwrap_err(),
+            ParseError::BadMagic([0, 77, 105, 76])
+        );
+
+        let mut truncated = &RAW_HEADER[..0x10];
+        assert!(matches!(
+            LimeHeader::next_header_from_file(&mut truncated, &mut ByteOrder::Auto),
+            Ok(None)
+        ));
+    }
+
+    #[test]
+    fn big_endian_headers_are_detected() {
+        let header = RangeHeader::new(0x1000, 0x1fff).to_bytes(Endian::Big);
+        let mut byte_order = ByteOrder::Auto;
+        let header = LimeHeader::from_bytes(&header, &mut byte_order).unwrap();
+
+        assert_eq!(byte_order, ByteOrder::Big);
+        assert_eq!((header.s_addr, header.e_addr), (0x1000, 0x1fff));
+    }
+
+    #[test]
+    fn header_serialization_roundtrips() {
+        let header = LimeHeader::new(0x40000000, 0xFBD00000 - 1);
+        assert_eq!(header.to_bytes(), RAW_HEADER);
+    }
+}
@@ -0,0 +1,385 @@
+use crate::config::HIBERFIL_SIGNATURES;
+use crate::dumpfile::{DecodedStream, DumpFile};
+use crate::segment::{open_dump, LimeSegment};
+
+use memflow::prelude::v1::*;
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+const PAGE_SIZE: u64 = 0x1000;
+/// Offset of the page size in the header
+const PAGE_SIZE_OFFSET: usize = 0x18;
+/// Offset of the page number of the first memory range table in the header
+const FIRST_TABLE_PAGE_OFFSET: usize = 0x68;
+/// Offset of the ranges in a memory range table, after the link to the next table
+const RANGES_OFFSET: usize = 0x10;
+/// Size of a range of a memory range table: first page and page past the end
+const RANGE_SIZE: usize = 0x10;
+/// Most ranges a memory range table holds
+const MAX_RANGES: usize = (PAGE_SIZE as usize - RANGES_OFFSET) / RANGE_SIZE;
+/// Magic number starting every compressed block
+const XPRESS_MAGIC: &[u8; 8] = b"\x81\x81xpress";
+/// Size of the header of a compressed block
+const XPRESS_HEADER_SIZE: u64 = 0x20;
+
+/// Open the hibernation file at `path`, in the layout of 64-bit Windows 7.
+///
+/// The file starts with a header pointing to a chain of memory range tables, each listing up
+/// to 255 ranges of physical pages. The pages of the ranges of a table follow it in blocks of
+/// up to 16 pages compressed with Xpress (LZ77), blocks that do not shrink being stored as
+/// they are. The returned dump is the concatenation of the decompressed pages, in table
+/// order, blocks being decompressed on demand.
+///
+/// Windows 8 and later write hibernation files in another layout, with restoration sets
+/// compressed with Xpress Huffman, which is not supported.
+///
+/// # Errors
+///
+/// Returns `Err` if the file can not be read, is not a hibernation file or uses an
+/// unsupported layout, or if a table or a block is corrupt
+///
+pub(crate) fn open_hiberfil(path: &Path) -> Result<(DumpFile, Vec<LimeSegment>)> {
+    let file = open_dump(path)?;
+    let file_len = file
+        .metadata()
+        .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?
+        .len();
+    let mut reader = BufReader::new(file);
+
+    let header = read_page(&mut reader, 0)?;
+    if !HIBERFIL_SIGNATURES
+        .iter()
+        .any(|signature| header.starts_with(*signature))
+    {
+        return Err(corrupt(format!(
+            "{} is not a hibernation file, or its header was wiped",
+            path.display()
+        )));
+    }
+    let first_table = u64_at(&header, FIRST_TABLE_PAGE_OFFSET);
+    let first_block = first_table
+        .checked_add(1)
+        .and_then(|page| page.checked_mul(PAGE_SIZE))
+        .filter(|offset| *offset < file_len);
+    if u32_at(&header, PAGE_SIZE_OFFSET) != PAGE_SIZE as u32
+        || first_table == 0
+        || first_block.is_none_or(|offset| read_block_header(&mut reader, offset).is_err())
+    {
+        return Err(
+            Error(ErrorOrigin::Connector, ErrorKind::NotSupported).log_error(format!(
+                "{} is not in the hibernation file layout of 64-bit Windows 7",
+                path.display()
+            )),
+        );
+    }
+
+    let mut segments = Vec::new();
+    let mut blocks = Vec::new();
+    let mut decoded = 0;
+    let mut table_page = first_table;
+    while table_page != 0 {
+        let table = read_page(&mut reader, table_page * PAGE_SIZE)?;
+        let next_table = u64_at(&table, 0);
+        let count = u32_at(&table, 8) as usize;
+        if count > MAX_RANGES {
+            return Err(corrupt(format!(
+                "memory range table at page {:#x} lists {} ranges",
+                table_page, count
+            )));
+        }
+
+        let mut pages = 0u64;
+        for range in 0..count {
+            let offset = RANGES_OFFSET + range * RANGE_SIZE;
+            let (start, end) = (u64_at(&table, offset), u64_at(&table, offset + 8));
+            let s_addr = start.checked_mul(PAGE_SIZE);
+            let e_addr = end
+                .checked_mul(PAGE_SIZE)
+                .and_then(|end| end.checked_sub(1));
+            let (Some(s_addr), Some(e_addr)) = (s_addr, e_addr.filter(|_| end > start)) else {
+                return Err(corrupt(format!(
+                    "memory range table at page {:#x} holds the impossible range of pages \
+                    {:#x}-{:#x}",
+                    table_page, start, end
+                )));
+            };
+            segments.push(LimeSegment {
+                s_addr,
+                e_addr,
+                file_offset: decoded + pages * PAGE_SIZE,
+            });
+            pages += end - start;
+        }
+
+        let mut offset = (table_page + 1) * PAGE_SIZE;
+        while pages > 0 {
+            let block = read_block_header(&mut reader, offset)?;
+            if block.pages > pages || offset + XPRESS_HEADER_SIZE + block.len > file_len {
+                return Err(corrupt(format!(
+                    "compressed block at offset {:#x} does not fit its memory range table",
+                    offset
+                )));
+            }
+            pages -= block.pages;
+            blocks.push(Block {
+                file_offset: offset + XPRESS_HEADER_SIZE,
+                decoded_offset: decoded,
+                ..block
+            });
+            decoded += block.pages * PAGE_SIZE;
+            offset += XPRESS_HEADER_SIZE + block.len.next_multiple_of(8);
+        }
+
+        // Tables are written one after the other, a link going back would loop forever.
+        if next_table != 0 && (next_table <= table_page || next_table >= file_len / PAGE_SIZE) {
+            return Err(corrupt(format!(
+                "memory range table at page {:#x} links to page {:#x}, out of order",
+                table_page, next_table
+            )));
+        }
+        table_page = next_table;
+    }
+    log::info!(
+        "Mapping {} ranges of {} compressed blocks of the hibernation file {}",
+        segments.len(),
+        blocks.len(),
+        path.display()
+    );
+
+    let stream = HiberStream {
+        file: Mutex::new(reader.into_inner()),
+        blocks,
+        size: decoded,
+        cache: Mutex::new(None),
+    };
+    Ok((DumpFile::decoded(vec![Arc::new(stream)]), segments))
+}
+
+/// Compressed block of pages
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    /// Offset of the compressed data in the file
+    file_offset: u64,
+    /// Size of the compressed data
+    len: u64,
+    /// Pages held
+    pages: u64,
+    /// Offset of the first page in the decompressed stream
+    decoded_offset: u64,
+}
+
+impl Block {
+    /// Whether the block is stored uncompressed, having not shrunk.
+    const fn is_stored(&self) -> bool {
+        self.len == self.pages * PAGE_SIZE
+    }
+}
+
+/// Read the header of the compressed block at `offset`.
+///
+/// The header holds the number of pages minus one in its low byte and the size of the
+/// compressed data minus one from bit 10 on.
+fn read_block_header<R: Read + Seek>(reader: &mut R, offset: u64) -> Result<Block> {
+    let mut header = [0u8; XPRESS_HEADER_SIZE as usize];
+    reader
+        .seek(SeekFrom::Start(offset))
+        .and_then(|_| reader.read_exact(&mut header))
+        .map_err(|_| corrupt(format!("compressed block at offset {:#x} is cut", offset)))?;
+    if &header[..8] != XPRESS_MAGIC {
+        return Err(corrupt(format!(
+            "no compressed block at offset {:#x}",
+            offset
+        )));
+    }
+    let info = u32_at(&header, 8);
+    Ok(Block {
+        file_offset: offset,
+        len: u64::from(info >> 10) + 1,
+        pages: u64::from(info & 0xff) + 1,
+        decoded_offset: 0,
+    })
+}
+
+/// Decompressed pages of a hibernation file
+struct HiberStream {
+    file: Mutex<File>,
+    /// Blocks in the order of their pages
+    blocks: Vec<Block>,
+    size: u64,
+    /// Last block decompressed
+    cache: Mutex<Option<(usize, Arc<[u8]>)>>,
+}
+
+impl HiberStream {
+    /// Decompress block number `index`.
+    fn block(&self, index: usize) -> io::Result<Arc<[u8]>> {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((cached, data)) = cache.as_ref() {
+            if *cached == index {
+                return Ok(data.clone());
+            }
+        }
+
+        let block = self.blocks[index];
+        let mut raw = vec![0; block.len as usize];
+        {
+            let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+            file.seek(SeekFrom::Start(block.file_offset))?;
+            file.read_exact(&mut raw)?;
+        }
+        let data: Arc<[u8]> = match block.is_stored() {
+            true => raw.into(),
+            false => xpress_decompress(&raw, (block.pages * PAGE_SIZE) as usize)?.into(),
+        };
+        *cache = Some((index, data.clone()));
+        Ok(data)
+    }
+}
+
+impl DecodedStream for HiberStream {
+    fn len(&self) -> u64 {
+        self.size
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        if offset >= self.size || buf.is_empty() {
+            return Ok(0);
+        }
+        let index = self
+            .blocks
+            .partition_point(|block| block.decoded_offset <= offset)
+            - 1;
+        let data = self.block(index)?;
+        let within = (offset - self.blocks[index].decoded_offset) as usize;
+        let read = (data.len() - within).min(buf.len());
+        buf[..read].copy_from_slice(&data[within..within + read]);
+        Ok(read)
+    }
+}
+
+/// Decompress `input`, compressed with the plain LZ77 variant of Xpress, into `len` bytes.
+///
+/// source: [MS-XCA 2.4 Plain LZ77 Decompression](https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-xca/34cb9ab9-5ce6-42d7-a518-107c1c7c65e7)
+fn xpress_decompress(input: &[u8], len: usize) -> io::Result<Vec<u8>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "corrupt Xpress block");
+    let mut output = Vec::with_capacity(len);
+    let mut pos = 0;
+    let mut flags = 0u32;
+    let mut flag_count = 0;
+    // Byte whose high nibble holds the length of the next long match, if any.
+    let mut half_byte = None;
+    let read = |pos: &mut usize, size: usize| -> io::Result<u32> {
+        let bytes = input.get(*pos..*pos + size).ok_or_else(invalid)?;
+        *pos += size;
+        Ok(bytes
+            .iter()
+            .rev()
+            .fold(0, |value, byte| value << 8 | u32::from(*byte)))
+    };
+
+    loop {
+        if flag_count == 0 {
+            flags = read(&mut pos, 4)?;
+            flag_count = 32;
+        }
+        flag_count -= 1;
+        if pos == input.len() {
+            break;
+        }
+        if flags & (1 << flag_count) == 0 {
+            output.push(input[pos]);
+            pos += 1;
+        } else {
+            let match_bytes = read(&mut pos, 2)? as usize;
+            let offset = (match_bytes >> 3) + 1;
+            let mut match_len = match_bytes & 7;
+            if match_len == 7 {
+                match_len = match half_byte.take() {
+                    Some(byte) => usize::from(input[byte] >> 4),
+                    None => {
+                        half_byte = Some(pos);
+                        read(&mut pos, 1)? as usize & 0xf
+                    }
+                };
+                if match_len == 15 {
+                    match_len = read(&mut pos, 1)? as usize;
+                    if match_len == 255 {
+                        match_len = read(&mut pos, 2)? as usize;
+                        if match_len == 0 {
+                            match_len = read(&mut pos, 4)? as usize;
+                        }
+                        match_len = match_len.checked_sub(15 + 7).ok_or_else(invalid)?;
+                    }
+                    match_len += 15;
+                }
+                match_len += 7;
+            }
+            match_len += 3;
+            let start = output.len().checked_sub(offset).ok_or_else(invalid)?;
+            if output.len() + match_len > len {
+                return Err(invalid());
+            }
+            // The match may overlap the bytes it produces, so it is copied byte by byte.
+            for at in start..start + match_len {
+                output.push(output[at]);
+            }
+        }
+        if output.len() > len {
+            return Err(invalid());
+        }
+    }
+    match output.len() == len {
+        true => Ok(output),
+        false => Err(invalid()),
+    }
+}
+
+/// Read the page at `offset`.
+fn read_page<R: Read + Seek>(reader: &mut R, offset: u64) -> Result<Vec<u8>> {
+    let mut page = vec![0; PAGE_SIZE as usize];
+    reader
+        .seek(SeekFrom::Start(offset))
+        .and_then(|_| reader.read_exact(&mut page))
+        .map_err(|_| corrupt(format!("hibernation file is cut at offset {:#x}", offset)))?;
+    Ok(page)
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(buf[offset..offset + 4].try_into().unwrap())
+}
+
+fn u64_at(buf: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(buf[offset..offset + 8].try_into().unwrap())
+}
+
+fn corrupt(msg: String) -> Error {
+    Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xpress_blocks_are_decompressed() {
+        // Examples of MS-XCA 3.1
+        let mut alphabet = vec![0x3f, 0, 0, 0];
+        alphabet.extend(b'a'..=b'z');
+        assert_eq!(
+            xpress_decompress(&alphabet, 26).unwrap(),
+            (b'a'..=b'z').collect::<Vec<_>>()
+        );
+        let abc = [
+            0xff, 0xff, 0xff, 0x1f, 0x61, 0x62, 0x63, 0x17, 0x00, 0x0f, 0xff, 0x26, 0x01,
+        ];
+        assert_eq!(xpress_decompress(&abc, 300).unwrap(), b"abc".repeat(100));
+
+        assert!(xpress_decompress(&abc, 299).is_err());
+        assert!(xpress_decompress(&abc[..10], 300).is_err());
+        // Match reaching before the start of the output
+        assert!(xpress_decompress(&[0, 0, 0, 0x80, 0x08, 0x00, 0x00], 3).is_err());
+    }
+}
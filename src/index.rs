@@ -0,0 +1,339 @@
+use crate::config::{ByteOrder, ParseMode, ParseOptions};
+use crate::segment::{check_limits, LimeLayout, LimeSegment, LimeTruncation};
+use crate::trailer::LimeDigest;
+
+use memflow::prelude::v1::*;
+
+use sha2::{Digest, Sha256};
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Magic number identifying a `LiME` index file
+const INDEX_MAGIC: &[u8; 8] = b"LIMEIDX\0";
+
+/// Version of the index file format
+const INDEX_VERSION: u32 = 1;
+
+/// Bytes hashed at each end of the dump to detect in-place rewrites keeping size and mtime
+const FINGERPRINT_WINDOW: u64 = 4096;
+
+/// Default location of the index cache of `lime_path`, i.e. `<lime_path>.limeidx`.
+pub fn default_index_path<P: AsRef<Path>>(lime_path: P) -> PathBuf {
+    let mut path = OsString::from(lime_path.as_ref());
+    path.push(".limeidx");
+    PathBuf::from(path)
+}
+
+/// Parse `lime_dump` using the index cache at `index_path`.
+///
+/// The cached layout is used if the index was written with the same parse options for a dump
+/// with the same size, modification time and fingerprint. Otherwise the dump is parsed with
+/// `scan` and the index is rewritten, a failure to write it is only logged.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading or parsing the dump
+///
+pub(crate) fn parse_dump_indexed<F>(
+    lime_dump: &mut File,
+    options: ParseOptions,
+    index_path: &Path,
+    scan: F,
+) -> Result<LimeLayout>
+where
+    F: FnOnce(&mut File) -> Result<LimeLayout>,
+{
+    let stamp = DumpStamp::of(lime_dump)?;
+
+    let cached = fs::read(index_path)
+        .ok()
+        .and_then(|index| decode(&index, options, &stamp));
+    if let Some(layout) = cached {
+        log::debug!("Using the LiME index {}", index_path.display());
+        // The index does not depend on the limits, they are checked on every open.
+        check_limits(&layout.segments, options.limits)?;
+        return Ok(layout);
+    }
+
+    log::info!(
+        "LiME index {} is missing or stale, scanning the dump",
+        index_path.display()
+    );
+    let layout = scan(lime_dump)?;
+    if let Err(e) = fs::write(index_path, encode(&layout, options, &stamp)) {
+        log::warn!(
+            "Unable to write the LiME index {}: {}",
+            index_path.display(),
+            e
+        );
+    }
+    Ok(layout)
+}
+
+/// Identity of a dump file, an index is only valid for the dump it was written for
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DumpStamp {
+    len: u64,
+    mtime_secs: u64,
+    mtime_nanos: u32,
+    fingerprint: [u8; 32],
+}
+
+impl DumpStamp {
+    /// Stamp `lime_dump`, leaving its seek at the beginning of the file.
+    fn of(lime_dump: &mut File) -> Result<Self> {
+        let metadata = lime_dump
+            .metadata()
+            .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        let len = metadata.len();
+
+        let mut hasher = Sha256::new();
+        let mut buff = Vec::new();
+        for start in [0, len.saturating_sub(FINGERPRINT_WINDOW)] {
+            buff.clear();
+            lime_dump
+                .seek(SeekFrom::Start(start))
+                .and_then(|_| {
+                    lime_dump
+                        .by_ref()
+                        .take(FINGERPRINT_WINDOW)
+                        .read_to_end(&mut buff)
+                })
+                .map_err(|_| {
+                    Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+                        .log_error("Unable to fingerprint the LiME file")
+                })?;
+            hasher.update(&buff);
+        }
+        lime_dump
+            .seek(SeekFrom::Start(0))
+            .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToSeekFile))?;
+
+        Ok(Self {
+            len,
+            mtime_secs: mtime.as_secs(),
+            mtime_nanos: mtime.subsec_nanos(),
+            fingerprint: hasher.finalize().into(),
+        })
+    }
+}
+
+/// Serialize `layout` along with what it depends on, followed by the SHA-256 of the index.
+fn encode(layout: &LimeLayout, options: ParseOptions, stamp: &DumpStamp) -> Vec<u8> {
+    let mut index = Vec::new();
+    index.extend_from_slice(INDEX_MAGIC);
+    index.extend_from_slice(&INDEX_VERSION.to_le_bytes());
+    index.extend_from_slice(&encode_options(options));
+    index.extend_from_slice(&stamp.len.to_le_bytes());
+    index.extend_from_slice(&stamp.mtime_secs.to_le_bytes());
+    index.extend_from_slice(&stamp.mtime_nanos.to_le_bytes());
+    index.extend_from_slice(&stamp.fingerprint);
+
+    index.extend_from_slice(&(layout.segments.len() as u64).to_le_bytes());
+    for segment in &layout.segments {
+        index.extend_from_slice(&segment.s_addr.to_le_bytes());
+        index.extend_from_slice(&segment.e_addr.to_le_bytes());
+        index.extend_from_slice(&segment.file_offset.to_le_bytes());
+    }
+
+    match &layout.truncation {
+        Some(truncation) => {
+            index.push(1);
+            index.extend_from_slice(&truncation.s_addr.to_le_bytes());
+            index.extend_from_slice(&truncation.e_addr.to_le_bytes());
+        }
+        None => index.push(0),
+    }
+
+    match &layout.digest {
+        Some(digest) => {
+            index.push(1);
+            index.extend_from_slice(&digest.covered_len.to_le_bytes());
+            index.push(digest.value.len() as u8);
+            index.extend_from_slice(&digest.value);
+        }
+        None => index.push(0),
+    }
+
+    let checksum = Sha256::digest(&index);
+    index.extend_from_slice(&checksum);
+    index
+}
+
+/// Deserialize an index, returns `None` if it is malformed or does not match `options` and
+/// `stamp`.
+fn decode(index: &[u8], options: ParseOptions, stamp: &DumpStamp) -> Option<LimeLayout> {
+    let (body, checksum) = index.split_at(index.len().checked_sub(32)?);
+    if Sha256::digest(body)[..] != *checksum {
+        return None;
+    }
+
+    let mut reader = IndexReader(body);
+    if reader.take(8)? != INDEX_MAGIC
+        || reader.u32()? != INDEX_VERSION
+        || reader.take(3)? != encode_options(options)
+    {
+        return None;
+    }
+
+    let cached = DumpStamp {
+        len: reader.u64()?,
+        mtime_secs: reader.u64()?,
+        mtime_nanos: reader.u32()?,
+        fingerprint: reader.take(32)?.try_into().ok()?,
+    };
+    if cached != *stamp {
+        return None;
+    }
+
+    let count = reader.u64()?;
+    let mut segments = Vec::new();
+    for _ in 0..count {
+        segments.push(LimeSegment {
+            s_addr: reader.u64()?,
+            e_addr: reader.u64()?,
+            file_offset: reader.u64()?,
+        });
+    }
+
+    let truncation = match reader.u8()? {
+        0 => None,
+        _ => Some(LimeTruncation {
+            s_addr: reader.u64()?,
+            e_addr: reader.u64()?,
+        }),
+    };
+
+    let digest = match reader.u8()? {
+        0 => None,
+        _ => {
+            let covered_len = reader.u64()?;
+            let len = reader.u8()?;
+            Some(LimeDigest {
+                value: reader.take(len as usize)?.to_vec(),
+                covered_len,
+            })
+        }
+    };
+
+    Some(LimeLayout {
+        segments,
+        digest,
+        truncation,
+        anomalies: Vec::new(),
+        reserved: Vec::new(),
+        skipped: Vec::new(),
+    })
+}
+
+/// Serialize the parse options the layout depends on.
+fn encode_options(options: ParseOptions) -> [u8; 3] {
+    let mode = match options.mode {
+        ParseMode::Strict => 0,
+        ParseMode::Lenient => 1,
+    } | (options.recover as u8) << 1
+        | (options.carve as u8) << 2;
+    let byte_order = match options.byte_order {
+        ByteOrder::Auto => 0,
+        ByteOrder::Little => 1,
+        ByteOrder::Big => 2,
+    };
+    [mode, options.allow_unknown_version as u8, byte_order]
+}
+
+/// Little endian reader over the bytes of an index
+struct IndexReader<'a>(&'a [u8]);
+
+impl<'a> IndexReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stamp() -> DumpStamp {
+        DumpStamp {
+            len: 0x3060,
+            mtime_secs: 1_700_000_000,
+            mtime_nanos: 42,
+            fingerprint: [7; 32],
+        }
+    }
+
+    #[test]
+    fn index_roundtrips() {
+        let layout = LimeLayout {
+            segments: vec![
+                LimeSegment {
+                    s_addr: 0x1000,
+                    e_addr: 0x1fff,
+                    file_offset: 0x20,
+                },
+                LimeSegment {
+                    s_addr: 0x4000,
+                    e_addr: 0x4fff,
+                    file_offset: 0x1040,
+                },
+            ],
+            digest: LimeDigest::from_trailer(b"d41d8cd98f00b204e9800998ecf8427e\n", 0x2040),
+            truncation: Some(LimeTruncation {
+                s_addr: 0x5000,
+                e_addr: 0x5fff,
+            }),
+            anomalies: Vec::new(),
+            reserved: Vec::new(),
+            skipped: Vec::new(),
+        };
+        let options = ParseOptions::default();
+        let index = encode(&layout, options, &stamp());
+
+        let decoded = decode(&index, options, &stamp()).unwrap();
+        assert_eq!(decoded.segments, layout.segments);
+        assert_eq!(decoded.digest, layout.digest);
+        assert_eq!(decoded.truncation, layout.truncation);
+    }
+
+    #[test]
+    fn stale_or_corrupt_index_is_ignored() {
+        let options = ParseOptions::default();
+        let mut index = encode(&LimeLayout::default(), options, &stamp());
+
+        let mut modified = stamp();
+        modified.mtime_nanos += 1;
+        assert!(decode(&index, options, &modified).is_none());
+        assert!(decode(&index, ParseMode::Lenient.into(), &stamp()).is_none());
+
+        index[20] ^= 1;
+        assert!(decode(&index, options, &stamp()).is_none());
+        assert!(decode(&index[..16], options, &stamp()).is_none());
+    }
+}
@@ -0,0 +1,314 @@
+use crate::blockdev::file_size;
+use crate::config::ParseMode;
+use crate::segment::{read_layout, LimeSegment};
+
+use memflow::prelude::v1::*;
+
+use sha2::{Digest, Sha256};
+
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Size of a SHA-256 digest in bytes
+pub const SHA256_DIGEST_SIZE: usize = 32;
+
+/// Compute the SHA-256 digest of the file at `path`.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading the file
+///
+pub fn sha256_digest<P: AsRef<Path>>(path: P) -> Result<[u8; SHA256_DIGEST_SIZE]> {
+    sha256_digest_with_progress(path, |_, _| ())
+}
+
+/// Compute the SHA-256 digest of the file at `path`, reporting progress.
+///
+/// `progress` is called with the number of bytes hashed so far and the size of the file
+/// after every chunk.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading the file
+///
+pub fn sha256_digest_with_progress<P, F>(
+    path: P,
+    mut progress: F,
+) -> Result<[u8; SHA256_DIGEST_SIZE]>
+where
+    P: AsRef<Path>,
+    F: FnMut(u64, u64),
+{
+    let mut file =
+        File::open(path).map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+    let total =
+        file_size(&file).map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+    let mut hasher = Sha256::new();
+    let mut buff = vec![0u8; 1 << 20];
+    let mut hashed = 0;
+
+    loop {
+        match file.read(&mut buff) {
+            Ok(0) => break,
+            Ok(n) => {
+                hasher.update(&buff[..n]);
+                hashed += n as u64;
+                progress(hashed, total);
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => {
+                return Err(Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+                    .log_error("Unable to read the file while hashing it"))
+            }
+        }
+    }
+
+    Ok(hasher.finalize().into())
+}
+
+/// Verify the SHA-256 digest of `lime_path` against a sidecar hash file.
+///
+/// The sidecar may either contain the bare hex digest or be in the `sha256sum` format
+/// (`<digest>  <file name>`), only the first digest of the file is considered.
+///
+/// # Errors
+///
+/// Returns `Err` if one of the files can not be read, if the sidecar is malformed or if the
+/// digests do not match
+///
+pub fn verify_sha256<P: AsRef<Path>, Q: AsRef<Path>>(lime_path: P, sidecar_path: Q) -> Result<()> {
+    verify_sha256_with_progress(lime_path, sidecar_path, |_, _| ())
+}
+
+/// Verify the SHA-256 digest of `lime_path` against a sidecar hash file, reporting the
+/// hashing progress as [`sha256_digest_with_progress`] does.
+///
+/// # Errors
+///
+/// Returns `Err` if one of the files can not be read, if the sidecar is malformed or if the
+/// digests do not match
+///
+pub fn verify_sha256_with_progress<P, Q, F>(
+    lime_path: P,
+    sidecar_path: Q,
+    progress: F,
+) -> Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    F: FnMut(u64, u64),
+{
+    let sidecar = fs::read_to_string(sidecar_path).map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+            .log_error("Unable to read the SHA-256 sidecar file")
+    })?;
+    let expected = sidecar
+        .split_whitespace()
+        .next()
+        .and_then(parse_hex_digest)
+        .ok_or_else(|| {
+            Error(ErrorOrigin::Connector, ErrorKind::Encoding)
+                .log_error("Malformed SHA-256 sidecar file")
+        })?;
+
+    let actual = sha256_digest_with_progress(lime_path, progress)?;
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+                "SHA-256 digest mismatch: expected {}, found {}",
+                to_hex(&expected),
+                to_hex(&actual)
+            )),
+        )
+    }
+}
+
+/// Default location of the SHA-256 sidecar of `lime_path`, i.e. `<lime_path>.sha256`.
+pub fn default_sha256_sidecar<P: AsRef<Path>>(lime_path: P) -> PathBuf {
+    let mut path = OsString::from(lime_path.as_ref());
+    path.push(".sha256");
+    path.into()
+}
+
+/// Hash function applied by [`hash_segments`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    /// SHA-256, 32 bytes digests
+    Sha256,
+    /// BLAKE3, 32 bytes digests, requires the `blake3` feature
+    #[cfg(feature = "blake3")]
+    Blake3,
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashAlgorithm::Sha256 => f.write_str("sha256"),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => f.write_str("blake3"),
+        }
+    }
+}
+
+/// Digest of the memory held by a single `LiME` range
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimeSegmentDigest {
+    /// Range that was hashed
+    pub segment: LimeSegment,
+    /// Digest of the bytes of the range, headers excluded
+    pub digest: Vec<u8>,
+}
+
+/// Streaming state of a [`HashAlgorithm`]
+enum SegmentHasher {
+    Sha256(Sha256),
+    #[cfg(feature = "blake3")]
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl SegmentHasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha256 => SegmentHasher::Sha256(Sha256::new()),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => SegmentHasher::Blake3(Box::default()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            SegmentHasher::Sha256(hasher) => hasher.update(data),
+            #[cfg(feature = "blake3")]
+            SegmentHasher::Blake3(hasher) => {
+                hasher.update(data);
+            }
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            SegmentHasher::Sha256(hasher) => hasher.finalize().to_vec(),
+            #[cfg(feature = "blake3")]
+            SegmentHasher::Blake3(hasher) => hasher.finalize().as_bytes().to_vec(),
+        }
+    }
+}
+
+/// Hash every memory range of the `LiME` file at `path` on its own, in file order.
+///
+/// Only the memory bytes of each range are hashed, so that the digests match the ones of
+/// the same regions acquired in another format.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading or parsing the file
+///
+pub fn hash_segments<P: AsRef<Path>>(
+    path: P,
+    algorithm: HashAlgorithm,
+) -> Result<Vec<LimeSegmentDigest>> {
+    let segments = read_layout(&path, ParseMode::Strict)?.segments;
+    let mut file =
+        File::open(path).map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+    let mut buff = vec![0u8; 1 << 20];
+
+    segments
+        .into_iter()
+        .map(|segment| {
+            file.seek(SeekFrom::Start(segment.file_offset))
+                .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToSeekFile))?;
+            let mut hasher = SegmentHasher::new(algorithm);
+            let mut left = segment.size();
+            while left > 0 {
+                let len = left.min(buff.len() as u64) as usize;
+                file.read_exact(&mut buff[..len]).map_err(|_| {
+                    Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+                        "Unable to read range {:#x}-{:#x} while hashing it",
+                        segment.s_addr, segment.e_addr
+                    ))
+                })?;
+                hasher.update(&buff[..len]);
+                left -= len as u64;
+            }
+            Ok(LimeSegmentDigest {
+                segment,
+                digest: hasher.finalize(),
+            })
+        })
+        .collect()
+}
+
+/// Write the digests computed by [`hash_segments`] to a manifest file at `manifest_path`.
+///
+/// The manifest starts with a `# <algorithm>` line, followed by one
+/// `<digest>  <s_addr>-<e_addr>  <file offset>` line per range, addresses being inclusive
+/// and in hexadecimal.
+///
+/// # Errors
+///
+/// Returns `Err` if the manifest can not be written
+///
+pub fn write_hash_manifest<P: AsRef<Path>>(
+    manifest_path: P,
+    algorithm: HashAlgorithm,
+    digests: &[LimeSegmentDigest],
+) -> Result<()> {
+    let write = || -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(manifest_path)?);
+        writeln!(out, "# {}", algorithm)?;
+        for digest in digests {
+            writeln!(
+                out,
+                "{}  {:#x}-{:#x}  {:#x}",
+                to_hex(&digest.digest),
+                digest.segment.s_addr,
+                digest.segment.e_addr,
+                digest.segment.file_offset
+            )?;
+        }
+        out.flush()
+    };
+    write().map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+            .log_error("Unable to write the hash manifest")
+    })
+}
+
+/// Parse a hex encoded SHA-256 digest.
+fn parse_hex_digest(hex: &str) -> Option<[u8; SHA256_DIGEST_SIZE]> {
+    if hex.len() != SHA256_DIGEST_SIZE * 2 || !hex.is_ascii() {
+        return None;
+    }
+
+    let mut digest = [0u8; SHA256_DIGEST_SIZE];
+    for (i, byte) in digest.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(digest)
+}
+
+/// Hex encode `bytes` in lower case.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_digest_roundtrips() {
+        let digest = [0xabu8; SHA256_DIGEST_SIZE];
+        assert_eq!(parse_hex_digest(&to_hex(&digest)), Some(digest));
+        assert_eq!(
+            parse_hex_digest(&to_hex(&digest).to_uppercase()),
+            Some(digest)
+        );
+        assert_eq!(parse_hex_digest("abcd"), None);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::dumpfile::DumpFile;
+use crate::elf::{read_program_headers, PT_LOAD};
+use crate::segment::{open_dump, LimeSegment};
+
+use memflow::prelude::v1::*;
+
+use std::fs::File;
+use std::path::Path;
+
+/// `p_paddr` of the segments not backed by RAM, like vmalloc or module areas
+const NO_PADDR: u64 = u64::MAX;
+
+/// Loadable segment of an ELF core file like `/proc/kcore`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KcoreRange {
+    /// Kernel virtual address of the first byte of the range
+    pub virt_addr: u64,
+    /// Physical address of the first byte of the range, `None` if the range is not backed
+    /// by RAM, like vmalloc or module areas
+    pub phys_addr: Option<u64>,
+    /// Offset in the file of the first byte of the range
+    pub file_offset: u64,
+    /// Size in bytes of the range
+    pub size: u64,
+}
+
+/// List the loadable segments of the ELF core file at `path`, like `/proc/kcore`, in the
+/// order of its program headers.
+///
+/// # Errors
+///
+/// Returns `Err` if the file can not be read or is not a 64-bit ELF core file
+///
+pub fn kcore_ranges<P: AsRef<Path>>(path: P) -> Result<Vec<KcoreRange>> {
+    let path = path.as_ref();
+    let mut file = open_dump(path)?;
+    read_ranges(&mut file, path)
+}
+
+/// Open the ELF core file at `path` to read the physical memory of the local machine, the
+/// target being `/proc/kcore`.
+///
+/// The kernel describes the RAM, the kernel text, vmalloc and the module areas with one
+/// loadable segment each, at the kernel virtual address they are mapped at. The segments
+/// holding RAM carry their physical address and are mapped there; the kernel text is dropped
+/// as it repeats part of a RAM segment, and so are the segments without a physical address.
+/// Reading `/proc/kcore` needs `CAP_SYS_RAWIO`, and kernels built with
+/// `CONFIG_STRICT_DEVMEM` may still refuse some pages.
+///
+/// Returns the file and the segments mapping it, in the order of their physical addresses.
+///
+/// # Errors
+///
+/// Returns `Err` if the file can not be read or is not a 64-bit ELF core file
+///
+pub(crate) fn open_kcore(path: &Path) -> Result<(DumpFile, Vec<LimeSegment>)> {
+    let mut file = open_dump(path)?;
+    let ranges = read_ranges(&mut file, path)?;
+    let segments = physical_segments(&ranges);
+    log::info!(
+        "Mapping {} of the {} loadable segments of {}",
+        segments.len(),
+        ranges.len(),
+        path.display()
+    );
+    Ok((file.into(), segments))
+}
+
+/// Physical segments of `ranges`, sorted by address, dropping the ranges without a physical
+/// address and the ones contained in another range.
+fn physical_segments(ranges: &[KcoreRange]) -> Vec<LimeSegment> {
+    let mut segments = ranges
+        .iter()
+        .filter(|range| range.size > 0)
+        .filter_map(|range| {
+            let s_addr = range.phys_addr?;
+            Some(LimeSegment {
+                s_addr,
+                e_addr: s_addr.checked_add(range.size - 1)?,
+                file_offset: range.file_offset,
+            })
+        })
+        .collect::<Vec<_>>();
+    segments.sort_by_key(|segment| (segment.s_addr, u64::MAX - segment.e_addr));
+
+    let mut kept: Vec<LimeSegment> = Vec::with_capacity(segments.len());
+    for segment in segments {
+        match kept.last() {
+            Some(last) if last.e_addr >= segment.e_addr => {
+                log::debug!(
+                    "Skipping the segment {:#x}-{:#x}, already mapped",
+                    segment.s_addr,
+                    segment.e_addr
+                );
+            }
+            _ => kept.push(segment),
+        }
+    }
+    kept
+}
+
+/// Read the loadable segments of the ELF core file `file`.
+fn read_ranges(file: &mut File, path: &Path) -> Result<Vec<KcoreRange>> {
+    let (_, phdrs) = read_program_headers(file, path)?;
+    Ok(phdrs
+        .iter()
+        .filter(|phdr| phdr.p_type == PT_LOAD)
+        .map(|phdr| KcoreRange {
+            virt_addr: phdr.vaddr,
+            phys_addr: Some(phdr.paddr).filter(|paddr| *paddr != NO_PADDR),
+            file_offset: phdr.offset,
+            size: phdr.filesz,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contained_and_virtual_ranges_are_dropped() {
+        let range = |phys_addr, file_offset, size| KcoreRange {
+            virt_addr: 0xffff_8880_0000_0000,
+            phys_addr,
+            file_offset,
+            size,
+        };
+        let ranges = [
+            range(Some(0x100_0000), 0x1000, 0x1000),
+            range(None, 0x2000, 0x1000),
+            range(Some(0x10_0000), 0x3000, 0x100_0000),
+            range(Some(0x0), 0x4000, 0),
+        ];
+        assert_eq!(
+            physical_segments(&ranges),
+            [LimeSegment {
+                s_addr: 0x10_0000,
+                e_addr: 0x10f_ffff,
+                file_offset: 0x3000,
+            }]
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use crate::memory::LimeMemory;
+use crate::pattern::PatternScan;
+
+use memflow::prelude::v1::*;
+
+/// Longest text read after a landmark
+const MAX_LANDMARK_TEXT: usize = 256;
+
+/// Kind of kernel landmark found by [`LimeMemory::find_landmarks`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum LimeLandmarkKind {
+    /// `Linux version ...` banner, as in `linux_banner`, `/proc/version` or the kernel log
+    Banner,
+    /// `OSRELEASE=` line of the `vmcoreinfo` note, holding the kernel release
+    VmcoreInfo,
+    /// `swapper/0` name of the idle task, stored in the `comm` field of `init_task`
+    Swapper,
+}
+
+impl LimeLandmarkKind {
+    /// Bytes starting the landmark.
+    const fn needle(self) -> &'static [u8] {
+        match self {
+            Self::Banner => b"Linux version ",
+            Self::VmcoreInfo => b"OSRELEASE=",
+            Self::Swapper => b"swapper/0\0",
+        }
+    }
+}
+
+/// Kernel landmark found in a dump
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LimeLandmark {
+    /// What was found
+    pub kind: LimeLandmarkKind,
+    /// Physical address of the first byte of the landmark
+    pub addr: u64,
+    /// Printable text of the landmark, up to the end of its line or string
+    pub text: String,
+}
+
+/// Search the ranges mapped by `mem` for the kernel landmarks in one pass, keeping the
+/// candidates whose text is printable.
+pub(crate) fn find_landmarks(mem: &mut LimeMemory) -> Result<Vec<LimeLandmark>> {
+    let kinds = [
+        LimeLandmarkKind::Banner,
+        LimeLandmarkKind::VmcoreInfo,
+        LimeLandmarkKind::Swapper,
+    ];
+    let patterns = kinds
+        .iter()
+        .map(|kind| kind.needle().iter().copied().map(Some).collect())
+        .collect();
+    let mut found = Vec::new();
+    let mut scan = PatternScan::new(mem, patterns);
+    while let Some((kind, addr)) = scan.next_match() {
+        found.push((kinds[kind], addr));
+    }
+
+    let mut landmarks = Vec::with_capacity(found.len());
+    for (kind, addr) in found {
+        if let Some(text) = read_text(mem, addr) {
+            landmarks.push(LimeLandmark {
+                kind,
+                addr: addr.to_umem(),
+                text,
+            });
+        }
+    }
+    Ok(landmarks)
+}
+
+/// Read the line or string starting at `addr`, `None` if it holds non-printable characters.
+fn read_text(mem: &mut LimeMemory, addr: PhysicalAddress) -> Option<String> {
+    // The text is cut short by the end of its range.
+    let addr = addr.to_umem();
+    let available = mem
+        .mem_map()
+        .iter()
+        .map(|mapping| (mapping.base().to_umem(), mapping.output().1))
+        .find(|(base, size)| (*base..*base + *size).contains(&addr))
+        .map_or(0, |(base, size)| base + size - addr);
+    let mut buf = vec![0u8; (MAX_LANDMARK_TEXT as u64).min(available) as usize];
+    mem.phys_view().read_raw_into(addr.into(), &mut buf).ok()?;
+
+    let end = buf
+        .iter()
+        .position(|byte| matches!(byte, b'\0' | b'\n'))
+        .unwrap_or(buf.len());
+    let text = &buf[..end];
+    if !text
+        .iter()
+        .all(|byte| byte.is_ascii_graphic() || *byte == b' ')
+    {
+        return None;
+    }
+    String::from_utf8(text.to_vec()).ok()
+}
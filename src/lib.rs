@@ -1,138 +1,559 @@
-use binread::{BinRead, BinReaderExt};
+mod acquire;
+#[cfg(feature = "aff4")]
+mod aff4;
+mod analysis;
+#[cfg(feature = "archive")]
+mod archive;
+#[cfg(feature = "tokio")]
+mod asynchronous;
+mod batch;
+mod blockdev;
+mod cache;
+mod capture;
+mod checksum;
+mod compose;
+#[cfg(feature = "compression")]
+mod compression;
+mod config;
+mod diff;
+mod direct;
+mod dumpfile;
+#[cfg(any(feature = "kcore", feature = "vbcore"))]
+mod elf;
+#[cfg(feature = "age")]
+mod encrypted;
+mod error;
+mod export;
+mod fault;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod header;
+#[cfg(feature = "hiberfil")]
+mod hiberfil;
+mod index;
+mod integrity;
+#[cfg(feature = "kcore")]
+mod kcore;
+mod landmark;
+mod memory;
+#[cfg(feature = "win32")]
+mod os;
+mod overlay;
+mod pattern;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "rawjson")]
+mod rawjson;
+mod readahead;
+mod report;
+mod scan;
+mod segment;
+#[cfg(feature = "signature")]
+mod signature;
+mod spec;
+mod split;
+#[cfg(feature = "ssh")]
+mod ssh;
+mod stats;
+mod targets;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+mod timestamps;
+mod trailer;
+mod uri;
+#[cfg(feature = "vbcore")]
+mod vbcore;
+mod volatility;
+mod writer;
+
+pub use acquire::{system_ram_ranges, IOMEM_PATH, MEMORY_DEVICES};
+pub use analysis::{LimePageStats, HIGH_ENTROPY};
+#[cfg(feature = "tokio")]
+pub use asynchronous::AsyncLimeMemory;
+pub use capture::LimeCaptureStatus;
+pub use checksum::{
+    block_checksums, default_blocks_sidecar, verify_block_checksums, LimeBlockChecksum,
+    LimeBlockManifest,
+};
+#[cfg(feature = "compression")]
+pub use compression::{extract_range_compressed, CompressedWriter, LimeCompression};
+pub use config::{
+    AlignPolicy, ByteOrder, CaptureConfig, DecryptionKey, DumpFormat, FaultConfig, FaultKind,
+    FaultRange, FollowConfig, GapFill, IoMode, LimeConfig, OverlapPolicy, PageCacheConfig,
+    ParseLimits, ParseMode, ParseStrategy, ReadAheadConfig, ScanStrategy, SignatureConfig,
+    SshConfig, ThrottleConfig, WriteMode,
+};
+pub use diff::{lime_diff, LimeChange, LimeChangeKind};
+pub use error::LimeError;
+pub use export::{
+    convert_to_memprocfs, convert_to_raw, convert_to_raw_with_options,
+    convert_to_raw_with_progress, default_memmap_path, extract_range, RawOutput,
+};
+pub use index::default_index_path;
+pub use integrity::{
+    default_sha256_sidecar, hash_segments, sha256_digest, sha256_digest_with_progress,
+    verify_sha256, verify_sha256_with_progress, write_hash_manifest, HashAlgorithm,
+    LimeSegmentDigest, SHA256_DIGEST_SIZE,
+};
+#[cfg(feature = "kcore")]
+pub use kcore::{kcore_ranges, KcoreRange};
+pub use landmark::{LimeLandmark, LimeLandmarkKind};
+pub use memory::LimeMemory;
+#[cfg(feature = "win32")]
+pub use os::{open_win32, open_win32_with_args, LimeWin32Kernel};
+pub use pattern::PatternScan;
+pub use report::{report, LimeGap, LimeReport};
+pub use segment::{
+    carve_layout, read_digest_trailer, read_layout, read_layout_detailed, read_segments,
+    recover_layout, LimeLayout, LimeReserved, LimeSegment, LimeSkipped, LimeTruncation,
+};
+#[cfg(feature = "signature")]
+pub use signature::{verify_signature, verify_signature_with_progress};
+pub use spec::{args_spec, ArgSpec};
+pub use stats::LimeStats;
+pub use targets::{list_dumps, target_list, EVIDENCE_DIR_ENV};
+pub use timestamps::{
+    default_timestamps_sidecar, read_timed_segments, LimeSegmentTime, LimeTimestamps,
+};
+pub use trailer::LimeDigest;
+#[cfg(feature = "vbcore")]
+pub use vbcore::{vbox_core_info, VBoxCore, VBoxCoreRange};
+pub use volatility::{volatility_layer, VolatilityLayer, VolatilitySegment, VOLATILITY_LIME_LAYER};
+pub use writer::{LimeAppender, LimeWriter};
+
+use checksum::BlockVerifier;
+use direct::DirectFile;
+use dumpfile::DumpFile;
+use index::parse_dump_indexed;
+use memory::{map_segments, mapped_segments};
+use scan::parse_dump_parallel;
+use segment::{
+    align_segments, open_dump, open_dump_writable, parse_dump_with_progress, AlignedSegments,
+};
 
-use memflow::connector::fileio::{CloneFile, FileIoMemory};
 use memflow::prelude::v1::*;
 
 use std::fs::File;
-use std::io;
-use std::io::{Cursor, Read, Seek, SeekFrom};
-
-/// Header defined by the `LiME` file format, version 1
-///
-/// source: [LiME Memory Range Header Version 1 Specification](https://github.com/504ensicsLabs/LiME/blob/master/doc/README.md#Spec)
-#[derive(Debug, BinRead)]
-#[br(magic = 0x4C69_4D45_u32)] //LiME
-struct LimeHeader {
-    /// Header version number
-    #[br(assert(version == 1, "Unsupported LiME version: {}", version))]
-    #[allow(dead_code)]
-    version: u32,
-    /// Starting address of physical RAM range
-    s_addr: u64,
-    /// Ending address of physical RAM range
-    #[br(assert(e_addr >= s_addr, "End address can not be lower than start address"))]
-    e_addr: u64,
-    /// Currently all zeros
-    #[br(assert(reserved == [0; 8], "Unsupported LiME reserved fields values"))]
-    #[allow(dead_code)]
-    reserved: [u8; 8],
+
+/// Create connector to a `LiME` file.
+///
+/// # Arguments
+///
+/// * `args` - the target field may contain the `LiME` file path or a `file://` URI, the
+///   extra arguments are listed by [`help`] and [`args_spec`] and parsed into a
+///   [`LimeConfig`]
+///
+/// # Errors
+///
+/// Returns `Err` if an argument is unknown or invalid, or if an error occurred while reading
+/// or parsing the file
+///
+#[connector(name = "lime", help_fn = "help", target_list_fn = "target_list")]
+pub fn create_connector(args: &ConnectorArgs) -> Result<LimeMemory> {
+    create_connector_with_progress(args, |_, _| ())
 }
 
-impl LimeHeader {
-    /// Size in bytes of `LimeHeader`
-    const HEADER_SIZE_IN_BYTES: usize = 32;
-
-    /// Get the `LiME` header from file.
-    ///
-    /// Returns `Ok(None)` if the End Of File is reached\
-    /// Returns `Ok(Some(...))` if the `LimeHeader` is parsed correctly\
-    ///
-    /// # Arguments
-    ///
-    /// * `lime_dump` - file to read from, the seek of the file  must be already at the start of the header or at EOF.
-    ///
-    /// # Errors
-    ///
-    /// Returns `Err` if an error occurred while reading the file or parsing the header
-    ///
-    fn next_header_from_file(lime_dump: &mut File) -> Result<Option<LimeHeader>> {
-        let mut buff = [0u8; LimeHeader::HEADER_SIZE_IN_BYTES];
-
-        match lime_dump.read_exact(&mut buff) {
-            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
-            Err(_) => Err(Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)),
-            Ok(()) => {
-                let header = Cursor::new(&buff).read_le().map_err(|_| {
-                    Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
-                        .log_error("Unable to parse the LiME file.")
-                })?;
-
-                Ok(Some(header))
-            }
+/// Create connector to a `LiME` file, reporting the progress of the work done on open.
+///
+/// `progress` is called with the number of bytes processed so far and the size of the file
+/// while a remote dump is spooled (`ssh://` targets), while the dump is hashed (`verify`) and
+/// while its headers are scanned, each phase starting over from zero. Nothing is reported if the range table comes from an up to date index or
+/// with `parse=lazy`, `reload` or `follow`.
+///
+/// # Arguments
+///
+/// * `args` - connector arguments, as for [`create_connector`]
+/// * `progress` - callback receiving `(bytes_processed, total_bytes)`
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading or parsing the file
+///
+pub fn create_connector_with_progress<F>(
+    args: &ConnectorArgs,
+    mut progress: F,
+) -> Result<LimeMemory>
+where
+    F: FnMut(u64, u64),
+{
+    let config = LimeConfig::from_args(args)?;
+    open_connector(&config, &mut progress)
+        .and_then(|mem| with_block_checks(mem, &config))
+        .map(|mem| with_debug_modes(mem, &config))
+}
+
+/// Check the blocks read against the manifest given with `verify_blocks`.
+fn with_block_checks(mem: LimeMemory, config: &LimeConfig) -> Result<LimeMemory> {
+    let Some(manifest_path) = &config.verify_blocks else {
+        return Ok(mem);
+    };
+    let file = if config.split.is_empty() {
+        open_dump(&config.path)?.into()
+    } else {
+        DumpFile::open_parts(&config.split)?
+    };
+    Ok(mem.with_block_verifier(BlockVerifier::new(file, manifest_path)?))
+}
+
+/// Open the dump described by `config`, see [`create_connector_with_progress`].
+fn open_connector(config: &LimeConfig, mut progress: impl FnMut(u64, u64)) -> Result<LimeMemory> {
+    let lime_path = config.path.as_path();
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!("lime_open", path = %lime_path.display()).entered();
+    #[cfg(feature = "ssh")]
+    if let Some(ssh) = &config.ssh {
+        ssh::acquire(ssh, lime_path, &mut progress)?;
+    }
+    if let Some(capture) = &config.capture {
+        let capture = capture::start(capture, lime_path)?;
+        return open_lazy(open_dump(lime_path)?.into(), None, config)
+            .map(|mem| mem.with_capture(capture));
+    }
+    let options = config.parse_options();
+
+    if let Some(sidecar_path) = &config.verify {
+        if verify_sha256_with_progress(lime_path, sidecar_path, &mut progress).is_err() {
+            config
+                .mode
+                .tolerate("The LiME file failed the SHA-256 verification")?;
         }
     }
 
-    /// Size in bytes of the memory represented by this header
-    const fn mem_section_size(&self) -> u64 {
-        self.e_addr - self.s_addr + 1
+    #[cfg(feature = "signature")]
+    if let Some(signature) = &config.signature {
+        if signature::verify_signature_with_progress(
+            lime_path,
+            &signature.signature,
+            &signature.pubkey,
+            &mut progress,
+        )
+        .is_err()
+        {
+            config
+                .mode
+                .tolerate("The LiME file failed the signature verification")?;
+        }
+    }
+
+    #[cfg(feature = "aff4")]
+    if config.format == DumpFormat::Aff4 {
+        let (image, segments) = aff4::open_image(lime_path)?;
+        return map_image(image, &segments, config);
+    }
+
+    #[cfg(feature = "hiberfil")]
+    if config.format == DumpFormat::Hiberfil {
+        let (image, segments) = hiberfil::open_hiberfil(lime_path)?;
+        return map_image(image, &segments, config);
+    }
+
+    #[cfg(feature = "kcore")]
+    if config.format == DumpFormat::Kcore {
+        let (image, segments) = kcore::open_kcore(lime_path)?;
+        return map_image(image, &segments, config);
+    }
+
+    #[cfg(feature = "vbcore")]
+    if config.format == DumpFormat::VBoxCore {
+        let (image, segments) = vbcore::open_vbcore(lime_path)?;
+        return map_image(image, &segments, config);
+    }
+
+    #[cfg(feature = "rawjson")]
+    if config.format == DumpFormat::RawJson {
+        let (image, segments) = rawjson::open_run_list(lime_path)?;
+        return map_image(image, &segments, config);
+    }
+
+    if !config.overlays.is_empty() {
+        let (layers, mapped) = overlay::open_layers(
+            lime_path,
+            &config.overlays,
+            options,
+            config.overlap,
+            &mut progress,
+        )?;
+        let (mapped, padding) = restrict_and_align(mapped, config)?;
+        return LimeMemory::new(
+            map_segments(layers, None, &mapped)?,
+            config.fill_gaps,
+            config.cache,
+        )
+        .map(|mem| mem.with_padding(padding));
+    }
+
+    if !config.composed.is_empty() {
+        let (parts, mapped) =
+            compose::open_composed(&config.composed, options, config.overlap, &mut progress)?;
+        let (mapped, padding) = restrict_and_align(mapped, config)?;
+        return LimeMemory::new(
+            map_segments(parts, None, &mapped)?,
+            config.fill_gaps,
+            config.cache,
+        )
+        .map(|mem| mem.with_padding(padding));
+    }
+
+    if let Some(mut lime_dump) = open_split_or_member(config)? {
+        if config.parse == ParseStrategy::Lazy || config.reload || config.follow.is_some() {
+            return open_lazy(lime_dump, None, config);
+        }
+        let segments = parse_dump_with_progress(&mut lime_dump, options, &mut progress)?.segments;
+        let (mapped, padding) =
+            restrict_and_align(mapped_segments(&segments, config.overlap)?, config)?;
+        return LimeMemory::new(
+            map_segments(lime_dump, None, &mapped)?,
+            config.fill_gaps,
+            config.cache,
+        )
+        .map(|mem| mem.with_padding(padding));
+    }
+
+    let mut lime_dump = match config.write {
+        WriteMode::ReadOnly => open_dump(lime_path)?,
+        WriteMode::InPlace => {
+            log::warn!(
+                "write=inplace: writes to the physical memory modify {}",
+                lime_path.display()
+            );
+            open_dump_writable(lime_path)?
+        }
+    };
+    let direct = match config.io_mode {
+        IoMode::Buffered => None,
+        IoMode::Direct => DirectFile::open(lime_path)
+            .map_err(|e| {
+                log::warn!(
+                    "Unable to open the LiME file with O_DIRECT, using buffered reads: {}",
+                    e
+                )
+            })
+            .ok(),
+    };
+    if config.parse == ParseStrategy::Lazy || config.reload || config.follow.is_some() {
+        return open_lazy(lime_dump.into(), direct, config);
+    }
+
+    let mut scan = |lime_dump: &mut File| match config.scan {
+        ScanStrategy::Serial => parse_dump_with_progress(lime_dump, options, &mut progress),
+        ScanStrategy::Parallel => parse_dump_parallel(lime_dump, lime_path, options, &mut progress),
+    };
+    let segments = match &config.index {
+        None => scan(&mut lime_dump)?,
+        Some(index_path) => parse_dump_indexed(&mut lime_dump, options, index_path, scan)?,
+    }
+    .segments;
+
+    let (mem, mapped) = map_file(lime_dump, direct, &segments, config)?;
+    match config.readahead {
+        Some(readahead) => mem.with_readahead(lime_path, mapped, readahead),
+        None => Ok(mem),
     }
 }
 
-/// Create connector to a `LiME` file.
+/// Create connector to a `LiME` file already opened by the caller.
+///
+/// Services without filesystem access can build the connector from a handle received from
+/// another process, e.g. over a unix socket with `File::from_raw_fd`. The handle must be
+/// readable and seekable, and writable for `write=inplace`.
 ///
 /// # Arguments
 ///
-/// * `args` - the target field may contain the `LiME` file path
+/// * `lime_dump` - handle of the `LiME` file, read from its start
+/// * `args` - connector arguments without a target, the extra arguments being parsed into a
+///   [`LimeConfig`] by [`LimeConfig::from_file_args`]
 ///
 /// # Errors
 ///
-/// Returns `Err` if an error occurred while reading or parsing the file
+/// Returns `Err` if an argument is unknown, invalid or needs the path of the dump, or if an
+/// error occurred while reading or parsing the file
+///
+pub fn create_connector_from_file(lime_dump: File, args: &ConnectorArgs) -> Result<LimeMemory> {
+    let config = LimeConfig::from_file_args(args)?;
+    if config.write == WriteMode::InPlace {
+        log::warn!("write=inplace: writes to the physical memory modify the LiME file");
+    }
+    let mem = if config.parse == ParseStrategy::Lazy || config.reload || config.follow.is_some() {
+        open_lazy(lime_dump.into(), None, &config)?
+    } else {
+        let mut lime_dump = lime_dump;
+        let segments =
+            parse_dump_with_progress(&mut lime_dump, config.parse_options(), &mut |_, _| ())?
+                .segments;
+        map_file(lime_dump, None, &segments, &config)?.0
+    };
+    Ok(with_debug_modes(mem, &config))
+}
+
+/// Slow the reads of `mem` down as `throttle` and `bandwidth` request, and make them fail as
+/// `faults` requests.
+fn with_debug_modes(mut mem: LimeMemory, config: &LimeConfig) -> LimeMemory {
+    if let Some(throttle) = config.throttle {
+        mem = mem.with_throttle(throttle);
+    }
+    if let Some(faults) = &config.faults {
+        mem = mem.with_faults(faults.clone());
+    }
+    mem
+}
+
+/// Map the `segments` of the single file `lime_dump`, reading through `direct` if set.
 ///
-#[connector(name = "lime", help_fn = "help")]
-pub fn create_connector(args: &ConnectorArgs) -> Result<FileIoMemory<CloneFile>> {
-    let mut lime_dump = File::open(
-        args.target
-            .as_ref()
-            .ok_or(
-                Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
-                    .log_error("LiME file path not specified"),
-            )?
-            .as_ref(),
+/// Returns the connector and the segments mapped.
+fn map_file(
+    lime_dump: File,
+    direct: Option<DirectFile>,
+    segments: &[LimeSegment],
+    config: &LimeConfig,
+) -> Result<(LimeMemory, Vec<LimeSegment>)> {
+    let lime_dump = DumpFile::from(lime_dump);
+    let (mapped, padding) = restrict_and_align(mapped_segments(segments, config.overlap)?, config)?;
+    let mut mem = LimeMemory::new(
+        map_segments(lime_dump, direct, &mapped)?,
+        config.fill_gaps,
+        config.cache,
+    )?
+    .with_padding(padding);
+    if config.write == WriteMode::InPlace {
+        mem = mem.with_writes();
+    }
+    Ok((mem, mapped))
+}
+
+/// Map the `segments` of an image read from another container format than `LiME`.
+#[cfg(any(
+    feature = "aff4",
+    feature = "rawjson",
+    feature = "hiberfil",
+    feature = "kcore",
+    feature = "vbcore"
+))]
+fn map_image(image: DumpFile, segments: &[LimeSegment], config: &LimeConfig) -> Result<LimeMemory> {
+    segment::check_limits(segments, config.limits)?;
+    let (mapped, padding) = restrict_and_align(mapped_segments(segments, config.overlap)?, config)?;
+    LimeMemory::new(
+        map_segments(image, None, &mapped)?,
+        config.fill_gaps,
+        config.cache,
     )
-    .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+    .map(|mem| mem.with_padding(padding))
+}
 
-    let mut map = MemoryMap::new();
-    let mut offset = 0;
+/// Shift the `mapped` segments as `rebase` requests, keep the parts falling in the windows
+/// of `ranges`, then align them as `align` requests.
+///
+/// Returns the segments and the windows padding them to whole pages, read as zeros.
+fn restrict_and_align(mapped: Vec<LimeSegment>, config: &LimeConfig) -> Result<AlignedSegments> {
+    let mapped = if config.rebase.is_empty() {
+        mapped
+    } else {
+        segment::rebase_segments(&mapped, &config.rebase)?
+    };
+    let mapped = match &config.ranges {
+        Some(windows) => segment::restrict_segments(&mapped, windows),
+        None => mapped,
+    };
+    align_segments(mapped, config.align)
+}
 
-    while let Some(header) = LimeHeader::next_header_from_file(&mut lime_dump)? {
-        offset += LimeHeader::HEADER_SIZE_IN_BYTES as u64;
+/// Open the dump of `config` if it is split in parts, stored in an archive or encrypted,
+/// returns `None` if it is a single plain file.
+fn open_split_or_member(config: &LimeConfig) -> Result<Option<DumpFile>> {
+    #[cfg(feature = "age")]
+    if let Some(key) = &config.key {
+        return encrypted::open_encrypted(&config.path, key).map(Some);
+    }
+    #[cfg(feature = "archive")]
+    if let Some(member) = &config.member {
+        return archive::open_member(&config.path, member).map(Some);
+    }
+    if config.split.is_empty() {
+        return Ok(None);
+    }
+    DumpFile::open_parts(&config.split).map(Some)
+}
 
-        map.push_remap(
-            header.s_addr.into(),
-            header.mem_section_size(),
-            offset.into(),
-        );
-        offset = lime_dump
-            .seek(SeekFrom::Current(header.mem_section_size() as i64))
-            .map_err(|_| {
-                Error(ErrorOrigin::Connector, ErrorKind::UnableToSeekFile)
-                    .log_error("Corrupted LiME file")
-            })?;
-    }
-
-    lime_dump.seek(SeekFrom::Start(0)).map_err(|_| {
-        Error(ErrorOrigin::Connector, ErrorKind::UnableToSeekFile)
-            .log_error("Unable to seek back to the beginning of the file")
-    })?;
-    FileIoMemory::with_mem_map(lime_dump.into(), map)
+/// Map `lime_dump` lazily for `parse=lazy`, `reload` and `follow`, walking every header
+/// right away unless `parse=lazy` is given.
+fn open_lazy(
+    lime_dump: DumpFile,
+    direct: Option<DirectFile>,
+    config: &LimeConfig,
+) -> Result<LimeMemory> {
+    let mut mem = LimeMemory::new_lazy(
+        lime_dump,
+        direct,
+        config.parse_options(),
+        config.overlap,
+        config.fill_gaps,
+        config.cache,
+        config.reload,
+    )?;
+    if let Some(follow) = config.follow {
+        mem = mem.with_follow(follow);
+    }
+    if config.write == WriteMode::InPlace {
+        mem = mem.with_writes();
+    }
+    if config.parse == ParseStrategy::Eager {
+        mem.discover_all()?;
+    }
+    Ok(mem)
 }
 
 /// Retrieve the help text for the `LiME` Connector.
 pub fn help() -> String {
-    "\
+    let intro = "\
 The `lime` connector implements the LiME file format parser.
 
-The `target` argument specifies the filename of the file to be opened.
-    "
-    .to_string()
+The `target` argument specifies the filename of the file to be opened, either
+as a plain path or as a `file://` URI (e.g. `file:///evidence/dump.lime`).
+Other URI schemes are refused until a backend supports them. A dump split in
+parts is opened from the comma-separated list of its parts, or from a pattern
+ending with `*` matching their numbered names (e.g. `dump.lime.*` for
+`dump.lime.000`, `dump.lime.001`, ...). Dumps acquired separately, e.g. per NUMA node, are merged
+into one address space from the `;`-separated list of their paths, each
+optionally followed by `@` and an offset added to its addresses (e.g.
+`node0.lime;node1.lime@0x1000000000`). An existing file is opened whole even if
+its path holds a `,` or a `;`, and has no offset even if it holds a `@`. With the `archive` feature, a dump
+stored in a zip or tar archive is opened in place from the path of the archive
+followed by `!` and the path of the member (e.g. `case.zip!memory/dump.lime`).
+Other formats are detected from the magic number starting the file, or named by the
+`format` argument, the file name never being looked at.
+With the `aff4` feature, the physical memory image of an AFF4 container is
+mapped instead. With the `rawjson` feature, the raw image described by a JSON
+run list, as written by DumpIt, Magnet RAM Capture or WinPmem, or by the e820-style
+memory map of a QEMU `pmemsave` image, is mapped instead.
+With the `hiberfil` feature, a Windows 7 hibernation file is
+mapped instead, its pages being decompressed on demand.
+With the `kcore` feature, the physical memory of the local machine is read live
+from `/proc/kcore`, its ELF program headers holding RAM being mapped at their
+physical addresses.
+With the `vbcore` feature, the guest-physical memory of a VirtualBox core file
+written by `VBoxManage debugvm dumpvmcore`, told apart by its `VBCORE` note, is
+mapped instead.
+With the `ssh` feature, a dump is acquired from a remote host with an
+`ssh://[user@]host[:port]/path` target, the remote file or the output of `ssh_cmd`
+being streamed into a local spool file, resumed if interrupted, that is then opened.
+A dump streamed by LiME in its TCP mode (`path=tcp:<port>`) is received from a
+`tcp://host:port` target into the new file named by `spool`, its ranges being read
+while the rest of the stream arrives, reads past them waiting for the data.
+A LiME acquisition written straight to a raw partition is opened from the block
+device (e.g. `/dev/sdb1`), the LiME data ending at the first position not holding a
+header.
+With the `age` feature, a LiME file encrypted with age is opened with the `key`
+or `keyfile` argument, being decrypted in memory as it is read.
+The `.lime` and `.lime.gz` files of the directory named by the
+`MEMFLOW_LIME_DIR` environment variable are listed as targets.
+
+Optional arguments:
+";
+    format!("{}{}", intro, spec::help_list(78))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
-    use std::fs::OpenOptions;
-    use std::io::{Seek, SeekFrom, Write};
 
     #[test]
     fn unspecified_file_causes_error() {
@@ -143,33 +564,4 @@ mod tests {
             Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
         );
     }
-
-    #[test]
-    fn header_parser_works() {
-        let raw_header: [u8; LimeHeader::HEADER_SIZE_IN_BYTES] = [
-            69, 77, 105, 76, 1, 0, 0, 0, 0, 0, 0, 64, 0, 0, 0, 0, 255, 255, 207, 251, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0,
-        ];
-        let tmp_file_path = "./test_header.tmp";
-        let mut tmp_file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .open(tmp_file_path)
-            .unwrap();
-
-        tmp_file.write(&raw_header).unwrap();
-        tmp_file.seek(SeekFrom::Start(0)).unwrap();
-
-        let header = LimeHeader::next_header_from_file(&mut tmp_file)
-            .unwrap()
-            .unwrap();
-
-        fs::remove_file(tmp_file_path).unwrap();
-
-        assert_eq!(header.version, 1);
-        assert_eq!(header.s_addr, 0x40000000);
-        assert_eq!(header.e_addr, 0xFBD00000 - 1);
-        assert_eq!(header.reserved, [0; 8]);
-    }
 }
@@ -0,0 +1,903 @@
+use crate::analysis::{self, LimePageStats};
+use crate::batch::BatchedFile;
+use crate::cache::MappedFile;
+use crate::capture::{Capture, LimeCaptureStatus};
+use crate::checksum::{BlockVerifier, LimeBlockChecksum};
+use crate::config::{
+    FaultConfig, FaultKind, FollowConfig, GapFill, OverlapPolicy, PageCacheConfig, ParseOptions,
+    ReadAheadConfig, ThrottleConfig,
+};
+use crate::direct::DirectFile;
+use crate::dumpfile::DumpFile;
+use crate::fault::FaultInjector;
+use crate::landmark::{self, LimeLandmark};
+use crate::pattern::{parse_pattern, PatternScan};
+use crate::readahead::Prefetcher;
+use crate::report::LimeGap;
+use crate::segment::{coalesce, resolve_overlaps, HeaderWalker, LimeSegment};
+use crate::stats::LimeStats;
+
+use memflow::cglue;
+use memflow::prelude::v1::*;
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+cglue_impl_group!(LimeMemory, ConnectorInstance, {});
+
+/// Physical memory backed by a `LiME` file
+#[derive(Clone)]
+pub struct LimeMemory {
+    mem: MappedFile,
+    cache: Option<PageCacheConfig>,
+    /// Highest address mapped, null if nothing is mapped
+    max_address: Address,
+    /// Bytes of physical memory mapped, headers and gaps excluded
+    real_size: umem,
+    mem_map: MemoryMap<(Address, umem)>,
+    gap_fill: GapFill,
+    /// Inclusive windows padding misaligned ranges to whole pages, read as zeros
+    /// (`align=extend`)
+    padding: Vec<(u64, u64)>,
+    stats: Arc<LimeStats>,
+    lazy: Option<LazyMap>,
+    readahead: Option<Prefetcher>,
+    /// Whether writes reach the dump (`write=inplace`)
+    writable: bool,
+    /// Artificial slowdown of the reads (`throttle`, `bandwidth`)
+    throttle: Option<ThrottleConfig>,
+    /// Failures injected into the reads (`faults`)
+    faults: Option<FaultInjector>,
+    /// Checksums the blocks read are checked against (`verify_blocks`)
+    blocks: Option<BlockVerifier>,
+    /// Capture writing the dump while it is read (`tcp://` targets)
+    capture: Option<Arc<Capture>>,
+}
+
+/// State of a lazy parse, the headers not walked yet are parsed as reads need them
+#[derive(Clone)]
+struct LazyMap {
+    file: DumpFile,
+    direct: Option<DirectFile>,
+    walker: HeaderWalker,
+    /// Segments walked so far, in file order
+    segments: Vec<LimeSegment>,
+    /// Physical ranges backed by `segments`, merged, by first address to last address
+    covered: BTreeMap<u64, u64>,
+    overlap: OverlapPolicy,
+    /// Resume the walk when the file grows after it is over
+    reload: bool,
+    /// Wait for the file to grow when a read goes past the ranges walked so far
+    follow: Option<FollowConfig>,
+    /// Capture writing the file, the waits ending with it
+    capture: Option<Arc<Capture>>,
+}
+
+impl LazyMap {
+    /// Whether the segments discovered so far back every byte of `[s_addr, e_addr]`.
+    fn covers(&self, s_addr: u64, e_addr: u64) -> bool {
+        self.covered
+            .range(..=s_addr)
+            .next_back()
+            .is_some_and(|(_, &end)| end >= e_addr)
+    }
+
+    /// Whether `e_addr` lies past every range walked so far, in data not written yet.
+    fn is_ahead(&self, e_addr: u64) -> bool {
+        self.covered
+            .last_key_value()
+            .is_none_or(|(_, &end)| end < e_addr)
+    }
+
+    /// Whether a page of `page_size` bytes holding bytes of `segment` also holds bytes of
+    /// the segments discovered so far, a cached copy of it being stale once `segment` is
+    /// mapped.
+    fn shares_page(&self, segment: &LimeSegment, page_size: u64) -> bool {
+        let s_addr = segment.s_addr - segment.s_addr % page_size;
+        let e_addr = segment
+            .e_addr
+            .saturating_add(page_size - 1 - segment.e_addr % page_size);
+        self.covered
+            .range(..=e_addr)
+            .next_back()
+            .is_some_and(|(_, &end)| end >= s_addr)
+    }
+
+    /// Add `segment` to the segments discovered so far.
+    fn push(&mut self, segment: LimeSegment) {
+        self.segments.push(segment);
+        self.cover(segment.s_addr, segment.e_addr);
+    }
+
+    /// Merge `[s_addr, e_addr]` into the covered ranges.
+    fn cover(&mut self, mut s_addr: u64, mut e_addr: u64) {
+        if let Some((&start, &end)) = self.covered.range(..=s_addr).next_back() {
+            if end.saturating_add(1) >= s_addr {
+                s_addr = start;
+                e_addr = e_addr.max(end);
+            }
+        }
+        while let Some((&start, &end)) = self.covered.range(s_addr..).next() {
+            if start > e_addr.saturating_add(1) {
+                break;
+            }
+            e_addr = e_addr.max(end);
+            self.covered.remove(&start);
+        }
+        self.covered.insert(s_addr, e_addr);
+    }
+
+    /// Resume the walk if the file grew since it ended, returns whether it did.
+    fn grow(&mut self) -> Result<bool> {
+        let file_len = self
+            .file
+            .len()
+            .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+        match self.walker.resume(file_len) {
+            Some(partial) => {
+                log::debug!("LiME file grew to {:#x} bytes, resuming the walk", file_len);
+                if partial {
+                    self.segments.pop();
+                    self.covered.clear();
+                    for segment in self.segments.clone() {
+                        self.cover(segment.s_addr, segment.e_addr);
+                    }
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+/// Sorted and non-overlapping segments backing the physical memory, overlaps being resolved
+/// according to `overlap`.
+///
+/// # Errors
+///
+/// Returns `Err` if two segments overlap and `overlap` is [`OverlapPolicy::Error`]
+///
+pub(crate) fn mapped_segments(
+    segments: &[LimeSegment],
+    overlap: OverlapPolicy,
+) -> Result<Vec<LimeSegment>> {
+    Ok(coalesce(resolve_overlaps(segments, overlap)?))
+}
+
+/// Highest address and size in bytes of the memory mapped by `mem`.
+///
+/// `MemoryMap` reports the top of the address space as the highest address of an empty map,
+/// the null address is returned instead.
+fn mapped_extent(mem: &BatchedFile) -> (Address, umem) {
+    let metadata = mem.metadata();
+    if metadata.real_size == 0 {
+        (Address::null(), 0)
+    } else {
+        (metadata.max_address, metadata.real_size)
+    }
+}
+
+/// Whether `phys_addr` is mapped by none of the ranges of `mem_map` and lies between the
+/// first and the last of them, reads failing there being zero-filled by `fill_gaps=zero`.
+///
+/// Reads failing in a mapped range, like the ones of a file truncated after it was opened,
+/// are no gap.
+fn in_gap(mem_map: &MemoryMap<(Address, umem)>, phys_addr: Address) -> bool {
+    let Some(first) = mem_map.iter().next() else {
+        return false;
+    };
+    phys_addr >= first.base()
+        && phys_addr <= mem_map.max_address()
+        && !mem_map.iter().any(|mapping| {
+            (mapping.base()..mapping.base() + mapping.output().1).contains(&phys_addr)
+        })
+}
+
+/// Bytes from `phys_addr` up to the end of the window of `padding` holding it, and `true`;
+/// or up to the next window, and `false` if no window holds it.
+fn padding_at(padding: &[(u64, u64)], phys_addr: u64) -> (umem, bool) {
+    let index = padding.partition_point(|&(_, end)| end < phys_addr);
+    match padding.get(index) {
+        Some(&(start, end)) if start <= phys_addr => ((end - phys_addr).saturating_add(1), true),
+        Some(&(start, _)) => (start - phys_addr, false),
+        None => (umem::MAX, false),
+    }
+}
+
+/// Map the `mapped` segments of `file`, as returned by [`mapped_segments`], into physical
+/// memory, reading them through `direct` if set.
+///
+/// # Errors
+///
+/// Returns `Err` if the memory map can not be built
+///
+pub(crate) fn map_segments(
+    file: DumpFile,
+    direct: Option<DirectFile>,
+    mapped: &[LimeSegment],
+) -> Result<BatchedFile> {
+    let mut map = MemoryMap::new();
+    for segment in mapped {
+        map.push_remap(
+            segment.s_addr.into(),
+            segment.size(),
+            segment.file_offset.into(),
+        );
+    }
+    BatchedFile::new(file, direct, map)
+}
+
+impl LimeMemory {
+    /// Wrap `mem`, the file mapped according to the `LiME` headers, behind a page cache if
+    /// `cache` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the cache can not be built
+    ///
+    pub(crate) fn new(
+        mem: BatchedFile,
+        gap_fill: GapFill,
+        cache: Option<PageCacheConfig>,
+    ) -> Result<Self> {
+        let stats = Arc::new(LimeStats::default());
+        let (max_address, real_size) = mapped_extent(&mem);
+        Ok(Self {
+            max_address,
+            real_size,
+            mem_map: mem.mem_map().clone(),
+            mem: MappedFile::new(mem, cache, stats.clone())?,
+            cache,
+            gap_fill,
+            padding: Vec::new(),
+            stats,
+            lazy: None,
+            readahead: None,
+            writable: false,
+            throttle: None,
+            faults: None,
+            blocks: None,
+            capture: None,
+        })
+    }
+
+    /// Prefetch windows of the dump at `path` following sequential reads, `mapped` being the
+    /// segments `self` was mapped with.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the dump can not be reopened or the prefetching thread can not be
+    /// spawned
+    ///
+    pub(crate) fn with_readahead(
+        mut self,
+        path: &Path,
+        mapped: Vec<LimeSegment>,
+        config: ReadAheadConfig,
+    ) -> Result<Self> {
+        let prefetcher = Prefetcher::new(path, mapped.into(), config.window).map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+                .log_error("Unable to start the LiME read-ahead thread")
+        })?;
+        self.readahead = Some(prefetcher);
+        Ok(self)
+    }
+
+    /// Serve the dump `capture` is receiving, reads past the data received so far waiting
+    /// for it until the capture is over, `self` having been created by [`Self::new_lazy`]
+    /// and [`Self::with_follow`].
+    pub(crate) fn with_capture(mut self, capture: Arc<Capture>) -> Self {
+        if let Some(lazy) = self.lazy.as_mut() {
+            lazy.capture = Some(capture.clone());
+        }
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Read the inclusive windows of `padding`, sorted by address and left unmapped, as zeros.
+    pub(crate) fn with_padding(mut self, padding: Vec<(u64, u64)>) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Let writes to the physical memory modify the dump, opened for writing.
+    pub(crate) fn with_writes(mut self) -> Self {
+        self.writable = true;
+        self
+    }
+
+    /// Make every read take at least the time `throttle` allows for it.
+    pub(crate) fn with_throttle(mut self, throttle: ThrottleConfig) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// Make the reads touching the ranges of `faults` fail.
+    pub(crate) fn with_faults(mut self, faults: FaultConfig) -> Self {
+        self.faults = Some(FaultInjector::new(faults));
+        self
+    }
+
+    /// Fail the reads of blocks that do not match their checksum in `blocks`.
+    pub(crate) fn with_block_verifier(mut self, blocks: BlockVerifier) -> Self {
+        self.blocks = Some(blocks);
+        self
+    }
+
+    /// Map `lime_dump` lazily, no header is parsed until the first read.
+    ///
+    /// With `reload`, the walk resumes whenever a read is not covered by the ranges walked so
+    /// far and the file grew since the walk ended.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the size of the file can not be read
+    ///
+    pub(crate) fn new_lazy(
+        lime_dump: DumpFile,
+        direct: Option<DirectFile>,
+        options: ParseOptions,
+        overlap: OverlapPolicy,
+        gap_fill: GapFill,
+        cache: Option<PageCacheConfig>,
+        reload: bool,
+    ) -> Result<Self> {
+        let file_len = lime_dump
+            .len()
+            .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+        let mut mem = Self::new(
+            map_segments(lime_dump.clone(), direct.clone(), &[])?,
+            gap_fill,
+            cache,
+        )?;
+        mem.lazy = Some(LazyMap {
+            file: lime_dump,
+            direct,
+            walker: HeaderWalker::new(file_len, options),
+            segments: Vec::new(),
+            covered: BTreeMap::new(),
+            overlap,
+            reload,
+            follow: None,
+            capture: None,
+        });
+        Ok(mem)
+    }
+
+    /// Make reads past the ranges walked so far wait for the file to grow, `self` having
+    /// been created by [`Self::new_lazy`].
+    pub(crate) fn with_follow(mut self, follow: FollowConfig) -> Self {
+        if let Some(lazy) = self.lazy.as_mut() {
+            lazy.reload = true;
+            lazy.follow = Some(follow);
+        }
+        self
+    }
+
+    /// Walk every header not parsed yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an error occurred while reading the file or if a header is corrupt
+    ///
+    pub(crate) fn discover_all(&mut self) -> Result<()> {
+        self.discover(u64::MAX, 1, false)
+    }
+
+    /// Memory map built from the `LiME` headers, to iterate over the physical ranges backed
+    /// by the dump instead of scanning the whole address space.
+    ///
+    /// Every mapping holds the offset of its data in the dump, the parts of a split dump or
+    /// the layers of an overlay being counted back-to-back, and its size. A lazily parsed
+    /// dump only maps the ranges walked so far.
+    pub fn mem_map(&self) -> &MemoryMap<(Address, umem)> {
+        &self.mem_map
+    }
+
+    /// Physical regions left unmapped between the lowest and the highest mapped address,
+    /// sorted, e.g. to check that a capture covers the whole RAM or to let a scanner skip
+    /// the holes.
+    ///
+    /// A lazily parsed dump only reports the gaps between the ranges walked so far.
+    pub fn gaps(&self) -> Vec<LimeGap> {
+        let mut gaps = Vec::new();
+        // Address following the last mapping seen.
+        let mut next: Option<u64> = None;
+        for mapping in self.mem_map.iter() {
+            let s_addr = mapping.base().to_umem();
+            if let Some(next) = next.filter(|next| *next < s_addr) {
+                gaps.push(LimeGap {
+                    s_addr: next,
+                    e_addr: s_addr - 1,
+                });
+            }
+            next = s_addr.checked_add(mapping.output().1);
+        }
+        gaps
+    }
+
+    /// Search the mapped ranges for `pattern`, whitespace-separated hexadecimal bytes where
+    /// `??` matches any byte (e.g. `"4c 69 ?? 45"`), yielding the address of every match.
+    ///
+    /// Only the ranges backed by the dump are read, in file order and in large chunks, so
+    /// the matches come in file order and the gaps are never touched. Matches straddling
+    /// two reads, or two physically contiguous ranges, are found too. Chunks that fail to
+    /// be read are skipped. A lazily parsed dump is parsed entirely first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the pattern is invalid or if an error occurred while parsing a lazy
+    /// dump
+    ///
+    pub fn scan(&mut self, pattern: &str) -> Result<PatternScan<'_>> {
+        let pattern = parse_pattern(pattern)?;
+        self.discover_all()?;
+        Ok(PatternScan::new(self, vec![pattern]))
+    }
+
+    /// Search the mapped ranges for the `Linux version` banner and other kernel landmarks in
+    /// one pass, returning the candidates with their physical address and text, e.g. to
+    /// identify the kernel a dump was taken from.
+    ///
+    /// Candidates whose text is not printable are left out. A lazily parsed dump is parsed
+    /// entirely first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an error occurred while parsing a lazy dump
+    ///
+    pub fn find_landmarks(&mut self) -> Result<Vec<LimeLandmark>> {
+        self.discover_all()?;
+        landmark::find_landmarks(self)
+    }
+
+    /// Classify the pages of the mapped ranges as zero, constant or populated and measure
+    /// their entropy, e.g. to locate packed or encrypted regions or to estimate how well
+    /// the dump compresses before archiving it.
+    ///
+    /// Pages are `page_size` bytes long and aligned on their size, the parts of a page
+    /// outside the ranges being left out. A lazily parsed dump is parsed entirely first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if `page_size` is zero or if an error occurred while reading the dump
+    ///
+    pub fn page_stats(&mut self, page_size: u64) -> Result<LimePageStats> {
+        self.discover_all()?;
+        analysis::page_stats(self, page_size)
+    }
+
+    /// Deconstruct the connector, getting back a handle of the file it read from and the
+    /// memory map built from its headers, see [`Self::mem_map`].
+    ///
+    /// The handle is not the connector's own but a duplicate made with [`File::try_clone`],
+    /// so that the file can be hashed or parsed again with other options without reopening
+    /// it. Duplicates share the file offset of the OS, which the positional reads of the
+    /// connector never use: where it stands is unspecified, parsing the headers moved it and
+    /// any other duplicate still open, like the handles of the connector's clones, may move
+    /// it again. Seek before reading.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` with [`ErrorKind::NotSupported`] if the dump is not read as a whole
+    /// from a single file: split and composed dumps, dumps with overlays, archive members,
+    /// encrypted dumps and the images decoded from other formats like AFF4 containers and
+    /// hibernation files. Returns `Err` with [`ErrorKind::UnableToReadFile`] if the handle can
+    /// not be duplicated
+    ///
+    pub fn into_inner(self) -> Result<(File, MemoryMap<(Address, umem)>)> {
+        let file = self
+            .mem
+            .into_inner()
+            .into_file()
+            .into_file()
+            .ok_or_else(|| {
+                Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+                    .log_error("The LiME dump is not read from a single file")
+            })?
+            .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+        Ok((file, self.mem_map))
+    }
+
+    /// Number of reads, or parts of reads, satisfied with zeros because they fell in a gap
+    /// between the ranges of the dump.
+    pub fn gap_fill_count(&self) -> u64 {
+        self.stats.gap_fills()
+    }
+
+    /// IO statistics of the connector, shared with its clones.
+    pub fn stats(&self) -> Arc<LimeStats> {
+        self.stats.clone()
+    }
+
+    /// Progress of the capture receiving the dump while it is read, `None` unless the
+    /// connector was opened from a `tcp://` target.
+    pub fn capture_status(&self) -> Option<LimeCaptureStatus> {
+        self.capture.as_deref().map(Capture::status)
+    }
+
+    /// Blocks found not to match their checksum so far, in file order, empty unless the dump
+    /// is opened with `verify_blocks`.
+    ///
+    /// Blocks are checked the first time a read touches them, the findings being shared
+    /// with the clones of the connector.
+    pub fn corrupt_blocks(&self) -> Vec<LimeBlockChecksum> {
+        self.blocks
+            .as_ref()
+            .map(BlockVerifier::corrupt_blocks)
+            .unwrap_or_default()
+    }
+}
+
+/// Physical windows, as `(address, size)`, of the part of `[s_addr, s_addr + len)` mapped by
+/// `mem_map` that lies in blocks failing their checksum, sorted.
+fn corrupt_ranges(
+    blocks: &mut BlockVerifier,
+    mem_map: &MemoryMap<(Address, umem)>,
+    s_addr: u64,
+    len: u64,
+) -> Vec<(u64, u64)> {
+    let e_addr = s_addr.saturating_add(len);
+    let mut ranges = Vec::new();
+    for mapping in mem_map.iter() {
+        let base = mapping.base().to_umem();
+        let (file_offset, size) = *mapping.output();
+        let low = base.max(s_addr);
+        let high = base.saturating_add(size).min(e_addr);
+        if low >= high {
+            continue;
+        }
+        let offset = file_offset.to_umem() + (low - base);
+        for (corrupt_offset, corrupt_len) in blocks.corrupt_windows(offset, high - low) {
+            ranges.push((low + (corrupt_offset - offset), corrupt_len));
+        }
+    }
+    ranges
+}
+
+/// Forward `data` to the optional output callback `out`.
+fn call_out<'a>(out: &RefCell<Option<&mut ReadCallback<'_, 'a>>>, data: ReadData<'a>) -> bool {
+    match out.borrow_mut().as_mut() {
+        Some(out) => out.call(data),
+        None => true,
+    }
+}
+
+impl LimeMemory {
+    /// Walk the headers not parsed yet until `[s_addr, s_addr + len)` is backed by the
+    /// discovered segments or every header has been walked, then remap the file.
+    ///
+    /// With `wait` and `follow`, a range past every segment is waited for until the file
+    /// grows enough to back it, the timeout expires or the capture writing the file ends.
+    fn discover(&mut self, s_addr: u64, len: u64, wait: bool) -> Result<()> {
+        const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+        let Some(lazy) = self.lazy.as_mut() else {
+            return Ok(());
+        };
+        let e_addr = s_addr.saturating_add(len.saturating_sub(1));
+        let follow = lazy.follow.filter(|_| wait);
+        let deadline = follow
+            .and_then(|follow| follow.timeout)
+            .map(|timeout| Instant::now() + timeout);
+
+        let page_size = self.cache.map(|cache| cache.page_size as u64);
+        let mut discovered = 0;
+        // Whether a page cached so far holds bytes of the segments discovered
+        let mut stale_pages = false;
+        while !lazy.covers(s_addr, e_addr) {
+            // Checked before the file is, a finished capture having written all of it.
+            let receiving = lazy
+                .capture
+                .as_ref()
+                .is_none_or(|capture| capture.is_receiving());
+            match lazy.walker.next_segment(&mut lazy.file)? {
+                Some(segment) => {
+                    stale_pages |=
+                        page_size.is_some_and(|page_size| lazy.shares_page(&segment, page_size));
+                    lazy.push(segment);
+                    discovered += 1;
+                }
+                None if lazy.reload && lazy.grow()? => {}
+                None if follow.is_some()
+                    && receiving
+                    && !lazy.walker.is_sealed()
+                    && lazy.is_ahead(e_addr)
+                    && deadline.is_none_or(|deadline| Instant::now() < deadline) =>
+                {
+                    let nap = deadline.map_or(POLL_INTERVAL, |deadline| {
+                        POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now()))
+                    });
+                    std::thread::sleep(nap);
+                }
+                None => break,
+            }
+        }
+
+        if discovered > 0 {
+            log::debug!(
+                "Discovered {} more LiME ranges while reading {:#x}",
+                discovered,
+                s_addr
+            );
+            let mapped = mapped_segments(&lazy.segments, lazy.overlap)?;
+            let mem = map_segments(lazy.file.clone(), lazy.direct.clone(), &mapped)?;
+            (self.max_address, self.real_size) = mapped_extent(&mem);
+            self.mem_map = mem.mem_map().clone();
+            if stale_pages {
+                self.mem = MappedFile::new(mem, self.cache, self.stats.clone())?;
+            } else {
+                self.mem.remap(mem);
+            }
+        }
+        if lazy.walker.is_done() && !lazy.reload {
+            self.lazy = None;
+        }
+        Ok(())
+    }
+
+    /// Fail the reads `faults` picks, passing the other ones to [`Self::read_checked`].
+    fn read_with_faults(&mut self, data: PhysicalReadMemOps) -> Result<()> {
+        let Some(faults) = self.faults.as_mut() else {
+            return self.read_checked(data);
+        };
+
+        let MemOps {
+            inp,
+            out,
+            mut out_fail,
+        } = data;
+        let mut passed = Vec::new();
+        for CTup3(addr, meta_addr, buf) in inp {
+            match faults.inject(addr.to_umem(), buf.len() as u64) {
+                None => passed.push(CTup3(addr, meta_addr, buf)),
+                Some(FaultKind::IoError) => {
+                    log::debug!("Injecting a read error at {:#x}", addr);
+                    opt_call(out_fail.as_deref_mut(), CTup2(meta_addr, buf));
+                }
+                Some(FaultKind::ShortRead) => {
+                    log::debug!("Injecting a short read at {:#x}", addr);
+                    let half = buf.len() / 2;
+                    let (head, tail) = <&mut [u8]>::from(buf).split_at_mut(half);
+                    opt_call(
+                        out_fail.as_deref_mut(),
+                        CTup2(meta_addr + half, tail.into()),
+                    );
+                    if half > 0 {
+                        passed.push(CTup3(addr, meta_addr, head.into()));
+                    }
+                }
+            }
+        }
+        MemOps::with_raw(passed.into_iter(), out, out_fail, |data| {
+            self.read_checked(data)
+        })
+    }
+
+    /// Fail the parts of the reads falling in blocks that do not match their checksum
+    /// (`verify_blocks`), passing the other parts to [`Self::read_all`].
+    fn read_checked(&mut self, data: PhysicalReadMemOps) -> Result<()> {
+        if self.blocks.is_none() {
+            return self.read_all(data);
+        }
+
+        let MemOps {
+            inp,
+            out,
+            mut out_fail,
+        } = data;
+        // The ranges read are walked first so that their blocks can be located.
+        let inp = inp.collect::<Vec<_>>();
+        for CTup3(addr, _, buf) in &inp {
+            self.discover(addr.to_umem(), buf.len() as u64, true)?;
+        }
+        let Some(blocks) = self.blocks.as_mut() else {
+            return Ok(());
+        };
+
+        let mut passed = Vec::new();
+        for CTup3(addr, meta_addr, buf) in inp {
+            let s_addr = addr.to_umem();
+            let corrupt = corrupt_ranges(blocks, &self.mem_map, s_addr, buf.len() as u64);
+            let mut rest = <&mut [u8]>::from(buf);
+            // Offset of `rest` in the request.
+            let mut offset = 0;
+            for (corrupt_addr, corrupt_len) in corrupt {
+                let (clean, tail) = rest.split_at_mut((corrupt_addr - s_addr - offset) as usize);
+                let (bad, tail) = tail.split_at_mut(corrupt_len as usize);
+                if !clean.is_empty() {
+                    passed.push(CTup3(
+                        (s_addr + offset).into(),
+                        meta_addr + offset as usize,
+                        clean.into(),
+                    ));
+                }
+                log::debug!("Failing read of corrupt block data at {:#x}", corrupt_addr);
+                opt_call(
+                    out_fail.as_deref_mut(),
+                    CTup2(meta_addr + (corrupt_addr - s_addr) as usize, bad.into()),
+                );
+                offset = corrupt_addr + corrupt_len - s_addr;
+                rest = tail;
+            }
+            if !rest.is_empty() {
+                passed.push(CTup3(
+                    (s_addr + offset).into(),
+                    meta_addr + offset as usize,
+                    rest.into(),
+                ));
+            }
+        }
+        MemOps::with_raw(passed.into_iter(), out, out_fail, |data| {
+            self.read_all(data)
+        })
+    }
+
+    /// Serve reads from the read-ahead window, walking the headers they need first with
+    /// `parse=lazy`.
+    fn read_all(
+        &mut self,
+        MemOps {
+            inp,
+            mut out,
+            out_fail,
+        }: PhysicalReadMemOps,
+    ) -> Result<()> {
+        let stats = self.stats.clone();
+        let inp = inp.inspect(move |CTup3(_, _, buf)| stats.record_read(buf.len() as u64));
+
+        if let Some(readahead) = self.readahead.as_mut() {
+            let mut misses = Vec::new();
+            for CTup3(addr, meta_addr, mut buf) in inp {
+                readahead.observe(addr.to_umem(), buf.len() as u64);
+                if readahead.serve(addr.to_umem(), &mut buf) {
+                    self.stats.record_readahead_hit();
+                    opt_call(out.as_deref_mut(), CTup2(meta_addr, buf));
+                } else {
+                    misses.push(CTup3(addr, meta_addr, buf));
+                }
+            }
+            return MemOps::with_raw(misses.into_iter(), out, out_fail, |data| {
+                self.read_mapped(data)
+            });
+        }
+        if self.lazy.is_none() {
+            return MemOps::with_raw(inp, out, out_fail, |data| self.read_mapped(data));
+        }
+
+        let inp = inp.collect::<Vec<_>>();
+        for CTup3(addr, _, buf) in &inp {
+            self.discover(addr.to_umem(), buf.len() as u64, true)?;
+        }
+        MemOps::with_raw(inp.into_iter(), out, out_fail, |data| {
+            self.read_mapped(data)
+        })
+    }
+
+    /// Read from the ranges mapped so far, zero-filling the padding and the gaps if requested.
+    fn read_mapped<'buf>(
+        &mut self,
+        MemOps {
+            inp,
+            out,
+            mut out_fail,
+        }: PhysicalReadMemOps<'buf, '_, '_, '_>,
+    ) -> Result<()> {
+        if self.gap_fill == GapFill::None && self.padding.is_empty() {
+            return self.mem.phys_read_raw_iter(MemOps { inp, out, out_fail });
+        }
+
+        // Reads are issued one by one so that the physical address of a failed chunk can be
+        // recovered from its offset within the request.
+        let (mem_map, padding) = (&self.mem_map, &self.padding);
+        let fill_gaps = self.gap_fill == GapFill::Zero;
+        let out = RefCell::new(out);
+        let mut gap_fills = 0;
+        for CTup3(addr, meta_addr, buf) in inp {
+            let pass = &mut |data| call_out(&out, data);
+            let fill = &mut |CTup2(mut fail_meta, data): ReadData<'buf>| {
+                // A failed chunk may run from the padding of a range into a gap, or the
+                // other way around, the pieces being handled apart.
+                let mut rest = Some(data);
+                let mut result = true;
+                while let Some(data) = rest.take() {
+                    let phys_addr = addr.address() + (fail_meta - meta_addr);
+                    let (len, padded) = padding_at(padding, phys_addr.to_umem());
+                    let (Some(mut piece), tail) = data.split_at(len) else {
+                        break;
+                    };
+                    rest = tail;
+                    fail_meta += piece.len();
+                    let piece_meta = fail_meta - piece.len();
+                    result &= if padded || (fill_gaps && in_gap(mem_map, phys_addr)) {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(
+                            phys_addr = phys_addr.to_umem(),
+                            len = piece.len(),
+                            padded,
+                            "zero-filling read in gap"
+                        );
+                        log::debug!(
+                            "Zero-filling read of {} bytes in {} at {:#x}",
+                            piece.len(),
+                            if padded { "padding" } else { "gap" },
+                            phys_addr
+                        );
+                        piece.iter_mut().for_each(|b| *b = 0);
+                        if !padded {
+                            gap_fills += 1;
+                        }
+                        call_out(&out, CTup2(piece_meta, piece))
+                    } else {
+                        opt_call(out_fail.as_deref_mut(), CTup2(piece_meta, piece))
+                    };
+                }
+                result
+            };
+
+            MemOps::with_raw(
+                std::iter::once(CTup3(addr, meta_addr, buf)),
+                Some(&mut pass.into()),
+                Some(&mut fill.into()),
+                |data| self.mem.phys_read_raw_iter(data),
+            )?;
+        }
+
+        self.stats.record_gap_fills(gap_fills);
+        Ok(())
+    }
+}
+
+impl PhysicalMemory for LimeMemory {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", name = "lime_read", skip_all)
+    )]
+    fn phys_read_raw_iter(&mut self, data: PhysicalReadMemOps) -> Result<()> {
+        let Some(throttle) = self.throttle else {
+            return self.read_with_faults(data);
+        };
+
+        let MemOps { inp, out, out_fail } = data;
+        let start = Instant::now();
+        let requested = Cell::new(0);
+        let inp = inp.inspect(|CTup3(_, _, buf)| requested.set(requested.get() + buf.len() as u64));
+        let result = MemOps::with_raw(inp, out, out_fail, |data| self.read_with_faults(data));
+        let pause = throttle
+            .min_duration(requested.get())
+            .saturating_sub(start.elapsed());
+        if !pause.is_zero() {
+            std::thread::sleep(pause);
+        }
+        result
+    }
+
+    fn phys_write_raw_iter(&mut self, data: PhysicalWriteMemOps) -> Result<()> {
+        if !self.writable {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::ReadOnly)
+                .log_error("The LiME file is opened read-only, see the `write` argument"));
+        }
+        // Writes are rare enough that the whole dump is simply parsed first.
+        self.discover_all()?;
+        if let Some(readahead) = self.readahead.as_mut() {
+            readahead.invalidate();
+        }
+        self.mem.phys_write_raw_iter(data)
+    }
+
+    /// The highest address and the size of the memory mapped, so that a dump with large
+    /// holes is neither over- nor under-scanned. A lazily parsed dump only reports the
+    /// ranges walked so far.
+    fn metadata(&self) -> PhysicalMemoryMetadata {
+        PhysicalMemoryMetadata {
+            max_address: self.max_address,
+            real_size: self.real_size,
+            readonly: !self.writable,
+            ..self.mem.metadata()
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Shortcuts opening a dump straight into a memflow OS layer.
+//!
+//! memflow has no Linux OS layer published yet, only the Windows one is wired here.
+
+use crate::memory::LimeMemory;
+
+use memflow::prelude::v1::*;
+use memflow_win32::prelude::v1::Win32Kernel;
+
+use std::path::Path;
+
+/// Windows kernel read from a `LiME` dump, with the default page and translation caches
+pub type LimeWin32Kernel = Win32Kernel<
+    CachedPhysicalMemory<'static, LimeMemory, DefaultCacheValidator>,
+    CachedVirtualTranslate<DirectTranslate, DefaultCacheValidator>,
+>;
+
+/// Open the `LiME` dump of a Windows machine at `path` and initialize the win32 OS layer on
+/// top of it.
+///
+/// The kernel is located by scanning the dump, and the default memflow caches are put in
+/// front of the physical memory and of the address translation. Use
+/// [`open_win32_with_args`] to pass connector arguments.
+///
+/// # Errors
+///
+/// Returns `Err` if the dump can not be opened or parsed, or if no Windows kernel is found
+/// in it
+///
+pub fn open_win32<P: AsRef<Path>>(path: P) -> Result<LimeWin32Kernel> {
+    let path = path.as_ref().to_str().ok_or_else(|| {
+        Error(ErrorOrigin::Connector, ErrorKind::InvalidPath)
+            .log_error("The LiME file path is not valid UTF-8")
+    })?;
+    open_win32_with_args(&ConnectorArgs::new(Some(path), Args::new(), None))
+}
+
+/// Open the dump described by `args` like [`create_connector`](crate::create_connector)
+/// and initialize the win32 OS layer on top of it, see [`open_win32`].
+///
+/// # Errors
+///
+/// Returns `Err` if an argument is unknown or invalid, if the dump can not be opened or
+/// parsed, or if no Windows kernel is found in it
+///
+pub fn open_win32_with_args(args: &ConnectorArgs) -> Result<LimeWin32Kernel> {
+    let connector = crate::create_connector(args)?;
+    Win32Kernel::builder(connector)
+        .build_default_caches()
+        .build()
+}
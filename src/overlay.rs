@@ -0,0 +1,65 @@
+use crate::config::{OverlapPolicy, ParseOptions};
+use crate::dumpfile::DumpFile;
+use crate::segment::{
+    coalesce, open_dump, parse_dump_with_progress, resolve_overlaps, shadow_segments, LimeSegment,
+};
+
+use memflow::prelude::v1::*;
+
+use std::path::{Path, PathBuf};
+
+/// Open the dump at `base` with the `overlays` stacked on top of it, in order.
+///
+/// Overlays are `LiME` files holding the ranges that changed since the layer below, e.g. the
+/// pages written by a what-if analysis or an incremental snapshot. Every layer shadows the
+/// ones below it wherever their ranges overlap. Overlapping ranges within a single layer are
+/// resolved according to `overlap`.
+///
+/// Returns the layers read as one file and the segments mapping them, sorted and free of
+/// overlaps.
+///
+/// # Errors
+///
+/// Returns `Err` if a layer can not be read or parsed, or if the ranges of a layer overlap
+/// and `overlap` is [`OverlapPolicy::Error`]
+///
+pub(crate) fn open_layers(
+    base: &Path,
+    overlays: &[PathBuf],
+    options: ParseOptions,
+    overlap: OverlapPolicy,
+    progress: &mut dyn FnMut(u64, u64),
+) -> Result<(DumpFile, Vec<LimeSegment>)> {
+    let paths = std::iter::once(base.to_path_buf())
+        .chain(overlays.iter().cloned())
+        .collect::<Vec<_>>();
+
+    let mut stacked = Vec::new();
+    let mut start = 0;
+    for path in &paths {
+        let mut layer = open_dump(path)?;
+        let len = layer
+            .metadata()
+            .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?
+            .len();
+        let segments = parse_dump_with_progress(&mut layer, options, progress)?.segments;
+        // The layer is resolved on its own first, so that `overlap` only applies within it.
+        stacked.extend(
+            resolve_overlaps(&segments, overlap)?
+                .into_iter()
+                .map(|segment| LimeSegment {
+                    file_offset: start + segment.file_offset,
+                    ..segment
+                }),
+        );
+        start += len;
+    }
+    log::info!(
+        "Stacked {} overlays on top of {}",
+        overlays.len(),
+        base.display()
+    );
+
+    let mapped = coalesce(shadow_segments(&stacked));
+    Ok((DumpFile::open_parts(&paths)?, mapped))
+}
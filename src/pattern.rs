@@ -0,0 +1,210 @@
+use crate::memory::LimeMemory;
+
+use memflow::prelude::v1::*;
+
+/// Bytes read from the dump at a time while scanning
+const SCAN_CHUNK_SIZE: usize = 1 << 20;
+
+/// Parse a pattern made of whitespace-separated hexadecimal bytes and `??` (or `?`)
+/// wildcards, e.g. `4c 69 ?? 45`.
+///
+/// # Errors
+///
+/// Returns `Err` if the pattern is empty or if a token is neither a byte nor a wildcard
+///
+pub(crate) fn parse_pattern(pattern: &str) -> Result<Vec<Option<u8>>> {
+    let bytes = pattern
+        .split_whitespace()
+        .map(|token| match token {
+            "?" | "??" => Ok(None),
+            _ if token.len() == 2 => u8::from_str_radix(token, 16).map(Some).map_err(|_| {
+                Error(ErrorOrigin::Connector, ErrorKind::InvalidArgument)
+                    .log_error(format!("invalid pattern byte `{}`", token))
+            }),
+            _ => Err(Error(ErrorOrigin::Connector, ErrorKind::InvalidArgument)
+                .log_error(format!("invalid pattern byte `{}`", token))),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if bytes.is_empty() {
+        return Err(Error(ErrorOrigin::Connector, ErrorKind::InvalidArgument)
+            .log_error("the pattern is empty"));
+    }
+    Ok(bytes)
+}
+
+/// Iterator over the addresses where a pattern matches, returned by [`LimeMemory::scan`]
+pub struct PatternScan<'a> {
+    mem: &'a mut LimeMemory,
+    /// Patterns searched at once, none of them empty
+    patterns: Vec<Vec<Option<u8>>>,
+    /// Length of the longest pattern
+    max_len: usize,
+    /// Mapped ranges as `(address, size)`, in file order
+    ranges: Vec<(u64, u64)>,
+    /// Index of the range being read
+    range: usize,
+    /// Bytes of the range read so far
+    read: u64,
+    /// Data read and not searched yet, the tail of the previous read included
+    buf: Vec<u8>,
+    /// Physical address of the first byte of `buf`
+    buf_addr: u64,
+    /// Next offset of `buf` to compare with the patterns
+    cursor: usize,
+    /// Next pattern to compare at `cursor`
+    next_pattern: usize,
+    /// Whether the tail of `buf` is searched before being dropped, the next read not
+    /// following it
+    flushing: bool,
+}
+
+impl<'a> PatternScan<'a> {
+    /// Search the ranges mapped by `mem` for every pattern of `patterns` at once.
+    pub(crate) fn new(mem: &'a mut LimeMemory, patterns: Vec<Vec<Option<u8>>>) -> Self {
+        let mut ranges = mem
+            .mem_map()
+            .iter()
+            .map(|mapping| {
+                (
+                    mapping.output().0.to_umem(),
+                    mapping.base().to_umem(),
+                    mapping.output().1,
+                )
+            })
+            .collect::<Vec<_>>();
+        ranges.sort_unstable();
+        let ranges = ranges
+            .into_iter()
+            .map(|(_, addr, size)| (addr, size))
+            .collect::<Vec<_>>();
+        let buf_addr = ranges.first().map_or(0, |(addr, _)| *addr);
+
+        Self {
+            mem,
+            max_len: patterns.iter().map(Vec::len).max().unwrap_or(1),
+            patterns,
+            ranges,
+            range: 0,
+            read: 0,
+            buf: Vec::new(),
+            buf_addr,
+            cursor: 0,
+            next_pattern: 0,
+            flushing: false,
+        }
+    }
+
+    /// Find the next match, returned with the index of the pattern matching.
+    pub(crate) fn next_match(&mut self) -> Option<(usize, PhysicalAddress)> {
+        loop {
+            // Offsets closer to the end of `buf` than the longest pattern wait for the next
+            // read, unless `buf` is flushed.
+            let limit = if self.flushing {
+                self.buf.len()
+            } else {
+                self.buf.len().saturating_sub(self.max_len - 1)
+            };
+            while self.cursor < limit {
+                while let Some(pattern) = self.patterns.get(self.next_pattern) {
+                    self.next_pattern += 1;
+                    if self.matches(pattern, self.cursor) {
+                        let addr = self.buf_addr + self.cursor as u64;
+                        return Some((self.next_pattern - 1, addr.into()));
+                    }
+                }
+                self.next_pattern = 0;
+                self.cursor += 1;
+            }
+            if !self.refill() {
+                return None;
+            }
+        }
+    }
+
+    fn matches(&self, pattern: &[Option<u8>], at: usize) -> bool {
+        self.buf.get(at..at + pattern.len()).is_some_and(|data| {
+            data.iter()
+                .zip(pattern)
+                .all(|(byte, expected)| expected.is_none_or(|expected| *byte == expected))
+        })
+    }
+
+    /// Read the next chunk of the ranges after the unsearched tail of `buf`, or have the
+    /// tail flushed first if the chunk does not follow it.
+    ///
+    /// Returns `false` once every range has been read and searched.
+    fn refill(&mut self) -> bool {
+        while self
+            .ranges
+            .get(self.range)
+            .is_some_and(|(_, size)| self.read == *size)
+        {
+            self.range += 1;
+            self.read = 0;
+        }
+        let next = self
+            .ranges
+            .get(self.range)
+            .map(|(addr, size)| (addr + self.read, *size));
+
+        // A match can only straddle two reads if they are physically contiguous.
+        let end = self.buf_addr + self.buf.len() as u64;
+        if next.map(|(addr, _)| addr) != Some(end) {
+            if !self.flushing && self.cursor < self.buf.len() {
+                self.flushing = true;
+                return true;
+            }
+            self.buf.clear();
+            self.cursor = 0;
+        }
+        self.flushing = false;
+        let Some((addr, size)) = next else {
+            return false;
+        };
+
+        self.buf.drain(..self.cursor);
+        self.cursor = 0;
+        self.buf_addr = addr - self.buf.len() as u64;
+
+        let len = (size - self.read).min(SCAN_CHUNK_SIZE as u64) as usize;
+        let tail = self.buf.len();
+        self.buf.resize(tail + len, 0);
+        self.read += len as u64;
+        if self
+            .mem
+            .phys_view()
+            .read_raw_into(addr.into(), &mut self.buf[tail..])
+            .is_err()
+        {
+            // Unreadable data can not match, the search resumes after it.
+            self.buf.clear();
+            self.buf_addr = addr + len as u64;
+        }
+        true
+    }
+}
+
+impl Iterator for PatternScan<'_> {
+    type Item = PhysicalAddress;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_match().map(|(_, addr)| addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_are_parsed() {
+        assert_eq!(
+            parse_pattern("4c 69 ?? 45 ?").unwrap(),
+            [Some(0x4c), Some(0x69), None, Some(0x45), None]
+        );
+        assert!(parse_pattern("").is_err());
+        assert!(parse_pattern("4c6945").is_err());
+        assert!(parse_pattern("4g").is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use crate::config::{LimeConfig, ParseMode};
+use crate::memory::LimeMemory;
+use crate::report::LimeReport;
+
+use memflow::prelude::v1::*;
+
+use pyo3::exceptions::PyIOError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+/// `LiME` dump opened from Python by `open_lime`
+///
+/// The connector is locked by each call so that the dump can be shared by Python threads.
+#[pyclass(name = "LimeDump", module = "memflow_lime")]
+struct PyLimeDump {
+    mem: Mutex<LimeMemory>,
+    path: PathBuf,
+    mode: ParseMode,
+}
+
+#[pymethods]
+impl PyLimeDump {
+    /// Read `len` bytes of physical memory at `addr`.
+    fn read_phys<'py>(
+        &self,
+        py: Python<'py>,
+        addr: u64,
+        len: usize,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let mut buff = vec![0u8; len];
+        self.lock()?
+            .phys_view()
+            .read_raw_into(addr.into(), &mut buff)
+            .map_err(|e| to_py_err(e.into()))?;
+        Ok(PyBytes::new(py, &buff))
+    }
+
+    /// Physical ranges held by the dump as `(s_addr, e_addr)` tuples sorted by address,
+    /// `e_addr` being inclusive.
+    fn ranges(&self) -> PyResult<Vec<(u64, u64)>> {
+        Ok(self
+            .lock()?
+            .mem_map()
+            .iter()
+            .map(|mapping| {
+                let s_addr = mapping.base().to_umem();
+                (s_addr, s_addr + mapping.output().1 - 1)
+            })
+            .collect())
+    }
+
+    /// Summary of the layout of the dump, see `memflow_lime::report`.
+    fn report<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let report = crate::report(&self.path, self.mode).map_err(to_py_err)?;
+        report_to_dict(py, &report)
+    }
+}
+
+impl PyLimeDump {
+    fn lock(&self) -> PyResult<MutexGuard<'_, LimeMemory>> {
+        self.mem
+            .lock()
+            .map_err(|_| PyIOError::new_err("a previous call on the LiME dump panicked"))
+    }
+}
+
+/// Open the `LiME` file at `path`, `args` holding the connector arguments as a
+/// `key=value,...` string.
+#[pyfunction]
+#[pyo3(signature = (path, args = None))]
+fn open_lime(path: &str, args: Option<&str>) -> PyResult<PyLimeDump> {
+    let extra_args = args
+        .unwrap_or_default()
+        .parse::<Args>()
+        .map_err(to_py_err)?;
+    let args = ConnectorArgs::new(Some(path), extra_args, None);
+    let config = LimeConfig::from_args(&args).map_err(to_py_err)?;
+    Ok(PyLimeDump {
+        mem: Mutex::new(crate::create_connector(&args).map_err(to_py_err)?),
+        path: config.path,
+        mode: config.mode,
+    })
+}
+
+/// Python module exposing the parser and the physical reads without the memflow plugin
+/// stack.
+#[pymodule]
+fn memflow_lime(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyLimeDump>()?;
+    module.add_function(wrap_pyfunction!(open_lime, module)?)
+}
+
+fn to_py_err(err: Error) -> PyErr {
+    PyIOError::new_err(err.to_string())
+}
+
+fn report_to_dict<'py>(py: Python<'py>, report: &LimeReport) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("path", &report.path)?;
+    dict.set_item("file_size", report.file_size)?;
+    dict.set_item(
+        "segments",
+        report
+            .segments
+            .iter()
+            .map(|segment| (segment.s_addr, segment.e_addr, segment.file_offset))
+            .collect::<Vec<_>>(),
+    )?;
+    dict.set_item(
+        "gaps",
+        report
+            .gaps
+            .iter()
+            .map(|gap| (gap.s_addr, gap.e_addr))
+            .collect::<Vec<_>>(),
+    )?;
+    dict.set_item("total_ram", report.total_ram)?;
+    dict.set_item("digest", &report.digest)?;
+    dict.set_item("digest_algorithm", &report.digest_algorithm)?;
+    dict.set_item(
+        "truncation",
+        report
+            .truncation
+            .map(|truncation| (truncation.s_addr, truncation.e_addr)),
+    )?;
+    dict.set_item("anomalies", &report.anomalies)?;
+    Ok(dict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LimeWriter;
+    use std::fs::{self, File};
+
+    #[test]
+    fn dump_is_read_from_python() {
+        let path = std::env::temp_dir().join("memflow_lime_python.lime");
+        let mut writer = LimeWriter::new(File::create(&path).unwrap());
+        writer.write_range(0x1000, &[0x11; 0x1000]).unwrap();
+        writer.write_range(0x4000, &[0x44; 0x1000]).unwrap();
+        writer.finish().unwrap();
+
+        pyo3::prepare_freethreaded_python();
+        let (data, ranges, total_ram, missing) = Python::with_gil(|py| {
+            let module = PyModule::new(py, "memflow_lime").unwrap();
+            memflow_lime(&module).unwrap();
+            let dump = module
+                .getattr("open_lime")
+                .unwrap()
+                .call1((path.to_str().unwrap(),))
+                .unwrap();
+            let data = dump
+                .call_method1("read_phys", (0x4ff0, 0x10))
+                .unwrap()
+                .extract::<Vec<u8>>()
+                .unwrap();
+            let ranges = dump
+                .call_method0("ranges")
+                .unwrap()
+                .extract::<Vec<(u64, u64)>>()
+                .unwrap();
+            let total_ram = dump
+                .call_method0("report")
+                .unwrap()
+                .get_item("total_ram")
+                .unwrap()
+                .extract::<u64>()
+                .unwrap();
+            let missing = dump.call_method1("read_phys", (0x2000, 0x10)).is_err();
+            (data, ranges, total_ram, missing)
+        });
+
+        fs::remove_file(path).unwrap();
+        assert_eq!(data, [0x44; 0x10]);
+        assert_eq!(ranges, [(0x1000, 0x1fff), (0x4000, 0x4fff)]);
+        assert_eq!(total_ram, 0x2000);
+        assert!(missing);
+    }
+}
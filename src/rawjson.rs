@@ -0,0 +1,340 @@
+use crate::dumpfile::DumpFile;
+use crate::segment::{open_dump, LimeSegment};
+
+use memflow::prelude::v1::*;
+
+use serde_json::{Map, Value};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Keys of the run list, compared ignoring case and underscores
+const RUNS_KEYS: &[&str] = &[
+    "runs",
+    "memoryruns",
+    "ranges",
+    "regions",
+    "e820",
+    "memorymap",
+];
+/// Keys of the path of the raw image, compared ignoring case and underscores
+const IMAGE_KEYS: &[&str] = &["image", "file", "filename"];
+/// Keys of the physical address of the first byte of the raw image
+const IMAGE_BASE_KEYS: &[&str] = &["imagebase", "pmemsaveaddress"];
+/// Keys of the first physical address of a run
+const START_KEYS: &[&str] = &[
+    "start",
+    "base",
+    "baseaddress",
+    "physicaladdress",
+    "address",
+    "addr",
+];
+/// Keys of the size in bytes of a run
+const LENGTH_KEYS: &[&str] = &["length", "size"];
+/// Keys of the last physical address of a run, inclusive
+const END_KEYS: &[&str] = &["end"];
+/// Keys of the offset of a run in the raw image
+const OFFSET_KEYS: &[&str] = &["offset", "fileoffset"];
+/// Keys of the kind of memory of a run
+const TYPE_KEYS: &[&str] = &["type"];
+/// Kinds of memory holding RAM, compared ignoring case, spaces and dashes
+const RAM_TYPES: &[&str] = &["ram", "usable", "systemram"];
+/// e820 type of usable RAM
+const E820_RAM: u64 = 1;
+
+/// Open the raw memory image described by the JSON run list at `path`.
+///
+/// Acquisition tools like DumpIt, Magnet RAM Capture or WinPmem write a flat raw image next
+/// to a JSON document listing the physical ranges it holds. The document is either the
+/// array of the runs or an object holding it under `runs`, along with the path of the raw
+/// image under `image`, relative to the document. Without `image` the raw image is the
+/// document path without its `.json` extension if it exists (`memory.raw.json` describing
+/// `memory.raw`), or the same name with the `.raw` extension.
+///
+/// Every run has a `start` address and a `length` in bytes, or an inclusive `end` address,
+/// as numbers or `0x` prefixed hex strings. Its data is at `offset` in the image, or at the
+/// offset equal to its address if `offset` is missing, as in the images padded with zeros
+/// over the holes of the memory map. Key names are matched ignoring case and underscores, so
+/// `BaseAddress`, `Size` or `file_offset` are recognized as well.
+///
+/// The same document describes the flat image QEMU writes with `pmemsave`: the guest memory
+/// map is given as e820-style `regions` whose `type` tells the RAM (`ram`, `usable`,
+/// `System RAM` or the e820 type `1`) from the reserved and MMIO ranges, which are skipped.
+/// The address `pmemsave` started at is given as `image_base`, the data of a run being at its
+/// address minus `image_base` when `offset` is missing.
+///
+/// Returns the raw image and the segments mapping it, in the order of the run list.
+///
+/// # Errors
+///
+/// Returns `Err` if the run list or the image can not be read, if the run list is malformed
+/// or if a run lies past the end of the image
+///
+pub(crate) fn open_run_list(path: &Path) -> Result<(DumpFile, Vec<LimeSegment>)> {
+    let json = fs::read(path).map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+            .log_error(format!("Unable to read the run list {}", path.display()))
+    })?;
+    let document = serde_json::from_slice::<Value>(&json).map_err(|e| {
+        malformed(format!(
+            "run list {} is not valid JSON: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let (runs, image) = parse_document(&document)?;
+
+    let image_path = match image {
+        Some(image) => path.with_file_name(image),
+        None => default_image_path(path),
+    };
+    let image = open_dump(&image_path).map_err(|err| {
+        err.log_error(format!(
+            "Unable to open the raw image {}",
+            image_path.display()
+        ))
+    })?;
+    let image_len = image
+        .metadata()
+        .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?
+        .len();
+    if let Some((index, segment)) = runs.iter().enumerate().find(|(_, segment)| {
+        segment
+            .file_offset
+            .checked_add(segment.size())
+            .is_none_or(|end| end > image_len)
+    }) {
+        return Err(malformed(format!(
+            "run #{} ({:#x}-{:#x}) lies past the end of the raw image {}",
+            index,
+            segment.s_addr,
+            segment.e_addr,
+            image_path.display()
+        )));
+    }
+    log::info!(
+        "Mapping {} runs of the raw image {}",
+        runs.len(),
+        image_path.display()
+    );
+    Ok((image.into(), runs))
+}
+
+/// Get the runs and the path of the image named by a run list.
+fn parse_document(document: &Value) -> Result<(Vec<LimeSegment>, Option<&str>)> {
+    let (runs, image, image_base) = match document {
+        Value::Array(runs) => (runs, None, 0),
+        Value::Object(object) => (
+            field(object, RUNS_KEYS)
+                .and_then(Value::as_array)
+                .ok_or_else(|| malformed("run list has no `runs` array".to_owned()))?,
+            field(object, IMAGE_KEYS).and_then(Value::as_str),
+            match field(object, IMAGE_BASE_KEYS) {
+                None => 0,
+                Some(value) => as_u64(value).ok_or_else(|| {
+                    malformed("run list: `image_base` is not an address".to_owned())
+                })?,
+            },
+        ),
+        _ => {
+            return Err(malformed(
+                "run list is neither an array nor an object".to_owned(),
+            ))
+        }
+    };
+    let runs = runs
+        .iter()
+        .enumerate()
+        .filter_map(|(index, run)| parse_run(run, index, image_base).transpose())
+        .collect::<Result<Vec<_>>>()?;
+    Ok((runs, image))
+}
+
+/// Parse run #`index` of a run list, returns `None` if it is empty or does not hold RAM.
+fn parse_run(run: &Value, index: usize, image_base: u64) -> Result<Option<LimeSegment>> {
+    let run = run
+        .as_object()
+        .ok_or_else(|| malformed(format!("run #{} is not an object", index)))?;
+    let number = |keys: &[&str]| -> Result<Option<u64>> {
+        field(run, keys)
+            .map(|value| {
+                as_u64(value).ok_or_else(|| {
+                    malformed(format!("run #{}: `{}` is not an address", index, keys[0]))
+                })
+            })
+            .transpose()
+    };
+    if let Some(kind) = field(run, TYPE_KEYS) {
+        if !is_ram(kind) {
+            log::debug!("Skipping run #{} of type {}", index, kind);
+            return Ok(None);
+        }
+    }
+    let (s_addr, e_addr) = match (number(START_KEYS)?, number(LENGTH_KEYS)?, number(END_KEYS)?) {
+        (Some(_), Some(0), _) => return Ok(None),
+        (Some(s_addr), Some(length), _) => {
+            let e_addr = s_addr.checked_add(length - 1).ok_or_else(|| {
+                malformed(format!(
+                    "run #{}: {:#x} bytes at {:#x} do not fit in the address space",
+                    index, length, s_addr
+                ))
+            })?;
+            (s_addr, e_addr)
+        }
+        (Some(s_addr), None, Some(e_addr)) if e_addr >= s_addr => (s_addr, e_addr),
+        (Some(s_addr), None, Some(e_addr)) => {
+            return Err(malformed(format!(
+                "run #{}: end {:#x} is below start {:#x}",
+                index, e_addr, s_addr
+            )))
+        }
+        _ => {
+            return Err(malformed(format!(
+                "run #{} needs a `start` and a `length` or an `end`",
+                index
+            )))
+        }
+    };
+    let file_offset = match number(OFFSET_KEYS)? {
+        Some(offset) => offset,
+        None => s_addr.checked_sub(image_base).ok_or_else(|| {
+            malformed(format!(
+                "run #{} starts at {:#x}, before the image base {:#x}",
+                index, s_addr, image_base
+            ))
+        })?,
+    };
+    Ok(Some(LimeSegment {
+        s_addr,
+        e_addr,
+        file_offset,
+    }))
+}
+
+/// Whether the `type` of a run names RAM.
+fn is_ram(kind: &Value) -> bool {
+    match kind {
+        Value::String(kind) => {
+            let kind = kind.replace([' ', '-', '_'], "").to_lowercase();
+            RAM_TYPES.contains(&kind.as_str())
+        }
+        kind => as_u64(kind) == Some(E820_RAM),
+    }
+}
+
+/// Value of the first key of `object` matching one of `keys`, ignoring case and
+/// underscores.
+fn field<'a>(object: &'a Map<String, Value>, keys: &[&str]) -> Option<&'a Value> {
+    object.iter().find_map(|(key, value)| {
+        let key = key.replace('_', "").to_lowercase();
+        keys.contains(&key.as_str()).then_some(value)
+    })
+}
+
+/// Read a number given as such or as a decimal or `0x` prefixed hex string.
+fn as_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(string) => match string
+            .strip_prefix("0x")
+            .or_else(|| string.strip_prefix("0X"))
+        {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => string.parse().ok(),
+        },
+        _ => None,
+    }
+}
+
+/// Raw image described by the run list at `path` when it does not name one.
+fn default_image_path(path: &Path) -> PathBuf {
+    let stripped = path.with_extension("");
+    match stripped.extension().is_some() && stripped.exists() {
+        true => stripped,
+        false => path.with_extension("raw"),
+    }
+}
+
+fn malformed(msg: String) -> Error {
+    Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_lists_are_parsed() {
+        let document = serde_json::json!({
+            "Image": "host.raw",
+            "Runs": [
+                { "BaseAddress": "0x1000", "Size": 4096 },
+                { "start": 0x100000, "length": "0x2000", "file_offset": 0x2000 },
+                { "start": 0x200000, "length": 0 },
+            ],
+        });
+        let (runs, image) = parse_document(&document).unwrap();
+        assert_eq!(image, Some("host.raw"));
+        assert_eq!(
+            runs,
+            [
+                LimeSegment {
+                    s_addr: 0x1000,
+                    e_addr: 0x1fff,
+                    file_offset: 0x1000,
+                },
+                LimeSegment {
+                    s_addr: 0x100000,
+                    e_addr: 0x101fff,
+                    file_offset: 0x2000,
+                },
+            ]
+        );
+
+        let pmemsave = serde_json::json!({
+            "image_base": "0x1000",
+            "regions": [
+                { "addr": "0x1000", "end": "0x9fbff", "type": "usable" },
+                { "addr": "0x9fc00", "end": "0x9ffff", "type": "reserved" },
+                { "start": "0xf0000", "size": "0x10000", "type": 2 },
+                { "start": "0x100000", "size": "0x100000", "type": "System RAM" },
+                { "start": "0x200000", "size": "0x1000", "type": 1 },
+            ],
+        });
+        assert_eq!(
+            parse_document(&pmemsave).unwrap().0,
+            [
+                LimeSegment {
+                    s_addr: 0x1000,
+                    e_addr: 0x9fbff,
+                    file_offset: 0,
+                },
+                LimeSegment {
+                    s_addr: 0x100000,
+                    e_addr: 0x1fffff,
+                    file_offset: 0xff000,
+                },
+                LimeSegment {
+                    s_addr: 0x200000,
+                    e_addr: 0x200fff,
+                    file_offset: 0x1ff000,
+                },
+            ]
+        );
+
+        let bare = serde_json::json!([{ "start": 0, "length": 16 }]);
+        assert_eq!(parse_document(&bare).unwrap().0.len(), 1);
+        for malformed in [
+            serde_json::json!({ "ranges": [{ "start": 0 }] }),
+            serde_json::json!({ "runs": [{ "start": "zero", "length": 16 }] }),
+            serde_json::json!({ "runs": [{ "start": u64::MAX, "length": 16 }] }),
+            serde_json::json!({ "image": "host.raw" }),
+            serde_json::json!({ "runs": [{ "start": 16, "end": 15 }] }),
+            serde_json::json!({ "image_base": 32, "runs": [{ "start": 16, "length": 16 }] }),
+            serde_json::json!(42),
+        ] {
+            assert!(parse_document(&malformed).is_err());
+        }
+    }
+}
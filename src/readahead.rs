@@ -0,0 +1,251 @@
+use crate::segment::LimeSegment;
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Background reader prefetching the physical memory following sequential reads
+///
+/// Reads are sequential when each one starts where the previous one ended. When that happens,
+/// the `window` bytes following the read are loaded by a dedicated thread, through its own
+/// handle of the dump, and the next reads falling in them are served from memory.
+pub(crate) struct Prefetcher {
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle<()>>,
+    path: PathBuf,
+    mapped: Arc<[LimeSegment]>,
+    window: u64,
+    /// Physical address right after the last read
+    next_expected: Option<u64>,
+    /// Physical address right after the last window requested
+    requested_until: u64,
+}
+
+/// State shared with the prefetching thread
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    wake: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    /// Window the thread has to load next
+    request: Option<(u64, u64)>,
+    /// Last window loaded, with its starting physical address
+    buffer: Option<(u64, Vec<u8>)>,
+    shutdown: bool,
+}
+
+impl Prefetcher {
+    /// Start prefetching windows of `window` bytes from the dump at `path`, whose sorted and
+    /// non-overlapping segments are `mapped`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the dump can not be opened or the thread can not be spawned
+    ///
+    pub(crate) fn new(path: &Path, mapped: Arc<[LimeSegment]>, window: u64) -> io::Result<Self> {
+        let shared = Arc::new(Shared::default());
+        let mut file = File::open(path)?;
+        let worker = {
+            let shared = shared.clone();
+            let mapped = mapped.clone();
+            thread::Builder::new()
+                .name("lime-readahead".into())
+                .spawn(move || prefetch_loop(&shared, &mut file, &mapped))?
+        };
+
+        Ok(Self {
+            shared,
+            worker: Some(worker),
+            path: path.to_path_buf(),
+            mapped,
+            window,
+            next_expected: None,
+            requested_until: 0,
+        })
+    }
+
+    /// Copy the prefetched bytes at physical address `addr` into `buf`.
+    ///
+    /// Returns `false` if they are not all prefetched, `buf` is left untouched in that case.
+    pub(crate) fn serve(&self, addr: u64, buf: &mut [u8]) -> bool {
+        let state = self.shared.state.lock().unwrap_or_else(|e| e.into_inner());
+        let Some((start, data)) = &state.buffer else {
+            return false;
+        };
+        let Some(offset) = addr.checked_sub(*start) else {
+            return false;
+        };
+        match usize::try_from(offset)
+            .ok()
+            .and_then(|offset| data.get(offset..offset.checked_add(buf.len())?))
+        {
+            Some(prefetched) => {
+                buf.copy_from_slice(prefetched);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Record a read of `len` bytes at physical address `addr`, requesting the next window if
+    /// the reads are sequential and the current window is running out.
+    pub(crate) fn observe(&mut self, addr: u64, len: u64) {
+        if self.worker.is_none() {
+            return;
+        }
+        let end = addr.saturating_add(len);
+        let sequential = self.next_expected == Some(addr);
+        self.next_expected = Some(end);
+
+        if sequential && end.saturating_add(self.window / 2) > self.requested_until {
+            self.requested_until = end.saturating_add(self.window);
+            let mut state = self.shared.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.request = Some((end, self.window));
+            self.shared.wake.notify_one();
+        }
+    }
+
+    /// Drop the prefetched bytes, e.g. after the dump has been written to.
+    pub(crate) fn invalidate(&mut self) {
+        let mut state = self.shared.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.buffer = None;
+        state.request = None;
+        self.requested_until = 0;
+    }
+}
+
+impl Clone for Prefetcher {
+    fn clone(&self) -> Self {
+        Self::new(&self.path, self.mapped.clone(), self.window).unwrap_or_else(|e| {
+            log::warn!("Read-ahead disabled on the cloned connector: {}", e);
+            Self {
+                shared: Arc::default(),
+                worker: None,
+                path: self.path.clone(),
+                mapped: self.mapped.clone(),
+                window: self.window,
+                next_expected: None,
+                requested_until: 0,
+            }
+        })
+    }
+}
+
+impl Drop for Prefetcher {
+    fn drop(&mut self) {
+        self.shared
+            .state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .shutdown = true;
+        self.shared.wake.notify_one();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Body of the prefetching thread, loads the requested windows until shut down.
+fn prefetch_loop(shared: &Shared, file: &mut File, mapped: &[LimeSegment]) {
+    loop {
+        let (addr, len) = {
+            let mut state = shared.state.lock().unwrap_or_else(|e| e.into_inner());
+            loop {
+                if state.shutdown {
+                    return;
+                }
+                if let Some(request) = state.request.take() {
+                    break request;
+                }
+                state = shared.wake.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+        };
+
+        match read_window(file, mapped, addr, len) {
+            Ok(data) if !data.is_empty() => {
+                log::trace!("Prefetched {} bytes at {:#x}", data.len(), addr);
+                shared
+                    .state
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .buffer = Some((addr, data));
+            }
+            Ok(_) => {}
+            Err(e) => log::debug!("Read-ahead at {:#x} failed: {}", addr, e),
+        }
+    }
+}
+
+/// Read up to `len` bytes of physical memory starting at `addr`, stopping at the first byte
+/// not backed by `mapped`.
+fn read_window<R: Read + Seek>(
+    file: &mut R,
+    mapped: &[LimeSegment],
+    addr: u64,
+    len: u64,
+) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut next = addr;
+    let mut left = len;
+
+    while left > 0 {
+        let Some(segment) = mapped
+            .iter()
+            .find(|segment| segment.s_addr <= next && next <= segment.e_addr)
+        else {
+            break;
+        };
+        let chunk = left.min(segment.e_addr - next + 1);
+        file.seek(SeekFrom::Start(
+            segment.file_offset + (next - segment.s_addr),
+        ))?;
+        file.by_ref().take(chunk).read_to_end(&mut data)?;
+
+        left -= chunk;
+        match next.checked_add(chunk) {
+            Some(following) => next = following,
+            None => break,
+        }
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn window_stops_at_gaps() {
+        let file = (0..0x40u8).collect::<Vec<_>>();
+        let mapped = [
+            LimeSegment {
+                s_addr: 0x1000,
+                e_addr: 0x100f,
+                file_offset: 0x0,
+            },
+            LimeSegment {
+                s_addr: 0x1010,
+                e_addr: 0x101f,
+                file_offset: 0x20,
+            },
+        ];
+        let mut file = Cursor::new(file);
+
+        let window = read_window(&mut file, &mapped, 0x100c, 0x8).unwrap();
+        assert_eq!(window, vec![0xc, 0xd, 0xe, 0xf, 0x20, 0x21, 0x22, 0x23]);
+
+        let window = read_window(&mut file, &mapped, 0x101c, 0x100).unwrap();
+        assert_eq!(window, vec![0x2c, 0x2d, 0x2e, 0x2f]);
+
+        assert!(read_window(&mut file, &mapped, 0x2000, 0x10)
+            .unwrap()
+            .is_empty());
+    }
+}
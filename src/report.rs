@@ -0,0 +1,121 @@
+use crate::blockdev::file_size;
+use crate::config::{ParseMode, ParseOptions};
+use crate::segment::{open_dump, parse_dump, LimeSegment, LimeTruncation};
+
+use memflow::prelude::v1::*;
+
+use std::path::{Path, PathBuf};
+
+/// Physical range between two ranges of a `LiME` file that the dump does not hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LimeGap {
+    /// First missing physical address
+    pub s_addr: u64,
+    /// Last missing physical address, inclusive
+    pub e_addr: u64,
+}
+
+impl LimeGap {
+    /// Size in bytes of the gap
+    pub const fn size(&self) -> u64 {
+        self.e_addr - self.s_addr + 1
+    }
+}
+
+/// Machine-readable summary of a `LiME` file, returned by [`report`]
+///
+/// With the `serde` feature the report implements `serde::Serialize`, so that it can be
+/// emitted as JSON, YAML or any other format with a serde serializer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct LimeReport {
+    /// Path of the `LiME` file
+    pub path: PathBuf,
+    /// Size in bytes of the file
+    pub file_size: u64,
+    /// Memory segments, in file order
+    pub segments: Vec<LimeSegment>,
+    /// Ranges missing between the lowest and the highest address of the dump, sorted
+    pub gaps: Vec<LimeGap>,
+    /// Bytes of physical memory held by the dump, overlapping ranges counting once
+    pub total_ram: u64,
+    /// Hex encoded digest of the digest trailer, if any
+    pub digest: Option<String>,
+    /// Digest algorithm most likely used, see
+    /// [`LimeDigest::algorithm_hint`](crate::LimeDigest::algorithm_hint)
+    pub digest_algorithm: Option<String>,
+    /// Range cut off from the last segment if the file is truncated
+    pub truncation: Option<LimeTruncation>,
+    /// Problems found while parsing, like unsupported header fields in lenient mode,
+    /// a truncated file or overlapping ranges
+    pub anomalies: Vec<String>,
+}
+
+/// Parse the `LiME` file at `path` and summarize its layout.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading or parsing the file
+///
+pub fn report<P: AsRef<Path>>(path: P, mode: ParseMode) -> Result<LimeReport> {
+    let path = path.as_ref();
+    let mut lime_dump = open_dump(path)?;
+    let file_size = file_size(&lime_dump)
+        .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+    let options = ParseOptions::from(mode).for_file(&lime_dump);
+    let layout = parse_dump(&mut lime_dump, options)?;
+
+    let mut sorted = layout.segments.clone();
+    sorted.sort_by_key(|segment| (segment.s_addr, segment.e_addr));
+    let mut anomalies = layout.anomalies;
+    let mut gaps = Vec::new();
+    let mut total_ram = 0;
+    // Last address covered so far and the segment reaching it.
+    let mut covered: Option<(u64, LimeSegment)> = None;
+    for segment in sorted {
+        match covered {
+            Some((end, other)) if segment.s_addr <= end => {
+                anomalies.push(format!(
+                    "LiME ranges {:#x}-{:#x} and {:#x}-{:#x} overlap",
+                    other.s_addr, other.e_addr, segment.s_addr, segment.e_addr
+                ));
+                if segment.e_addr > end {
+                    total_ram += segment.e_addr - end;
+                    covered = Some((segment.e_addr, segment));
+                }
+                continue;
+            }
+            Some((end, _)) if segment.s_addr > end + 1 => gaps.push(LimeGap {
+                s_addr: end + 1,
+                e_addr: segment.s_addr - 1,
+            }),
+            _ => {}
+        }
+        total_ram += segment.size();
+        covered = Some((segment.e_addr, segment));
+    }
+
+    Ok(LimeReport {
+        path: path.to_path_buf(),
+        file_size,
+        segments: layout.segments,
+        gaps,
+        total_ram,
+        digest: layout.digest.as_ref().map(|digest| {
+            digest
+                .value
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect()
+        }),
+        digest_algorithm: layout
+            .digest
+            .as_ref()
+            .and_then(|digest| digest.algorithm_hint())
+            .map(str::to_owned),
+        truncation: layout.truncation,
+        anomalies,
+    })
+}
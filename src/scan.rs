@@ -0,0 +1,215 @@
+use crate::blockdev::file_size;
+use crate::config::{ByteOrder, ParseOptions};
+use crate::header::LimeHeader;
+use crate::segment::{
+    parse_dump_with_progress, AddrMasker, LimeLayout, LimeReserved, LimeSegment, LimitGuard,
+};
+
+use memflow::prelude::v1::*;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::thread;
+
+/// Bytes each scanning thread reads at a time
+const SCAN_BLOCK_SIZE: u64 = 1 << 20;
+
+/// Raw header found while scanning, keyed by its file offset
+type Candidates = HashMap<u64, [u8; LimeHeader::HEADER_SIZE_IN_BYTES]>;
+
+/// Parse `lime_dump`, located at `path`, locating the headers with one thread per partition
+/// of the file.
+///
+/// Every partition is searched for the magic number concurrently, then the header chain is
+/// walked from the beginning of the file over the candidates found. If the chain is not made
+/// only of candidates ending exactly at the end of the file (truncated dumps, digest trailers,
+/// corrupted headers...) the dump is parsed with the serial scan of
+/// [`parse_dump_with_progress`] instead. `progress` is called with the offset reached and the
+/// size of the file once the scan is over, or by the serial scan.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading or parsing the file
+///
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "info", name = "lime_scan", skip_all, err(Display))
+)]
+pub(crate) fn parse_dump_parallel(
+    lime_dump: &mut File,
+    path: &Path,
+    options: ParseOptions,
+    progress: &mut dyn FnMut(u64, u64),
+) -> Result<LimeLayout> {
+    let file_len = file_size(lime_dump)
+        .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+
+    let layout = match find_candidates(path, file_len) {
+        Ok(candidates) => walk_chain(&candidates, file_len, options.byte_order),
+        Err(e) => {
+            log::debug!("Parallel LiME header scan failed: {}", e);
+            None
+        }
+    };
+
+    match layout {
+        Some(chain) => {
+            let mut layout = LimeLayout::default();
+            let mut masker = options.addr_mask.map(AddrMasker::new);
+            let mut guard = LimitGuard::new(options.limits);
+            for (index, (offset, header)) in chain.into_iter().enumerate() {
+                layout
+                    .anomalies
+                    .extend(header.check(options, index, offset)?);
+                if header.reserved != [0; 8] {
+                    layout.reserved.push(LimeReserved {
+                        segment: index,
+                        reserved: header.reserved,
+                    });
+                }
+                let segment = LimeSegment {
+                    s_addr: header.s_addr,
+                    e_addr: header.e_addr,
+                    file_offset: offset + LimeHeader::HEADER_SIZE_IN_BYTES as u64,
+                };
+                let segment = match masker.as_mut() {
+                    Some(masker) => masker.apply(segment, index)?,
+                    None => segment,
+                };
+                guard.admit(&segment, index)?;
+                layout.segments.push(segment);
+            }
+            progress(file_len, file_len);
+            Ok(layout)
+        }
+        None => {
+            log::debug!("Parallel LiME header scan was inconclusive, scanning serially");
+            parse_dump_with_progress(lime_dump, options, progress)
+        }
+    }
+}
+
+/// Search the whole file for header magic numbers, one thread per partition.
+fn find_candidates(path: &Path, file_len: u64) -> io::Result<Candidates> {
+    let threads = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(file_len.div_ceil(SCAN_BLOCK_SIZE).max(1) as usize) as u64;
+    let partition_len = file_len.div_ceil(threads).max(1);
+
+    thread::scope(|scope| {
+        let workers = (0..threads)
+            .map(|i| {
+                let start = (i * partition_len).min(file_len);
+                let end = (start + partition_len).min(file_len);
+                scope.spawn(move || scan_partition(path, start, end, file_len))
+            })
+            .collect::<Vec<_>>();
+
+        let mut candidates = Candidates::new();
+        for worker in workers {
+            let found = worker
+                .join()
+                .map_err(|_| io::Error::other("scanning thread panicked"))??;
+            candidates.extend(found);
+        }
+        Ok(candidates)
+    })
+}
+
+/// Collect the headers starting in `[start, end)` whose magic number matches in either byte
+/// order.
+fn scan_partition(
+    path: &Path,
+    start: u64,
+    end: u64,
+    file_len: u64,
+) -> io::Result<Vec<(u64, [u8; LimeHeader::HEADER_SIZE_IN_BYTES])>> {
+    const HEADER_SIZE: usize = LimeHeader::HEADER_SIZE_IN_BYTES;
+    let magics = [
+        LimeHeader::MAGIC.to_le_bytes(),
+        LimeHeader::MAGIC.to_be_bytes(),
+    ];
+
+    let mut file = File::open(path)?;
+    let mut buff = vec![0u8; SCAN_BLOCK_SIZE as usize + HEADER_SIZE - 1];
+    let mut found = Vec::new();
+    let mut pos = start;
+
+    while pos < end {
+        // Read a header worth of bytes past the block so that headers straddling two blocks
+        // are seen whole.
+        let len = (SCAN_BLOCK_SIZE + HEADER_SIZE as u64 - 1).min(file_len - pos) as usize;
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut buff[..len])?;
+
+        let starts = SCAN_BLOCK_SIZE.min(end - pos) as usize;
+        for i in 0..starts.min(len.saturating_sub(HEADER_SIZE - 1)) {
+            if magics.iter().any(|magic| buff[i..i + 4] == *magic) {
+                let mut raw = [0u8; HEADER_SIZE];
+                raw.copy_from_slice(&buff[i..i + HEADER_SIZE]);
+                found.push((pos + i as u64, raw));
+            }
+        }
+        pos += SCAN_BLOCK_SIZE;
+    }
+
+    Ok(found)
+}
+
+/// Follow the header chain from the beginning of the file over `candidates`.
+///
+/// Returns the headers of the chain with their offset, or `None` if the chain leaves the
+/// candidates or does not end exactly at the end of the file.
+fn walk_chain(
+    candidates: &Candidates,
+    file_len: u64,
+    mut byte_order: ByteOrder,
+) -> Option<Vec<(u64, LimeHeader)>> {
+    let mut chain = Vec::new();
+    let mut offset = 0;
+
+    while offset < file_len {
+        let header = LimeHeader::from_bytes(candidates.get(&offset)?, &mut byte_order).ok()?;
+        let next = offset
+            .checked_add(LimeHeader::HEADER_SIZE_IN_BYTES as u64)?
+            .checked_add(header.mem_section_size()?)?;
+        if next > file_len {
+            return None;
+        }
+        chain.push((offset, header));
+        offset = next;
+    }
+
+    Some(chain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_header(s_addr: u64, e_addr: u64) -> [u8; LimeHeader::HEADER_SIZE_IN_BYTES] {
+        LimeHeader::new(s_addr, e_addr).to_bytes()
+    }
+
+    #[test]
+    fn chain_is_walked_over_candidates() {
+        let mut candidates = Candidates::new();
+        candidates.insert(0, raw_header(0x1000, 0x1fff));
+        candidates.insert(0x1020, raw_header(0x3000, 0x30ff));
+        // Magic number found in the data of the first range
+        candidates.insert(0x800, raw_header(0x0, 0xffff_ffff));
+
+        let chain = walk_chain(&candidates, 0x1140, ByteOrder::Auto).unwrap();
+        assert_eq!(
+            chain.iter().map(|(offset, _)| *offset).collect::<Vec<_>>(),
+            vec![0, 0x1020]
+        );
+
+        // A trailer or truncated range leaves the chain to the serial scan
+        assert!(walk_chain(&candidates, 0x1180, ByteOrder::Auto).is_none());
+        assert!(walk_chain(&candidates, 0x1100, ByteOrder::Auto).is_none());
+    }
+}
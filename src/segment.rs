@@ -0,0 +1,1341 @@
+use crate::config::{
+    AlignPolicy, ByteOrder, OverlapPolicy, ParseLimits, ParseMode, ParseOptions, RebaseRule,
+};
+use crate::error::LimeError;
+use crate::header::{LimeHeader, LimeVersion};
+use crate::trailer::LimeDigest;
+
+use memflow::prelude::v1::*;
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Bound;
+use std::path::Path;
+
+/// Bytes searched at once for the next header when recovering from a corrupt one
+const RESYNC_BLOCK_SIZE: usize = 1 << 20;
+
+/// Physical memory range stored in a `LiME` file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LimeSegment {
+    /// Starting address of physical RAM range
+    pub s_addr: u64,
+    /// Ending address of physical RAM range, inclusive
+    pub e_addr: u64,
+    /// Offset in the `LiME` file of the first byte of the range
+    pub file_offset: u64,
+}
+
+impl LimeSegment {
+    /// Size in bytes of the memory represented by this segment
+    pub const fn size(&self) -> u64 {
+        self.e_addr - self.s_addr + 1
+    }
+
+    /// Restrict the segment to the window `[s_addr, e_addr]`, adjusting its file offset
+    /// accordingly.
+    ///
+    /// Returns `None` if the segment lies entirely outside the window or the window is empty.
+    pub(crate) fn clip(&self, s_addr: u64, e_addr: u64) -> Option<LimeSegment> {
+        if s_addr > e_addr || self.e_addr < s_addr || self.s_addr > e_addr {
+            return None;
+        }
+
+        let clipped_s_addr = self.s_addr.max(s_addr);
+        Some(LimeSegment {
+            s_addr: clipped_s_addr,
+            e_addr: self.e_addr.min(e_addr),
+            file_offset: self.file_offset + (clipped_s_addr - self.s_addr),
+        })
+    }
+}
+
+/// Physical range declared by a header but missing from a truncated `LiME` file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LimeTruncation {
+    /// First missing physical address
+    pub s_addr: u64,
+    /// Last missing physical address, inclusive
+    pub e_addr: u64,
+}
+
+/// Non-zero reserved bytes of a header, tolerated in lenient mode
+///
+/// Patched or vendor `LiME` modules may store their own metadata there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LimeReserved {
+    /// Position in [`LimeLayout::segments`] of the segment described by the header
+    pub segment: usize,
+    /// Raw reserved bytes of the header
+    pub reserved: [u8; 8],
+}
+
+/// Span of a `LiME` file skipped after a corrupt header (`recover`), or holding no segment
+/// (`carve`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LimeSkipped {
+    /// File offset of the corrupt header, or of the first byte skipped
+    pub offset: u64,
+    /// Number of bytes skipped, up to the next plausible header or to the end of the file
+    pub len: u64,
+}
+
+/// Layout of a parsed `LiME` file
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct LimeLayout {
+    /// Memory segments, in file order
+    pub segments: Vec<LimeSegment>,
+    /// Digest trailer found after the last segment, if any
+    pub digest: Option<LimeDigest>,
+    /// Range cut off from the last segment if the file is truncated
+    pub truncation: Option<LimeTruncation>,
+    /// Problems tolerated while parsing, like unsupported header fields in lenient mode or
+    /// a truncated file, in file order. Not kept by the index cache (`index`).
+    pub anomalies: Vec<String>,
+    /// Reserved bytes of the headers that are not zero, in file order. Not kept by the
+    /// index cache (`index`).
+    pub reserved: Vec<LimeReserved>,
+    /// Spans skipped to recover from corrupt headers (`recover`) or holding no segment
+    /// (`carve`), in file order. Not kept by the index cache (`index`).
+    pub skipped: Vec<LimeSkipped>,
+}
+
+/// Parse the `LiME` file at `path`.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading or parsing the file
+///
+pub fn read_layout<P: AsRef<Path>>(path: P, mode: ParseMode) -> Result<LimeLayout> {
+    let mut lime_dump = open_dump(path)?;
+    let options = ParseOptions::from(mode).for_file(&lime_dump);
+    parse_dump(&mut lime_dump, options)
+}
+
+/// Parse the `LiME` file at `path` like [`read_layout`], the error telling which header is
+/// corrupt and why instead of only being logged.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading or parsing the file
+///
+pub fn read_layout_detailed<P: AsRef<Path>>(
+    path: P,
+    mode: ParseMode,
+) -> std::result::Result<LimeLayout, LimeError> {
+    let mut lime_dump = open_dump(path)?;
+    let options = ParseOptions::from(mode).for_file(&lime_dump);
+    walk_dump(&mut lime_dump, options, &mut |_, _| ())
+}
+
+/// Parse the `LiME` file at `path` like [`read_layout`], skipping the corrupt headers up to
+/// the next plausible one instead of failing, like the `recover` connector argument. The
+/// spans skipped are listed in [`LimeLayout::skipped`].
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading the file or parsing a header found
+///
+pub fn recover_layout<P: AsRef<Path>>(path: P, mode: ParseMode) -> Result<LimeLayout> {
+    let mut lime_dump = open_dump(path)?;
+    let options = ParseOptions {
+        recover: true,
+        ..ParseOptions::from(mode).for_file(&lime_dump)
+    };
+    parse_dump(&mut lime_dump, options)
+}
+
+/// Search the arbitrary file at `path` for embedded `LiME` headers and return the segments
+/// found, like the `carve` connector argument. A header is kept if its fields are sane and
+/// its range ends within the file, the search resuming after its data.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading the file
+///
+pub fn carve_layout<P: AsRef<Path>>(path: P, mode: ParseMode) -> Result<LimeLayout> {
+    let mut lime_dump = open_dump(path)?;
+    let options = ParseOptions {
+        carve: true,
+        ..mode.into()
+    };
+    parse_dump(&mut lime_dump, options)
+}
+
+/// List the memory segments stored in the `LiME` file at `path`.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading or parsing the file
+///
+pub fn read_segments<P: AsRef<Path>>(path: P) -> Result<Vec<LimeSegment>> {
+    Ok(read_layout(path, ParseMode::Strict)?.segments)
+}
+
+/// Get the digest `LiME` appended after the memory ranges of the file at `path`, if any.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading or parsing the file
+///
+pub fn read_digest_trailer<P: AsRef<Path>>(path: P) -> Result<Option<LimeDigest>> {
+    Ok(read_layout(path, ParseMode::Strict)?.digest)
+}
+
+/// Open the `LiME` file at `path` for reading.
+pub(crate) fn open_dump<P: AsRef<Path>>(path: P) -> Result<File> {
+    File::open(path).map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))
+}
+
+/// Open the `LiME` file at `path` for reading and writing, for `write=inplace`.
+pub(crate) fn open_dump_writable<P: AsRef<Path>>(path: P) -> Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+                .log_error("Unable to open the LiME file for writing")
+        })
+}
+
+/// Walk all the headers of `lime_dump` and collect the segments they describe.
+///
+/// A digest trailer following the last segment ends the walk. If the file is truncated,
+/// the last segment is cut to the data actually present and the missing range is recorded.
+/// The seek of the file is restored to the beginning of the file before returning.
+pub(crate) fn parse_dump(lime_dump: &mut File, options: ParseOptions) -> Result<LimeLayout> {
+    parse_dump_with_progress(lime_dump, options, &mut |_, _| ())
+}
+
+/// Walk all the headers of `lime_dump` like [`parse_dump`], calling `progress` with the offset
+/// reached and the size of the file after every header.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "info", name = "lime_parse", skip_all, err(Display))
+)]
+pub(crate) fn parse_dump_with_progress<R: Read + Seek>(
+    lime_dump: &mut R,
+    options: ParseOptions,
+    progress: &mut dyn FnMut(u64, u64),
+) -> Result<LimeLayout> {
+    Ok(walk_dump(lime_dump, options, progress)?)
+}
+
+/// Walk all the headers of `lime_dump` like [`parse_dump_with_progress`], describing why a
+/// header is corrupt.
+fn walk_dump<R: Read + Seek>(
+    lime_dump: &mut R,
+    options: ParseOptions,
+    progress: &mut dyn FnMut(u64, u64),
+) -> std::result::Result<LimeLayout, LimeError> {
+    let file_len = lime_dump
+        .seek(SeekFrom::End(0))
+        .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToSeekFile))?;
+    let mut walker = HeaderWalker::new(file_len, options);
+    let mut segments = Vec::new();
+    while let Some(segment) = walker.try_next_segment(lime_dump)? {
+        segments.push(segment);
+        progress(walker.offset, file_len);
+    }
+    progress(file_len, file_len);
+
+    lime_dump.seek(SeekFrom::Start(0)).map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToSeekFile)
+            .log_error("Unable to seek back to the beginning of the file")
+    })?;
+
+    Ok(LimeLayout {
+        segments,
+        digest: walker.digest,
+        truncation: walker.truncation,
+        anomalies: walker.anomalies,
+        reserved: walker.reserved,
+        skipped: walker.skipped,
+    })
+}
+
+/// Incremental walk over the headers of a `LiME` file, one segment at a time
+#[derive(Debug, Clone)]
+pub(crate) struct HeaderWalker {
+    options: ParseOptions,
+    byte_order: ByteOrder,
+    file_len: u64,
+    /// Offset of the next header
+    offset: u64,
+    /// Index of the next header
+    index: usize,
+    done: bool,
+    /// Whether the last segment returned was cut short by the end of the file
+    partial: bool,
+    digest: Option<LimeDigest>,
+    truncation: Option<LimeTruncation>,
+    /// Problems tolerated so far
+    anomalies: Vec<String>,
+    /// Non-zero reserved bytes found so far
+    reserved: Vec<LimeReserved>,
+    /// Spans skipped after corrupt headers so far
+    skipped: Vec<LimeSkipped>,
+    masker: Option<AddrMasker>,
+    guard: LimitGuard,
+}
+
+impl HeaderWalker {
+    /// Start a walk from the beginning of a file of `file_len` bytes.
+    pub(crate) fn new(file_len: u64, options: ParseOptions) -> Self {
+        Self {
+            options,
+            byte_order: options.byte_order,
+            file_len,
+            offset: 0,
+            index: 0,
+            done: false,
+            partial: false,
+            digest: None,
+            truncation: None,
+            anomalies: Vec::new(),
+            reserved: Vec::new(),
+            skipped: Vec::new(),
+            masker: options.addr_mask.map(AddrMasker::new),
+            guard: LimitGuard::new(options.limits),
+        }
+    }
+
+    /// Whether every header of the file has been walked.
+    pub(crate) const fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Whether the walk ended with a digest trailer, nothing being appended to the dump
+    /// anymore.
+    pub(crate) fn is_sealed(&self) -> bool {
+        self.digest.is_some()
+    }
+
+    /// Continue a finished walk from the last header reached, the file having grown to
+    /// `file_len` bytes.
+    ///
+    /// Returns `None` if the walk can not continue because the file did not grow or ended
+    /// with a digest trailer. Otherwise returns whether the last segment returned was cut
+    /// short by the previous end of the file, the walk then returns it again in full.
+    pub(crate) fn resume(&mut self, file_len: u64) -> Option<bool> {
+        if !self.done || self.digest.is_some() || file_len <= self.file_len {
+            return None;
+        }
+        self.file_len = file_len;
+        self.done = false;
+        self.truncation = None;
+        let partial = std::mem::take(&mut self.partial);
+        if partial {
+            self.guard.retract_last();
+            let index = self.index;
+            self.reserved.retain(|reserved| reserved.segment != index);
+        }
+        Some(partial)
+    }
+
+    /// Parse the next header of `lime_dump` and return the segment it describes.
+    ///
+    /// Returns `Ok(None)` once the walk is over. The seek of the file is moved to the next
+    /// header before reading it, so the file may be shared with other readers between calls.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an error occurred while reading the file or if the header is corrupt
+    ///
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            name = "lime_header",
+            skip_all,
+            fields(index = self.index, offset = self.offset),
+            err(Display)
+        )
+    )]
+    pub(crate) fn next_segment<R: Read + Seek>(
+        &mut self,
+        lime_dump: &mut R,
+    ) -> Result<Option<LimeSegment>> {
+        Ok(self.try_next_segment(lime_dump)?)
+    }
+
+    /// Parse the next header of `lime_dump` like [`next_segment`](Self::next_segment),
+    /// describing why the header is corrupt.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an error occurred while reading the file or if the header is corrupt,
+    /// the error is not logged
+    ///
+    pub(crate) fn try_next_segment<R: Read + Seek>(
+        &mut self,
+        lime_dump: &mut R,
+    ) -> std::result::Result<Option<LimeSegment>, LimeError> {
+        if self.done {
+            return Ok(None);
+        }
+        if self.options.carve && !self.carve(lime_dump)? {
+            return Ok(None);
+        }
+        let (offset, index, file_len) = (self.offset, self.index, self.file_len);
+
+        lime_dump
+            .seek(SeekFrom::Start(offset))
+            .map_err(|source| LimeError::Io {
+                header: index,
+                offset,
+                source,
+            })?;
+
+        if offset < file_len && file_len - offset <= LimeDigest::MAX_TRAILER_SIZE {
+            self.digest = read_digest_trailer_at(lime_dump, offset)?;
+            if self.digest.is_some() {
+                self.done = true;
+                return Ok(None);
+            }
+        }
+
+        let header = match self.read_header(lime_dump, offset, index) {
+            Err(LimeError::BadMagic { .. }) if self.options.device && index > 0 => {
+                log::info!("End of the LiME data on the device at offset {:#x}", offset);
+                self.done = true;
+                return Ok(None);
+            }
+            Err(err) if self.options.recover && err.is_corrupt_header() => {
+                return self.resync(lime_dump, err);
+            }
+            header => header?,
+        };
+        let header = match header {
+            Some(header) => header,
+            None => {
+                if offset < file_len {
+                    let msg = format!(
+                        "LiME file is truncated, ignoring the {} bytes of the last incomplete header",
+                        file_len - offset
+                    );
+                    log::warn!("{}", msg);
+                    self.anomalies.push(msg);
+                }
+                self.done = true;
+                return Ok(None);
+            }
+        };
+        if header.reserved != [0; 8] {
+            self.reserved.push(LimeReserved {
+                segment: index,
+                reserved: header.reserved,
+            });
+        }
+        let data_offset = offset + LimeHeader::HEADER_SIZE_IN_BYTES as u64;
+
+        let mut segment = LimeSegment {
+            s_addr: header.s_addr,
+            e_addr: header.e_addr,
+            file_offset: data_offset,
+        };
+        let section_end = header
+            .mem_section_size()
+            .and_then(|size| data_offset.checked_add(size))
+            .ok_or_else(|| {
+                Error(ErrorOrigin::Connector, ErrorKind::InvalidMemorySize).log_error(format!(
+                    "LiME header #{}: range {:#x}-{:#x} does not fit in any file",
+                    index, header.s_addr, header.e_addr
+                ))
+            })?;
+
+        if section_end > file_len {
+            self.done = true;
+            let present = file_len.saturating_sub(data_offset);
+            let truncation = LimeTruncation {
+                s_addr: segment.s_addr + present,
+                e_addr: segment.e_addr,
+            };
+            let msg = format!(
+                "LiME file is truncated, range {:#x}-{:#x} of header #{} is missing",
+                truncation.s_addr, truncation.e_addr, index
+            );
+            log::warn!("{}", msg);
+            self.anomalies.push(msg);
+            self.truncation = Some(match &self.masker {
+                Some(masker) => LimeTruncation {
+                    s_addr: truncation.s_addr & masker.mask,
+                    e_addr: truncation.e_addr & masker.mask,
+                },
+                None => truncation,
+            });
+            if present == 0 {
+                return Ok(None);
+            }
+            self.partial = true;
+            segment.e_addr = truncation.s_addr - 1;
+            let segment = self.mask(segment, index)?;
+            self.guard.admit(&segment, index)?;
+            return Ok(Some(segment));
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            s_addr = segment.s_addr,
+            e_addr = segment.e_addr,
+            file_offset = segment.file_offset,
+            "LiME header parsed"
+        );
+        let segment = self.mask(segment, index)?;
+        self.guard.admit(&segment, index)?;
+        self.offset = section_end;
+        self.index += 1;
+        Ok(Some(segment))
+    }
+
+    /// Read and check header #`index` at `offset`, `None` at the end of the file.
+    fn read_header<R: Read>(
+        &mut self,
+        lime_dump: &mut R,
+        offset: u64,
+        index: usize,
+    ) -> std::result::Result<Option<LimeHeader>, LimeError> {
+        let header = LimeHeader::next_header_from_file(lime_dump, &mut self.byte_order)
+            .map_err(|err| LimeError::from_header(err, index, offset, self.byte_order))?;
+        if let Some(header) = &header {
+            self.anomalies
+                .extend(header.check(self.options, index, offset)?);
+        }
+        Ok(header)
+    }
+
+    /// Skip the corrupt header at the current offset, failing with `err`, up to the next
+    /// plausible header and return the segment it describes.
+    fn resync<R: Read + Seek>(
+        &mut self,
+        lime_dump: &mut R,
+        err: LimeError,
+    ) -> std::result::Result<Option<LimeSegment>, LimeError> {
+        let offset = self.offset;
+        let next = self.find_next_header(lime_dump, offset + 1)?;
+        let len = next.unwrap_or(self.file_len) - offset;
+        let msg = match next {
+            Some(next) => format!(
+                "{}, skipping {} bytes up to the next header at offset {:#x}",
+                err, len, next
+            ),
+            None => format!("{}, skipping the last {} bytes of the file", err, len),
+        };
+        log::warn!("{}", msg);
+        self.anomalies.push(msg);
+        self.skipped.push(LimeSkipped { offset, len });
+
+        match next {
+            Some(next) => {
+                self.offset = next;
+                self.try_next_segment(lime_dump)
+            }
+            None => {
+                self.done = true;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Move to the next plausible header of `lime_dump`, recording the bytes skipped to reach
+    /// it. Returns `false`, ending the walk, if there is none.
+    fn carve<R: Read + Seek>(&mut self, lime_dump: &mut R) -> std::result::Result<bool, LimeError> {
+        let next = self.find_next_header(lime_dump, self.offset)?;
+        let end = next.unwrap_or(self.file_len);
+        if end > self.offset {
+            log::debug!(
+                "Carving skipped {} bytes at offset {:#x}",
+                end - self.offset,
+                self.offset
+            );
+            self.skipped.push(LimeSkipped {
+                offset: self.offset,
+                len: end - self.offset,
+            });
+        }
+        match next {
+            Some(next) => {
+                self.offset = next;
+                Ok(true)
+            }
+            None => {
+                self.done = true;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Search `lime_dump` from `start` for the next plausible header: a magic number in the
+    /// byte order of the dump followed by a supported version, zero reserved fields and a
+    /// range ending within the file.
+    fn find_next_header<R: Read + Seek>(
+        &self,
+        lime_dump: &mut R,
+        start: u64,
+    ) -> std::result::Result<Option<u64>, LimeError> {
+        const HEADER_SIZE: usize = LimeHeader::HEADER_SIZE_IN_BYTES;
+        let magics = match self.byte_order {
+            ByteOrder::Auto => vec![
+                LimeHeader::MAGIC.to_le_bytes(),
+                LimeHeader::MAGIC.to_be_bytes(),
+            ],
+            ByteOrder::Little => vec![LimeHeader::MAGIC.to_le_bytes()],
+            ByteOrder::Big => vec![LimeHeader::MAGIC.to_be_bytes()],
+        };
+
+        let mut buff = vec![0u8; RESYNC_BLOCK_SIZE + HEADER_SIZE - 1];
+        let mut pos = start;
+        while pos + HEADER_SIZE as u64 <= self.file_len {
+            let len = (buff.len() as u64).min(self.file_len - pos) as usize;
+            lime_dump
+                .seek(SeekFrom::Start(pos))
+                .and_then(|_| lime_dump.read_exact(&mut buff[..len]))
+                .map_err(|source| LimeError::Io {
+                    header: self.index,
+                    offset: pos,
+                    source,
+                })?;
+
+            let starts = len - HEADER_SIZE + 1;
+            for i in 0..starts {
+                if magics.iter().any(|magic| buff[i..i + 4] == *magic) {
+                    let raw = buff[i..i + HEADER_SIZE].try_into().unwrap();
+                    if self.is_plausible(raw, pos + i as u64) {
+                        return Ok(Some(pos + i as u64));
+                    }
+                }
+            }
+            pos += starts as u64;
+        }
+        Ok(None)
+    }
+
+    /// Whether `raw`, found at `offset`, looks like a sane header.
+    fn is_plausible(&self, raw: &[u8; LimeHeader::HEADER_SIZE_IN_BYTES], offset: u64) -> bool {
+        let mut byte_order = self.byte_order;
+        let Ok(header) = LimeHeader::from_bytes(raw, &mut byte_order) else {
+            return false;
+        };
+        let version = LimeVersion::from_raw(header.version).is_some_and(LimeVersion::is_supported)
+            || self.options.allow_unknown_version;
+        version
+            && header.reserved == [0; 8]
+            && header
+                .mem_section_size()
+                .and_then(|size| offset.checked_add(size))
+                .and_then(|end| end.checked_add(LimeHeader::HEADER_SIZE_IN_BYTES as u64))
+                .is_some_and(|end| end <= self.file_len)
+    }
+
+    /// Apply `addr_mask` to `segment`, described by header #`index`.
+    fn mask(&mut self, segment: LimeSegment, index: usize) -> Result<LimeSegment> {
+        match self.masker.as_mut() {
+            Some(masker) => masker.apply(segment, index),
+            None => Ok(segment),
+        }
+    }
+}
+
+/// Running check of the ranges of a dump against its [`ParseLimits`]
+#[derive(Debug, Clone)]
+pub(crate) struct LimitGuard {
+    limits: ParseLimits,
+    segments: usize,
+    total_size: u64,
+    /// Size of the last range admitted
+    last_size: u64,
+}
+
+impl LimitGuard {
+    /// Check segments against `limits`, none being counted yet.
+    pub(crate) const fn new(limits: ParseLimits) -> Self {
+        Self {
+            limits,
+            segments: 0,
+            total_size: 0,
+            last_size: 0,
+        }
+    }
+
+    /// Count `segment`, described by header #`index`, towards the limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the segment exceeds a limit
+    ///
+    pub(crate) fn admit(&mut self, segment: &LimeSegment, index: usize) -> Result<()> {
+        let size = segment.size();
+        let exceeded = |msg: String| {
+            Err(Error(ErrorOrigin::Connector, ErrorKind::OutOfBounds)
+                .log_error(format!("LiME header #{}: {}", index, msg)))
+        };
+        if let Some(max) = self.limits.max_segments.filter(|max| self.segments >= *max) {
+            return exceeded(format!(
+                "the dump holds more than max_segments={} ranges",
+                max
+            ));
+        }
+        if let Some(max) = self.limits.max_segment_size.filter(|max| size > *max) {
+            return exceeded(format!(
+                "range {:#x}-{:#x} is larger than max_segment_size={:#x}",
+                segment.s_addr, segment.e_addr, max
+            ));
+        }
+        let total_size = self.total_size.saturating_add(size);
+        if let Some(max) = self.limits.max_total_size.filter(|max| total_size > *max) {
+            return exceeded(format!(
+                "the ranges hold more than max_total_size={:#x} bytes",
+                max
+            ));
+        }
+        self.segments += 1;
+        self.total_size = total_size;
+        self.last_size = size;
+        Ok(())
+    }
+
+    /// Forget the last segment admitted, about to be admitted again in full.
+    pub(crate) fn retract_last(&mut self) {
+        self.segments -= 1;
+        self.total_size -= self.last_size;
+        self.last_size = 0;
+    }
+}
+
+/// Check `segments` against `limits`, in order.
+///
+/// # Errors
+///
+/// Returns `Err` if a segment exceeds a limit
+///
+pub(crate) fn check_limits(segments: &[LimeSegment], limits: ParseLimits) -> Result<()> {
+    let mut guard = LimitGuard::new(limits);
+    segments
+        .iter()
+        .enumerate()
+        .try_for_each(|(index, segment)| guard.admit(segment, index))
+}
+
+/// Masking of the physical addresses of the headers (`addr_mask`)
+#[derive(Debug, Clone)]
+pub(crate) struct AddrMasker {
+    mask: u64,
+    /// Segments masked so far, with their start address before masking
+    masked: Vec<(LimeSegment, u64)>,
+}
+
+impl AddrMasker {
+    /// Mask addresses with `mask`, made of the low bits of an address.
+    pub(crate) const fn new(mask: u64) -> Self {
+        Self {
+            mask,
+            masked: Vec::new(),
+        }
+    }
+
+    /// Mask the addresses of `segment`, described by header #`index`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the masked range wraps around the mask, or if it overlaps a range
+    /// masked before while the ranges did not overlap before masking
+    ///
+    pub(crate) fn apply(&mut self, segment: LimeSegment, index: usize) -> Result<LimeSegment> {
+        let s_addr = segment.s_addr & self.mask;
+        let masked = LimeSegment {
+            s_addr,
+            e_addr: s_addr
+                .checked_add(segment.size() - 1)
+                .filter(|e_addr| *e_addr <= self.mask)
+                .ok_or_else(|| {
+                    Error(ErrorOrigin::Connector, ErrorKind::InvalidMemorySize).log_error(format!(
+                        "LiME header #{}: range {:#x}-{:#x} wraps around addr_mask {:#x}",
+                        index, segment.s_addr, segment.e_addr, self.mask
+                    ))
+                })?,
+            file_offset: segment.file_offset,
+        };
+
+        let original_e_addr = |other: &LimeSegment, original: u64| original + other.size() - 1;
+        let collision = self.masked.iter().find(|(other, original)| {
+            other.s_addr <= masked.e_addr
+                && other.e_addr >= masked.s_addr
+                && (*original > segment.e_addr
+                    || original_e_addr(other, *original) < segment.s_addr)
+        });
+        if let Some((other, original)) = collision {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::InvalidMemorySize).log_error(format!(
+                    "LiME header #{}: addr_mask {:#x} makes range {:#x}-{:#x} collide with \
+                    range {:#x}-{:#x}",
+                    index,
+                    self.mask,
+                    segment.s_addr,
+                    segment.e_addr,
+                    original,
+                    original_e_addr(other, *original)
+                )),
+            );
+        }
+
+        if masked.s_addr != segment.s_addr {
+            log::debug!(
+                "LiME header #{}: range {:#x}-{:#x} masked to {:#x}-{:#x}",
+                index,
+                segment.s_addr,
+                segment.e_addr,
+                masked.s_addr,
+                masked.e_addr
+            );
+        }
+        self.masked.push((masked, segment.s_addr));
+        Ok(masked)
+    }
+}
+
+/// Try to read a digest trailer spanning from `offset` to the end of `lime_dump`.
+///
+/// The seek of the file is left at `offset` if no trailer is found.
+fn read_digest_trailer_at<R: Read + Seek>(
+    lime_dump: &mut R,
+    offset: u64,
+) -> Result<Option<LimeDigest>> {
+    let mut trailer = Vec::new();
+    lime_dump
+        .read_to_end(&mut trailer)
+        .and_then(|_| lime_dump.seek(SeekFrom::Start(offset)))
+        .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+
+    Ok(LimeDigest::from_trailer(&trailer, offset))
+}
+
+/// Make `segments` non-overlapping according to `policy`.
+///
+/// The returned segments are sorted by physical address. With [`OverlapPolicy::First`] and
+/// [`OverlapPolicy::Last`] the losing segments are split around the winning ones.
+///
+/// # Errors
+///
+/// Returns `Err` if two segments overlap and `policy` is [`OverlapPolicy::Error`]
+///
+pub(crate) fn resolve_overlaps(
+    segments: &[LimeSegment],
+    policy: OverlapPolicy,
+) -> Result<Vec<LimeSegment>> {
+    resolve(segments, policy, true)
+}
+
+/// Make `segments` non-overlapping, the segments coming later shadowing the earlier ones
+/// without warning, like the layers of a stack of dumps.
+///
+/// The returned segments are sorted by physical address.
+pub(crate) fn shadow_segments(segments: &[LimeSegment]) -> Vec<LimeSegment> {
+    resolve(segments, OverlapPolicy::Last, false).unwrap_or_default()
+}
+
+fn resolve(
+    segments: &[LimeSegment],
+    policy: OverlapPolicy,
+    warn: bool,
+) -> Result<Vec<LimeSegment>> {
+    let mut accepted: BTreeMap<u64, LimeSegment> = BTreeMap::new();
+
+    let ordered: Box<dyn Iterator<Item = &LimeSegment>> = match policy {
+        OverlapPolicy::Last => Box::new(segments.iter().rev()),
+        OverlapPolicy::Error | OverlapPolicy::First => Box::new(segments.iter()),
+    };
+
+    for segment in ordered {
+        let overlapping = accepted
+            .range(..=segment.s_addr)
+            .next_back()
+            .map(|(_, a)| a)
+            .into_iter()
+            .chain(
+                accepted
+                    .range((
+                        Bound::Excluded(segment.s_addr),
+                        Bound::Included(segment.e_addr),
+                    ))
+                    .map(|(_, a)| a),
+            )
+            .filter(|a| a.e_addr >= segment.s_addr && a.s_addr <= segment.e_addr)
+            .copied()
+            .collect::<Vec<_>>();
+
+        if let Some(other) = overlapping.first() {
+            let msg = format!(
+                "LiME ranges {:#x}-{:#x} and {:#x}-{:#x} overlap",
+                segment.s_addr, segment.e_addr, other.s_addr, other.e_addr
+            );
+            if policy == OverlapPolicy::Error {
+                return Err(
+                    Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(msg)
+                );
+            }
+            if warn {
+                let kept = if policy == OverlapPolicy::First {
+                    "first"
+                } else {
+                    "last"
+                };
+                log::warn!("{}, keeping the data of the {} one in the file", msg, kept);
+            }
+        }
+
+        let mut cursor = Some(segment.s_addr);
+        for other in overlapping {
+            let Some(start) = cursor else { break };
+            if other.s_addr > start {
+                accepted.extend(
+                    segment
+                        .clip(start, other.s_addr - 1)
+                        .map(|piece| (piece.s_addr, piece)),
+                );
+            }
+            cursor = other.e_addr.checked_add(1).map(|c| c.max(start));
+        }
+        if let Some(piece) = cursor.and_then(|start| segment.clip(start, segment.e_addr)) {
+            accepted.insert(piece.s_addr, piece);
+        }
+    }
+
+    Ok(accepted.into_values().collect())
+}
+
+/// Merge the segments of `sorted` that are contiguous both in physical address and in file
+/// offset into a single segment.
+///
+/// `sorted` must be sorted by physical address and free of overlaps, as returned by
+/// [`resolve_overlaps`]. Back-to-back ranges of a `LiME` file are separated by the header of
+/// the second one, so only pieces of the same range split around other ranges, or ranges
+/// written without intermediate headers, can be merged.
+pub(crate) fn coalesce(sorted: Vec<LimeSegment>) -> Vec<LimeSegment> {
+    let count = sorted.len();
+    let mut merged: Vec<LimeSegment> = Vec::with_capacity(count);
+
+    for segment in sorted {
+        match merged.last_mut() {
+            Some(last)
+                if last.e_addr.checked_add(1) == Some(segment.s_addr)
+                    && last.file_offset + last.size() == segment.file_offset =>
+            {
+                last.e_addr = segment.e_addr;
+            }
+            _ => merged.push(segment),
+        }
+    }
+
+    log::debug!(
+        "Coalesced {} LiME ranges into {} mappings",
+        count,
+        merged.len()
+    );
+    merged
+}
+
+/// Shift the addresses of `sorted` according to `rules`, sorted by address without
+/// overlapping, a segment crossing the edge of a window being split there.
+///
+/// `sorted` must be sorted by physical address and free of overlaps, the result is too.
+///
+/// # Errors
+///
+/// Returns `Err` if a rule moves a segment outside of the address space or makes segments
+/// overlap
+///
+pub(crate) fn rebase_segments(
+    sorted: &[LimeSegment],
+    rules: &[RebaseRule],
+) -> Result<Vec<LimeSegment>> {
+    let mut rebased = Vec::with_capacity(sorted.len());
+    for segment in sorted {
+        let mut next = Some(segment.s_addr);
+        while let Some(s_addr) = next.filter(|s_addr| *s_addr <= segment.e_addr) {
+            // The piece extends up to the next edge of a window, or to the end of the segment.
+            let rule = rules
+                .iter()
+                .find(|rule| rule.s_addr <= s_addr && s_addr <= rule.e_addr);
+            let e_addr = match rule {
+                Some(rule) => rule.e_addr,
+                None => rules
+                    .iter()
+                    .find(|rule| rule.s_addr > s_addr)
+                    .map_or(u64::MAX, |rule| rule.s_addr - 1),
+            }
+            .min(segment.e_addr);
+            let piece = segment.clip(s_addr, e_addr).unwrap();
+            let offset = rule.map_or(0, |rule| rule.offset);
+            match (
+                piece.s_addr.checked_add_signed(offset),
+                piece.e_addr.checked_add_signed(offset),
+            ) {
+                (Some(s_addr), Some(e_addr)) => rebased.push(LimeSegment {
+                    s_addr,
+                    e_addr,
+                    ..piece
+                }),
+                _ => {
+                    return Err(
+                        Error(ErrorOrigin::Connector, ErrorKind::OutOfBounds).log_error(format!(
+                            "rebase moves range {:#x}-{:#x} outside of the address space",
+                            piece.s_addr, piece.e_addr
+                        )),
+                    )
+                }
+            }
+            next = e_addr.checked_add(1);
+        }
+    }
+
+    rebased.sort_unstable_by_key(|segment| segment.s_addr);
+    if let Some(pair) = rebased
+        .windows(2)
+        .find(|pair| pair[1].s_addr <= pair[0].e_addr)
+    {
+        return Err(
+            Error(ErrorOrigin::Connector, ErrorKind::InvalidMemorySize).log_error(format!(
+                "rebase makes ranges {:#x}-{:#x} and {:#x}-{:#x} collide",
+                pair[0].s_addr, pair[0].e_addr, pair[1].s_addr, pair[1].e_addr
+            )),
+        );
+    }
+    Ok(rebased)
+}
+
+/// Restrict `sorted` to the `windows`, inclusive and sorted by address without overlapping,
+/// dropping the segments lying outside all of them.
+///
+/// `sorted` must be sorted by physical address and free of overlaps, the result is too.
+pub(crate) fn restrict_segments(
+    sorted: &[LimeSegment],
+    windows: &[(u64, u64)],
+) -> Vec<LimeSegment> {
+    let restricted = sorted
+        .iter()
+        .flat_map(|segment| {
+            windows
+                .iter()
+                .filter_map(|(s_addr, e_addr)| segment.clip(*s_addr, *e_addr))
+        })
+        .collect::<Vec<_>>();
+    log::debug!(
+        "Mapping {} of {} ranges within {} windows",
+        restricted.len(),
+        sorted.len(),
+        windows.len()
+    );
+    restricted
+}
+
+/// Segments mapped and the inclusive windows padding them to whole pages, read as zeros
+pub(crate) type AlignedSegments = (Vec<LimeSegment>, Vec<(u64, u64)>);
+
+/// Handle the blocks of `sorted` that do not start or end on a page boundary according to
+/// `policy`, a block being a run of segments contiguous in physical address.
+///
+/// `sorted` must be sorted by physical address and free of overlaps, as returned by
+/// [`coalesce`]. Returns the segments mapped and the inclusive windows padding the blocks up
+/// to the page boundary or to the neighbouring block with [`AlignPolicy::Extend`]. The
+/// padding is not mapped, it reads as zeros rather than as the file data around the block,
+/// like the headers.
+///
+/// # Errors
+///
+/// Returns `Err` if a block is misaligned and `policy` is [`AlignPolicy::Error`]
+///
+pub(crate) fn align_segments(
+    sorted: Vec<LimeSegment>,
+    policy: AlignPolicy,
+) -> Result<AlignedSegments> {
+    const MASK: u64 = AlignPolicy::PAGE_SIZE - 1;
+
+    let mut blocks: Vec<Vec<LimeSegment>> = Vec::new();
+    for segment in sorted {
+        match blocks.last_mut() {
+            Some(block) if block.last().unwrap().e_addr.checked_add(1) == Some(segment.s_addr) => {
+                block.push(segment);
+            }
+            _ => blocks.push(vec![segment]),
+        }
+    }
+
+    let mut aligned = Vec::new();
+    let mut padding = Vec::new();
+    for index in 0..blocks.len() {
+        let s_addr = blocks[index][0].s_addr;
+        let e_addr = blocks[index].last().unwrap().e_addr;
+        if s_addr & MASK == 0 && e_addr & MASK == MASK {
+            aligned.append(&mut blocks[index]);
+            continue;
+        }
+        let msg = format!(
+            "LiME range {:#x}-{:#x} is not aligned to {:#x} byte pages",
+            s_addr,
+            e_addr,
+            AlignPolicy::PAGE_SIZE
+        );
+        match policy {
+            AlignPolicy::Keep => {
+                log::warn!("{}", msg);
+                aligned.append(&mut blocks[index]);
+            }
+            AlignPolicy::Error => {
+                return Err(
+                    Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(msg)
+                );
+            }
+            AlignPolicy::Trim => {
+                let start = if s_addr & MASK == 0 {
+                    Some(s_addr)
+                } else {
+                    (s_addr | MASK).checked_add(1)
+                };
+                let end = if e_addr & MASK == MASK {
+                    Some(e_addr)
+                } else {
+                    (e_addr & !MASK).checked_sub(1)
+                };
+                log::info!("{}, trimming it", msg);
+                // A range shorter than a page holds no whole page, its window being inverted.
+                if let (Some(start), Some(end)) = (start, end) {
+                    if start > end {
+                        continue;
+                    }
+                    aligned.extend(
+                        blocks[index]
+                            .iter()
+                            .filter_map(|segment| segment.clip(start, end)),
+                    );
+                }
+            }
+            AlignPolicy::Extend => {
+                // The neighbouring blocks are never padded over, `aligned` and `padding`
+                // already holding the previous one and its padding.
+                let prev_end = aligned
+                    .last()
+                    .map(|prev: &LimeSegment| prev.e_addr)
+                    .max(padding.last().map(|&(_, end)| end));
+                let start = prev_end.map_or(s_addr & !MASK, |prev_end| {
+                    (s_addr & !MASK).max(prev_end + 1)
+                });
+                let end = blocks.get(index + 1).map_or(e_addr | MASK, |next| {
+                    (e_addr | MASK).min(next[0].s_addr - 1)
+                });
+                if start < s_addr {
+                    padding.push((start, s_addr - 1));
+                }
+                if end > e_addr {
+                    padding.push((e_addr + 1, end));
+                }
+                log::info!("{}, padding it with zeros to {:#x}-{:#x}", msg, start, end);
+                aligned.append(&mut blocks[index]);
+            }
+        }
+    }
+    Ok((aligned, padding))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(s_addr: u64, e_addr: u64, file_offset: u64) -> LimeSegment {
+        LimeSegment {
+            s_addr,
+            e_addr,
+            file_offset,
+        }
+    }
+
+    #[test]
+    fn device_data_ends_at_the_first_non_header() {
+        let mut dump = LimeHeader::new(0x1000, 0x1fff).to_bytes().to_vec();
+        dump.extend_from_slice(&[0x11; 0x1000]);
+        // Stale data of the partition following the acquisition
+        dump.extend_from_slice(&[0x5a; 0x3000]);
+        let device = ParseOptions {
+            device: true,
+            ..ParseOptions::default()
+        };
+
+        let layout =
+            parse_dump_with_progress(&mut std::io::Cursor::new(&dump), device, &mut |_, _| ())
+                .unwrap();
+        assert_eq!(layout.segments, vec![segment(0x1000, 0x1fff, 0x20)]);
+        assert!(parse_dump_with_progress(
+            &mut std::io::Cursor::new(&dump),
+            ParseOptions::default(),
+            &mut |_, _| ()
+        )
+        .is_err());
+
+        // A device not starting with a header holds no LiME data
+        assert!(parse_dump_with_progress(
+            &mut std::io::Cursor::new(&dump[0x1020..]),
+            device,
+            &mut |_, _| ()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn segments_are_rebased() {
+        let rule = |s_addr, e_addr, offset| RebaseRule {
+            s_addr,
+            e_addr,
+            offset,
+        };
+        let sorted = [
+            segment(0x1000, 0x2fff, 0x20),
+            segment(0x8000_1000, 0x8000_1fff, 0x2040),
+        ];
+        assert_eq!(
+            rebase_segments(&sorted, &[rule(0, u64::MAX, 0x1000)]).unwrap(),
+            [
+                segment(0x2000, 0x3fff, 0x20),
+                segment(0x8000_2000, 0x8000_2fff, 0x2040)
+            ]
+        );
+        assert_eq!(
+            rebase_segments(
+                &sorted,
+                &[
+                    rule(0x2000, 0x2fff, 0x10_0000),
+                    rule(0x8000_0000, 0x8fff_ffff, -0x7000_0000)
+                ]
+            )
+            .unwrap(),
+            [
+                segment(0x1000, 0x1fff, 0x20),
+                segment(0x10_2000, 0x10_2fff, 0x1020),
+                segment(0x1000_1000, 0x1000_1fff, 0x2040)
+            ]
+        );
+        assert!(rebase_segments(&sorted, &[rule(0x8000_0000, 0x8fff_ffff, -0x8000_0000)]).is_err());
+        assert!(rebase_segments(&sorted, &[rule(0, u64::MAX, -0x2000)]).is_err());
+    }
+
+    #[test]
+    fn segments_are_restricted_to_windows() {
+        let sorted = [
+            segment(0x0, 0xfff, 0x20),
+            segment(0x2000, 0x5fff, 0x1040),
+            segment(0x10000, 0x10fff, 0x5060),
+        ];
+        assert_eq!(
+            restrict_segments(&sorted, &[(0x1000, 0x2fff), (0x4000, 0x4fff)]),
+            [
+                segment(0x2000, 0x2fff, 0x1040),
+                segment(0x4000, 0x4fff, 0x3040)
+            ]
+        );
+        assert_eq!(restrict_segments(&sorted, &[(0x20000, u64::MAX)]), []);
+    }
+
+    #[test]
+    fn clip_restricts_to_window() {
+        let segment = segment(0x1000, 0x4fff, 0x20);
+
+        assert_eq!(
+            segment.clip(0x2000, 0x2fff),
+            Some(LimeSegment {
+                s_addr: 0x2000,
+                e_addr: 0x2fff,
+                file_offset: 0x1020,
+            })
+        );
+        assert_eq!(segment.clip(0x0, 0x1fff).unwrap().s_addr, 0x1000);
+        assert_eq!(segment.clip(0x5000, 0x6fff), None);
+    }
+
+    #[test]
+    fn overlaps_are_resolved_by_policy() {
+        let segments = [
+            segment(0x1000, 0x3fff, 0x20),
+            segment(0x2000, 0x2fff, 0x3040),
+            segment(0x2000, 0x4fff, 0x4060),
+        ];
+
+        assert!(resolve_overlaps(&segments, OverlapPolicy::Error).is_err());
+        assert_eq!(
+            resolve_overlaps(&segments, OverlapPolicy::First).unwrap(),
+            vec![
+                segment(0x1000, 0x3fff, 0x20),
+                segment(0x4000, 0x4fff, 0x6060)
+            ]
+        );
+        assert_eq!(
+            resolve_overlaps(&segments, OverlapPolicy::Last).unwrap(),
+            vec![
+                segment(0x1000, 0x1fff, 0x20),
+                segment(0x2000, 0x4fff, 0x4060)
+            ]
+        );
+    }
+
+    #[test]
+    fn contiguous_segments_are_coalesced() {
+        let sorted = vec![
+            segment(0x1000, 0x1fff, 0x20),
+            segment(0x2000, 0x2fff, 0x1020),
+            segment(0x3000, 0x3fff, 0x2040),
+            segment(0x5000, 0x5fff, 0x3040),
+        ];
+        assert_eq!(
+            coalesce(sorted),
+            vec![
+                segment(0x1000, 0x2fff, 0x20),
+                segment(0x3000, 0x3fff, 0x2040),
+                segment(0x5000, 0x5fff, 0x3040),
+            ]
+        );
+    }
+
+    #[test]
+    fn disjoint_segments_are_kept() {
+        let segments = [
+            segment(0x2000, 0x2fff, 0x20),
+            segment(0x1000, 0x1fff, 0x1040),
+        ];
+        assert_eq!(
+            resolve_overlaps(&segments, OverlapPolicy::Error).unwrap(),
+            vec![segments[1], segments[0]]
+        );
+    }
+
+    #[test]
+    fn misaligned_blocks_follow_policy() {
+        // Two contiguous ranges forming an aligned block, then a block misaligned on both
+        // ends.
+        let sorted = vec![
+            segment(0x1000, 0x17ff, 0x20),
+            segment(0x1800, 0x1fff, 0x840),
+            segment(0x3800, 0x57ff, 0x1060),
+        ];
+
+        assert_eq!(
+            align_segments(sorted.clone(), AlignPolicy::Keep).unwrap(),
+            (sorted.clone(), vec![])
+        );
+        assert!(align_segments(sorted.clone(), AlignPolicy::Error).is_err());
+        assert_eq!(
+            align_segments(sorted.clone(), AlignPolicy::Trim).unwrap(),
+            (
+                vec![sorted[0], sorted[1], segment(0x4000, 0x4fff, 0x1860)],
+                vec![]
+            )
+        );
+        assert_eq!(
+            align_segments(sorted.clone(), AlignPolicy::Extend).unwrap(),
+            (sorted, vec![(0x3000, 0x37ff), (0x5800, 0x5fff)])
+        );
+    }
+}
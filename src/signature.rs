@@ -0,0 +1,90 @@
+use crate::blockdev::file_size;
+
+use memflow::prelude::v1::*;
+
+use minisign_verify::{PublicKey, Signature};
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Verify the detached minisign signature at `signature_path` of the file at `lime_path`.
+///
+/// `pubkey` is either a base64 encoded public key (`RW...`) or the path of a minisign
+/// public key file. Only the default, prehashed, signatures are supported since the dump is
+/// streamed through the verifier.
+///
+/// # Errors
+///
+/// Returns `Err` if one of the files can not be read, if the key or the signature is
+/// malformed or if the signature does not match
+///
+pub fn verify_signature<P: AsRef<Path>, Q: AsRef<Path>>(
+    lime_path: P,
+    signature_path: Q,
+    pubkey: &str,
+) -> Result<()> {
+    verify_signature_with_progress(lime_path, signature_path, pubkey, |_, _| ())
+}
+
+/// Verify the detached minisign signature of `lime_path`, reporting the hashing progress as
+/// [`sha256_digest_with_progress`](crate::sha256_digest_with_progress) does.
+///
+/// # Errors
+///
+/// Returns `Err` if one of the files can not be read, if the key or the signature is
+/// malformed or if the signature does not match
+///
+pub fn verify_signature_with_progress<P, Q, F>(
+    lime_path: P,
+    signature_path: Q,
+    pubkey: &str,
+    mut progress: F,
+) -> Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    F: FnMut(u64, u64),
+{
+    let public_key = PublicKey::from_base64(pubkey)
+        .or_else(|_| PublicKey::from_file(pubkey))
+        .map_err(|e| {
+            Error(ErrorOrigin::Connector, ErrorKind::ArgValidation)
+                .log_error(format!("Invalid minisign public key: {}", e))
+        })?;
+    let signature = Signature::from_file(signature_path).map_err(|e| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+            .log_error(format!("Unable to read the minisign signature: {}", e))
+    })?;
+    let mut verifier = public_key.verify_stream(&signature).map_err(|e| {
+        Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+            .log_error(format!("Unsupported minisign signature: {}", e))
+    })?;
+
+    let mut file = File::open(lime_path)
+        .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+    let total =
+        file_size(&file).map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+    let mut buff = vec![0u8; 1 << 20];
+    let mut hashed = 0;
+    loop {
+        match file.read(&mut buff) {
+            Ok(0) => break,
+            Ok(n) => {
+                verifier.update(&buff[..n]);
+                hashed += n as u64;
+                progress(hashed, total);
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => {
+                return Err(Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+                    .log_error("Unable to read the file while verifying its signature"))
+            }
+        }
+    }
+
+    verifier.finalize().map_err(|e| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+            .log_error(format!("minisign signature mismatch: {}", e))
+    })
+}
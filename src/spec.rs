@@ -0,0 +1,491 @@
+/// Description of a connector argument, for frontends building their own forms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArgSpec {
+    /// Name of the argument
+    pub name: &'static str,
+    /// Accepted values, alternatives being separated by `|` and placeholders written `<like
+    /// this>`
+    pub values: &'static str,
+    /// Value used when the argument is not given, `None` if it enables an optional feature
+    pub default: Option<&'static str>,
+    /// Description of the argument, in Markdown
+    pub description: &'static str,
+}
+
+/// Arguments accepted by the connector besides `target`
+const ARGS_SPEC: &[ArgSpec] = &[
+    ArgSpec {
+        name: "format",
+        values: "lime|aff4|rawjson|hiberfil|kcore|vbcore",
+        default: None,
+        description: "\
+            format of the target, detected from the magic number starting the file when not \
+            given, files matching no other format being opened as LiME dumps. The formats but \
+            `lime` require the feature of the same name, `vbcore` for VirtualBox core files. \
+            ELF core files carrying the `VBCORE` note are VirtualBox cores, the others kernel \
+            core files like `/proc/kcore`. Needed for hibernation files whose header was wiped.",
+    },
+    ArgSpec {
+        name: "mode",
+        values: "strict|lenient",
+        default: Some("strict"),
+        description: "\
+            `strict` (default) refuses dumps that deviate from the LiME specification, `lenient` \
+            logs recoverable deviations (unknown header versions, non-zero reserved fields, failed \
+            verification) and opens the dump anyway. Truncated dumps are opened in both modes, \
+            mapping only the data actually present.",
+    },
+    ArgSpec {
+        name: "allow_unknown_version",
+        values: "true|false",
+        default: Some("false"),
+        description: "\
+            `true` maps ranges whose header has a version unknown to the connector, assuming the \
+            version 1 layout, and logs a warning instead of refusing them. Defaults to `false`.",
+    },
+    ArgSpec {
+        name: "recover",
+        values: "true|false",
+        default: Some("false"),
+        description: "\
+            `true` skips a corrupt header by scanning forward for the next plausible one and maps \
+            the dump from there, reporting the bytes skipped. Defaults to `false`.",
+    },
+    ArgSpec {
+        name: "carve",
+        values: "true|false",
+        default: Some("false"),
+        description: "\
+            `true` searches the whole file for plausible headers and maps the segments found, for \
+            dumps embedded in arbitrary data like carved disk space or swap images. Defaults to \
+            `false`.",
+    },
+    ArgSpec {
+        name: "endian",
+        values: "le|be|auto",
+        default: Some("auto"),
+        description: "\
+            byte order of the headers, `le`, `be` or `auto` (default) to detect it from the magic \
+            number of the first header. LiME writes the headers in the byte order of the target, \
+            so dumps of big-endian devices need `be` or `auto`.",
+    },
+    ArgSpec {
+        name: "addr_mask",
+        values: "<mask>",
+        default: None,
+        description: "\
+            mask applied to the physical addresses of the headers, made of the low bits of an \
+            address (e.g. `0xFFFFFFFF`), for dumps of 32-bit boards whose ranges have high bits \
+            set. The dump is refused if a masked range would wrap around the mask or collide \
+            with another range. It can not be combined with `index`.",
+    },
+    ArgSpec {
+        name: "max_segments",
+        values: "<count>",
+        default: None,
+        description: "\
+            most ranges the dump may hold, as a decimal count. Together with \
+            `max_segment_size` and `max_total_size` it bounds the work and memory spent on \
+            untrusted dumps, the dump being refused as soon as a bound is exceeded.",
+    },
+    ArgSpec {
+        name: "max_segment_size",
+        values: "<hex size>k|m|g",
+        default: None,
+        description: "\
+            largest range the dump may hold, as a hex size followed by `k`, `m` or `g` (e.g. \
+            `400m`), the dump being refused if a range is larger.",
+    },
+    ArgSpec {
+        name: "max_total_size",
+        values: "<hex size>k|m|g",
+        default: None,
+        description: "\
+            most memory the ranges of the dump may hold together, as a hex size followed by \
+            `k`, `m` or `g` (e.g. `10g`), the dump being refused if they hold more.",
+    },
+    ArgSpec {
+        name: "parse",
+        values: "eager|lazy",
+        default: Some("eager"),
+        description: "\
+            `eager` (default) walks every header on open, `lazy` walks them only as far as needed \
+            to serve each read. In lazy mode corrupt headers are reported by the reads reaching \
+            them and the metadata only reflects the ranges discovered so far. It can not be \
+            combined with `index`, `scan=parallel` or `readahead`.",
+    },
+    ArgSpec {
+        name: "scan",
+        values: "serial|parallel",
+        default: Some("serial"),
+        description: "\
+            how the headers are located, `serial` (default) hops from header to header, `parallel` \
+            searches partitions of the file concurrently and then validates the header chain, \
+            falling back to the serial scan when the result is ambiguous (truncated dumps, digest \
+            trailers).",
+    },
+    ArgSpec {
+        name: "overlap",
+        values: "error|first|last",
+        default: Some("last"),
+        description: "\
+            how overlapping ranges are handled, `last` (default) keeps the data of the range that \
+            comes last in the file, `first` the data of the one that comes first and `error` \
+            refuses the dump.",
+    },
+    ArgSpec {
+        name: "align",
+        values: "none|trim|extend|error",
+        default: Some("none"),
+        description: "\
+            how ranges not starting or ending on a 4 KiB page boundary are mapped, `none` \
+            (default) logs them, `trim` shrinks them to the whole pages they hold, `extend` pads \
+            them to whole pages with zeros and `error` refuses the dump. \
+            Contiguous ranges are checked as a whole.",
+    },
+    ArgSpec {
+        name: "rebase",
+        values: "[<start>-<end>:]<+|-><offset>[,...]",
+        default: None,
+        description: "\
+            shift added to the physical addresses of the headers before they are mapped, e.g. \
+            `+0x80000000` for a capture recording bus addresses. A rule limited to an inclusive \
+            window only moves the addresses inside it, splitting the ranges crossing its edges \
+            (`rebase=\"0x0-0x3fffffff:+0x80000000,0x40000000-0x7fffffff:-0x40000000\"`). The \
+            rebased ranges must not collide. `ranges` selects rebased addresses. It can not be \
+            combined with `parse=lazy`, `reload` or `follow`.",
+    },
+    ArgSpec {
+        name: "ranges",
+        values: "<start>-<end>|<start>-[,...]",
+        default: None,
+        description: "\
+            physical windows mapped, the parts of the ranges outside them being skipped, e.g. \
+            to triage low memory and the kernel direct map of a large dump. Each window is \
+            inclusive, an empty end reaching the top of the address space. Quote the list when \
+            it holds more than one window (`ranges=\"0x100000-0x3fffffff,0x100000000-\"`). It \
+            can not be combined with `parse=lazy`, `reload` or `follow`.",
+    },
+    ArgSpec {
+        name: "fill_gaps",
+        values: "none|zero",
+        default: Some("none"),
+        description: "\
+            `none` (default) makes reads of addresses between the ranges of the dump fail, `zero` \
+            satisfies them with zeros.",
+    },
+    ArgSpec {
+        name: "cached",
+        values: "true|false",
+        default: Some("false"),
+        description: "\
+            `true` keeps a page cache in front of the dump, of `cache_size` or 2mb if it is not \
+            given, so that generic tools get a cached connector from its argument string alone.",
+    },
+    ArgSpec {
+        name: "cache_size",
+        values: "<hex size>kb|mb|gb",
+        default: None,
+        description: "\
+            size of a page cache kept in front of the dump, as a hex number followed by `kb`, `mb` \
+            or `gb` (e.g. `cache_size=10mb`). Pages stay cached until evicted since the dump does \
+            not change. No cache by default.",
+    },
+    ArgSpec {
+        name: "cache_page_size",
+        values: "<hex size>",
+        default: Some("1000"),
+        description: "\
+            size of a cached page as a hex number, defaults to `1000`.",
+    },
+    ArgSpec {
+        name: "cache_time",
+        values: "<milliseconds>",
+        default: None,
+        description: "\
+            time after which a cached page is read again from the dump, for dumps modified by \
+            another process while they are open. Pages stay cached until evicted by default.",
+    },
+    ArgSpec {
+        name: "readahead",
+        values: "<hex size>k|m|g",
+        default: None,
+        description: "\
+            size of the window loaded by a background thread after sequential reads, as a hex \
+            number followed by `k`, `m` or `g` (e.g. `readahead=8M`). The following reads falling \
+            in the window are served from memory, which helps on high-latency storage. Disabled by \
+            default.",
+    },
+    ArgSpec {
+        name: "odirect",
+        values: "true|false",
+        default: Some("false"),
+        description: "\
+            `true` reads the data of the dump with O_DIRECT, bypassing the page cache of the \
+            operating system, e.g. for benchmarks or one-pass scans of dumps larger than the \
+            memory. Reads are aligned as O_DIRECT requires and buffered reads are used if the \
+            filesystem refuses it. Requires Linux and the `odirect` feature, defaults to `false`.",
+    },
+    ArgSpec {
+        name: "verify",
+        values: "<path>|auto",
+        default: None,
+        description: "\
+            path of a `sha256sum` style file holding the SHA-256 digest of the dump, or `auto` to \
+            use `<target>.sha256`. The dump is hashed on open and refused if the digest does not \
+            match (unless `mode=lenient`).",
+    },
+    ArgSpec {
+        name: "verify_blocks",
+        values: "<path>|auto",
+        default: None,
+        description: "\
+            path of the block manifest of the dump, or `auto` to use `<target>.blocks`. Every \
+            block is checked against its CRC-32 the first time a read touches it, and the reads \
+            of blocks that do not match fail. Can not be combined with archive members, other \
+            formats than LiME, composed dumps, `overlay`, `key` or `write`.",
+    },
+    ArgSpec {
+        name: "signature",
+        values: "<path>|auto",
+        default: None,
+        description: "\
+            path of a detached minisign signature of the dump, or `auto` to use \
+            `<target>.minisig`, checked with `pubkey` on open. The dump is refused if the \
+            signature does not match (unless `mode=lenient`). Requires the `signature` \
+            feature.",
+    },
+    ArgSpec {
+        name: "pubkey",
+        values: "<base64 key>|<path>",
+        default: None,
+        description: "\
+            minisign public key the `signature` is checked with, either in base64 (`RW...`) \
+            or as the path of a public key file.",
+    },
+    ArgSpec {
+        name: "index",
+        values: "<path>|auto",
+        default: None,
+        description: "\
+            path of an index cache holding the range table of the dump, or `auto` to use \
+            `<target>.limeidx`. The cached table is used when it matches the size, modification \
+            time and fingerprint of the dump, otherwise the dump is scanned and the index \
+            rewritten.",
+    },
+    ArgSpec {
+        name: "reload",
+        values: "true|false",
+        default: Some("false"),
+        description: "\
+            `true` keeps following a dump that is still being written: a read the ranges \
+            parsed so far do not cover walks the headers appended since the last one parsed, \
+            the last range being completed as its data lands. It can not be combined with \
+            `index`, `scan=parallel` or `readahead`.",
+    },
+    ArgSpec {
+        name: "follow",
+        values: "true|false|<timeout_ms>",
+        default: Some("false"),
+        description: "\
+            reads past the end of a dump still being written wait for its data to land \
+            instead of failing, without limit with `true` or for up to the given number of \
+            milliseconds. It implies `reload` and can not be combined with the same \
+            arguments.",
+    },
+    ArgSpec {
+        name: "key",
+        values: "<age identity>",
+        default: None,
+        description: "\
+            age identity (`AGE-SECRET-KEY-1...`) the dump is decrypted with, the dump being \
+            an age encrypted LiME file. Only the chunks reads touch are decrypted, in memory. \
+            Requires the `age` feature and can not be combined with `keyfile`, split dumps, \
+            archive members, `overlay`, `write`, `verify`, `index`, `scan=parallel`, \
+            `readahead` or `odirect`.",
+    },
+    ArgSpec {
+        name: "keyfile",
+        values: "<path>",
+        default: None,
+        description: "\
+            path of an age identity file the dump is decrypted with, as `key` does. Prefer it \
+            to `key`, which leaves the secret key in the connector arguments.",
+    },
+    ArgSpec {
+        name: "ssh_cmd",
+        values: "<command>",
+        default: None,
+        description: "\
+            command run on the host of an `ssh://` target without path, writing the dump to \
+            its standard output (e.g. `ssh_cmd=\"sudo cat /dev/crash\"`). `{offset}` is \
+            replaced with the number of bytes already spooled when an interrupted acquisition \
+            resumes; without it the acquisition starts over. Requires the `ssh` feature.",
+    },
+    ArgSpec {
+        name: "ssh_bin",
+        values: "<path>",
+        default: Some("ssh"),
+        description: "\
+            SSH client run to reach the host of an `ssh://` target, taking OpenSSH arguments. \
+            Defaults to `ssh`.",
+    },
+    ArgSpec {
+        name: "spool",
+        values: "<path>",
+        default: None,
+        description: "\
+            local file an `ssh://` target is acquired into and opened from, defaulting to a \
+            file named after the host and the remote file in the temporary directory. A \
+            complete spool file is opened without connecting again, an interrupted acquisition \
+            resumes from its `.part` file. Required by `tcp://` targets, whose dump is \
+            received into it while it is read; the file must not exist yet.",
+    },
+    ArgSpec {
+        name: "throttle",
+        values: "<duration>us|ms|s",
+        default: None,
+        description: "\
+            latency added to every read, as a decimal number followed by `us`, `ms` or `s` \
+            (e.g. `throttle=10ms`), to test tools against slow evidence storage. Reads are not \
+            slowed down by default.",
+    },
+    ArgSpec {
+        name: "bandwidth",
+        values: "<size>B|kB|MB|GB[ps]",
+        default: None,
+        description: "\
+            most bytes read per second, as a decimal number followed by `B`, `kB`, `MB` or `GB` \
+            (e.g. `bandwidth=50MBps`), units being powers of 1000. Reads are slowed down to the \
+            given throughput on top of `throttle`. Not limited by default.",
+    },
+    ArgSpec {
+        name: "faults",
+        values: "<start>-<end>:io_error|short_read[,...]",
+        default: None,
+        description: "\
+            physical ranges whose reads fail on purpose, to exercise the error paths of tools \
+            built on the connector. Each entry is an inclusive range followed by `io_error`, \
+            failing the whole read, or `short_read`, failing the second half of the read. Quote \
+            the list when it holds more than one range \
+            (`faults=\"0x1000-0x1fff:io_error,0x8000-0x8fff:short_read\"`). No faults by default.",
+    },
+    ArgSpec {
+        name: "fault_rate",
+        values: "<probability>",
+        default: Some("1"),
+        description: "\
+            probability, between 0 and 1, that a read touching a range of `faults` fails. \
+            Defaults to `1`, failing every such read.",
+    },
+    ArgSpec {
+        name: "fault_seed",
+        values: "<seed>",
+        default: Some("0"),
+        description: "\
+            seed of the draws deciding which reads fail with `fault_rate`, the same seed and \
+            reads failing the same way on every run. Defaults to `0`.",
+    },
+    ArgSpec {
+        name: "write",
+        values: "none|inplace",
+        default: Some("none"),
+        description: "\
+            `none` (default) refuses writes to the physical memory, `inplace` opens the dump \
+            for writing and stores the written bytes in the dump itself, at the offsets the \
+            addresses map to, e.g. to redact secrets before sharing it. Writes outside the \
+            ranges of the dump fail. It can not be combined with split dumps, archive members \
+            or AFF4 containers.",
+    },
+    ArgSpec {
+        name: "overlay",
+        values: "<path>[,<path>...]",
+        default: None,
+        description: "\
+            LiME files stacked on top of the dump in order, e.g. the pages changed by a \
+            what-if analysis or an incremental snapshot. Each layer shadows the ones below it \
+            where their ranges overlap. Quote the list when it holds more than one path \
+            (`overlay=\"delta1.lime,delta2.lime\"`). It can not be combined with `parse=lazy`, \
+            `reload`, `follow`, `index`, `scan=parallel`, `readahead`, `odirect` or `write`.",
+    },
+];
+
+/// Arguments accepted by the connector besides `target`, in the order [`help`](crate::help)
+/// lists them.
+pub fn args_spec() -> &'static [ArgSpec] {
+    ARGS_SPEC
+}
+
+/// Find the argument `name` refers to, returns `None` if it is unknown.
+pub(crate) fn find_arg(name: &str) -> Option<&'static ArgSpec> {
+    ARGS_SPEC.iter().find(|spec| spec.name == name)
+}
+
+/// Known argument closest to the unknown `name`, if any is close enough to be a typo.
+pub(crate) fn suggest_arg(name: &str) -> Option<&'static str> {
+    ARGS_SPEC
+        .iter()
+        .map(|spec| (edit_distance(name, spec.name), spec.name))
+        .filter(|(distance, known)| *distance <= 2.max(known.len() / 4))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, known)| known)
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Render the arguments as the Markdown list of [`help`](crate::help), wrapped to `width`
+/// columns.
+pub(crate) fn help_list(width: usize) -> String {
+    let mut list = String::new();
+    for spec in ARGS_SPEC {
+        let mut line = format!("- `{}`:", spec.name);
+        for word in spec.description.split_whitespace() {
+            if line.len() + 1 + word.len() > width {
+                list.push_str(&line);
+                list.push('\n');
+                line = String::from(" ");
+            }
+            line.push(' ');
+            line.push_str(word);
+        }
+        list.push_str(&line);
+        list.push('\n');
+    }
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typos_get_suggestions() {
+        assert_eq!(suggest_arg("cache_sise"), Some("cache_size"));
+        assert_eq!(suggest_arg("fillgaps"), Some("fill_gaps"));
+        assert_eq!(suggest_arg("endianness"), None);
+        assert_eq!(suggest_arg("target_os"), None);
+    }
+
+    #[test]
+    fn help_lists_every_argument() {
+        let help = help_list(78);
+        assert!(help.lines().all(|line| line.len() <= 78));
+        for spec in args_spec() {
+            assert!(help.contains(&format!("- `{}`:", spec.name)));
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use memflow::prelude::v1::*;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Paths of the parts matching `pattern`, a path whose file name ends with `*`, sorted.
+///
+/// The parts are the files of the directory named like the pattern up to the `*`, followed
+/// by a part number (e.g. `dump.lime.*` matches `dump.lime.000` and `dump.lime.001`, but not
+/// `dump.lime.sha256`).
+///
+/// # Errors
+///
+/// Returns `Err` if the directory can not be read or no file matches the pattern
+///
+pub(crate) fn expand_parts(pattern: &Path) -> Result<Vec<PathBuf>> {
+    let prefix = pattern
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix('*'))
+        .ok_or_else(|| {
+            Error(ErrorOrigin::Connector, ErrorKind::InvalidPath).log_error(format!(
+                "LiME part pattern {} must end with `*`",
+                pattern.display()
+            ))
+        })?;
+    let dir = match pattern.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let entries = fs::read_dir(dir).map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadDir)
+            .log_error(format!("Unable to read the directory {}", dir.display()))
+    })?;
+    let mut parts = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix(prefix))
+                .is_some_and(|number| {
+                    !number.is_empty() && number.bytes().all(|c| c.is_ascii_digit())
+                })
+        })
+        .map(|entry| pattern.with_file_name(entry.file_name()))
+        .collect::<Vec<_>>();
+    parts.sort();
+
+    if parts.is_empty() {
+        return Err(Error(ErrorOrigin::Connector, ErrorKind::InvalidPath)
+            .log_error(format!("No LiME part matches {}", pattern.display())));
+    }
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dumpfile::DumpFile;
+    use std::io::{Read, Seek, SeekFrom};
+
+    #[test]
+    fn parts_are_read_as_one_file() {
+        let dir = std::env::temp_dir().join("memflow_lime_split_unit");
+        fs::create_dir_all(&dir).unwrap();
+        let paths = ["part.10", "part.02", "part.01", "part.sha256"].map(|name| dir.join(name));
+        for (path, data) in paths.iter().zip([&b"6789"[..], b"345", b"012", b"x"]) {
+            fs::write(path, data).unwrap();
+        }
+
+        let parts = expand_parts(&dir.join("part.*")).unwrap();
+        assert_eq!(parts, [&paths[2], &paths[1], &paths[0]].map(PathBuf::clone));
+        let mut file = DumpFile::open_parts(&parts).unwrap();
+        assert_eq!(file.len().unwrap(), 10);
+
+        let (mut a, mut b) = ([0u8; 2], [0u8; 4]);
+        file.seek(SeekFrom::Start(1)).unwrap();
+        file.read_exact(&mut a).unwrap();
+        file.read_exact(&mut b).unwrap();
+        assert_eq!((&a, &b), (b"12", b"3456"));
+        assert_eq!(file.seek(SeekFrom::End(-1)).unwrap(), 9);
+        let mut rest = Vec::new();
+        file.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, b"9");
+
+        fs::remove_dir_all(dir).unwrap();
+        assert!(expand_parts(Path::new("missing-dir/part.*")).is_err());
+    }
+}
@@ -0,0 +1,237 @@
+use crate::config::SshConfig;
+
+use memflow::prelude::v1::*;
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Size of the chunks copied from the SSH client to the spool file
+const CHUNK_SIZE: usize = 0x10_0000;
+
+/// Acquire the dump described by `ssh` into the spool file at `spool`, reporting the bytes
+/// spooled and the size of the dump to `progress`.
+///
+/// The dump is streamed from the standard output of the SSH client into `<spool>.part`,
+/// renamed to `spool` once complete. A complete spool file is used as is, without connecting.
+/// An interrupted acquisition resumes from the bytes already in `<spool>.part`: a remote file
+/// is read from there on with `tail`, a remote command gets their number in place of
+/// `{offset}` and starts over if it has none. The size of a remote command output is
+/// unknown, reported as zero.
+///
+/// The client runs with `BatchMode=yes`, so the host must accept a key or agent without
+/// prompting.
+///
+/// # Errors
+///
+/// Returns `Err` if the client can not be run or fails, if the remote file ends before its
+/// size, or if the spool file can not be written
+///
+pub(crate) fn acquire(
+    ssh: &SshConfig,
+    spool: &Path,
+    progress: &mut dyn FnMut(u64, u64),
+) -> Result<()> {
+    if spool.is_file() {
+        log::info!(
+            "Using the dump of {} already spooled to {}",
+            ssh.destination,
+            spool.display()
+        );
+        return Ok(());
+    }
+
+    let partial = partial_path(spool);
+    if let Some(dir) = spool.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| spool_error(spool, e))?;
+    }
+    let mut out = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&partial)
+        .map_err(|e| spool_error(&partial, e))?;
+    let spooled = out
+        .seek(SeekFrom::End(0))
+        .map_err(|e| spool_error(&partial, e))?;
+    let (command, resumed) = remote_command(ssh, spooled);
+    if resumed < spooled {
+        out.set_len(resumed)
+            .and_then(|_| out.seek(SeekFrom::Start(resumed)))
+            .map_err(|e| spool_error(&partial, e))?;
+    }
+    log::info!(
+        "Acquiring the dump of {} into {} from byte {:#x}",
+        ssh.destination,
+        spool.display(),
+        resumed
+    );
+
+    let mut client = Command::new(&ssh.program);
+    if let Some(port) = ssh.port {
+        client.arg("-p").arg(port.to_string());
+    }
+    let mut child = client
+        .args(["-o", "BatchMode=yes", "--"])
+        .arg(&ssh.destination)
+        .arg(&command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+                "Unable to run the SSH client {}: {}",
+                ssh.program.display(),
+                e
+            ))
+        })?;
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let stderr = thread::spawn(move || {
+        let mut msg = String::new();
+        stderr.read_to_string(&mut msg).map(|_| msg)
+    });
+    let mut stdout =
+        BufReader::with_capacity(CHUNK_SIZE, child.stdout.take().expect("stdout is piped"));
+
+    let copied = (|| {
+        let total = match ssh.remote_path {
+            Some(_) => {
+                let mut size = String::new();
+                stdout.read_line(&mut size)?;
+                size.trim().parse::<u64>().map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("the remote file size {:?} is not a number", size.trim()),
+                    )
+                })?
+            }
+            None => 0,
+        };
+        let mut done = resumed;
+        progress(done, total);
+        loop {
+            let chunk = stdout.fill_buf()?;
+            if chunk.is_empty() {
+                break;
+            }
+            out.write_all(chunk)?;
+            let len = chunk.len();
+            stdout.consume(len);
+            done += len as u64;
+            progress(done, total);
+        }
+        out.sync_all()?;
+        Ok::<_, io::Error>((done, total))
+    })();
+    drop(stdout);
+    let status = child.wait();
+    let stderr = stderr
+        .join()
+        .ok()
+        .and_then(|msg| msg.ok())
+        .unwrap_or_default();
+
+    let failure = match (status, copied) {
+        (_, Err(e)) => Some(e.to_string()),
+        (Err(e), _) => Some(e.to_string()),
+        (Ok(status), _) if !status.success() => Some(format!("{}: {}", status, stderr.trim())),
+        (Ok(_), Ok((done, total))) if ssh.remote_path.is_some() && done != total => Some(format!(
+            "the remote file ended after {:#x} of its {:#x} bytes",
+            done, total
+        )),
+        _ => None,
+    };
+    if let Some(failure) = failure {
+        return Err(
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile).log_error(format!(
+                "Acquisition of the dump of {} failed, {} is kept to resume it: {}",
+                ssh.destination,
+                partial.display(),
+                failure
+            )),
+        );
+    }
+    fs::rename(&partial, spool).map_err(|e| spool_error(spool, e))
+}
+
+/// Command run on the remote host to get the dump from byte `spooled` on, and the offset
+/// its output starts at.
+fn remote_command(ssh: &SshConfig, spooled: u64) -> (String, u64) {
+    match (&ssh.remote_path, &ssh.command) {
+        (Some(path), _) => {
+            let path = shell_quote(path);
+            (
+                format!(
+                    "stat -L -c %s {} && tail -c +{} {}",
+                    path,
+                    spooled + 1,
+                    path
+                ),
+                spooled,
+            )
+        }
+        (None, Some(command)) if command.contains("{offset}") => {
+            (command.replace("{offset}", &spooled.to_string()), spooled)
+        }
+        (None, command) => {
+            if spooled > 0 {
+                log::warn!("ssh_cmd has no {{offset}} to resume from, acquiring the dump again");
+            }
+            (command.clone().unwrap_or_default(), 0)
+        }
+    }
+}
+
+/// Quote `arg` for a POSIX shell.
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// File the dump is spooled to until it is complete, i.e. `<spool>.part`.
+fn partial_path(spool: &Path) -> PathBuf {
+    let mut path = spool.as_os_str().to_owned();
+    path.push(".part");
+    path.into()
+}
+
+fn spool_error(path: &Path, e: io::Error) -> Error {
+    Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile).log_error(format!(
+        "Unable to write the spool file {}: {}",
+        path.display(),
+        e
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_commands_resume() {
+        let mut ssh = SshConfig {
+            destination: "ir@host".into(),
+            port: None,
+            remote_path: Some("/tmp/it's.lime".into()),
+            command: None,
+            program: "ssh".into(),
+        };
+        assert_eq!(
+            remote_command(&ssh, 0x10),
+            (
+                r"stat -L -c %s '/tmp/it'\''s.lime' && tail -c +17 '/tmp/it'\''s.lime'".into(),
+                0x10
+            )
+        );
+        ssh.remote_path = None;
+        ssh.command = Some("acquire --skip {offset}".into());
+        assert_eq!(
+            remote_command(&ssh, 0x10),
+            ("acquire --skip 16".into(), 0x10)
+        );
+        ssh.command = Some("cat /proc/kcore".into());
+        assert_eq!(remote_command(&ssh, 0x10), ("cat /proc/kcore".into(), 0));
+    }
+}
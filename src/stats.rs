@@ -0,0 +1,105 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// IO statistics of a [`LimeMemory`](crate::LimeMemory)
+///
+/// The counters are shared by a connector and its clones, and can be read at any time from
+/// the [`Arc`](std::sync::Arc) returned by [`LimeMemory::stats`](crate::LimeMemory::stats),
+/// including from other threads.
+#[derive(Debug, Default)]
+pub struct LimeStats {
+    read_ops: AtomicU64,
+    bytes_read: AtomicU64,
+    file_read_ops: AtomicU64,
+    file_bytes_read: AtomicU64,
+    cache_lookups: AtomicU64,
+    cache_misses: AtomicU64,
+    readahead_hits: AtomicU64,
+    gap_fills: AtomicU64,
+}
+
+impl LimeStats {
+    /// Number of reads requested from the connector, each entry of a batch counting as one.
+    pub fn read_ops(&self) -> u64 {
+        self.read_ops.load(Ordering::Relaxed)
+    }
+
+    /// Number of bytes requested from the connector.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read.load(Ordering::Relaxed)
+    }
+
+    /// Number of reads issued to the dump file, contiguous requests being read at once.
+    pub fn file_read_ops(&self) -> u64 {
+        self.file_read_ops.load(Ordering::Relaxed)
+    }
+
+    /// Number of bytes read from the dump file.
+    pub fn file_bytes_read(&self) -> u64 {
+        self.file_bytes_read.load(Ordering::Relaxed)
+    }
+
+    /// Number of pages found in the page cache (`cache_size`).
+    pub fn cache_hits(&self) -> u64 {
+        self.cache_lookups
+            .load(Ordering::Relaxed)
+            .saturating_sub(self.cache_misses())
+    }
+
+    /// Number of pages loaded from the dump file into the page cache (`cache_size`).
+    pub fn cache_misses(&self) -> u64 {
+        self.cache_misses.load(Ordering::Relaxed)
+    }
+
+    /// Number of reads served from the read-ahead window (`readahead`).
+    pub fn readahead_hits(&self) -> u64 {
+        self.readahead_hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of reads, or parts of reads, satisfied with zeros because they fell in a gap
+    /// between the ranges of the dump (`fill_gaps`).
+    pub fn gap_fills(&self) -> u64 {
+        self.gap_fills.load(Ordering::Relaxed)
+    }
+
+    /// Set every counter back to zero.
+    pub fn reset(&self) {
+        for counter in [
+            &self.read_ops,
+            &self.bytes_read,
+            &self.file_read_ops,
+            &self.file_bytes_read,
+            &self.cache_lookups,
+            &self.cache_misses,
+            &self.readahead_hits,
+            &self.gap_fills,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn record_read(&self, len: u64) {
+        self.read_ops.fetch_add(1, Ordering::Relaxed);
+        self.bytes_read.fetch_add(len, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_file_read(&self, len: u64) {
+        self.file_read_ops.fetch_add(1, Ordering::Relaxed);
+        self.file_bytes_read.fetch_add(len, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_cache_lookups(&self, pages: u64) {
+        self.cache_lookups.fetch_add(pages, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_cache_misses(&self, pages: u64) {
+        self.cache_misses.fetch_add(pages, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_readahead_hit(&self) {
+        self.readahead_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_gap_fills(&self, count: u64) {
+        self.gap_fills.fetch_add(count, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,63 @@
+use memflow::prelude::v1::*;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the evidence directory listed by [`target_list`]
+pub const EVIDENCE_DIR_ENV: &str = "MEMFLOW_LIME_DIR";
+
+/// File name extensions of the dumps listed in an evidence directory
+const DUMP_EXTENSIONS: [&str; 2] = [".lime", ".lime.gz"];
+
+/// List the `LiME` dumps (`.lime` and `.lime.gz` files) found in `dir`, sorted by path.
+///
+/// Subdirectories are not searched.
+///
+/// # Errors
+///
+/// Returns `Err` if the directory can not be read
+///
+pub fn list_dumps<P: AsRef<Path>>(dir: P) -> Result<Vec<PathBuf>> {
+    let entries = fs::read_dir(dir.as_ref()).map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadDir).log_error(format!(
+            "Unable to read the evidence directory {}",
+            dir.as_ref().display()
+        ))
+    })?;
+
+    let mut dumps = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| !file_type.is_dir()))
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_lowercase())
+                .is_some_and(|name| DUMP_EXTENSIONS.iter().any(|ext| name.ends_with(ext)))
+        })
+        .collect::<Vec<_>>();
+    dumps.sort();
+    Ok(dumps)
+}
+
+/// List the `LiME` dumps of the evidence directory named by the `MEMFLOW_LIME_DIR`
+/// environment variable, the name of each target being the path of the dump.
+///
+/// The list is empty if the variable is not set.
+///
+/// # Errors
+///
+/// Returns `Err` if the directory can not be read
+///
+pub fn target_list() -> Result<Vec<TargetInfo>> {
+    let Some(dir) = std::env::var_os(EVIDENCE_DIR_ENV) else {
+        log::debug!("{} is not set, no LiME dump to list", EVIDENCE_DIR_ENV);
+        return Ok(Vec::new());
+    };
+
+    Ok(list_dumps(dir)?
+        .into_iter()
+        .map(|path| TargetInfo {
+            name: path.to_string_lossy().as_ref().into(),
+        })
+        .collect())
+}
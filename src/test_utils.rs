@@ -0,0 +1,121 @@
+//! Fixtures for the tests of crates reading `LiME` dumps.
+
+use memflow_lime_core::{Endian, RangeHeader};
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Builder of `LiME` files, section by section
+///
+/// Ranges are written in call order, so gaps, adjacent or overlapping ranges are obtained by
+/// choosing their addresses. Corrupt dumps are built with [`header`](Self::header),
+/// [`bytes`](Self::bytes) and [`truncate`](Self::truncate).
+///
+/// ```
+/// use memflow_lime::test_utils::LimeFixture;
+///
+/// let dump = LimeFixture::new()
+///     .range(0x1000, &[0x11; 0x1000])
+///     .range(0x2000, &[0x22; 0x1000]) // adjacent to the first range
+///     .filled(0x8000, 0x1000, 0x88) // after a gap
+///     .truncate(0x800)
+///     .build();
+/// assert_eq!(dump.len(), 3 * (32 + 0x1000) - 0x800);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LimeFixture {
+    bytes: Vec<u8>,
+    endian: Endian,
+}
+
+impl Default for LimeFixture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LimeFixture {
+    /// Start an empty dump with little-endian headers.
+    pub fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            endian: Endian::Little,
+        }
+    }
+
+    /// Write the headers added from now on in big-endian byte order, like `LiME` does on
+    /// big-endian machines.
+    pub fn big_endian(mut self) -> Self {
+        self.endian = Endian::Big;
+        self
+    }
+
+    /// Add the physical range starting at `s_addr` holding `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty or the range does not fit in the address space
+    ///
+    pub fn range(mut self, s_addr: u64, data: &[u8]) -> Self {
+        let e_addr = (data.len() as u64)
+            .checked_sub(1)
+            .and_then(|last| s_addr.checked_add(last))
+            .expect("the range must be non-empty and fit in the address space");
+        self.bytes
+            .extend_from_slice(&RangeHeader::new(s_addr, e_addr).to_bytes(self.endian));
+        self.bytes.extend_from_slice(data);
+        self
+    }
+
+    /// Add the physical range of `len` bytes starting at `s_addr`, every byte being `byte`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is zero or the range does not fit in the address space
+    ///
+    pub fn filled(self, s_addr: u64, len: usize, byte: u8) -> Self {
+        self.range(s_addr, &vec![byte; len])
+    }
+
+    /// Add a header with arbitrary fields and no data after it, e.g. with an unknown
+    /// `version`, non-zero `reserved` bytes or `e_addr` lower than `s_addr`.
+    pub fn header(mut self, version: u32, s_addr: u64, e_addr: u64, reserved: [u8; 8]) -> Self {
+        let header = RangeHeader {
+            version,
+            s_addr,
+            e_addr,
+            reserved,
+        };
+        self.bytes.extend_from_slice(&header.to_bytes(self.endian));
+        self
+    }
+
+    /// Add raw `bytes`, e.g. the data of a range started by [`header`](Self::header) or
+    /// garbage between two ranges.
+    pub fn bytes(mut self, bytes: &[u8]) -> Self {
+        self.bytes.extend_from_slice(bytes);
+        self
+    }
+
+    /// Cut `len` bytes off the end of the dump, like a capture interrupted before its end.
+    pub fn truncate(mut self, len: usize) -> Self {
+        self.bytes.truncate(self.bytes.len().saturating_sub(len));
+        self
+    }
+
+    /// Get the content of the dump.
+    pub fn build(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Write the dump to `path`, replacing any existing file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file can not be written
+    ///
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, &self.bytes)
+    }
+}
@@ -0,0 +1,192 @@
+use crate::checksum::parse_hex;
+use crate::config::ParseMode;
+use crate::segment::{read_layout, LimeSegment};
+
+use memflow::prelude::v1::*;
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Time a section of a `LiME` file was acquired, as recorded in a [`LimeTimestamps`] sidecar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LimeSegmentTime {
+    /// Physical address of the first byte of the section
+    pub s_addr: u64,
+    /// Physical address of the last byte of the section
+    pub e_addr: u64,
+    /// Offset of the section data in the file
+    pub file_offset: u64,
+    /// When the writer started the section
+    pub started: SystemTime,
+    /// When the last byte of the section was written
+    pub finished: SystemTime,
+}
+
+impl LimeSegmentTime {
+    /// Time taken to acquire the section.
+    pub fn duration(&self) -> Duration {
+        self.finished
+            .duration_since(self.started)
+            .unwrap_or_default()
+    }
+}
+
+/// Acquisition times of the sections of a `LiME` file, stored next to it in a sidecar
+/// (`<dump>.times`)
+///
+/// An acquisition of a running machine takes long enough for memory to change between the
+/// first and the last range read; the times tell which ranges are consistent with each other
+/// on a timeline. The dump itself is left untouched, so readers unaware of the sidecar are not
+/// affected.
+///
+/// The sidecar starts with a `# timestamps unix_ns` line, followed by one
+/// `<s_addr>-<e_addr>  <file offset>  <started>  <finished>` line per section, in file order,
+/// the addresses being inclusive and in hexadecimal and the times in nanoseconds since the
+/// Unix epoch.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LimeTimestamps {
+    /// Times of the sections, in file order
+    pub sections: Vec<LimeSegmentTime>,
+}
+
+impl LimeTimestamps {
+    /// Read the timestamps sidecar at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the sidecar can not be read or is malformed
+    ///
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let sidecar = fs::read_to_string(path).map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+                .log_error("Unable to read the timestamps sidecar")
+        })?;
+        let malformed = |line: &str| {
+            Error(ErrorOrigin::Connector, ErrorKind::Encoding)
+                .log_error(format!("Malformed timestamps sidecar line `{}`", line))
+        };
+
+        let mut lines = sidecar.lines();
+        let first = lines.next().unwrap_or_default();
+        if first.trim() != "# timestamps unix_ns" {
+            return Err(malformed(first));
+        }
+
+        let mut sections = Vec::new();
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            let mut fields = line.split_whitespace();
+            let section = (|| {
+                let (s_addr, e_addr) = fields.next()?.split_once('-')?;
+                let section = LimeSegmentTime {
+                    s_addr: parse_hex(s_addr)?,
+                    e_addr: parse_hex(e_addr)?,
+                    file_offset: parse_hex(fields.next()?)?,
+                    started: parse_time(fields.next()?)?,
+                    finished: parse_time(fields.next()?)?,
+                };
+                (fields.next().is_none()
+                    && section.s_addr <= section.e_addr
+                    && section.started <= section.finished)
+                    .then_some(section)
+            })()
+            .ok_or_else(|| malformed(line))?;
+            sections.push(section);
+        }
+        Ok(Self { sections })
+    }
+
+    /// Write the timestamps to `path`, creating or truncating it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the sidecar can not be written
+    ///
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let write = || -> io::Result<()> {
+            let mut out = BufWriter::new(File::create(path)?);
+            writeln!(out, "# timestamps unix_ns")?;
+            for section in &self.sections {
+                writeln!(
+                    out,
+                    "{:#x}-{:#x}  {:#x}  {}  {}",
+                    section.s_addr,
+                    section.e_addr,
+                    section.file_offset,
+                    unix_ns(section.started),
+                    unix_ns(section.finished)
+                )?;
+            }
+            out.flush()
+        };
+        write().map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+                .log_error("Unable to write the timestamps sidecar")
+        })
+    }
+
+    /// Time of the section whose data starts at `file_offset`, `None` if none was recorded.
+    pub fn find(&self, file_offset: u64) -> Option<&LimeSegmentTime> {
+        self.sections
+            .iter()
+            .find(|section| section.file_offset == file_offset)
+    }
+
+    /// Time between the start of the first section and the end of the last one, the window
+    /// over which the memory of the dump may be smeared. `None` if no section was recorded.
+    pub fn smear(&self) -> Option<Duration> {
+        let started = self.sections.iter().map(|section| section.started).min()?;
+        let finished = self.sections.iter().map(|section| section.finished).max()?;
+        Some(finished.duration_since(started).unwrap_or_default())
+    }
+}
+
+/// Default location of the timestamps sidecar of `lime_path`, i.e. `<lime_path>.times`.
+pub fn default_timestamps_sidecar<P: AsRef<Path>>(lime_path: P) -> PathBuf {
+    let mut path = OsString::from(lime_path.as_ref());
+    path.push(".times");
+    path.into()
+}
+
+/// List the memory segments stored in the `LiME` file at `path` like
+/// [`read_segments`](crate::read_segments), each with its acquisition time if the
+/// timestamps sidecar next to the file (`<path>.times`) records it.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading or parsing the file, or if the sidecar
+/// exists but can not be read or is malformed
+///
+pub fn read_timed_segments<P: AsRef<Path>>(
+    path: P,
+) -> Result<Vec<(LimeSegment, Option<LimeSegmentTime>)>> {
+    let segments = read_layout(&path, ParseMode::Strict)?.segments;
+    let sidecar = default_timestamps_sidecar(&path);
+    let timestamps = match sidecar.exists() {
+        true => LimeTimestamps::read(&sidecar)?,
+        false => LimeTimestamps::default(),
+    };
+    Ok(segments
+        .into_iter()
+        .map(|segment| {
+            let time = timestamps.find(segment.file_offset).copied();
+            (segment, time)
+        })
+        .collect())
+}
+
+/// Nanoseconds from the Unix epoch to `time`, zero for earlier times.
+fn unix_ns(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+}
+
+/// Parse a time written by [`unix_ns`].
+fn parse_time(ns: &str) -> Option<SystemTime> {
+    let ns = ns.parse::<u64>().ok()?;
+    UNIX_EPOCH.checked_add(Duration::from_nanos(ns))
+}
@@ -0,0 +1,78 @@
+use crate::header::LimeHeader;
+
+/// Digest appended by `LiME` after the memory ranges.
+///
+/// When `LiME` is run with `digest=<algo>` in TCP mode, the hex encoded digest of the dump is
+/// sent over the same connection right after the last memory range, so it ends up at the end
+/// of the captured file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimeDigest {
+    /// Raw digest value
+    pub value: Vec<u8>,
+    /// Number of bytes at the beginning of the file covered by the digest, i.e. the offset of
+    /// the trailer
+    pub covered_len: u64,
+}
+
+impl LimeDigest {
+    /// Largest trailer that can hold a digest, a hex encoded SHA-512 plus a line terminator
+    pub(crate) const MAX_TRAILER_SIZE: u64 = 130;
+
+    /// Name of the digest algorithm most likely used, inferred from the digest size.
+    ///
+    /// `LiME` does not record the algorithm in the trailer, so this is only a hint based on the
+    /// algorithms supported by the kernel crypto API `LiME` relies on.
+    pub fn algorithm_hint(&self) -> Option<&'static str> {
+        match self.value.len() {
+            16 => Some("md5"),
+            20 => Some("sha1"),
+            28 => Some("sha224"),
+            32 => Some("sha256"),
+            48 => Some("sha384"),
+            64 => Some("sha512"),
+            _ => None,
+        }
+    }
+
+    /// Try to interpret `trailer`, found at `offset` in the file, as a digest trailer.
+    pub(crate) fn from_trailer(trailer: &[u8], offset: u64) -> Option<Self> {
+        if trailer.starts_with(&LimeHeader::MAGIC.to_le_bytes()) {
+            return None;
+        }
+
+        let hex = std::str::from_utf8(trailer).ok()?.trim_end();
+        if hex.is_empty() || hex.len() % 2 != 0 || !hex.is_ascii() {
+            return None;
+        }
+
+        let value = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        let digest = Self {
+            value,
+            covered_len: offset,
+        };
+        digest.algorithm_hint().map(|_| digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digest_trailer_is_recognized() {
+        let digest = LimeDigest::from_trailer(b"d41d8cd98f00b204e9800998ecf8427e\n", 0x20).unwrap();
+        assert_eq!(digest.algorithm_hint(), Some("md5"));
+        assert_eq!(digest.value[..2], [0xd4, 0x1d]);
+        assert_eq!(digest.covered_len, 0x20);
+
+        assert_eq!(LimeDigest::from_trailer(b"d41d8cd9", 0x20), None);
+        assert_eq!(
+            LimeDigest::from_trailer(&[0x45, 0x4d, 0x69, 0x4c], 0x20),
+            None
+        );
+    }
+}
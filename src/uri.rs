@@ -0,0 +1,345 @@
+use memflow::prelude::v1::*;
+
+use std::borrow::Cow;
+use std::path::Path;
+
+/// Backend a connector target points to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DumpTarget<'a> {
+    /// Dump file on the local filesystem
+    File(Cow<'a, str>),
+    /// Dump split across the listed files, in order
+    Split(Vec<Cow<'a, str>>),
+    /// Dump split across the files whose name is the one of the path, ending with `*`,
+    /// followed by a part number
+    Pattern(Cow<'a, str>),
+    /// Dump stored as `member` of the zip or tar archive at `archive`
+    Archive {
+        archive: Cow<'a, str>,
+        member: &'a str,
+    },
+    /// Dumps merged into one physical address space, each with the offset added to its
+    /// addresses
+    Composed(Vec<(Cow<'a, str>, u64)>),
+    /// Dump acquired from `destination`, `[user@]host`, over SSH, read from the remote
+    /// `path` unless it is empty
+    Ssh {
+        destination: &'a str,
+        port: Option<u16>,
+        path: Cow<'a, str>,
+    },
+    /// Dump streamed by `LiME` in its TCP mode, listening at `address`, `host:port`
+    Tcp { address: &'a str },
+}
+
+impl<'a> DumpTarget<'a> {
+    /// Parse a connector target, either a plain path or a `scheme://` URI.
+    ///
+    /// Only `file://` URIs are supported, with an empty or `localhost` host and a
+    /// percent-encoded absolute path (e.g. `file:///evidence/dump%201.lime`). The parts of a
+    /// split dump are given either as a comma-separated list or as a path ending with `*`.
+    /// A dump inside a zip or tar archive is given as the path of the archive followed by `!`
+    /// and the path of the member (e.g. `case.zip!memory/dump.lime`). Dumps merged into one
+    /// address space are separated by `;`, each optionally followed by `@` and the offset
+    /// added to its addresses (e.g. `node0.lime;node1.lime@0x1000000000`). A dump acquired
+    /// over SSH is given as an `ssh://[user@]host[:port]/path` URI, the path being empty when
+    /// a remote command produces the dump. A dump streamed by `LiME` listening in its TCP mode
+    /// is given as a `tcp://host:port` URI. An existing file is opened whole even if its path
+    /// holds a `,` or a `;`, and a part of a composed target naming an existing file has no
+    /// offset even if its path holds a `@`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the scheme is not supported or the URI is malformed
+    ///
+    pub(crate) fn parse(target: &'a str) -> Result<Self> {
+        if let Some((scheme, rest)) = split_scheme(target) {
+            if scheme.eq_ignore_ascii_case("ssh") {
+                return parse_ssh(target, rest);
+            }
+            if scheme.eq_ignore_ascii_case("tcp") {
+                return parse_tcp(target, rest);
+            }
+        }
+        // A file whose name holds a separator is opened whole.
+        let exists = Path::new(target).exists();
+        if target.contains(';') && !exists {
+            return target
+                .split(';')
+                .map(|part| {
+                    let (path, rebase) = split_rebase(part)?;
+                    Ok((parse_path(path)?, rebase))
+                })
+                .collect::<Result<_>>()
+                .map(Self::Composed);
+        }
+        if target.contains(',') && !exists {
+            return target
+                .split(',')
+                .map(parse_path)
+                .collect::<Result<_>>()
+                .map(Self::Split);
+        }
+        if let Some((archive, member)) = split_archive(target) {
+            return Ok(Self::Archive {
+                archive: parse_path(archive)?,
+                member,
+            });
+        }
+        let path = parse_path(target)?;
+        Ok(match path.ends_with('*') {
+            true => Self::Pattern(path),
+            false => Self::File(path),
+        })
+    }
+}
+
+/// Get the path of a single file target, either a plain path or a `scheme://` URI.
+fn parse_path(target: &str) -> Result<Cow<'_, str>> {
+    let Some((scheme, rest)) = split_scheme(target) else {
+        return Ok(Cow::Borrowed(target));
+    };
+
+    match scheme.to_lowercase().as_str() {
+        "file" => {
+            let path = match rest.find('/') {
+                Some(0) => rest,
+                Some(start) if rest[..start].eq_ignore_ascii_case("localhost") => &rest[start..],
+                _ => {
+                    return Err(
+                        Error(ErrorOrigin::Connector, ErrorKind::InvalidPath).log_error(format!(
+                            "file URI {} must have an empty or `localhost` host",
+                            target
+                        )),
+                    )
+                }
+            };
+            percent_decode(path).ok_or_else(|| {
+                Error(ErrorOrigin::Connector, ErrorKind::InvalidPath)
+                    .log_error(format!("file URI {} is not properly encoded", target))
+            })
+        }
+        _ => Err(
+            Error(ErrorOrigin::Connector, ErrorKind::NotSupported).log_error(format!(
+                "target scheme `{}` is not supported by the LiME connector",
+                scheme
+            )),
+        ),
+    }
+}
+
+/// Parse the part `rest` of the `ssh://` URI `target` following the scheme.
+fn parse_ssh<'a>(target: &str, rest: &'a str) -> Result<DumpTarget<'a>> {
+    let (authority, path) = match rest.find('/') {
+        Some(start) => rest.split_at(start),
+        None => (rest, ""),
+    };
+    let (destination, port) = match authority.rsplit_once(':') {
+        Some((destination, port)) if !port.contains(']') => {
+            let port = port.parse().map_err(|_| {
+                Error(ErrorOrigin::Connector, ErrorKind::InvalidPath)
+                    .log_error(format!("invalid port in the SSH URI {}", target))
+            })?;
+            (destination, Some(port))
+        }
+        _ => (authority, None),
+    };
+    let host = destination
+        .rsplit_once('@')
+        .map_or(destination, |(_, host)| host);
+    if host.is_empty() {
+        return Err(Error(ErrorOrigin::Connector, ErrorKind::InvalidPath)
+            .log_error(format!("SSH URI {} has no host", target)));
+    }
+    // The SSH client would take them for options, e.g. `-oProxyCommand=...`.
+    if destination.starts_with('-') || host.starts_with('-') {
+        return Err(
+            Error(ErrorOrigin::Connector, ErrorKind::InvalidPath).log_error(format!(
+                "SSH URI {} has a user or host starting with `-`",
+                target
+            )),
+        );
+    }
+    let path = match path {
+        "" | "/" => Cow::Borrowed(""),
+        path => percent_decode(path).ok_or_else(|| {
+            Error(ErrorOrigin::Connector, ErrorKind::InvalidPath)
+                .log_error(format!("SSH URI {} is not properly encoded", target))
+        })?,
+    };
+    Ok(DumpTarget::Ssh {
+        destination,
+        port,
+        path,
+    })
+}
+
+/// Parse the part `rest` of the `tcp://` URI `target` following the scheme.
+fn parse_tcp<'a>(target: &str, rest: &'a str) -> Result<DumpTarget<'a>> {
+    let address = rest.strip_suffix('/').unwrap_or(rest);
+    match address.rsplit_once(':') {
+        Some((host, port))
+            if !host.is_empty() && !address.contains('/') && port.parse::<u16>().is_ok() =>
+        {
+            Ok(DumpTarget::Tcp { address })
+        }
+        _ => Err(Error(ErrorOrigin::Connector, ErrorKind::InvalidPath)
+            .log_error(format!("TCP URI {} must be `tcp://host:port`", target))),
+    }
+}
+
+/// Split `target` into the path of a zip or tar archive and the path of a member, returns
+/// `None` if it does not point into an archive.
+fn split_archive(target: &str) -> Option<(&str, &str)> {
+    let (archive, member) = target.split_once('!')?;
+    let lowercase = archive.to_lowercase();
+    let is_archive = lowercase.ends_with(".zip") || lowercase.ends_with(".tar");
+    (is_archive && !member.is_empty()).then_some((archive, member))
+}
+
+/// Split a part of a composed target into its path and the offset following `@`, zero if
+/// there is none or if the part is the path of an existing file.
+fn split_rebase(part: &str) -> Result<(&str, u64)> {
+    let Some((path, rebase)) = part.rsplit_once('@') else {
+        return Ok((part, 0));
+    };
+    if Path::new(part).exists() {
+        return Ok((part, 0));
+    }
+    let rebase = match rebase
+        .strip_prefix("0x")
+        .or_else(|| rebase.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => rebase.parse(),
+    };
+    rebase.map(|rebase| (path, rebase)).map_err(|_| {
+        Error(ErrorOrigin::Connector, ErrorKind::InvalidPath).log_error(format!(
+            "invalid offset in the composed target part {}",
+            part
+        ))
+    })
+}
+
+/// Split `target` into its scheme and the part following `://`, returns `None` if it is not
+/// a URI.
+fn split_scheme(target: &str) -> Option<(&str, &str)> {
+    let (scheme, rest) = target.split_once("://")?;
+    let mut chars = scheme.chars();
+    let valid = chars.next()?.is_ascii_alphabetic()
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then_some((scheme, rest))
+}
+
+/// Decode the `%XX` escapes of `path`, returns `None` if an escape is malformed or the result
+/// is not UTF-8.
+pub(crate) fn percent_decode(path: &str) -> Option<Cow<'_, str>> {
+    if !path.contains('%') {
+        return Some(Cow::Borrowed(path));
+    }
+
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut bytes = path.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte == b'%' {
+            let hex = [bytes.next()?, bytes.next()?];
+            decoded.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            decoded.push(byte);
+        }
+    }
+    String::from_utf8(decoded).ok().map(Cow::Owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets_are_parsed() {
+        assert_eq!(
+            DumpTarget::parse("dumps/a.lime").unwrap(),
+            DumpTarget::File("dumps/a.lime".into())
+        );
+        assert_eq!(
+            DumpTarget::parse("file:///evidence/dump%201.lime").unwrap(),
+            DumpTarget::File("/evidence/dump 1.lime".into())
+        );
+        assert_eq!(
+            DumpTarget::parse("FILE://localhost/a.lime").unwrap(),
+            DumpTarget::File("/a.lime".into())
+        );
+        assert!(DumpTarget::parse("file://server/a.lime").is_err());
+        assert!(DumpTarget::parse("file:///a%2.lime").is_err());
+        assert_eq!(
+            DumpTarget::parse("gopher://example.com/a.lime").unwrap_err(),
+            Error(ErrorOrigin::Connector, ErrorKind::NotSupported)
+        );
+        assert!(DumpTarget::parse("http://example.com/a.lime").is_err());
+        assert_eq!(
+            DumpTarget::parse("a.lime.000,file:///b/a.lime.001").unwrap(),
+            DumpTarget::Split(vec!["a.lime.000".into(), "/b/a.lime.001".into()])
+        );
+        assert_eq!(
+            DumpTarget::parse("file:///b/a.lime.*").unwrap(),
+            DumpTarget::Pattern("/b/a.lime.*".into())
+        );
+        assert!(DumpTarget::parse("a.lime.000,tcp://0.0.0.0:4444").is_err());
+        assert_eq!(
+            DumpTarget::parse("file:///cases/Case%201.ZIP!memory/dump.lime").unwrap(),
+            DumpTarget::Archive {
+                archive: "/cases/Case 1.ZIP".into(),
+                member: "memory/dump.lime"
+            }
+        );
+        assert_eq!(
+            DumpTarget::parse("dumps/why!.lime").unwrap(),
+            DumpTarget::File("dumps/why!.lime".into())
+        );
+        assert_eq!(
+            DumpTarget::parse("node0.lime;file:///b/node1.lime@0x1000000000;dev.lime@4096")
+                .unwrap(),
+            DumpTarget::Composed(vec![
+                ("node0.lime".into(), 0),
+                ("/b/node1.lime".into(), 0x10_0000_0000),
+                ("dev.lime".into(), 0x1000),
+            ])
+        );
+        assert!(DumpTarget::parse("node0.lime;node1.lime@far").is_err());
+        assert_eq!(
+            DumpTarget::parse("ssh://ir@10.0.0.5:2222/var/tmp/mem%201.lime").unwrap(),
+            DumpTarget::Ssh {
+                destination: "ir@10.0.0.5",
+                port: Some(2222),
+                path: "/var/tmp/mem 1.lime".into(),
+            }
+        );
+        assert_eq!(
+            DumpTarget::parse("SSH://[fe80::1]").unwrap(),
+            DumpTarget::Ssh {
+                destination: "[fe80::1]",
+                port: None,
+                path: "".into(),
+            }
+        );
+        assert!(DumpTarget::parse("ssh://host:ssh/a.lime").is_err());
+        assert!(DumpTarget::parse("ssh://ir@/a.lime").is_err());
+        assert!(DumpTarget::parse("ssh://-oProxyCommand=touch%20pwned/x").is_err());
+        assert!(DumpTarget::parse("ssh://-oProxyCommand=id@host/x").is_err());
+        assert!(DumpTarget::parse("ssh://ir@-oProxyCommand=id/x").is_err());
+        assert_eq!(
+            DumpTarget::parse("tcp://10.0.0.5:4444").unwrap(),
+            DumpTarget::Tcp {
+                address: "10.0.0.5:4444"
+            }
+        );
+        assert_eq!(
+            DumpTarget::parse("TCP://[fe80::1]:4444/").unwrap(),
+            DumpTarget::Tcp {
+                address: "[fe80::1]:4444"
+            }
+        );
+        assert!(DumpTarget::parse("tcp://10.0.0.5").is_err());
+        assert!(DumpTarget::parse("tcp://10.0.0.5:4444/a.lime").is_err());
+    }
+}
@@ -0,0 +1,187 @@
+use crate::dumpfile::DumpFile;
+use crate::elf::{corrupt, read_notes, read_program_headers, Elf, Note, PT_LOAD};
+use crate::segment::{open_dump, LimeSegment};
+
+use memflow::prelude::v1::*;
+
+use std::fs::File;
+use std::path::Path;
+
+/// Owner of the note describing a VirtualBox core
+const VBCORE_NAME: &[u8] = b"VBCORE";
+/// Owner of the notes holding the state of a virtual CPU
+const VBCPU_NAME: &[u8] = b"VBCPU";
+/// `n_type` of the `VBCORE` note, `NT_VBOXCORE`
+const NT_VBOXCORE: u32 = 0xb00;
+/// `n_type` of the `VBCPU` notes, `NT_VBOXCPU`
+const NT_VBOXCPU: u32 = 0xb01;
+/// `u32Magic` of the core descriptor, `DBGFCORE_MAGIC`
+const DBGFCORE_MAGIC: u32 = 0xc01a_c0de;
+/// Size of the core descriptor, `DBGFCOREDESCRIPTOR`
+const DESCRIPTOR_SIZE: usize = 0x18;
+/// Alignment of the names and descriptors of the notes written by VirtualBox
+const NOTE_ALIGN: usize = 8;
+
+/// Description of a VirtualBox core file, as written by `VBoxManage debugvm dumpvmcore`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VBoxCore {
+    /// Version of the core format, `DBGFCORE_FMT_VERSION` of the VirtualBox that wrote it
+    pub format_version: u32,
+    /// Version of VirtualBox, as `(major, minor, build)`
+    pub vbox_version: (u32, u32, u32),
+    /// Source revision of VirtualBox
+    pub vbox_revision: u32,
+    /// Number of virtual CPUs of the guest
+    pub cpus: u32,
+    /// Guest-physical ranges of the guest, in the order of their program headers
+    pub ranges: Vec<VBoxCoreRange>,
+}
+
+/// Guest-physical range of a VirtualBox core file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VBoxCoreRange {
+    /// Guest-physical address of the first byte of the range
+    pub phys_addr: u64,
+    /// Offset in the file of the first byte of the range
+    pub file_offset: u64,
+    /// Size in bytes of the range in the guest
+    pub size: u64,
+    /// Bytes of the range stored in the file from its start, zero for the MMIO ranges
+    /// VirtualBox lists without their data
+    pub file_size: u64,
+}
+
+/// Read the description and the guest-physical ranges of the VirtualBox core file at
+/// `path`.
+///
+/// # Errors
+///
+/// Returns `Err` if the file can not be read, is not a 64-bit ELF core file or lacks the
+/// `VBCORE` note of VirtualBox cores
+///
+pub fn vbox_core_info<P: AsRef<Path>>(path: P) -> Result<VBoxCore> {
+    let path = path.as_ref();
+    let mut file = open_dump(path)?;
+    read_core(&mut file, path)
+}
+
+/// Whether the ELF core file at `path` carries the `VBCORE` note of VirtualBox cores.
+pub(crate) fn is_vbox_core(path: &Path) -> bool {
+    let Ok(mut file) = File::open(path) else {
+        return false;
+    };
+    read_program_headers(&mut file, path)
+        .and_then(|(elf, phdrs)| read_notes(&mut file, elf, &phdrs, NOTE_ALIGN))
+        .is_ok_and(|notes| {
+            notes
+                .iter()
+                .any(|note| note.name == VBCORE_NAME && note.n_type == NT_VBOXCORE)
+        })
+}
+
+/// Open the VirtualBox core file at `path` to read the guest-physical memory it holds.
+///
+/// VirtualBox writes one loadable segment per range of the guest-physical address space,
+/// at its guest-physical address. The ranges are those of the guest, not of a machine the
+/// file was read on, so `p_vaddr` means nothing; and MMIO ranges have a segment with a
+/// memory size but no data in the file. Only the bytes stored in the file are mapped.
+///
+/// Returns the file and the segments mapping it, in the order of their physical addresses.
+///
+/// # Errors
+///
+/// Returns `Err` if the file can not be read, is not a 64-bit ELF core file or lacks the
+/// `VBCORE` note of VirtualBox cores
+///
+pub(crate) fn open_vbcore(path: &Path) -> Result<(DumpFile, Vec<LimeSegment>)> {
+    let mut file = open_dump(path)?;
+    let core = read_core(&mut file, path)?;
+    let mut segments = core
+        .ranges
+        .iter()
+        .filter(|range| range.file_size > 0)
+        .map(|range| LimeSegment {
+            s_addr: range.phys_addr,
+            e_addr: range.phys_addr + (range.file_size - 1),
+            file_offset: range.file_offset,
+        })
+        .collect::<Vec<_>>();
+    segments.sort_by_key(|segment| segment.s_addr);
+    log::info!(
+        "Mapping {} of the {} ranges of the core of a {}-CPU guest written by VirtualBox \
+        {}.{}.{}r{}",
+        segments.len(),
+        core.ranges.len(),
+        core.cpus,
+        core.vbox_version.0,
+        core.vbox_version.1,
+        core.vbox_version.2,
+        core.vbox_revision
+    );
+    Ok((file.into(), segments))
+}
+
+/// Read the description and the ranges of the VirtualBox core file `file`.
+fn read_core(file: &mut File, path: &Path) -> Result<VBoxCore> {
+    let (elf, phdrs) = read_program_headers(file, path)?;
+    let notes = read_notes(file, elf, &phdrs, NOTE_ALIGN)?;
+    let mut core = notes
+        .iter()
+        .find(|note| note.name == VBCORE_NAME && note.n_type == NT_VBOXCORE)
+        .map(|note| parse_descriptor(elf, note))
+        .ok_or_else(|| {
+            Error(ErrorOrigin::Connector, ErrorKind::NotSupported).log_error(format!(
+                "{} is not a VirtualBox core file, it has no VBCORE note",
+                path.display()
+            ))
+        })??;
+
+    let cpu_notes = notes
+        .iter()
+        .filter(|note| note.name == VBCPU_NAME && note.n_type == NT_VBOXCPU)
+        .count();
+    if cpu_notes != core.cpus as usize {
+        log::warn!(
+            "The VirtualBox core describes {} CPUs but holds the state of {}",
+            core.cpus,
+            cpu_notes
+        );
+    }
+
+    core.ranges = phdrs
+        .iter()
+        .filter(|phdr| phdr.p_type == PT_LOAD && phdr.memsz > 0)
+        .map(|phdr| {
+            if phdr.filesz > phdr.memsz || phdr.paddr.checked_add(phdr.memsz - 1).is_none() {
+                return Err(corrupt(format!(
+                    "VirtualBox core range of {:#x} bytes at {:#x}, {:#x} of them in the file, \
+                    is invalid",
+                    phdr.memsz, phdr.paddr, phdr.filesz
+                )));
+            }
+            Ok(VBoxCoreRange {
+                phys_addr: phdr.paddr,
+                file_offset: phdr.offset,
+                size: phdr.memsz,
+                file_size: phdr.filesz,
+            })
+        })
+        .collect::<Result<_>>()?;
+    Ok(core)
+}
+
+/// Parse the `DBGFCOREDESCRIPTOR` held by the `VBCORE` note, the ranges being left empty.
+fn parse_descriptor(elf: Elf, note: &Note) -> Result<VBoxCore> {
+    let desc = &note.desc;
+    if desc.len() < DESCRIPTOR_SIZE || elf.u32_at(desc, 0) != DBGFCORE_MAGIC {
+        return Err(corrupt("The VBCORE note has no valid descriptor".into()));
+    }
+    let version = elf.u32_at(desc, 0xc);
+    Ok(VBoxCore {
+        format_version: elf.u32_at(desc, 0x4),
+        vbox_version: (version >> 24, (version >> 16) & 0xff, version & 0xffff),
+        vbox_revision: elf.u32_at(desc, 0x10),
+        cpus: elf.u32_at(desc, 0x14),
+        ranges: Vec::new(),
+    })
+}
@@ -0,0 +1,162 @@
+use crate::config::ParseMode;
+use crate::segment::{open_dump, parse_dump};
+
+use memflow::prelude::v1::*;
+
+use std::fmt::Write;
+use std::path::Path;
+
+/// Python class Volatility3 instantiates for `LiME` files
+pub const VOLATILITY_LIME_LAYER: &str = "volatility3.framework.layers.lime.LimeLayer";
+
+/// Segment of a Volatility3 segmented layer, in the order of the tuples of its
+/// `_segments` list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VolatilitySegment {
+    /// Physical address of the segment
+    pub offset: u64,
+    /// Offset of the segment data in the file
+    pub file_offset: u64,
+    /// Size in bytes of the segment in the physical address space
+    pub length: u64,
+    /// Size in bytes of the segment data in the file
+    pub mapped_length: u64,
+}
+
+/// Layer definition of a `LiME` file as Volatility3 builds it, returned by
+/// [`volatility_layer`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VolatilityLayer {
+    /// `file://` URL of the dump, as Volatility3 expects its locations
+    pub location: String,
+    /// Segments of the layer, sorted by physical address
+    pub segments: Vec<VolatilitySegment>,
+}
+
+impl VolatilityLayer {
+    /// Serialize the layer to a JSON object holding the layer class, the location of the
+    /// dump and the segments as `[offset, file_offset, length, mapped_length]` arrays.
+    pub fn to_json(&self) -> String {
+        let mut json = format!(
+            "{{\n  \"class\": \"{}\",\n  \"location\": \"{}\",\n  \"segments\": [",
+            VOLATILITY_LIME_LAYER, self.location
+        );
+        for (i, segment) in self.segments.iter().enumerate() {
+            let _ = write!(
+                json,
+                "{}\n    [{}, {}, {}, {}]",
+                if i == 0 { "" } else { "," },
+                segment.offset,
+                segment.file_offset,
+                segment.length,
+                segment.mapped_length
+            );
+        }
+        json.push_str(if self.segments.is_empty() {
+            "]\n}\n"
+        } else {
+            "\n  ]\n}\n"
+        });
+        json
+    }
+
+    /// Command line arguments of `vol` opening the dump this layer was built from.
+    ///
+    /// `--single-location` sets the `automagic.LayerStacker.single_location` option to the
+    /// URL of the dump, from which the layer stacker instantiates the `LimeLayer` described
+    /// here.
+    pub fn as_volatility_args(&self) -> Vec<String> {
+        vec!["--single-location".to_owned(), self.location.clone()]
+    }
+}
+
+/// Parse the `LiME` file at `path` into the layer Volatility3 builds from it, so that both
+/// tools map the same physical ranges at the same file offsets.
+///
+/// # Errors
+///
+/// Returns `Err` if an error occurred while reading or parsing the file, or if Volatility3
+/// would refuse it, a range starting below the end address of the previous one
+///
+pub fn volatility_layer<P: AsRef<Path>>(path: P, mode: ParseMode) -> Result<VolatilityLayer> {
+    let path = path.as_ref();
+    let mut lime_dump = open_dump(path)?;
+    let layout = parse_dump(&mut lime_dump, mode.into())?;
+
+    // Volatility3 keeps the highest end seen so far and rejects a range starting below it.
+    let mut max_addr = 0;
+    let mut segments = Vec::with_capacity(layout.segments.len());
+    for segment in layout.segments {
+        if segment.s_addr < max_addr {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::InvalidExeFile).log_error(format!(
+                    "LiME range {:#x}-{:#x} starts below the previous one, Volatility3 can not \
+                     load it",
+                    segment.s_addr, segment.e_addr
+                )),
+            );
+        }
+        max_addr = segment.e_addr;
+        segments.push(VolatilitySegment {
+            offset: segment.s_addr,
+            file_offset: segment.file_offset,
+            length: segment.size(),
+            mapped_length: segment.size(),
+        });
+    }
+
+    let absolute = path
+        .canonicalize()
+        .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?;
+    Ok(VolatilityLayer {
+        location: file_url(&absolute),
+        segments,
+    })
+}
+
+/// `file://` URL of the absolute `path`, percent-encoding the bytes outside the unreserved
+/// set of RFC 3986.
+fn file_url(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    // Windows canonical paths are verbatim, `\\?\C:\...`.
+    let path = path
+        .strip_prefix(r"\\?\")
+        .unwrap_or(&path)
+        .replace('\\', "/");
+    let mut url = String::from("file://");
+    if !path.starts_with('/') {
+        url.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                url.push(byte as char)
+            }
+            // Drive letters of Windows paths.
+            b':' if url.len() == "file:///C".len() => url.push(':'),
+            _ => {
+                let _ = write!(url, "%{:02X}", byte);
+            }
+        }
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_are_percent_encoded() {
+        assert_eq!(
+            file_url(Path::new("/evidence/case 1/mem.lime")),
+            "file:///evidence/case%201/mem.lime"
+        );
+        assert_eq!(
+            file_url(Path::new("C:\\dumps\\mem.lime")),
+            "file:///C:/dumps/mem.lime"
+        );
+    }
+}
@@ -0,0 +1,666 @@
+use crate::checksum::{BlockHasher, LimeBlockManifest};
+use crate::config::ParseMode;
+use crate::header::LimeHeader;
+use crate::segment::{parse_dump, LimeSegment};
+use crate::timestamps::{LimeSegmentTime, LimeTimestamps};
+
+use memflow::prelude::v1::*;
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, IoSlice, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Default size of the buffer copying ranges from their source
+const DEFAULT_CHUNK_SIZE: usize = 1 << 20;
+
+/// Writer of `LiME` files
+///
+/// Every range passed to [`write_range`](Self::write_range) is written as a version 1 header
+/// followed by its data, in call order. With [`with_zero_skipping`](Self::with_zero_skipping)
+/// the pages of a range that are entirely zero are left out, the range being split around
+/// them, so that dumps of mostly idle machines shrink while staying spec-compliant. Readers
+/// map the missing pages as gaps (see `fill_gaps`). With
+/// [`with_segment_size`](Self::with_segment_size) large ranges are split into several
+/// sections, for tools handling many moderate sections better than one enormous one.
+///
+/// With [`with_block_checksums`](Self::with_block_checksums) the CRC-32 of every block of the
+/// sections written is recorded for a block manifest stored next to the dump, and with
+/// [`with_timestamps`](Self::with_timestamps) the time every section was acquired.
+///
+/// An interrupted acquisition is picked up where it stopped with [`resume`](Self::resume).
+#[derive(Debug)]
+pub struct LimeWriter<W: Write> {
+    out: W,
+    /// Size of the pages checked for zeros, `None` if every byte is written
+    zero_page_size: Option<u64>,
+    /// Largest section written, sections being aligned on it, `None` if ranges are not split
+    segment_size: Option<u64>,
+    /// Size of the buffer of [`Self::write_range_from`]
+    chunk_size: usize,
+    ranges_written: u64,
+    bytes_written: u64,
+    bytes_skipped: u64,
+    /// Address following the last range written, `None` if none was or it reached the top
+    /// of the address space
+    next_address: Option<u64>,
+    /// Offset in the file of the next byte written
+    offset: u64,
+    /// Checksums of the blocks written, `None` if they are not recorded
+    blocks: Option<BlockHasher>,
+    /// Acquisition times of the sections written, `None` if they are not recorded
+    times: Option<LimeTimestamps>,
+}
+
+impl<W: Write> LimeWriter<W> {
+    /// Write a `LiME` file to `out`, from its start.
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            zero_page_size: None,
+            segment_size: None,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            ranges_written: 0,
+            bytes_written: 0,
+            bytes_skipped: 0,
+            next_address: None,
+            offset: 0,
+            blocks: None,
+            times: None,
+        }
+    }
+
+    /// Leave out the pages of `page_size` bytes, aligned on their size in physical memory,
+    /// that are entirely zero. The first and last page of a range may be partial.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if `page_size` is zero
+    ///
+    pub fn with_zero_skipping(mut self, page_size: u64) -> Result<Self> {
+        if page_size == 0 {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::InvalidArgument)
+                .log_error("The zero page size can not be zero"));
+        }
+        self.zero_page_size = Some(page_size);
+        Ok(self)
+    }
+
+    /// Split the ranges written at the physical addresses multiple of `segment_size`, so
+    /// that no section holds more than `segment_size` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if `segment_size` is zero
+    ///
+    pub fn with_segment_size(mut self, segment_size: u64) -> Result<Self> {
+        if segment_size == 0 {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::InvalidArgument)
+                .log_error("The segment size can not be zero"));
+        }
+        self.segment_size = Some(segment_size);
+        Ok(self)
+    }
+
+    /// Copy ranges from their source in chunks of `chunk_size` bytes with
+    /// [`write_range_from`](Self::write_range_from), 1 MiB by default. The chunk size bounds
+    /// the memory used while copying.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if `chunk_size` is zero
+    ///
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Result<Self> {
+        if chunk_size == 0 {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::InvalidArgument)
+                .log_error("The chunk size can not be zero"));
+        }
+        self.chunk_size = chunk_size;
+        Ok(self)
+    }
+
+    /// Record the CRC-32 of the blocks of `block_size` bytes, aligned on their size in
+    /// physical memory, of the sections written from now on, see
+    /// [`block_manifest`](Self::block_manifest).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if `block_size` is zero
+    ///
+    pub fn with_block_checksums(mut self, block_size: u64) -> Result<Self> {
+        if block_size == 0 {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::InvalidArgument)
+                .log_error("The block size can not be zero"));
+        }
+        self.blocks = Some(BlockHasher::new(block_size));
+        Ok(self)
+    }
+
+    /// Record the time every section written from now on was started and finished, see
+    /// [`timestamps`](Self::timestamps). Sections copied with
+    /// [`write_range_from`](Self::write_range_from) are started before their first byte is
+    /// read from the source, so their times cover the acquisition of their memory.
+    pub fn with_timestamps(mut self) -> Self {
+        self.times = Some(LimeTimestamps::default());
+        self
+    }
+
+    /// Write the physical range starting at `s_addr` holding `data`.
+    ///
+    /// Nothing is written if `data` is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the range does not fit in the address space or an error occurred
+    /// while writing
+    ///
+    pub fn write_range(&mut self, s_addr: u64, data: &[u8]) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        if s_addr.checked_add(data.len() as u64 - 1).is_none() {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::InvalidMemorySize).log_error(format!(
+                    "Range of {} bytes at {:#x} does not fit in the address space",
+                    data.len(),
+                    s_addr
+                )),
+            );
+        }
+        self.next_address = (s_addr + data.len() as u64 - 1).checked_add(1);
+        let Some(page_size) = self.zero_page_size else {
+            return self.write_section(s_addr, data);
+        };
+
+        // Start of the run of non-zero pages being collected, relative to `data`.
+        let mut run_start = None;
+        let mut start = 0;
+        while start < data.len() {
+            let addr = s_addr + start as u64;
+            let page_end = usize::try_from(page_size - addr % page_size)
+                .map_or(data.len(), |len| data.len().min(start.saturating_add(len)));
+            let is_zero = data[start..page_end].iter().all(|byte| *byte == 0);
+            match (is_zero, run_start) {
+                (false, None) => run_start = Some(start),
+                (true, Some(run)) => {
+                    self.write_section(s_addr + run as u64, &data[run..start])?;
+                    run_start = None;
+                }
+                _ => {}
+            }
+            if is_zero {
+                self.bytes_skipped += (page_end - start) as u64;
+            }
+            start = page_end;
+        }
+        match run_start {
+            Some(run) => self.write_section(s_addr + run as u64, &data[run..]),
+            None => Ok(()),
+        }
+    }
+
+    /// Write the physical range starting at `s_addr` holding the `len` bytes read from
+    /// `source`, copying them a chunk at a time (see [`with_chunk_size`](Self::with_chunk_size)).
+    ///
+    /// With zero skipping every chunk is written as by [`write_range`](Self::write_range),
+    /// so sections may also end at chunk boundaries.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the range does not fit in the address space, if `source` ends before
+    /// `len` bytes or an error occurred while reading it or writing
+    ///
+    pub fn write_range_from<R: Read>(
+        &mut self,
+        s_addr: u64,
+        len: u64,
+        source: &mut R,
+    ) -> Result<()> {
+        if len == 0 {
+            return Ok(());
+        }
+        let e_addr = s_addr.checked_add(len - 1).ok_or_else(|| {
+            Error(ErrorOrigin::Connector, ErrorKind::InvalidMemorySize).log_error(format!(
+                "Range of {} bytes at {:#x} does not fit in the address space",
+                len, s_addr
+            ))
+        })?;
+        let mut buf = vec![0u8; (self.chunk_size as u64).min(len) as usize];
+        let mut read_chunk = |buf: &mut [u8]| {
+            source.read_exact(buf).map_err(|_| {
+                Error(ErrorOrigin::Connector, ErrorKind::UnableToReadMemory)
+                    .log_error("Unable to read the source of the range")
+            })
+        };
+
+        if self.zero_page_size.is_some() {
+            let mut addr = s_addr;
+            while addr <= e_addr {
+                let chunk = &mut buf[..(e_addr - addr + 1).min(self.chunk_size as u64) as usize];
+                read_chunk(chunk)?;
+                self.write_range(addr, chunk)?;
+                match addr.checked_add(chunk.len() as u64) {
+                    Some(next) => addr = next,
+                    None => break,
+                }
+            }
+            return Ok(());
+        }
+
+        self.next_address = e_addr.checked_add(1);
+        for (seg_s_addr, seg_e_addr) in self.segments(s_addr, e_addr) {
+            let started = SystemTime::now();
+            LimeHeader::new(seg_s_addr, seg_e_addr).write_to(&mut self.out)?;
+            self.offset += LimeHeader::HEADER_SIZE_IN_BYTES as u64;
+            let mut addr = seg_s_addr;
+            let mut left = seg_e_addr - seg_s_addr + 1;
+            while left > 0 {
+                let chunk = &mut buf[..left.min(self.chunk_size as u64) as usize];
+                read_chunk(chunk)?;
+                self.out.write_all(chunk).map_err(|_| {
+                    Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+                        .log_error("Unable to write the memory section")
+                })?;
+                if let Some(blocks) = self.blocks.as_mut() {
+                    blocks.update(addr, self.offset, chunk);
+                }
+                self.offset += chunk.len() as u64;
+                addr = addr.wrapping_add(chunk.len() as u64);
+                left -= chunk.len() as u64;
+            }
+            if let Some(blocks) = self.blocks.as_mut() {
+                blocks.end_block();
+            }
+            self.record_time(seg_s_addr, seg_e_addr, started);
+            self.ranges_written += 1;
+            self.bytes_written += seg_e_addr - seg_s_addr + 1;
+        }
+        Ok(())
+    }
+
+    /// Write the physical ranges of `ranges`, pairs of a starting address and the data
+    /// following it, as few sections as possible.
+    ///
+    /// The ranges are sorted by address and the ones that are contiguous in physical memory
+    /// are merged into a single section, written with one vectored write of its header and
+    /// buffers. With zero skipping every range is written as by
+    /// [`write_range`](Self::write_range). Empty buffers are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if a range does not fit in the address space, if two ranges overlap or
+    /// if an error occurred while writing
+    ///
+    pub fn write_ranges<'a, I>(&mut self, ranges: I) -> Result<()>
+    where
+        I: IntoIterator<Item = (u64, &'a [u8])>,
+    {
+        let mut ranges = ranges
+            .into_iter()
+            .filter(|(_, data)| !data.is_empty())
+            .map(|(s_addr, data)| {
+                s_addr
+                    .checked_add(data.len() as u64 - 1)
+                    .map(|e_addr| (s_addr, e_addr, data))
+                    .ok_or_else(|| {
+                        Error(ErrorOrigin::Connector, ErrorKind::InvalidMemorySize).log_error(
+                            format!(
+                                "Range of {} bytes at {:#x} does not fit in the address space",
+                                data.len(),
+                                s_addr
+                            ),
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        ranges.sort_by_key(|(s_addr, _, _)| *s_addr);
+        if let Some(pair) = ranges.windows(2).find(|pair| pair[1].0 <= pair[0].1) {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::InvalidArgument).log_error(format!(
+                    "Ranges {:#x}-{:#x} and {:#x}-{:#x} overlap",
+                    pair[0].0, pair[0].1, pair[1].0, pair[1].1
+                )),
+            );
+        }
+
+        if self.zero_page_size.is_some() {
+            return ranges
+                .into_iter()
+                .try_for_each(|(s_addr, _, data)| self.write_range(s_addr, data));
+        }
+        let mut ranges = ranges.into_iter().peekable();
+        while let Some((s_addr, mut e_addr, data)) = ranges.next() {
+            let mut run = vec![data];
+            while let Some((_, next_e_addr, data)) =
+                ranges.next_if(|(next_s_addr, _, _)| e_addr.checked_add(1) == Some(*next_s_addr))
+            {
+                e_addr = next_e_addr;
+                run.push(data);
+            }
+            self.write_run(s_addr, e_addr, &run)?;
+            self.next_address = e_addr.checked_add(1);
+        }
+        Ok(())
+    }
+
+    /// Number of sections written so far, ranges split around zero pages or at segment
+    /// boundaries counting once per piece.
+    pub fn ranges_written(&self) -> u64 {
+        self.ranges_written
+    }
+
+    /// Number of bytes of memory written so far, headers excluded.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Number of bytes of memory left out because they were in zero pages.
+    pub fn bytes_skipped(&self) -> u64 {
+        self.bytes_skipped
+    }
+
+    /// Physical address following the last range written, from which an acquisition
+    /// continues. `None` if no range was written yet, or if the last one reached the top of
+    /// the address space.
+    pub fn next_address(&self) -> Option<u64> {
+        self.next_address
+    }
+
+    /// Checksums of the blocks of the sections written so far, `None` unless
+    /// [`with_block_checksums`](Self::with_block_checksums) was called. Store them next to
+    /// the dump with [`LimeBlockManifest::write`], at
+    /// [`default_blocks_sidecar`](crate::default_blocks_sidecar) for `verify_blocks=auto`.
+    pub fn block_manifest(&self) -> Option<&LimeBlockManifest> {
+        self.blocks.as_ref().map(BlockHasher::manifest)
+    }
+
+    /// Acquisition times of the sections written so far, `None` unless
+    /// [`with_timestamps`](Self::with_timestamps) was called. Store them next to the dump
+    /// with [`LimeTimestamps::write`], at
+    /// [`default_timestamps_sidecar`](crate::default_timestamps_sidecar) for
+    /// [`read_timed_segments`](crate::read_timed_segments).
+    pub fn timestamps(&self) -> Option<&LimeTimestamps> {
+        self.times.as_ref()
+    }
+
+    /// Flush the file and get back the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an error occurred while flushing
+    ///
+    pub fn finish(mut self) -> Result<W> {
+        self.out.flush().map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+                .log_error("Unable to flush the LiME file")
+        })?;
+        Ok(self.out)
+    }
+
+    /// Sections holding the range `[s_addr, e_addr]`, split at the segment boundaries.
+    fn segments(&self, s_addr: u64, e_addr: u64) -> Vec<(u64, u64)> {
+        let mut segments = Vec::new();
+        let mut addr = s_addr;
+        loop {
+            let end = self
+                .segment_size
+                .and_then(|size| (addr - addr % size).checked_add(size - 1))
+                .map_or(e_addr, |end| end.min(e_addr));
+            segments.push((addr, end));
+            if end == e_addr {
+                return segments;
+            }
+            addr = end + 1;
+        }
+    }
+
+    /// Write the range `[s_addr, e_addr]` held by the buffers of `run`, each section being a
+    /// header followed by its data written with vectored writes.
+    fn write_run(&mut self, s_addr: u64, e_addr: u64, run: &[&[u8]]) -> Result<()> {
+        let mut run = run.iter().copied();
+        let mut current: &[u8] = &[];
+        for (seg_s_addr, seg_e_addr) in self.segments(s_addr, e_addr) {
+            let mut pieces = Vec::new();
+            let mut left = seg_e_addr - seg_s_addr + 1;
+            while left > 0 {
+                if current.is_empty() {
+                    current = run.next().unwrap_or_default();
+                }
+                let (head, tail) = current.split_at(left.min(current.len() as u64) as usize);
+                pieces.push(head);
+                left -= head.len() as u64;
+                current = tail;
+            }
+            let started = SystemTime::now();
+            let header = LimeHeader::new(seg_s_addr, seg_e_addr).to_bytes();
+            let mut slices = vec![IoSlice::new(&header)];
+            slices.extend(pieces.iter().map(|piece| IoSlice::new(piece)));
+            write_all_vectored(&mut self.out, &mut slices).map_err(|_| {
+                Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+                    .log_error("Unable to write the memory section")
+            })?;
+            self.offset += LimeHeader::HEADER_SIZE_IN_BYTES as u64;
+            let mut addr = seg_s_addr;
+            for piece in pieces {
+                if let Some(blocks) = self.blocks.as_mut() {
+                    blocks.update(addr, self.offset, piece);
+                }
+                self.offset += piece.len() as u64;
+                addr = addr.wrapping_add(piece.len() as u64);
+            }
+            if let Some(blocks) = self.blocks.as_mut() {
+                blocks.end_block();
+            }
+            self.record_time(seg_s_addr, seg_e_addr, started);
+            self.ranges_written += 1;
+            self.bytes_written += seg_e_addr - seg_s_addr + 1;
+        }
+        Ok(())
+    }
+
+    /// Record the times of the section `[s_addr, e_addr]` just written, started at `started`,
+    /// if they are recorded.
+    fn record_time(&mut self, s_addr: u64, e_addr: u64, started: SystemTime) {
+        if let Some(times) = self.times.as_mut() {
+            times.sections.push(LimeSegmentTime {
+                s_addr,
+                e_addr,
+                file_offset: self.offset - (e_addr - s_addr + 1),
+                started,
+                finished: SystemTime::now(),
+            });
+        }
+    }
+
+    /// Write the sections holding `data`, `data` not being empty.
+    fn write_section(&mut self, s_addr: u64, data: &[u8]) -> Result<()> {
+        self.write_run(s_addr, s_addr + data.len() as u64 - 1, &[data])
+    }
+}
+
+impl LimeWriter<File> {
+    /// Resume writing the `LiME` file at `path` after an interrupted acquisition.
+    ///
+    /// The file is parsed and cut after its last complete range, dropping a range or a
+    /// header cut short, and the ranges written next are appended. The counters start from
+    /// the complete ranges and [`next_address`](Self::next_address) is the address following
+    /// the last one, so that the acquisition continues from there. An empty file is written
+    /// from its start.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file can not be opened for writing, if it is not a `LiME` file or
+    /// ends with a digest trailer, sealing a complete dump
+    ///
+    pub fn resume<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(|_| {
+                Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+                    .log_error("Unable to open the LiME file for writing")
+            })?;
+        let layout = parse_dump(&mut file, ParseMode::Strict.into())?;
+        if layout.digest.is_some() {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::AlreadyExists)
+                .log_error("The LiME file is sealed by a digest trailer"));
+        }
+
+        let file_len = file
+            .metadata()
+            .map_err(|_| Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile))?
+            .len();
+
+        let mut segments = layout.segments;
+        // A range cut short is returned with the part present, up to the end of the file.
+        if layout.truncation.is_some()
+            && segments
+                .last()
+                .is_some_and(|segment| segment.file_offset + segment.size() == file_len)
+        {
+            segments.pop();
+        }
+        let end = segments
+            .last()
+            .map_or(0, |segment| segment.file_offset + segment.size());
+        file.set_len(end)
+            .and_then(|_| file.seek(SeekFrom::Start(end)))
+            .map_err(|_| {
+                Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+                    .log_error("Unable to cut the incomplete range of the LiME file")
+            })?;
+
+        let mut writer = Self::new(file);
+        writer.ranges_written = segments.len() as u64;
+        writer.bytes_written = segments.iter().map(LimeSegment::size).sum();
+        writer.next_address = segments
+            .last()
+            .and_then(|segment| segment.e_addr.checked_add(1));
+        writer.offset = end;
+        Ok(writer)
+    }
+}
+
+/// Write every buffer of `slices` to `out`, like [`Write::write_all`] does for one buffer.
+fn write_all_vectored<W: Write>(out: &mut W, mut slices: &mut [IoSlice]) -> io::Result<()> {
+    IoSlice::advance_slices(&mut slices, 0);
+    while !slices.is_empty() {
+        match out.write_vectored(slices) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(written) => IoSlice::advance_slices(&mut slices, written),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Appender of ranges to an evolving `LiME` file
+///
+/// Every range passed to [`append`](Self::append) is written after the ones already in the
+/// file and synced to disk, so that regions captured periodically accumulate into one
+/// evidence file. Ranges overlapping one already in the file are refused, keeping every
+/// physical address described by a single header.
+#[derive(Debug)]
+pub struct LimeAppender {
+    writer: LimeWriter<File>,
+    /// Inclusive ranges of the file, sorted by address
+    ranges: Vec<(u64, u64)>,
+}
+
+impl LimeAppender {
+    /// Open the `LiME` file at `path` to append ranges to it, creating it if it does not
+    /// exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file can not be opened for writing, if it is not a `LiME` file,
+    /// if it is truncated (see [`LimeWriter::resume`]) or ends with a digest trailer
+    ///
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|_| {
+                Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+                    .log_error("Unable to open the LiME file for writing")
+            })?;
+        let layout = parse_dump(&mut file, ParseMode::Strict.into())?;
+        if layout.truncation.is_some() || !layout.anomalies.is_empty() {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::InvalidExeFile)
+                .log_error("The LiME file is incomplete, resume it first"));
+        }
+        if layout.digest.is_some() {
+            return Err(Error(ErrorOrigin::Connector, ErrorKind::AlreadyExists)
+                .log_error("The LiME file is sealed by a digest trailer"));
+        }
+        file.seek(SeekFrom::End(0)).map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToSeekFile)
+                .log_error("Unable to seek to the end of the LiME file")
+        })?;
+
+        let mut ranges = layout
+            .segments
+            .iter()
+            .map(|segment| (segment.s_addr, segment.e_addr))
+            .collect::<Vec<_>>();
+        ranges.sort_unstable();
+        let mut writer = LimeWriter::new(file);
+        writer.ranges_written = ranges.len() as u64;
+        writer.bytes_written = layout.segments.iter().map(LimeSegment::size).sum();
+        Ok(Self { writer, ranges })
+    }
+
+    /// Append the physical range starting at `s_addr` holding `data` and sync it to disk.
+    ///
+    /// Nothing is written if `data` is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the range does not fit in the address space, if it overlaps a range
+    /// of the file or if an error occurred while writing
+    ///
+    pub fn append(&mut self, s_addr: u64, data: &[u8]) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let e_addr = s_addr.checked_add(data.len() as u64 - 1).ok_or_else(|| {
+            Error(ErrorOrigin::Connector, ErrorKind::InvalidMemorySize).log_error(format!(
+                "Range of {} bytes at {:#x} does not fit in the address space",
+                data.len(),
+                s_addr
+            ))
+        })?;
+        let index = self.ranges.partition_point(|range| range.1 < s_addr);
+        if let Some(range) = self.ranges.get(index).filter(|range| range.0 <= e_addr) {
+            return Err(
+                Error(ErrorOrigin::Connector, ErrorKind::InvalidArgument).log_error(format!(
+                    "Range {:#x}-{:#x} overlaps range {:#x}-{:#x} of the LiME file",
+                    s_addr, e_addr, range.0, range.1
+                )),
+            );
+        }
+
+        self.writer.write_range(s_addr, data)?;
+        self.writer.out.sync_data().map_err(|_| {
+            Error(ErrorOrigin::Connector, ErrorKind::UnableToWriteFile)
+                .log_error("Unable to sync the LiME file")
+        })?;
+        self.ranges.insert(index, (s_addr, e_addr));
+        Ok(())
+    }
+
+    /// Inclusive physical ranges of the file, sorted by address.
+    pub fn ranges(&self) -> &[(u64, u64)] {
+        &self.ranges
+    }
+
+    /// Number of bytes of memory in the file, headers excluded.
+    pub fn bytes_written(&self) -> u64 {
+        self.writer.bytes_written()
+    }
+}
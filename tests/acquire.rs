@@ -0,0 +1,81 @@
+mod common;
+
+use common::{header_bytes, tmp_path};
+use memflow::prelude::ErrorKind;
+use memflow_lime::{system_ram_ranges, LimeWriter};
+use std::fs;
+
+const IOMEM: &str = "\
+00000000-00000fff : Reserved
+00001000-00002fff : System RAM
+000a0000-000fffff : PCI Bus 0000:00
+00100000-00100fff : System RAM
+  00100000-001007ff : Kernel code
+  00100800-00100fff : Crash kernel
+fed00000-fed003ff : HPET 0
+";
+
+/// The top-level `System RAM` ranges of `/proc/iomem` are read from the memory device at their
+/// physical address and written as one section each.
+#[test]
+fn system_ram_is_acquired() {
+    let iomem = tmp_path("acquire.iomem");
+    let device = tmp_path("acquire.mem");
+    fs::write(&iomem, IOMEM).unwrap();
+    // A file standing for /dev/mem, its byte at offset n being the byte at address n.
+    let mut mem = vec![0u8; 0x10_1000];
+    mem[0x1000..0x3000].fill(0x11);
+    mem[0x10_0000..].fill(0x22);
+    fs::write(&device, &mem).unwrap();
+
+    let ranges = system_ram_ranges(&iomem).unwrap();
+    let mut progress = Vec::new();
+    let mut writer = LimeWriter::new(Vec::new()).with_chunk_size(0x1000).unwrap();
+    writer
+        .write_device_ranges(&device, &ranges, |done, total| progress.push((done, total)))
+        .unwrap();
+    let lime = writer.finish().unwrap();
+    let missing = LimeWriter::new(Vec::new()).write_device_ranges(
+        tmp_path("acquire.nodevice"),
+        &ranges,
+        |_, _| (),
+    );
+    // Ranges past the end of the device can not be read.
+    let past = LimeWriter::new(Vec::new()).write_device_ranges(
+        &device,
+        &[(0x10_0000, 0x10_1fff)],
+        |_, _| (),
+    );
+    fs::remove_file(&iomem).unwrap();
+    fs::remove_file(&device).unwrap();
+
+    let mut expected = header_bytes(1, 0x1000, 0x2fff, [0; 8]);
+    expected.extend([0x11; 0x2000]);
+    expected.extend(header_bytes(1, 0x10_0000, 0x10_0fff, [0; 8]));
+    expected.extend([0x22; 0x1000]);
+    assert_eq!(ranges, [(0x1000, 0x2fff), (0x10_0000, 0x10_0fff)]);
+    assert_eq!(lime, expected);
+    assert_eq!(progress.last(), Some(&(0x3000, 0x3000)));
+    assert_eq!(progress.len(), 3);
+    assert_eq!(missing.err().unwrap().1, ErrorKind::UnableToReadFile);
+    assert_eq!(past.err().unwrap().1, ErrorKind::UnableToReadMemory);
+}
+
+/// The kernel hides the addresses of `/proc/iomem` from readers lacking privileges, zeroing
+/// them.
+#[test]
+fn hidden_addresses_are_refused() {
+    let iomem = tmp_path("acquire_hidden.iomem");
+    fs::write(
+        &iomem,
+        "00000000-00000000 : Reserved\n00000000-00000000 : System RAM\n",
+    )
+    .unwrap();
+    let hidden = system_ram_ranges(&iomem);
+    fs::write(&iomem, "00001000-zz : System RAM\n").unwrap();
+    let malformed = system_ram_ranges(&iomem);
+    fs::remove_file(&iomem).unwrap();
+
+    assert_eq!(hidden.err().unwrap().1, ErrorKind::UnableToReadFile);
+    assert!(malformed.is_err());
+}
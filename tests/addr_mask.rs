@@ -0,0 +1,49 @@
+mod common;
+
+use common::{open, tmp_path, write_lime};
+use memflow::prelude::{Args, MemoryView, PhysicalMemory};
+use std::fs;
+
+/// Masked ranges are mapped at their low address, the dump being refused if masking makes
+/// ranges wrap or collide.
+#[test]
+fn addresses_are_masked() {
+    let masked = tmp_path("addr_mask.lime");
+    let colliding = tmp_path("addr_mask_collide.lime");
+    let wrapping = tmp_path("addr_mask_wrap.lime");
+    write_lime(
+        &masked,
+        &[
+            (0x8000_1000, &[0x11; 0x1000]),
+            (0x1_0000_3000, &[0x33; 0x1000]),
+        ],
+    );
+    write_lime(
+        &colliding,
+        &[(0x1000, &[0x11; 0x1000]), (0x1_0000_1800, &[0x33; 0x1000])],
+    );
+    write_lime(&wrapping, &[(0x1_ffff_f800, &[0x11; 0x1000])]);
+
+    let mask = || Args::new().insert("addr_mask", "0xFFFFFFFF");
+    let mut connector = open(&masked, mask()).unwrap();
+    let mut buff = [0u8; 0x10];
+    connector
+        .phys_view()
+        .read_raw_into(0x3ff0.into(), &mut buff)
+        .unwrap();
+    let results = [
+        open(&masked, Args::new().insert("addr_mask", "0xFFFF0000")).is_err(),
+        open(&colliding, Args::new()).is_ok(),
+        open(&colliding, mask()).is_err(),
+        open(&colliding, mask().insert("parse", "lazy")).is_ok(),
+        open(&wrapping, mask()).is_err(),
+        open(&masked, mask().insert("index", "auto")).is_err(),
+    ];
+
+    for path in [masked, colliding, wrapping] {
+        fs::remove_file(path).unwrap();
+    }
+    assert_eq!(buff, [0x33; 0x10]);
+    assert_eq!(connector.metadata().max_address.to_umem(), 0x8000_1fff);
+    assert_eq!(results, [true; 6]);
+}
@@ -0,0 +1,153 @@
+#![cfg(feature = "aff4")]
+
+mod common;
+
+use common::tmp_path;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::CompressionMethod;
+
+const STREAM: &str = "aff4://c0ffee-stream";
+
+/// Write an AFF4 volume holding a zlib image stream of three 0x1000 byte chunks filled with
+/// 0x11, 0x22 and 0x33, two per bevy, and a map over it if `map` is given.
+fn write_aff4(path: &Path, map: Option<&[(u64, u64, u64, u32)]>) {
+    let mut turtle = format!(
+        "@prefix aff4: <http://aff4.org/Schema#> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+
+<{}> a aff4:ImageStream ;
+    aff4:chunkSize 4096 ;
+    aff4:chunksInSegment 2 ;
+    aff4:size \"12288\"^^xsd:long ;
+    aff4:compressionMethod <https://www.ietf.org/rfc/rfc1950.txt> .
+",
+        STREAM
+    );
+    if map.is_some() {
+        turtle.push_str("\n<aff4://volume/map> a aff4:Map, aff4:Image .\n");
+    }
+
+    let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+    zip.set_comment("aff4://volume");
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut member = |name: &str, options, data: &[u8]| {
+        zip.start_file(name, options).unwrap();
+        zip.write_all(data).unwrap();
+    };
+    member("information.turtle", deflated, turtle.as_bytes());
+
+    // The first chunk does not shrink and is stored as it is.
+    let chunks = [
+        vec![0x11; 0x1000],
+        zlib(&[0x22; 0x1000]),
+        zlib(&[0x33; 0x1000]),
+    ];
+    let encoded_stream = STREAM.replace(':', "%3A").replace('/', "%2F");
+    for (bevy, (chunks, options)) in [(&chunks[..2], stored), (&chunks[2..], deflated)]
+        .into_iter()
+        .enumerate()
+    {
+        let (mut data, mut index) = (Vec::new(), Vec::new());
+        for chunk in chunks {
+            index.extend((data.len() as u64).to_le_bytes());
+            index.extend((chunk.len() as u32).to_le_bytes());
+            data.extend(chunk);
+        }
+        let name = format!("{}/{:08}", encoded_stream, bevy);
+        member(&name, options, &data);
+        member(&format!("{}.index", name), stored, &index);
+    }
+
+    if let Some(map) = map {
+        let mut entries = Vec::new();
+        for (s_addr, len, target_offset, target) in map {
+            entries.extend(s_addr.to_le_bytes());
+            entries.extend(len.to_le_bytes());
+            entries.extend(target_offset.to_le_bytes());
+            entries.extend(target.to_le_bytes());
+        }
+        member("map/map", stored, &entries);
+        let idx = format!("{}\nhttp://aff4.org/Schema#Zero\n", STREAM);
+        member("map/idx", stored, idx.as_bytes());
+    }
+    zip.finish().unwrap();
+}
+
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// The ranges of the map of an AFF4 volume are read from its image stream, a volume without
+/// a map being its image stream mapped from address zero.
+#[test]
+fn aff4_images_are_mapped() {
+    let path = tmp_path("image.aff4");
+    let mut buff = [0u8; 0x10];
+
+    write_aff4(
+        &path,
+        Some(&[
+            (0x1000, 0x2000, 0, 0),
+            (0x5000, 0x1000, 0x2000, 0),
+            (0x8000, 0x1000, 0, 1),
+        ]),
+    );
+    let mut connector = create_connector(&ConnectorArgs::new(
+        Some(path.to_str().unwrap()),
+        Args::new(),
+        None,
+    ))
+    .unwrap();
+    for (addr, byte) in [
+        (0x1000, 0x11),
+        (0x1ff8, 0x11),
+        (0x2ff0, 0x22),
+        (0x5800, 0x33),
+    ] {
+        connector
+            .phys_view()
+            .read_raw_into(addr.into(), &mut buff)
+            .unwrap();
+        if addr == 0x1ff8 {
+            assert_eq!(buff[..8], [0x11; 8]);
+            assert_eq!(buff[8..], [0x22; 8]);
+        } else {
+            assert_eq!(buff, [byte; 0x10]);
+        }
+    }
+    for addr in [0x3000, 0x8000] {
+        assert!(connector
+            .phys_view()
+            .read_raw_into(addr.into(), &mut buff)
+            .is_err());
+    }
+
+    write_aff4(&path, None);
+    let mut connector = create_connector(&ConnectorArgs::new(
+        Some(path.to_str().unwrap()),
+        Args::new(),
+        None,
+    ))
+    .unwrap();
+    connector
+        .phys_view()
+        .read_raw_into(0x2ff0.into(), &mut buff)
+        .unwrap();
+    assert_eq!(buff, [0x33; 0x10]);
+    assert!(connector
+        .phys_view()
+        .read_raw_into(0x3000.into(), &mut buff)
+        .is_err());
+
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::{open, tmp_path, write_lime};
+use memflow::prelude::{Args, MemoryView, PhysicalMemory};
+use std::fs;
+
+/// Misaligned ranges are kept, trimmed, padded with zeros or refused.
+#[test]
+fn misaligned_ranges_follow_align() {
+    let lime_path = tmp_path("align.lime");
+    write_lime(
+        &lime_path,
+        &[
+            (0x1000, &[0x11; 0x1000]),
+            (0x2800, &[0x22; 0x800]),
+            (0x4000, &[0x44; 0x1000]),
+        ],
+    );
+
+    let read = |align: &str, addr: u64| -> Option<[u8; 0x10]> {
+        let mut buff = [0u8; 0x10];
+        let mut connector = open(&lime_path, Args::new().insert("align", align)).ok()?;
+        connector
+            .phys_view()
+            .read_raw_into(addr.into(), &mut buff)
+            .ok()
+            .map(|_| buff)
+    };
+    let kept = read("none", 0x2800);
+    let trimmed = read("trim", 0x2800);
+    // The padding reads as zeros, not as the header and the first range preceding the range
+    // in the file.
+    let extended = read("extend", 0x27f0);
+    let padded_start = read("extend", 0x2000);
+    let straddling = read("extend", 0x27f8);
+    let refused = open(&lime_path, Args::new().insert("align", "error")).is_err();
+    let lazy = open(
+        &lime_path,
+        Args::new().insert("align", "trim").insert("parse", "lazy"),
+    )
+    .is_err();
+
+    fs::remove_file(lime_path).unwrap();
+    assert_eq!(kept.unwrap(), [0x22; 0x10]);
+    assert_eq!(trimmed, None);
+    assert_eq!(extended.unwrap(), [0; 0x10]);
+    assert_eq!(padded_start.unwrap(), [0; 0x10]);
+    assert_eq!(straddling.unwrap()[..0x8], [0; 0x8]);
+    assert_eq!(straddling.unwrap()[0x8..], [0x22; 0x8]);
+    assert!(refused);
+    assert!(lazy);
+}
+
+/// A range shorter than a page but crossing a page boundary holds no whole page, trimming it
+/// leaves nothing mapped.
+#[test]
+fn trimming_short_range_maps_nothing() {
+    let lime_path = tmp_path("align_short.lime");
+    write_lime(
+        &lime_path,
+        &[(0x1100, &[0x11; 0x1e01]), (0x4000, &[0x44; 0x1000])],
+    );
+
+    let mut connector = open(&lime_path, Args::new().insert("align", "trim")).unwrap();
+    let short = connector
+        .phys_view()
+        .read_raw_into(0x1100.into(), &mut [0u8; 0x10]);
+    let mut kept = [0u8; 0x10];
+    connector
+        .phys_view()
+        .read_raw_into(0x4000.into(), &mut kept)
+        .unwrap();
+    let real_size = connector.metadata().real_size;
+
+    fs::remove_file(lime_path).unwrap();
+    assert!(short.is_err());
+    assert_eq!(kept, [0x44; 0x10]);
+    assert_eq!(real_size, 0x1000);
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "archive")]
+
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs::{self, File};
+use std::io::Write;
+use zip::write::SimpleFileOptions;
+use zip::CompressionMethod;
+
+/// Members of zip archives, stored or deflated, and of tar archives are read in place.
+#[test]
+fn archive_members_are_opened() {
+    let lime_path = tmp_path("archive.lime");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x1000]), (0x3000, &[0x33; 0x1000])],
+    );
+    let lime = fs::read(&lime_path).unwrap();
+
+    let zip_path = tmp_path("archive.zip");
+    let mut zip = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+    for (name, method) in [
+        ("notes.txt", CompressionMethod::Deflated),
+        ("memory/stored.lime", CompressionMethod::Stored),
+        ("memory/deflated.lime", CompressionMethod::Deflated),
+    ] {
+        zip.start_file(
+            name,
+            SimpleFileOptions::default().compression_method(method),
+        )
+        .unwrap();
+        zip.write_all(&lime).unwrap();
+    }
+    zip.finish().unwrap();
+
+    let tar_path = tmp_path("archive.tar");
+    let mut tar = tar::Builder::new(File::create(&tar_path).unwrap());
+    tar.append_path_with_name(&lime_path, "notes.txt").unwrap();
+    tar.append_path_with_name(&lime_path, "memory/dump.lime")
+        .unwrap();
+    tar.finish().unwrap();
+    drop(tar);
+
+    let zip_target = zip_path.to_str().unwrap();
+    let tar_target = tar_path.to_str().unwrap();
+    let mut buff = [0u8; 0x10];
+    for target in [
+        format!("{}!memory/stored.lime", zip_target),
+        format!("{}!memory/deflated.lime", zip_target),
+        format!("{}!./memory/dump.lime", tar_target),
+    ] {
+        let mut connector =
+            create_connector(&ConnectorArgs::new(Some(&target), Args::new(), None)).unwrap();
+        connector
+            .phys_view()
+            .read_raw_into(0x3ff0.into(), &mut buff)
+            .unwrap();
+        assert_eq!(buff, [0x33; 0x10]);
+        assert!(connector
+            .phys_view()
+            .read_raw_into(0x2000.into(), &mut buff)
+            .is_err());
+    }
+    let missing = create_connector(&ConnectorArgs::new(
+        Some(&format!("{}!memory/missing.lime", zip_target)),
+        Args::new(),
+        None,
+    ));
+
+    for path in [lime_path, zip_path, tar_path] {
+        fs::remove_file(path).unwrap();
+    }
+    assert!(missing.is_err());
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "tokio")]
+
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs};
+use memflow_lime::AsyncLimeMemory;
+use std::fs;
+
+/// Concurrent asynchronous reads return the data of the dump, reads of gaps failing.
+#[test]
+fn reads_are_awaited() {
+    let lime_path = tmp_path("async_read.lime");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x1000]), (0x10000, &[0x22; 0x1000])],
+    );
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let (reads, gap, ranges) = runtime.block_on(async {
+        let mem = AsyncLimeMemory::open(ConnectorArgs::new(lime_path.to_str(), Args::new(), None))
+            .await
+            .unwrap();
+        let tasks = [0x1000u64, 0x10800, 0x1ff0, 0x10000]
+            .into_iter()
+            .map(|addr| {
+                let mem = mem.clone();
+                tokio::spawn(async move { mem.read_phys(addr.into(), 0x10).await })
+            })
+            .collect::<Vec<_>>();
+        let mut reads = Vec::new();
+        for task in tasks {
+            reads.push(task.await.unwrap().unwrap());
+        }
+        let gap = mem.read_phys(0x8000.into(), 0x10).await;
+        (reads, gap, mem.ranges().to_vec())
+    });
+
+    fs::remove_file(&lime_path).unwrap();
+    assert_eq!(
+        reads,
+        [
+            vec![0x11; 0x10],
+            vec![0x22; 0x10],
+            vec![0x11; 0x10],
+            vec![0x22; 0x10]
+        ]
+    );
+    assert!(gap.is_err());
+    assert_eq!(ranges, [(0x1000, 0x1fff), (0x10000, 0x10fff)]);
+}
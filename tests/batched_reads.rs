@@ -0,0 +1,42 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+
+/// Out of order batches spanning several ranges are served entry by entry, the entries
+/// falling outside the ranges failing on their own.
+#[test]
+fn batches_are_read_out_of_order() {
+    let lime_path = tmp_path("batched_reads.lime");
+    write_lime(
+        &lime_path,
+        &[
+            (0x3000, &[0x33; 0x1000]),
+            (0x1000, &[0x11; 0x1000]),
+            (0x2000, &[0x22; 0x1000]),
+        ],
+    );
+    let mut connector =
+        create_connector(&ConnectorArgs::new(lime_path.to_str(), Args::new(), None)).unwrap();
+
+    let (mut a, mut b, mut c, mut d) = ([0u8; 4], [0u8; 4], [0u8; 0x10], [0u8; 4]);
+    let mut view = connector.phys_view();
+    let mut batcher = view.batcher();
+    batcher
+        .read_raw_into(0x3ffe.into(), &mut a)
+        .read_raw_into(0x1ffe.into(), &mut b)
+        .read_raw_into(0x2ff8.into(), &mut c)
+        .read_raw_into(0x5000.into(), &mut d);
+    let result = batcher.commit_rw();
+    drop(batcher);
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert!(result.is_err());
+    assert_eq!(a, [0x33, 0x33, 0, 0]);
+    assert_eq!(b, [0x11, 0x11, 0x22, 0x22]);
+    assert_eq!(&c[..], &[[0x22; 8], [0x33; 8]].concat()[..]);
+    assert_eq!(d, [0; 4]);
+}
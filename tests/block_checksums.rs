@@ -0,0 +1,80 @@
+mod common;
+
+use common::tmp_path;
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::{
+    block_checksums, create_connector, default_blocks_sidecar, verify_block_checksums,
+    LimeBlockManifest, LimeWriter,
+};
+use std::fs;
+
+/// The writer records the checksums of the blocks it writes, and a block rotting on disk is
+/// located by a full check and by the reads touching it.
+#[test]
+fn corrupt_blocks_are_located() {
+    let data: Vec<u8> = (0..0x3000u32).map(|i| (i % 253) as u8).collect();
+    let mut writer = LimeWriter::new(Vec::new())
+        .with_block_checksums(0x1000)
+        .unwrap();
+    writer.write_range(0x10800, &data[..0x2000]).unwrap();
+    writer.write_range(0x20000, &data[0x2000..]).unwrap();
+    let manifest = writer.block_manifest().unwrap().clone();
+    let mut lime = writer.finish().unwrap();
+
+    let lime_path = tmp_path("block_checksums.lime");
+    let manifest_path = default_blocks_sidecar(&lime_path);
+    fs::write(&lime_path, &lime).unwrap();
+    manifest.write(&manifest_path).unwrap();
+    let recomputed = block_checksums(&lime_path, 0x1000).unwrap();
+    let reread = LimeBlockManifest::read(&manifest_path).unwrap();
+    let clean = verify_block_checksums(&lime_path, &manifest_path).unwrap();
+
+    // Flip a byte of the block 0x11000-0x11fff.
+    lime[0x20 + 0x900] ^= 0xff;
+    fs::write(&lime_path, &lime).unwrap();
+    let corrupt = verify_block_checksums(&lime_path, &manifest_path).unwrap();
+    let mut connector = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("verify_blocks", "auto"),
+        None,
+    ))
+    .unwrap();
+    let mut buf = vec![0u8; 0x800];
+    let before = connector
+        .phys_view()
+        .read_raw_into(0x10800.into(), &mut buf);
+    let in_block = connector
+        .phys_view()
+        .read_raw_into(0x11100.into(), &mut [0u8; 0x10]);
+    let mut spanning = vec![0u8; 0x1000];
+    let across = connector
+        .phys_view()
+        .read_raw_into(0x10c00.into(), &mut spanning);
+    let found = connector.corrupt_blocks();
+    fs::remove_file(&lime_path).unwrap();
+    fs::remove_file(&manifest_path).unwrap();
+
+    assert_eq!(
+        manifest
+            .blocks
+            .iter()
+            .map(|block| (block.s_addr, block.e_addr, block.file_offset))
+            .collect::<Vec<_>>(),
+        [
+            (0x10800, 0x10fff, 0x20),
+            (0x11000, 0x11fff, 0x820),
+            (0x12000, 0x127ff, 0x1820),
+            (0x20000, 0x20fff, 0x2040),
+        ]
+    );
+    assert_eq!(recomputed, manifest);
+    assert_eq!(reread, manifest);
+    assert!(clean.is_empty());
+    assert_eq!(corrupt, [manifest.blocks[1]]);
+    assert!(before.is_ok());
+    assert_eq!(buf[..], data[..0x800]);
+    assert!(in_block.is_err());
+    assert!(across.is_err());
+    assert_eq!(spanning[..0x400], data[0x400..0x800]);
+    assert_eq!(found, [manifest.blocks[1]]);
+}
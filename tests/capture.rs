@@ -0,0 +1,105 @@
+mod common;
+
+use common::{header_bytes, tmp_path};
+use memflow::prelude::{Args, ConnectorArgs, ErrorKind, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+use std::io::Write;
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A dump streamed over TCP is written to the spool file while the ranges already received
+/// are read, reads past them waiting for the rest of the stream until it ends.
+#[test]
+fn ranges_are_read_while_received() {
+    let spool = tmp_path("capture.lime");
+    let _ = fs::remove_file(&spool);
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let lime = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream
+            .write_all(&header_bytes(1, 0x1000, 0x1fff, [0; 8]))
+            .unwrap();
+        stream.write_all(&[0x11; 0x1000]).unwrap();
+        thread::sleep(Duration::from_millis(200));
+        stream
+            .write_all(&header_bytes(1, 0x4000, 0x5fff, [0; 8]))
+            .unwrap();
+        stream.write_all(&[0x22; 0x1000]).unwrap();
+        thread::sleep(Duration::from_millis(200));
+        stream.write_all(&[0x33; 0x1000]).unwrap();
+    });
+
+    let mut connector = create_connector(&ConnectorArgs::new(
+        Some(&format!("tcp://{}", address)),
+        Args::new().insert("spool", spool.to_str().unwrap()),
+        None,
+    ))
+    .unwrap();
+    let mut first = [0u8; 0x10];
+    connector
+        .phys_view()
+        .read_raw_into(0x1000.into(), &mut first)
+        .unwrap();
+    let mut last = [0u8; 0x10];
+    connector
+        .phys_view()
+        .read_raw_into(0x5ff0.into(), &mut last)
+        .unwrap();
+    lime.join().unwrap();
+    let start = Instant::now();
+    while !connector.capture_status().unwrap().finished {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        thread::sleep(Duration::from_millis(10));
+    }
+    let status = connector.capture_status().unwrap();
+    // The stream is over, nothing more is waited for.
+    let past = connector
+        .phys_view()
+        .read_raw_into(0x8000.into(), &mut [0u8; 0x10]);
+    let again = create_connector(&ConnectorArgs::new(
+        Some(&format!("tcp://{}", address)),
+        Args::new().insert("spool", spool.to_str().unwrap()),
+        None,
+    ));
+    let received = fs::read(&spool).unwrap();
+    fs::remove_file(&spool).unwrap();
+
+    assert_eq!(first, [0x11; 0x10]);
+    assert_eq!(last, [0x33; 0x10]);
+    assert_eq!(status.bytes_received, 0x3040);
+    assert_eq!(status.error, None);
+    assert_eq!(received.len(), 0x3040);
+    assert!(past.is_err());
+    // The spool file of a capture is never overwritten.
+    assert_eq!(again.err().unwrap().1, ErrorKind::AlreadyExists);
+}
+
+/// A `tcp://` target needs a spool file, and a failed connection leaves none behind.
+#[test]
+fn spool_is_required() {
+    let missing = create_connector(&ConnectorArgs::new(
+        Some("tcp://127.0.0.1:4444"),
+        Args::new(),
+        None,
+    ));
+    let spool = tmp_path("capture_refused.lime");
+    let _ = fs::remove_file(&spool);
+    // Nothing listens on a port just released.
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let refused = create_connector(&ConnectorArgs::new(
+        Some(&format!("tcp://127.0.0.1:{}", port)),
+        Args::new().insert("spool", spool.to_str().unwrap()),
+        None,
+    ));
+
+    assert_eq!(missing.err().unwrap().1, ErrorKind::ArgValidation);
+    assert!(refused.is_err());
+    assert!(!spool.exists());
+}
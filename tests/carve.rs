@@ -0,0 +1,75 @@
+mod common;
+
+use common::{header_bytes, tmp_path};
+use memflow::prelude::{Args, ConnectorArgs, PhysicalAddress, PhysicalMemory};
+use memflow_lime::{carve_layout, create_connector, LimeSkipped, ParseMode};
+use std::fs;
+
+/// Append a `LiME` range of `len` bytes of `fill` at `s_addr` to `blob`.
+fn push_range(blob: &mut Vec<u8>, s_addr: u64, len: usize, fill: u8) {
+    blob.extend_from_slice(&header_bytes(1, s_addr, s_addr + len as u64 - 1, [0; 8]));
+    blob.extend(std::iter::repeat_n(fill, len));
+}
+
+/// Segments embedded in arbitrary data are found and mapped with `carve=true`.
+#[test]
+fn segments_are_carved_from_a_blob() {
+    let lime_path = tmp_path("carve.bin");
+    let mut blob = vec![0xa5; 0x777];
+    // A magic number followed by an impossible range is not a header
+    blob.extend_from_slice(&header_bytes(1, 0x2000, 0x1000, [0; 8]));
+    push_range(&mut blob, 0x1000, 0x1000, 0x11);
+    push_range(&mut blob, 0x2000, 0x800, 0x22);
+    blob.extend_from_slice(&[0x5a; 0x123]);
+    push_range(&mut blob, 0x8000, 0x100, 0x33);
+    // A range running past the end of the file is not a header either
+    blob.extend_from_slice(&header_bytes(1, 0x9000, 0x9fff, [0; 8]));
+    fs::write(&lime_path, &blob).unwrap();
+
+    let layout = carve_layout(&lime_path, ParseMode::Strict).unwrap();
+    let refused =
+        create_connector(&ConnectorArgs::new(lime_path.to_str(), Args::new(), None)).is_err();
+    let mut mem = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("carve", "true"),
+        None,
+    ))
+    .unwrap();
+    let mut buff = [0u8; 2];
+    mem.phys_read_into(PhysicalAddress::from(0x8000), &mut buff)
+        .unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert!(refused);
+    assert_eq!(buff, [0x33, 0x33]);
+    assert_eq!(
+        layout
+            .segments
+            .iter()
+            .map(|segment| (segment.s_addr, segment.e_addr, segment.file_offset))
+            .collect::<Vec<_>>(),
+        vec![
+            (0x1000, 0x1fff, 0x7b7),
+            (0x2000, 0x27ff, 0x17d7),
+            (0x8000, 0x80ff, 0x211a),
+        ]
+    );
+    assert_eq!(
+        layout.skipped,
+        vec![
+            LimeSkipped {
+                offset: 0,
+                len: 0x797,
+            },
+            LimeSkipped {
+                offset: 0x1fd7,
+                len: 0x123,
+            },
+            LimeSkipped {
+                offset: 0x221a,
+                len: 0x20,
+            },
+        ]
+    );
+}
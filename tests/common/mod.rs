@@ -0,0 +1,39 @@
+#![allow(dead_code)]
+
+use memflow::prelude::{Args, ConnectorArgs};
+use memflow_lime::{create_connector, LimeMemory};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Path of a scratch file in the system temporary directory.
+pub fn tmp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("memflow_lime_{}", name))
+}
+
+/// Open the dump at `lime_path` with the connector arguments `args`.
+pub fn open(lime_path: &Path, args: Args) -> memflow::error::Result<LimeMemory> {
+    create_connector(&ConnectorArgs::new(lime_path.to_str(), args, None))
+}
+
+/// Raw `LiME` header for the physical range `[s_addr, e_addr]`.
+pub fn header_bytes(version: u32, s_addr: u64, e_addr: u64, reserved: [u8; 8]) -> Vec<u8> {
+    let mut header = Vec::with_capacity(32);
+    header.extend_from_slice(&0x4C69_4D45_u32.to_le_bytes());
+    header.extend_from_slice(&version.to_le_bytes());
+    header.extend_from_slice(&s_addr.to_le_bytes());
+    header.extend_from_slice(&e_addr.to_le_bytes());
+    header.extend_from_slice(&reserved);
+    header
+}
+
+/// Write a `LiME` file made of the given `(start address, data)` ranges.
+pub fn write_lime<P: AsRef<Path>>(path: P, ranges: &[(u64, &[u8])]) {
+    let mut file = File::create(path).unwrap();
+    for (s_addr, data) in ranges {
+        let e_addr = s_addr + data.len() as u64 - 1;
+        file.write_all(&header_bytes(1, *s_addr, e_addr, [0; 8]))
+            .unwrap();
+        file.write_all(data).unwrap();
+    }
+}
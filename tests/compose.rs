@@ -0,0 +1,97 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+
+/// The ranges of every dump are mapped, moved up by the offset of the dump.
+#[test]
+fn dumps_are_composed_into_one_address_space() {
+    let node0 = tmp_path("compose_node0.lime");
+    let node1 = tmp_path("compose_node1.lime");
+    write_lime(
+        &node0,
+        &[(0x1000, &[0x10; 0x1000]), (0x4000, &[0x14; 0x1000])],
+    );
+    write_lime(&node1, &[(0x1000, &[0x21; 0x1000])]);
+
+    let open = |target: String, extra_args: Args| {
+        create_connector(&ConnectorArgs::new(Some(&target), extra_args, None))
+    };
+    let mut composed = open(
+        format!("{};{}@0x100000", node0.display(), node1.display()),
+        Args::new(),
+    )
+    .unwrap();
+    let mut first = [0u8; 0x10];
+    composed
+        .phys_view()
+        .read_raw_into(0x4ff0.into(), &mut first)
+        .unwrap();
+    let mut second = [0u8; 0x10];
+    composed
+        .phys_view()
+        .read_raw_into(0x101000.into(), &mut second)
+        .unwrap();
+    let ranges = composed.mem_map().iter().count();
+
+    let colliding = open(
+        format!("{};{}", node0.display(), node1.display()),
+        Args::new().insert("overlap", "error"),
+    );
+    let mut first_wins = open(
+        format!("{};{}", node0.display(), node1.display()),
+        Args::new().insert("overlap", "first"),
+    )
+    .unwrap();
+    let mut kept = [0u8; 0x10];
+    first_wins
+        .phys_view()
+        .read_raw_into(0x1000.into(), &mut kept)
+        .unwrap();
+    let with_overlay = open(
+        format!("{};{}", node0.display(), node1.display()),
+        Args::new().insert("overlay", node1.to_str().unwrap()),
+    );
+
+    fs::remove_file(node0).unwrap();
+    fs::remove_file(node1).unwrap();
+    assert_eq!(first, [0x14; 0x10]);
+    assert_eq!(second, [0x21; 0x10]);
+    assert_eq!(ranges, 3);
+    assert!(colliding.is_err());
+    assert_eq!(kept, [0x10; 0x10]);
+    assert!(with_overlay.is_err());
+}
+
+/// A file whose name holds a `;` is opened whole, and a listed file whose name holds a `@`
+/// has no offset.
+#[test]
+fn separators_in_file_names_are_kept() {
+    let semicolon = tmp_path("compose_a;b.lime");
+    let at = tmp_path("compose_c@2.lime");
+    let other = tmp_path("compose_d.lime");
+    write_lime(&semicolon, &[(0x1000, &[0x31; 0x100])]);
+    write_lime(&at, &[(0x1000, &[0x32; 0x100])]);
+    write_lime(&other, &[(0x2000, &[0x33; 0x100])]);
+
+    let read = |target: String, addr: u64| {
+        let mut buff = [0u8; 0x10];
+        create_connector(&ConnectorArgs::new(Some(&target), Args::new(), None))
+            .unwrap()
+            .phys_view()
+            .read_raw_into(addr.into(), &mut buff)
+            .unwrap();
+        buff
+    };
+    let whole = read(semicolon.to_str().unwrap().to_owned(), 0x1000);
+    let target = format!("{};{}", at.display(), other.display());
+    let composed = [read(target.clone(), 0x1000), read(target, 0x2000)];
+
+    for path in [semicolon, at, other] {
+        fs::remove_file(path).unwrap();
+    }
+    assert_eq!(whole, [0x31; 0x10]);
+    assert_eq!(composed, [[0x32; 0x10], [0x33; 0x10]]);
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "compression")]
+
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow_lime::{extract_range, extract_range_compressed, LimeCompression, LimeWriter};
+use std::fs;
+use std::io::Read;
+
+/// Dumps written compressed decompress to the bytes the plain writer produces.
+#[test]
+fn writer_compresses_its_output() {
+    let data: Vec<u8> = (0..0x4000u32).map(|i| (i % 7) as u8).collect();
+    let mut expected = LimeWriter::new(Vec::new());
+    expected.write_range(0x1000, &data).unwrap();
+    let expected = expected.finish().unwrap();
+
+    let gz_path = tmp_path("compressed_writer.lime.gz");
+    let zst_path = tmp_path("compressed_writer.lime.zst");
+    for path in [&gz_path, &zst_path] {
+        let compression = LimeCompression::from_path(path).unwrap();
+        let mut writer = LimeWriter::create_compressed(path, compression).unwrap();
+        writer.write_range(0x1000, &data).unwrap();
+        writer.finish_compressed().unwrap();
+    }
+    let gz = fs::read(&gz_path).unwrap();
+    let zst = fs::read(&zst_path).unwrap();
+    fs::remove_file(&gz_path).unwrap();
+    fs::remove_file(&zst_path).unwrap();
+
+    let mut gunzipped = Vec::new();
+    flate2::read::GzDecoder::new(&gz[..])
+        .read_to_end(&mut gunzipped)
+        .unwrap();
+    assert!(gz.len() < expected.len());
+    assert_eq!(gunzipped, expected);
+    assert!(zst.len() < expected.len());
+    assert_eq!(zstd::decode_all(&zst[..]).unwrap(), expected);
+    assert_eq!(LimeCompression::from_path("dump.lime"), None);
+}
+
+/// Windows extracted compressed hold the sections a plain extraction writes.
+#[test]
+fn extracted_range_is_compressed() {
+    let lime_path = tmp_path("compressed_extract.lime");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x2000]), (0x5000, &[0x55; 0x1000])],
+    );
+    let plain_path = tmp_path("compressed_extract_out.lime");
+    let zst_path = tmp_path("compressed_extract_out.lime.zst");
+    extract_range(&lime_path, &plain_path, 0x2000, 0x5fff).unwrap();
+    extract_range_compressed(&lime_path, &zst_path, 0x2000, 0x5fff, LimeCompression::Zstd).unwrap();
+    let outside =
+        extract_range_compressed(&lime_path, &zst_path, 0x8000, 0x8fff, LimeCompression::Gzip);
+    let plain = fs::read(&plain_path).unwrap();
+    let zst = fs::read(&zst_path).unwrap();
+    fs::remove_file(&lime_path).unwrap();
+    fs::remove_file(&plain_path).unwrap();
+    fs::remove_file(&zst_path).unwrap();
+
+    assert_eq!(zstd::decode_all(&zst[..]).unwrap(), plain);
+    assert!(outside.is_err());
+}
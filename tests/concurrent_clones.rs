@@ -0,0 +1,50 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+use std::thread;
+
+/// Clones of a connector read concurrently from different threads without racing on the
+/// file position.
+#[test]
+fn clones_read_concurrently() {
+    let lime_path = tmp_path("concurrent_clones.lime");
+    let ranges = (0..8u8)
+        .map(|i| (0x100000 * i as u64, vec![i; 0x10000]))
+        .collect::<Vec<_>>();
+    write_lime(
+        &lime_path,
+        &ranges
+            .iter()
+            .map(|(s_addr, data)| (*s_addr, data.as_slice()))
+            .collect::<Vec<_>>(),
+    );
+
+    let mem = create_connector(&ConnectorArgs::new(lime_path.to_str(), Args::new(), None)).unwrap();
+    let threads = (0..8u8)
+        .map(|i| {
+            let mut mem = mem.clone();
+            thread::spawn(move || {
+                let mut buf = [0u8; 0x100];
+                for round in 0..20000u64 {
+                    // Each thread reads the ranges in a different order.
+                    let range = (i as u64 + round) % 8;
+                    let offset = (round * 0x100) % 0x10000;
+                    mem.phys_view()
+                        .read_raw_into((range * 0x100000 + offset).into(), &mut buf)
+                        .unwrap();
+                    assert!(buf.iter().all(|byte| *byte as u64 == range));
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    let results = threads
+        .into_iter()
+        .map(|thread| thread.join())
+        .collect::<Vec<_>>();
+
+    fs::remove_file(&lime_path).unwrap();
+    assert!(results.iter().all(Result::is_ok));
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow_lime::{
+    convert_to_memprocfs, convert_to_raw, convert_to_raw_with_options, default_memmap_path,
+    RawOutput,
+};
+use std::fs;
+
+/// Gaps must be zero-filled and data must land at its physical address.
+#[test]
+fn convert_sample_dump() {
+    let out_path = tmp_path("convert_sample.raw");
+    convert_to_raw("./tests/deb-x86_64-slice.lime", &out_path).unwrap();
+    let raw = fs::read(&out_path).unwrap();
+    fs::remove_file(&out_path).unwrap();
+
+    let volatility_output = fs::read("./tests/deb-x86_64-slice_0x1000_volatility3_out").unwrap();
+    assert_eq!(raw.len(), 0xa0000);
+    assert!(raw[..0x1000].iter().all(|b| *b == 0));
+    assert_eq!(raw[0x1000..0x1080], volatility_output[..]);
+}
+
+/// Overlapping ranges are resolved in favor of the last one in the file.
+#[test]
+fn convert_overlapping_ranges() {
+    let lime_path = tmp_path("convert_overlap.lime");
+    let out_path = tmp_path("convert_overlap.raw");
+    write_lime(
+        &lime_path,
+        &[(0x10, &[1; 0x10]), (0x18, &[2; 0x4]), (0x30, &[3; 0x2])],
+    );
+
+    convert_to_raw(&lime_path, &out_path).unwrap();
+    let raw = fs::read(&out_path).unwrap();
+    fs::remove_file(&lime_path).unwrap();
+    fs::remove_file(&out_path).unwrap();
+
+    let mut expected = vec![0u8; 0x32];
+    expected[0x10..0x20].fill(1);
+    expected[0x18..0x1c].fill(2);
+    expected[0x30..0x32].fill(3);
+    assert_eq!(raw, expected);
+}
+
+/// Gaps are holes of a sparse file by default, and zero-filled in a dense image.
+#[test]
+fn convert_sparse_and_dense() {
+    let lime_path = tmp_path("convert_sparse.lime");
+    let sparse_path = tmp_path("convert_sparse.raw");
+    let dense_path = tmp_path("convert_dense.raw");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[1; 0x1000]), (0x4000000, &[2; 0x1000])],
+    );
+
+    convert_to_raw(&lime_path, &sparse_path).unwrap();
+    convert_to_raw_with_options(&lime_path, &dense_path, RawOutput::Dense, |_, _| ()).unwrap();
+    let sparse = fs::read(&sparse_path).unwrap();
+    let dense = fs::read(&dense_path).unwrap();
+    #[cfg(unix)]
+    let allocated = {
+        use std::os::unix::fs::MetadataExt;
+        let sparse = fs::metadata(&sparse_path).unwrap().blocks() * 512;
+        let dense = fs::metadata(&dense_path).unwrap().blocks() * 512;
+        (sparse, dense)
+    };
+    fs::remove_file(&lime_path).unwrap();
+    fs::remove_file(&sparse_path).unwrap();
+    fs::remove_file(&dense_path).unwrap();
+
+    assert_eq!(sparse.len(), 0x4001000);
+    assert_eq!(sparse, dense);
+    assert!(sparse[0x2000..0x4000000].iter().all(|b| *b == 0));
+    #[cfg(unix)]
+    assert!(allocated.0 < 0x100000 && allocated.1 >= 0x4000000);
+}
+
+/// The MemProcFS output is the raw image and the memory map of its merged ranges.
+#[test]
+fn convert_for_memprocfs() {
+    let lime_path = tmp_path("convert_memprocfs.lime");
+    let raw_path = tmp_path("convert_memprocfs.raw");
+    write_lime(
+        &lime_path,
+        &[
+            (0x1000, &[1; 0x1000]),
+            (0x2000, &[2; 0x800]),
+            (0x10000, &[3; 0x10]),
+            (0x1800, &[4; 0x10]),
+        ],
+    );
+    let mut last = (0, 0);
+    convert_to_memprocfs(&lime_path, &raw_path, |done, total| last = (done, total)).unwrap();
+    let raw = fs::read(&raw_path).unwrap();
+    let memmap_path = default_memmap_path(&raw_path);
+    let memmap = fs::read_to_string(&memmap_path).unwrap();
+    fs::remove_file(&lime_path).unwrap();
+    fs::remove_file(&raw_path).unwrap();
+    fs::remove_file(&memmap_path).unwrap();
+
+    assert_eq!(raw.len(), 0x10010);
+    assert_eq!(raw[0x1800], 4);
+    assert_eq!(raw[0x2000], 2);
+    assert_eq!(last, (0x1820, 0x1820));
+    assert_eq!(
+        memmap,
+        "0000             1000 -             27ff\n\
+         0001            10000 -            1000f\n"
+    );
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use common::{header_bytes, tmp_path};
+use memflow::prelude::ErrorKind;
+use memflow_lime::{read_layout, ParseMode};
+use std::fs;
+
+/// A header whose declared size can not be represented in a file offset is refused instead
+/// of wrapping the offset computation around.
+#[test]
+fn impossible_segment_size_is_refused() {
+    let lime_path = tmp_path("impossible_size.lime");
+    fs::write(&lime_path, header_bytes(1, 0x1, u64::MAX, [0; 8])).unwrap();
+
+    let layout = read_layout(&lime_path, ParseMode::Lenient);
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(layout.unwrap_err().1, ErrorKind::InvalidMemorySize);
+}
+
+/// A range covering the whole address space has a size that does not fit in a `u64`.
+#[test]
+fn whole_address_space_range_is_refused() {
+    let lime_path = tmp_path("whole_address_space.lime");
+    fs::write(&lime_path, header_bytes(1, 0, u64::MAX, [0; 8])).unwrap();
+
+    let layout = read_layout(&lime_path, ParseMode::Lenient);
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(layout.unwrap_err().1, ErrorKind::InvalidMemorySize);
+}
+
+/// An inverted range after a valid one is refused even in lenient mode.
+#[test]
+fn inverted_range_is_refused() {
+    let lime_path = tmp_path("inverted_range.lime");
+    let mut dump = header_bytes(1, 0x1000, 0x1fff, [0; 8]);
+    dump.extend_from_slice(&[0xaa; 0x1000]);
+    dump.extend_from_slice(&header_bytes(1, 0x3000, 0x2fff, [0; 8]));
+    fs::write(&lime_path, dump).unwrap();
+
+    let layout = read_layout(&lime_path, ParseMode::Lenient);
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(layout.unwrap_err().1, ErrorKind::InvalidMemorySize);
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow_lime::{lime_diff, LimeChange, LimeChangeKind};
+use std::fs;
+
+fn change(s_addr: u64, e_addr: u64, kind: LimeChangeKind) -> LimeChange {
+    LimeChange {
+        s_addr,
+        e_addr,
+        kind,
+    }
+}
+
+/// Blocks whose content differs are reported whole, memory held by one dump only exactly.
+#[test]
+fn changed_ranges_are_listed() {
+    let mut before = vec![0x11; 0x4000];
+    let mut after = before.clone();
+    after[0x1010] = 0xff;
+    after[0x1ff0] = 0xff;
+    after[0x3fff] = 0xff;
+    before[0..0x10].fill(0);
+
+    let path_a = tmp_path("diff_a.lime");
+    let path_b = tmp_path("diff_b.lime");
+    write_lime(&path_a, &[(0x10000, &before), (0x20000, &[0x22; 0x800])]);
+    write_lime(
+        &path_b,
+        &[
+            (0x10000, &after[..0x2000]),
+            (0x12000, &after[0x2000..0x3800]),
+            (0x30000, &[0x33; 0x10]),
+        ],
+    );
+
+    let pages = lime_diff(&path_a, &path_b, 0x1000).unwrap();
+    let bytes = lime_diff(&path_a, &path_b, 1).unwrap();
+    let same = lime_diff(&path_a, &path_a, 0x1000).unwrap();
+    let zero = lime_diff(&path_a, &path_b, 0);
+    fs::remove_file(path_a).unwrap();
+    fs::remove_file(path_b).unwrap();
+
+    // The last byte changed is past the end of the second dump.
+    assert_eq!(
+        pages,
+        [
+            change(0x10000, 0x11fff, LimeChangeKind::Modified),
+            change(0x13800, 0x13fff, LimeChangeKind::Removed),
+            change(0x20000, 0x207ff, LimeChangeKind::Removed),
+            change(0x30000, 0x3000f, LimeChangeKind::Added),
+        ]
+    );
+    assert_eq!(
+        bytes[..3],
+        [
+            change(0x10000, 0x1000f, LimeChangeKind::Modified),
+            change(0x11010, 0x11010, LimeChangeKind::Modified),
+            change(0x11ff0, 0x11ff0, LimeChangeKind::Modified),
+        ]
+    );
+    assert!(same.is_empty());
+    assert!(zero.is_err());
+}
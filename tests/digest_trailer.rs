@@ -0,0 +1,50 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{ConnectorArgs, PhysicalAddress, PhysicalMemory};
+use memflow_lime::{create_connector, read_digest_trailer, read_segments, sha256_digest};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+/// A dump captured over TCP with `digest=sha256` ends with the hex digest of the ranges.
+#[test]
+fn digest_trailer_is_skipped_and_exposed() {
+    let lime_path = tmp_path("digest_trailer.lime");
+    write_lime(&lime_path, &[(0x1000, &[0x55; 0x100])]);
+    let digest = sha256_digest(&lime_path).unwrap();
+    let hex = digest
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    OpenOptions::new()
+        .append(true)
+        .open(&lime_path)
+        .unwrap()
+        .write_all(hex.as_bytes())
+        .unwrap();
+
+    let trailer = read_digest_trailer(&lime_path).unwrap().unwrap();
+    let segments = read_segments(&lime_path).unwrap();
+    let args = ConnectorArgs::new(lime_path.to_str(), Default::default(), None);
+    let mut byte = 0u8;
+    create_connector(&args)
+        .unwrap()
+        .phys_read_into(PhysicalAddress::from(0x10ff), &mut byte)
+        .unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(trailer.value, digest);
+    assert_eq!(trailer.algorithm_hint(), Some("sha256"));
+    assert_eq!(trailer.covered_len, 0x120);
+    assert_eq!(segments.len(), 1);
+    assert_eq!(byte, 0x55);
+}
+
+#[test]
+fn sample_dump_has_no_digest_trailer() {
+    assert_eq!(
+        read_digest_trailer("./tests/deb-x86_64-slice.lime").unwrap(),
+        None
+    );
+}
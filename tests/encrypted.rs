@@ -0,0 +1,64 @@
+#![cfg(feature = "age")]
+
+mod common;
+
+use age::secrecy::ExposeSecret;
+use age::x25519::Identity;
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs::{self, File};
+use std::io::Write;
+
+#[test]
+fn encrypted_dump_is_decrypted_on_demand() {
+    let plain_path = tmp_path("encrypted_plain.lime");
+    let age_path = tmp_path("encrypted.lime.age");
+    let keyfile_path = tmp_path("encrypted.key");
+    // Larger than an age chunk, so that reads decrypt chunks in the middle of the file.
+    let data = (0..0x30000u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    write_lime(&plain_path, &[(0x1000, &data), (0x100000, &[0x77; 0x1000])]);
+
+    let identity = Identity::generate();
+    let recipient = identity.to_public();
+    let encryptor =
+        age::Encryptor::with_recipients(std::iter::once(&recipient as &dyn age::Recipient))
+            .unwrap();
+    let mut writer = encryptor
+        .wrap_output(File::create(&age_path).unwrap())
+        .unwrap();
+    writer.write_all(&fs::read(&plain_path).unwrap()).unwrap();
+    writer.finish().unwrap();
+    let key = identity.to_string().expose_secret().to_owned();
+    fs::write(&keyfile_path, format!("# test identity\n{}\n", key)).unwrap();
+
+    let open = |extra_args: Args| {
+        create_connector(&ConnectorArgs::new(age_path.to_str(), extra_args, None))
+    };
+    let mut with_key = open(Args::new().insert("key", &key)).unwrap();
+    let mut with_keyfile =
+        open(Args::new().insert("keyfile", keyfile_path.to_str().unwrap())).unwrap();
+    let wrong_key =
+        open(Args::new().insert("key", Identity::generate().to_string().expose_secret()));
+    let without_key = open(Args::new());
+
+    let mut middle = [0u8; 0x20];
+    with_key
+        .phys_view()
+        .read_raw_into((0x1000 + 0x1fff0).into(), &mut middle)
+        .unwrap();
+    let mut last = [0u8; 0x10];
+    with_keyfile
+        .phys_view()
+        .read_raw_into(0x100ff0.into(), &mut last)
+        .unwrap();
+
+    fs::remove_file(&plain_path).unwrap();
+    fs::remove_file(&age_path).unwrap();
+    fs::remove_file(&keyfile_path).unwrap();
+
+    assert_eq!(middle, data[0x1fff0..0x20010]);
+    assert_eq!(last, [0x77; 0x10]);
+    assert!(wrong_key.is_err());
+    assert!(without_key.is_err());
+}
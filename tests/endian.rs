@@ -0,0 +1,51 @@
+mod common;
+
+use common::tmp_path;
+use memflow::prelude::{Args, ConnectorArgs, PhysicalAddress, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+
+/// `LiME` file of a big-endian target holding the physical range `[0x1000, 0x10ff]`.
+fn big_endian_lime() -> Vec<u8> {
+    let mut dump = Vec::new();
+    dump.extend_from_slice(&0x4C69_4D45_u32.to_be_bytes());
+    dump.extend_from_slice(&1u32.to_be_bytes());
+    dump.extend_from_slice(&0x1000u64.to_be_bytes());
+    dump.extend_from_slice(&0x10ffu64.to_be_bytes());
+    dump.extend_from_slice(&[0; 8]);
+    dump.extend_from_slice(&[0x44; 0x100]);
+    dump
+}
+
+/// Big-endian headers are detected by default and can be requested explicitly, while forcing
+/// little-endian headers refuses the dump.
+#[test]
+fn big_endian_headers_are_parsed() {
+    let lime_path = tmp_path("big_endian.lime");
+    fs::write(&lime_path, big_endian_lime()).unwrap();
+    let args = |endian: &str| {
+        ConnectorArgs::new(
+            lime_path.to_str(),
+            Args::new().insert("endian", endian),
+            None,
+        )
+    };
+
+    let mut detected =
+        create_connector(&ConnectorArgs::new(lime_path.to_str(), Args::new(), None)).unwrap();
+    let mut forced = create_connector(&args("be")).unwrap();
+    let little = create_connector(&args("le"));
+
+    fs::remove_file(&lime_path).unwrap();
+
+    let mut buff = [0u8; 2];
+    detected
+        .phys_read_into(PhysicalAddress::from(0x10fe), &mut buff)
+        .unwrap();
+    assert_eq!(buff, [0x44, 0x44]);
+    forced
+        .phys_read_into(PhysicalAddress::from(0x1000), &mut buff)
+        .unwrap();
+    assert_eq!(buff, [0x44, 0x44]);
+    assert!(little.is_err());
+}
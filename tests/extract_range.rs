@@ -0,0 +1,38 @@
+use memflow::prelude::{ConnectorArgs, PhysicalAddress, PhysicalMemory};
+use memflow_lime::{create_connector, extract_range, read_segments, LimeSegment};
+use std::fs;
+
+/// Extract a window of the sample dump and check that the new file is a valid `LiME` file
+/// holding the same data.
+#[test]
+fn extract_range_roundtrip() {
+    let out_path = std::env::temp_dir().join("memflow_lime_extract_range.lime");
+    extract_range("./tests/deb-x86_64-slice.lime", &out_path, 0x1000, 0x107f).unwrap();
+
+    assert_eq!(
+        read_segments(&out_path).unwrap(),
+        vec![LimeSegment {
+            s_addr: 0x1000,
+            e_addr: 0x107f,
+            file_offset: 32,
+        }]
+    );
+
+    let volatility_output = fs::read("./tests/deb-x86_64-slice_0x1000_volatility3_out").unwrap();
+    let args = ConnectorArgs::new(out_path.to_str(), Default::default(), None);
+    let mut con = create_connector(&args).unwrap();
+    let mut buff = [0u8; 128];
+    con.phys_read_into(PhysicalAddress::from(0x1000), &mut buff)
+        .unwrap();
+
+    fs::remove_file(&out_path).unwrap();
+
+    assert_eq!(buff[..], volatility_output[..]);
+}
+
+#[test]
+fn extract_unbacked_range_fails() {
+    let out_path = std::env::temp_dir().join("memflow_lime_extract_unbacked.lime");
+    assert!(extract_range("./tests/deb-x86_64-slice.lime", &out_path, 0x0, 0xfff).is_err());
+    assert!(!out_path.exists());
+}
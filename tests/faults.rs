@@ -0,0 +1,81 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::v1::*;
+use memflow_lime::create_connector;
+use std::fs;
+
+#[test]
+fn reads_fail_in_faulty_ranges() {
+    let lime_path = tmp_path("faults.lime");
+    write_lime(&lime_path, &[(0x1000, &[0x11; 0x4000])]);
+    let mut mem = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("faults", "0x2000-0x2fff:io_error,0x4000-0x4fff:short_read"),
+        None,
+    ))
+    .unwrap();
+
+    let mut healthy = [0u8; 0x10];
+    let healthy_result = mem.phys_view().read_raw_into(0x1000.into(), &mut healthy);
+    let io_error = mem.phys_view().read_raw_into(0x2ff0.into(), &mut [0; 0x20]);
+
+    let mut short = [0u8; 0x20];
+    let mut failed = Vec::new();
+    let short_result = MemOps::with_raw(
+        std::iter::once(CTup3(
+            PhysicalAddress::from(0x4000),
+            Address::from(0x4000),
+            (&mut short[..]).into(),
+        )),
+        None,
+        Some(
+            &mut (&mut |CTup2(addr, data): ReadData| {
+                failed.push((addr, data.len()));
+                true
+            })
+                .into(),
+        ),
+        |data| mem.phys_read_raw_iter(data),
+    );
+
+    fs::remove_file(&lime_path).unwrap();
+    assert!(healthy_result.is_ok());
+    assert_eq!(healthy, [0x11; 0x10]);
+    assert!(io_error.is_err());
+    assert!(short_result.is_ok());
+    assert_eq!(short[..0x10], [0x11; 0x10]);
+    assert_eq!(short[0x10..], [0; 0x10]);
+    assert_eq!(failed, [(Address::from(0x4010), 0x10)]);
+}
+
+#[test]
+fn fault_rate_is_reproducible() {
+    let lime_path = tmp_path("fault_rate.lime");
+    write_lime(&lime_path, &[(0x1000, &[0x11; 0x1000])]);
+    let failures = |seed: &str| {
+        let mut mem = create_connector(&ConnectorArgs::new(
+            lime_path.to_str(),
+            Args::new()
+                .insert("faults", "0x1000-0x1fff:io_error")
+                .insert("fault_rate", "0.5")
+                .insert("fault_seed", seed),
+            None,
+        ))
+        .unwrap();
+        (0..32)
+            .map(|_| {
+                mem.phys_view()
+                    .read_raw_into(0x1000.into(), &mut [0; 8])
+                    .is_err()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let first = failures("1");
+    let second = failures("1");
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(first, second);
+    assert!(first.contains(&true) && first.contains(&false));
+}
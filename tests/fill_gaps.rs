@@ -0,0 +1,71 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs::{self, OpenOptions};
+
+#[test]
+fn gaps_are_zero_filled_on_request() {
+    let lime_path = tmp_path("fill_gaps.lime");
+    write_lime(&lime_path, &[(0x1000, &[1; 0x100]), (0x2000, &[2; 0x100])]);
+    let args = |fill_gaps: &str| {
+        ConnectorArgs::new(
+            lime_path.to_str(),
+            Args::new().insert("fill_gaps", fill_gaps),
+            None,
+        )
+    };
+
+    let mut strict = create_connector(&args("none")).unwrap();
+    let mut buff = [0xffu8; 0x1000];
+    let unfilled = strict.phys_view().read_raw_into(0x10f0.into(), &mut buff);
+
+    let mut filling = create_connector(&args("zero")).unwrap();
+    let filled = filling.phys_view().read_raw_into(0x10f0.into(), &mut buff);
+    let mut past_end = [0xffu8; 0x10];
+    let beyond = filling
+        .phys_view()
+        .read_raw_into(0x20f8.into(), &mut past_end);
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert!(unfilled.is_err());
+    assert!(filled.is_ok());
+    assert!(buff[..0x10].iter().all(|b| *b == 1));
+    assert!(buff[0x10..0xf10].iter().all(|b| *b == 0));
+    assert!(buff[0xf10..].iter().all(|b| *b == 2));
+    assert!(beyond.is_err());
+    assert!(filling.gap_fill_count() > 0);
+}
+
+/// Reads failing in a mapped range are no gap, and fail with `fill_gaps=zero` too.
+#[test]
+fn failed_reads_of_mapped_ranges_are_not_filled() {
+    let lime_path = tmp_path("fill_gaps_truncated.lime");
+    write_lime(&lime_path, &[(0x1000, &[1; 0x100]), (0x2000, &[2; 0x100])]);
+    let args = ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("fill_gaps", "zero"),
+        None,
+    );
+    let mut filling = create_connector(&args).unwrap();
+    // Cut the second range once the dump is mapped.
+    OpenOptions::new()
+        .write(true)
+        .open(&lime_path)
+        .unwrap()
+        .set_len(2 * 0x20 + 0x180)
+        .unwrap();
+
+    let mut buff = [0xffu8; 0x20];
+    let cut = filling.phys_view().read_raw_into(0x20f0.into(), &mut buff);
+    let mut below = [0xffu8; 0x10];
+    let before_first = filling.phys_view().read_raw_into(0x800.into(), &mut below);
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert!(cut.is_err());
+    assert!(before_first.is_err());
+    assert_eq!(filling.gap_fill_count(), 0);
+}
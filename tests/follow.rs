@@ -0,0 +1,57 @@
+mod common;
+
+use common::{header_bytes, tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// With `follow`, a read past the end of the dump waits for the data to be appended, and
+/// fails once the timeout expires if it never is.
+#[test]
+fn reads_wait_for_appended_data() {
+    let lime_path = tmp_path("follow.lime");
+    write_lime(&lime_path, &[(0x1000, &[0x11; 0x1000])]);
+    let mut connector = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("follow", "5000"),
+        None,
+    ))
+    .unwrap();
+
+    let writer = {
+        let lime_path = lime_path.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            let mut file = OpenOptions::new().append(true).open(lime_path).unwrap();
+            file.write_all(&header_bytes(1, 0x2000, 0x2fff, [0; 8]))
+                .unwrap();
+            file.write_all(&[0x22; 0x1000]).unwrap();
+        })
+    };
+    let mut buff = [0u8; 0x10];
+    connector
+        .phys_view()
+        .read_raw_into(0x2ff0.into(), &mut buff)
+        .unwrap();
+    writer.join().unwrap();
+    assert_eq!(buff, [0x22; 0x10]);
+
+    let mut impatient = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("follow", "100"),
+        None,
+    ))
+    .unwrap();
+    let start = Instant::now();
+    let missing = impatient
+        .phys_view()
+        .read_raw_into(0x5000.into(), &mut buff);
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert!(missing.is_err());
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
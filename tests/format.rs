@@ -0,0 +1,50 @@
+mod common;
+
+use common::{open, tmp_path, write_lime};
+use memflow::prelude::{Args, ErrorKind, MemoryView, PhysicalMemory};
+use std::fs;
+
+/// The format of a dump is detected from its content, a LiME dump opening whatever its name.
+#[test]
+fn lime_dumps_open_whatever_their_name() {
+    let mut read = Vec::new();
+    for name in [
+        "x.json",
+        "x.elf",
+        "x.core",
+        "x.aff4",
+        "kcore",
+        "hiberfil.sys",
+    ] {
+        let lime_path = tmp_path(&format!("format_{}", name));
+        write_lime(&lime_path, &[(0x1000, &[0x11; 0x1000])]);
+        let mut buff = [0u8; 4];
+        open(&lime_path, Args::new())
+            .unwrap()
+            .phys_view()
+            .read_raw_into(0x1000.into(), &mut buff)
+            .unwrap();
+        fs::remove_file(&lime_path).unwrap();
+        read.push(buff);
+    }
+
+    assert_eq!(read, [[0x11; 4]; 6]);
+}
+
+/// `format` overrides the detection, formats of features missing from the build being refused.
+#[test]
+fn format_names_the_format() {
+    let lime_path = tmp_path("format_explicit.json");
+    write_lime(&lime_path, &[(0x1000, &[0x11; 0x1000])]);
+    let lime = open(&lime_path, Args::new().insert("format", "lime"))
+        .unwrap()
+        .metadata()
+        .real_size;
+    let hiberfil = open(&lime_path, Args::new().insert("format", "hiberfil"));
+    let unknown = open(&lime_path, Args::new().insert("format", "vmem"));
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(lime, 0x1000);
+    assert!(hiberfil.is_err());
+    assert_eq!(unknown.err().unwrap().1, ErrorKind::ArgValidation);
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector_from_file;
+use std::fs::{self, File};
+
+/// A connector built from an open handle reads like one built from the path, arguments
+/// needing the path being refused.
+#[test]
+fn connector_from_open_file() {
+    let lime_path = tmp_path("from_file.lime");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x1000]), (0x4000, &[0x44; 0x1000])],
+    );
+
+    let open = |target: Option<&str>, args: Args| {
+        let file = File::open(&lime_path).unwrap();
+        create_connector_from_file(file, &ConnectorArgs::new(target, args, None))
+    };
+    let mut buffs = [[0u8; 0x10]; 2];
+    for (buff, args) in buffs
+        .iter_mut()
+        .zip([Args::new(), Args::new().insert("parse", "lazy")])
+    {
+        open(None, args)
+            .unwrap()
+            .phys_view()
+            .read_raw_into(0x4ff0.into(), buff)
+            .unwrap();
+    }
+    let refused = [
+        open(lime_path.to_str(), Args::new()).is_err(),
+        open(None, Args::new().insert("index", "auto")).is_err(),
+        open(None, Args::new().insert("odirect", "true")).is_err(),
+    ];
+
+    fs::remove_file(lime_path).unwrap();
+    assert_eq!(buffs, [[0x44; 0x10]; 2]);
+    assert_eq!(refused, [true; 3]);
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs};
+use memflow_lime::{create_connector, LimeGap};
+use std::fs;
+
+/// The holes between the mapped ranges are reported with their sizes, adjacent ranges
+/// leaving none.
+#[test]
+fn gaps_between_ranges_are_reported() {
+    let lime_path = tmp_path("gaps.lime");
+    write_lime(
+        &lime_path,
+        &[
+            (0x1000, &[0x11; 0x1000]),
+            (0x2000, &[0x22; 0x1000]),
+            (0x10000, &[0x33; 0x1000]),
+            (0x100000, &[0x44; 0x2000]),
+        ],
+    );
+
+    let mem = create_connector(&ConnectorArgs::new(lime_path.to_str(), Args::new(), None)).unwrap();
+    let gaps = mem.gaps();
+
+    fs::remove_file(&lime_path).unwrap();
+    assert_eq!(
+        gaps,
+        [
+            LimeGap {
+                s_addr: 0x3000,
+                e_addr: 0xffff
+            },
+            LimeGap {
+                s_addr: 0x11000,
+                e_addr: 0xfffff
+            }
+        ]
+    );
+    assert_eq!(gaps[0].size(), 0xd000);
+    assert_eq!(gaps[1].size(), 0xef000);
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::tmp_path;
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::{create_connector, read_layout, ParseMode};
+use std::fs;
+use std::process::Command;
+
+/// The generated dump maps the requested ranges, filled with their addresses and holding the
+/// strings at their addresses, and is the same on every run.
+#[test]
+fn generated_dumps_are_deterministic() {
+    let path = tmp_path("generated.lime");
+    let generate = || {
+        Command::new(env!("CARGO_BIN_EXE_memflow-lime-gen"))
+            .arg(&path)
+            .args(["--range", "0x1000:8k", "--range", "0x100000:0x1000"])
+            .args(["--string", "0x1ffc:needle", "--segment-size", "4k"])
+            .status()
+            .unwrap()
+    };
+    assert!(generate().success());
+    let first = fs::read(&path).unwrap();
+    assert!(generate().success());
+    let second = fs::read(&path).unwrap();
+    let layout = read_layout(&path, ParseMode::Strict).unwrap();
+    let mut connector =
+        create_connector(&ConnectorArgs::new(path.to_str(), Args::new(), None)).unwrap();
+    let mut word = [0u8; 8];
+    connector
+        .phys_view()
+        .read_raw_into(0x100ff8.into(), &mut word)
+        .unwrap();
+    let mut needle = [0u8; 6];
+    connector
+        .phys_view()
+        .read_raw_into(0x1ffc.into(), &mut needle)
+        .unwrap();
+    let random = Command::new(env!("CARGO_BIN_EXE_memflow-lime-gen"))
+        .arg(&path)
+        .args(["--range", "0x1000:0", "--fill", "random:7"])
+        .status()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(layout.segments.len(), 3);
+    assert_eq!(u64::from_le_bytes(word), 0x100ff8);
+    assert_eq!(&needle, b"needle");
+    assert_eq!(random.code(), Some(2));
+}
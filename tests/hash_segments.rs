@@ -0,0 +1,49 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow_lime::{hash_segments, write_hash_manifest, HashAlgorithm};
+use sha2::{Digest, Sha256};
+use std::fs;
+
+#[test]
+fn every_range_is_hashed_on_its_own() {
+    let lime_path = tmp_path("hash_segments.lime");
+    let manifest_path = tmp_path("hash_segments.manifest");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x1000]), (0x8000, &[0x88; 0x234])],
+    );
+
+    let digests = hash_segments(&lime_path, HashAlgorithm::Sha256).unwrap();
+    write_hash_manifest(&manifest_path, HashAlgorithm::Sha256, &digests).unwrap();
+    let manifest = fs::read_to_string(&manifest_path).unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+    fs::remove_file(&manifest_path).unwrap();
+
+    assert_eq!(digests.len(), 2);
+    assert_eq!(digests[0].segment.s_addr, 0x1000);
+    assert_eq!(digests[0].digest, Sha256::digest([0x11; 0x1000]).to_vec());
+    assert_eq!(digests[1].segment.e_addr, 0x8233);
+    assert_eq!(digests[1].digest, Sha256::digest([0x88; 0x234]).to_vec());
+
+    let lines = manifest.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "# sha256");
+    assert!(lines[2].ends_with("  0x8000-0x8233  0x1040"));
+}
+
+#[cfg(feature = "blake3")]
+#[test]
+fn blake3_digests() {
+    let lime_path = tmp_path("hash_segments_blake3.lime");
+    write_lime(&lime_path, &[(0x2000, &[0x22; 0x3000])]);
+
+    let digests = hash_segments(&lime_path, HashAlgorithm::Blake3).unwrap();
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(
+        digests[0].digest,
+        blake3::hash(&[0x22; 0x3000]).as_bytes().to_vec()
+    );
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "hiberfil")]
+
+mod common;
+
+use common::tmp_path;
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+
+const PAGE_SIZE: usize = 0x1000;
+
+/// Compressed block of `pages` pages holding `data`.
+fn block(pages: usize, data: &[u8]) -> Vec<u8> {
+    let info = ((data.len() as u32 - 1) << 10) | (pages as u32 - 1);
+    let mut block = b"\x81\x81xpress".to_vec();
+    block.extend_from_slice(&info.to_le_bytes());
+    block.resize(0x20, 0);
+    block.extend_from_slice(data);
+    block.resize(block.len().next_multiple_of(8), 0);
+    block
+}
+
+/// Hibernation file holding pages 0x1 and 0x10-0x11, the first page being compressed.
+fn write_hiberfil(path: &std::path::Path) {
+    let mut file = vec![0u8; 2 * PAGE_SIZE];
+    file[..4].copy_from_slice(b"HIBR");
+    file[0x18..0x1c].copy_from_slice(&(PAGE_SIZE as u32).to_le_bytes());
+    file[0x68..0x70].copy_from_slice(&1u64.to_le_bytes());
+    let table = &mut file[PAGE_SIZE..];
+    table[8..12].copy_from_slice(&2u32.to_le_bytes());
+    for (index, (start, end)) in [(0x1u64, 0x2u64), (0x10, 0x12)].into_iter().enumerate() {
+        let entry = 0x10 + index * 0x10;
+        table[entry..entry + 8].copy_from_slice(&start.to_le_bytes());
+        table[entry + 8..entry + 16].copy_from_slice(&end.to_le_bytes());
+    }
+    // "abc" repeated over a page, compressed with Xpress.
+    let abc = [
+        0x00, 0x00, 0x00, 0x18, 0x61, 0x62, 0x63, 0x17, 0x00, 0x0f, 0xff, 0xfa, 0x0f,
+    ];
+    file.extend(block(1, &abc));
+    file.extend(block(2, &[0x44; 2 * PAGE_SIZE]));
+    fs::write(path, file).unwrap();
+}
+
+/// Compressed and stored blocks are mapped at the addresses of their memory ranges.
+#[test]
+fn hibernation_files_are_mapped() {
+    let dir = tmp_path("hiberfil");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("hiberfil.sys");
+    write_hiberfil(&path);
+
+    let mut connector =
+        create_connector(&ConnectorArgs::new(path.to_str(), Args::new(), None)).unwrap();
+    let mut compressed = [0u8; 6];
+    connector
+        .phys_view()
+        .read_raw_into(0x1ffa.into(), &mut compressed)
+        .unwrap();
+    let mut stored = [0u8; 0x10];
+    connector
+        .phys_view()
+        .read_raw_into(0x11ff0.into(), &mut stored)
+        .unwrap();
+    let gap = connector
+        .phys_view()
+        .read_raw_into(0x2000.into(), &mut [0u8; 0x10])
+        .is_err();
+    drop(connector);
+
+    let mut wiped = fs::read(&path).unwrap();
+    wiped[..4].fill(0);
+    fs::write(&path, wiped).unwrap();
+    let refused = create_connector(&ConnectorArgs::new(path.to_str(), Args::new(), None));
+
+    fs::remove_dir_all(dir).unwrap();
+    // 0x1000 % 3 == 1, so the page ends with "...bca" followed by "b"
+    assert_eq!(&compressed, b"bcabca");
+    assert_eq!(stored, [0x44; 0x10]);
+    assert!(gap);
+    assert!(refused.is_err());
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, PhysicalAddress, PhysicalMemory};
+use memflow_lime::{create_connector, default_index_path};
+use std::fs;
+
+/// The index is written on the first open and rebuilt once the dump changes.
+#[test]
+fn index_is_created_and_refreshed() {
+    let lime_path = tmp_path("index_cache.lime");
+    let index_path = default_index_path(&lime_path);
+    let _ = fs::remove_file(&index_path);
+    let args = ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("index", "auto"),
+        None,
+    );
+    let mut buff = [0u8; 2];
+
+    write_lime(&lime_path, &[(0x1000, &[0x55; 0x100])]);
+    create_connector(&args)
+        .unwrap()
+        .phys_read_into(PhysicalAddress::from(0x1000), &mut buff)
+        .unwrap();
+    let first_index = fs::read(&index_path).unwrap();
+    assert_eq!(buff, [0x55, 0x55]);
+
+    create_connector(&args).unwrap();
+    assert_eq!(fs::read(&index_path).unwrap(), first_index);
+
+    write_lime(&lime_path, &[(0x2000, &[0x66; 0x200])]);
+    create_connector(&args)
+        .unwrap()
+        .phys_read_into(PhysicalAddress::from(0x2000), &mut buff)
+        .unwrap();
+    let second_index = fs::read(&index_path).unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+    fs::remove_file(&index_path).unwrap();
+
+    assert_eq!(buff, [0x66, 0x66]);
+    assert_ne!(second_index, first_index);
+}
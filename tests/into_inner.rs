@@ -0,0 +1,52 @@
+mod common;
+
+use common::{open, tmp_path, write_lime};
+use memflow::prelude::{Args, ErrorKind};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// The file handle and the memory map are handed back, unless the dump spans several files.
+#[test]
+fn into_inner_returns_file_and_map() {
+    let lime_path = tmp_path("into_inner.lime");
+    let delta_path = tmp_path("into_inner_delta.lime");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x1000]), (0x4000, &[0x44; 0x1000])],
+    );
+    write_lime(&delta_path, &[(0x1000, &[0x22; 0x10])]);
+
+    let (mut file, mem_map) = open(&lime_path, Args::new().insert("cache_size", "1mb"))
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    let mut magic = [0u8; 4];
+    file.seek(SeekFrom::Start(0)).unwrap();
+    file.read_exact(&mut magic).unwrap();
+    let overlay = open(
+        &lime_path,
+        Args::new().insert("overlay", delta_path.to_str().unwrap()),
+    )
+    .unwrap()
+    .into_inner()
+    .unwrap_err();
+    let composed = open(
+        Path::new(&format!(
+            "{};{}@0x100000",
+            lime_path.display(),
+            delta_path.display()
+        )),
+        Args::new(),
+    )
+    .unwrap()
+    .into_inner()
+    .unwrap_err();
+
+    fs::remove_file(lime_path).unwrap();
+    fs::remove_file(delta_path).unwrap();
+    assert_eq!(mem_map.iter().count(), 2);
+    assert_eq!(&magic, b"EMiL");
+    assert_eq!(overlay.1, ErrorKind::NotSupported);
+    assert_eq!(composed.1, ErrorKind::NotSupported);
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "kcore")]
+
+mod common;
+
+use common::tmp_path;
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::{create_connector, kcore_ranges, KcoreRange};
+use std::fs;
+
+/// ELF core file with the kernel text, a vmalloc area and 0x2000 bytes of RAM at 0x100000,
+/// like `/proc/kcore` lists them.
+fn write_kcore(path: &std::path::Path) {
+    let phdrs = [
+        // PT_NOTE
+        (4u32, 0x1000u64, 0u64, 0u64, 0x10u64),
+        // Kernel text, repeating the second page of RAM
+        (1, 0x2000, 0xffff_ffff_8100_0000, 0x10_1000, 0x1000),
+        // vmalloc, without physical address
+        (1, 0x5000, 0xffff_c900_0000_0000, u64::MAX, 0x1000),
+        // RAM
+        (1, 0x2000, 0xffff_8880_0010_0000, 0x10_0000, 0x2000),
+    ];
+    let mut file = vec![0u8; 0x40];
+    file[..4].copy_from_slice(b"\x7fELF");
+    file[4] = 2;
+    file[5] = 1;
+    file[6] = 1;
+    file[0x10..0x12].copy_from_slice(&4u16.to_le_bytes());
+    file[0x20..0x28].copy_from_slice(&0x40u64.to_le_bytes());
+    file[0x36..0x38].copy_from_slice(&0x38u16.to_le_bytes());
+    file[0x38..0x3a].copy_from_slice(&(phdrs.len() as u16).to_le_bytes());
+    for (p_type, offset, vaddr, paddr, size) in phdrs {
+        let mut phdr = [0u8; 0x38];
+        phdr[..4].copy_from_slice(&p_type.to_le_bytes());
+        phdr[8..0x10].copy_from_slice(&offset.to_le_bytes());
+        phdr[0x10..0x18].copy_from_slice(&vaddr.to_le_bytes());
+        phdr[0x18..0x20].copy_from_slice(&paddr.to_le_bytes());
+        phdr[0x20..0x28].copy_from_slice(&size.to_le_bytes());
+        phdr[0x28..0x30].copy_from_slice(&size.to_le_bytes());
+        file.extend_from_slice(&phdr);
+    }
+    file.resize(0x2000, 0);
+    file.extend([0x11; 0x1000]);
+    file.extend([0x22; 0x1000]);
+    fs::write(path, file).unwrap();
+}
+
+/// The RAM segments are mapped at their physical addresses, the kernel text and the areas
+/// without physical address are left out.
+#[test]
+fn kcore_ram_is_mapped() {
+    let dir = tmp_path("kcore");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("kcore");
+    write_kcore(&path);
+
+    let ranges = kcore_ranges(&path).unwrap();
+    let mut connector =
+        create_connector(&ConnectorArgs::new(path.to_str(), Args::new(), None)).unwrap();
+    let mut ram = [0u8; 4];
+    connector
+        .phys_view()
+        .read_raw_into(0x100ffe.into(), &mut ram)
+        .unwrap();
+    let past_end = connector
+        .phys_view()
+        .read_raw_into(0x102000.into(), &mut [0u8; 4]);
+    let real_size = connector.metadata().real_size;
+    fs::write(&path, b"not an ELF file").unwrap();
+    let not_elf = create_connector(&ConnectorArgs::new(
+        path.to_str(),
+        Args::new().insert("format", "kcore"),
+        None,
+    ));
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(ranges.len(), 3);
+    assert_eq!(
+        ranges[2],
+        KcoreRange {
+            virt_addr: 0xffff_8880_0010_0000,
+            phys_addr: Some(0x10_0000),
+            file_offset: 0x2000,
+            size: 0x2000,
+        }
+    );
+    assert_eq!(ranges[1].phys_addr, None);
+    assert_eq!(ram, [0x11, 0x11, 0x22, 0x22]);
+    assert!(past_end.is_err());
+    assert_eq!(real_size, 0x2000);
+    assert!(not_elf.is_err());
+}
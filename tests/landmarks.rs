@@ -0,0 +1,48 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs};
+use memflow_lime::{create_connector, LimeLandmark, LimeLandmarkKind};
+use std::fs;
+
+const BANNER: &[u8] = b"Linux version 6.1.0-18-amd64 (debian-kernel@lists.debian.org)\n";
+
+/// Landmarks are found in one pass with their text, including one ending with its range,
+/// while unprintable candidates are left out.
+#[test]
+fn landmarks_are_found() {
+    let lime_path = tmp_path("landmarks.lime");
+    let mut kernel = vec![0u8; 0x2000];
+    kernel[0x100..0x100 + BANNER.len()].copy_from_slice(BANNER);
+    kernel[0x800..0x80a].copy_from_slice(b"swapper/0\0");
+    kernel[0x1000..0x1010].copy_from_slice(b"Linux version \x01\x02");
+    let mut note = vec![0u8; 0x1000];
+    note[0xfda..].copy_from_slice(b"PAGESIZE=4096\nOSRELEASE=6.1.0-18-amd64");
+    write_lime(&lime_path, &[(0x1000000, &kernel), (0x3000, &note)]);
+
+    let mut mem =
+        create_connector(&ConnectorArgs::new(lime_path.to_str(), Args::new(), None)).unwrap();
+    let landmarks = mem.find_landmarks().unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+    assert_eq!(
+        landmarks,
+        [
+            LimeLandmark {
+                kind: LimeLandmarkKind::Banner,
+                addr: 0x1000100,
+                text: String::from_utf8(BANNER[..BANNER.len() - 1].to_vec()).unwrap()
+            },
+            LimeLandmark {
+                kind: LimeLandmarkKind::Swapper,
+                addr: 0x1000800,
+                text: "swapper/0".to_owned()
+            },
+            LimeLandmark {
+                kind: LimeLandmarkKind::VmcoreInfo,
+                addr: 0x3fe8,
+                text: "OSRELEASE=6.1.0-18-amd64".to_owned()
+            }
+        ]
+    );
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, PhysicalAddress, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+
+/// A lazy parse only walks the headers reads need, so a corrupt header past the ranges read
+/// does not prevent opening the dump.
+#[test]
+fn lazy_parse_walks_headers_on_demand() {
+    let lime_path = tmp_path("lazy_parse.lime");
+    write_lime(
+        &lime_path,
+        &[
+            (0x1000, &[0x11; 0x100]),
+            (0x2000, &[0x22; 0x100]),
+            (0x3000, &[0x33; 0x100]),
+        ],
+    );
+    let mut file = OpenOptions::new().write(true).open(&lime_path).unwrap();
+    file.seek(SeekFrom::Start(2 * 0x120)).unwrap();
+    file.write_all(b"XXXX").unwrap();
+    drop(file);
+
+    let eager = create_connector(&ConnectorArgs::new(lime_path.to_str(), Args::new(), None));
+    let mut lazy = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("parse", "lazy"),
+        None,
+    ))
+    .unwrap();
+
+    let mut buff = [0u8; 2];
+    lazy.phys_read_into(PhysicalAddress::from(0x20fe), &mut buff)
+        .unwrap();
+    let max_address = lazy.metadata().max_address;
+    let past_corruption = lazy.phys_read_into(PhysicalAddress::from(0x3000), &mut [0u8; 2]);
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert!(eager.is_err());
+    assert_eq!(buff, [0x22, 0x22]);
+    assert_eq!(max_address, 0x20ff.into());
+    assert!(past_corruption.is_err());
+}
+
+/// Discovering more ranges keeps the pages cached so far, and reads spanning adjacent ranges
+/// discovered one at a time are backed.
+#[test]
+fn lazy_discovery_keeps_the_page_cache() {
+    let lime_path = tmp_path("lazy_parse_cache.lime");
+    write_lime(
+        &lime_path,
+        &[
+            (0x1000, &[0x11; 0x1000]),
+            (0x2000, &[0x22; 0x1000]),
+            (0x8000, &[0x88; 0x1000]),
+        ],
+    );
+    let mut lazy = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new()
+            .insert("parse", "lazy")
+            .insert("cache_size", "64kb"),
+        None,
+    ))
+    .unwrap();
+
+    let mut first = [0u8; 2];
+    lazy.phys_read_into(PhysicalAddress::from(0x1000), &mut first)
+        .unwrap();
+    let mut file = OpenOptions::new().write(true).open(&lime_path).unwrap();
+    file.seek(SeekFrom::Start(0x20)).unwrap();
+    file.write_all(&[0xff; 2]).unwrap();
+    drop(file);
+
+    let mut spanning = [0u8; 4];
+    lazy.phys_read_into(PhysicalAddress::from(0x1ffe), &mut spanning)
+        .unwrap();
+    let mut last = [0u8; 2];
+    lazy.phys_read_into(PhysicalAddress::from(0x8000), &mut last)
+        .unwrap();
+    let mut cached = [0u8; 2];
+    lazy.phys_read_into(PhysicalAddress::from(0x1000), &mut cached)
+        .unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(first, [0x11, 0x11]);
+    assert_eq!(spanning, [0x11, 0x11, 0x22, 0x22]);
+    assert_eq!(last, [0x88, 0x88]);
+    // Served from the cache filled before the other ranges were discovered.
+    assert_eq!(cached, [0x11, 0x11]);
+}
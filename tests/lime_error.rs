@@ -0,0 +1,69 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Error, ErrorKind, ErrorOrigin};
+use memflow_lime::{read_layout_detailed, LimeError, ParseMode};
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+
+/// A corrupt magic number is reported with the header, its offset and the bytes found.
+#[test]
+fn bad_magic_is_located() {
+    let lime_path = tmp_path("lime_error_magic.lime");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x1000]), (0x3000, &[0x22; 0x1000])],
+    );
+    let mut file = OpenOptions::new().write(true).open(&lime_path).unwrap();
+    file.seek(SeekFrom::Start(0x1020)).unwrap();
+    file.write_all(b"XXXX").unwrap();
+    drop(file);
+
+    let err = read_layout_detailed(&lime_path, ParseMode::Strict).unwrap_err();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert!(matches!(
+        err,
+        LimeError::BadMagic {
+            header: 1,
+            offset: 0x1020,
+            expected: [0x45, 0x4d, 0x69, 0x4c],
+            found: [b'X', b'X', b'X', b'X'],
+        }
+    ));
+    assert!(err.to_string().contains("#1 at offset 0x1020"));
+    assert_eq!(
+        Error::from(err),
+        Error(ErrorOrigin::Connector, ErrorKind::UnableToReadFile)
+    );
+}
+
+/// Unsupported versions are refused in strict mode with the version found, and tolerated in
+/// lenient mode.
+#[test]
+fn unsupported_version_is_located() {
+    let lime_path = tmp_path("lime_error_version.lime");
+    write_lime(&lime_path, &[(0x1000, &[0x11; 0x1000])]);
+    let mut file = OpenOptions::new().write(true).open(&lime_path).unwrap();
+    file.seek(SeekFrom::Start(4)).unwrap();
+    file.write_all(&3u32.to_le_bytes()).unwrap();
+    drop(file);
+
+    let strict = read_layout_detailed(&lime_path, ParseMode::Strict).unwrap_err();
+    let lenient = read_layout_detailed(&lime_path, ParseMode::Lenient);
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert!(matches!(
+        strict,
+        LimeError::UnsupportedVersion {
+            header: 0,
+            offset: 0,
+            expected: 1,
+            found: 3,
+        }
+    ));
+    assert_eq!((strict.header(), strict.offset()), (Some(0), Some(0)));
+    assert_eq!(lenient.unwrap().anomalies.len(), 1);
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::{open, tmp_path, write_lime};
+use memflow::prelude::{Args, ErrorKind, MemoryView, PhysicalMemory};
+use std::fs;
+
+/// Dumps exceeding a limit are refused, dumps within the limits being opened as usual. With
+/// `parse=lazy` the reads reaching past a limit fail instead.
+#[test]
+fn limits_are_enforced() {
+    let lime_path = tmp_path("limits.lime");
+    write_lime(
+        &lime_path,
+        &[
+            (0x1000, &[0x11; 0x1000]),
+            (0x4000, &[0x44; 0x2000]),
+            (0x8000, &[0x88; 0x1000]),
+        ],
+    );
+
+    let within = Args::new()
+        .insert("max_segments", "3")
+        .insert("max_segment_size", "8k")
+        .insert("max_total_size", "16k");
+    let mut connector = open(&lime_path, within).unwrap();
+    let mut buff = [0u8; 0x10];
+    connector
+        .phys_view()
+        .read_raw_into(0x5ff0.into(), &mut buff)
+        .unwrap();
+
+    let kinds = [
+        ("max_segments", "2"),
+        ("max_segment_size", "4k"),
+        ("max_total_size", "3k"),
+    ]
+    .into_iter()
+    .flat_map(|(key, value)| {
+        [None, Some(("scan", "parallel"))]
+            .into_iter()
+            .map(move |extra| {
+                let mut args = Args::new().insert(key, value);
+                if let Some((extra_key, extra_value)) = extra {
+                    args = args.insert(extra_key, extra_value);
+                }
+                args
+            })
+    })
+    .map(|args| open(&lime_path, args).err().map(|err| err.1))
+    .collect::<Vec<_>>();
+    let lazy = Args::new()
+        .insert("parse", "lazy")
+        .insert("max_segments", "2");
+    let lazy_read = open(&lime_path, lazy)
+        .unwrap()
+        .phys_view()
+        .read_raw_into(0x8000.into(), &mut buff)
+        .is_err();
+
+    fs::remove_file(&lime_path).unwrap();
+    assert_eq!(buff, [0x44; 0x10]);
+    assert!(kinds
+        .iter()
+        .all(|kind| *kind == Some(ErrorKind::OutOfBounds)));
+    assert!(lazy_read);
+}
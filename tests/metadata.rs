@@ -0,0 +1,74 @@
+mod common;
+
+use common::{open, tmp_path, write_lime};
+use memflow::prelude::{Args, PhysicalMemory};
+use std::fs;
+
+/// The metadata reports the highest mapped address and the mapped size, not the file size.
+#[test]
+fn metadata_matches_mapped_ranges() {
+    let holes = tmp_path("metadata_holes.lime");
+    let overlapping = tmp_path("metadata_overlap.lime");
+    let empty = tmp_path("metadata_empty.lime");
+    write_lime(
+        &holes,
+        &[(0x1000, &[0x11; 0x1000]), (0x10_0000, &[0x22; 0x2000])],
+    );
+    write_lime(
+        &overlapping,
+        &[(0x1000, &[0x11; 0x2000]), (0x2000, &[0x22; 0x2000])],
+    );
+    write_lime(&empty, &[]);
+
+    let extent = |path: &std::path::Path, args: Args| {
+        let metadata = open(path, args).unwrap().metadata();
+        (metadata.max_address.to_umem(), metadata.real_size)
+    };
+    let results = [
+        extent(&holes, Args::new()),
+        extent(&holes, Args::new().insert("fill_gaps", "zero")),
+        extent(&overlapping, Args::new().insert("overlap", "last")),
+        extent(&empty, Args::new()),
+        extent(&holes, Args::new().insert("parse", "lazy")),
+    ];
+
+    for path in [holes, overlapping, empty] {
+        fs::remove_file(path).unwrap();
+    }
+    assert_eq!(
+        results,
+        [
+            (0x10_1fff, 0x3000),
+            (0x10_1fff, 0x3000),
+            (0x3fff, 0x3000),
+            (0, 0),
+            (0, 0),
+        ]
+    );
+}
+
+/// The memory map lists the backed ranges with the offset of their data in the file.
+#[test]
+fn mem_map_lists_backed_ranges() {
+    let lime_path = tmp_path("metadata_mem_map.lime");
+    write_lime(
+        &lime_path,
+        &[(0x10_0000, &[0x22; 0x2000]), (0x1000, &[0x11; 0x1000])],
+    );
+
+    let connector = open(&lime_path, Args::new()).unwrap();
+    let mappings = connector
+        .mem_map()
+        .iter()
+        .map(|mapping| {
+            let (file_offset, size) = *mapping.output();
+            (mapping.base().to_umem(), size, file_offset.to_umem())
+        })
+        .collect::<Vec<_>>();
+
+    fs::remove_file(lime_path).unwrap();
+    assert_eq!(
+        mappings,
+        [(0x1000, 0x1000, 0x2040), (0x10_0000, 0x2000, 0x20)]
+    );
+}
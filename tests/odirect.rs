@@ -0,0 +1,46 @@
+#![cfg(all(target_os = "linux", feature = "odirect"))]
+
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, PhysicalAddress, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+
+/// Unaligned reads through `odirect=true` return the same data as buffered reads, whether the
+/// filesystem accepts `O_DIRECT` or not.
+#[test]
+fn direct_reads_match_buffered_reads() {
+    let lime_path = tmp_path("odirect.lime");
+    let data = (0..0x2345).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    write_lime(&lime_path, &[(0x1000, &data), (0x8000, &data[..0x123])]);
+    let open = |extra_args: Args| {
+        create_connector(&ConnectorArgs::new(lime_path.to_str(), extra_args, None)).unwrap()
+    };
+    let mut direct = open(Args::new().insert("odirect", "true"));
+    let mut buffered = open(Args::new());
+
+    let mut reads = Vec::new();
+    for (addr, len) in [
+        (0x1000, 0x10),
+        (0x1ffd, 0x1001),
+        (0x3300, 0x45),
+        (0x8100, 0x23),
+    ] {
+        let mut from_direct = vec![0u8; len];
+        let mut from_buffered = vec![0u8; len];
+        direct
+            .phys_read_into(PhysicalAddress::from(addr), &mut from_direct[..])
+            .unwrap();
+        buffered
+            .phys_read_into(PhysicalAddress::from(addr), &mut from_buffered[..])
+            .unwrap();
+        reads.push((addr, from_direct, from_buffered));
+    }
+
+    fs::remove_file(&lime_path).unwrap();
+
+    for (addr, from_direct, from_buffered) in reads {
+        assert_eq!(from_direct, from_buffered, "read at {:#x}", addr);
+    }
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, PhysicalAddress, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+
+#[test]
+fn overlapping_ranges_follow_policy() {
+    let lime_path = tmp_path("overlap.lime");
+    write_lime(&lime_path, &[(0x1000, &[1; 0x100]), (0x1080, &[2; 0x100])]);
+    let args = |policy: &str| {
+        ConnectorArgs::new(
+            lime_path.to_str(),
+            Args::new().insert("overlap", policy),
+            None,
+        )
+    };
+
+    let refused = create_connector(&args("error"));
+    let mut default = [0u8; 2];
+    create_connector(&ConnectorArgs::new(lime_path.to_str(), Args::new(), None))
+        .unwrap()
+        .phys_read_into(PhysicalAddress::from(0x107f), &mut default)
+        .unwrap();
+    let mut first = [0u8; 2];
+    create_connector(&args("first"))
+        .unwrap()
+        .phys_read_into(PhysicalAddress::from(0x10ff), &mut first)
+        .unwrap();
+    let mut last = [0u8; 2];
+    create_connector(&args("last"))
+        .unwrap()
+        .phys_read_into(PhysicalAddress::from(0x107f), &mut last)
+        .unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert!(refused.is_err());
+    assert_eq!(first, [1, 2]);
+    assert_eq!(last, [1, 2]);
+    assert_eq!(default, last);
+}
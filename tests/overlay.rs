@@ -0,0 +1,55 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::{create_connector, LimeWriter};
+use std::fs::{self, File};
+
+/// Every overlay shadows the base dump and the overlays before it where their ranges overlap.
+#[test]
+fn overlays_shadow_the_layers_below() {
+    let base = tmp_path("overlay_base.lime");
+    let delta1 = tmp_path("overlay_delta1.lime");
+    let delta2 = tmp_path("overlay_delta2.lime");
+    write_lime(&base, &[(0x1000, &[0x11; 0x3000])]);
+    let mut writer = LimeWriter::new(File::create(&delta1).unwrap());
+    writer.write_range(0x2000, &[0xd1; 0x1000]).unwrap();
+    writer.finish().unwrap();
+    let mut writer = LimeWriter::new(File::create(&delta2).unwrap());
+    writer.write_range(0x2800, &[0xd2; 0x800]).unwrap();
+    writer.write_range(0x8000, &[0xd2; 0x1000]).unwrap();
+    writer.finish().unwrap();
+
+    let overlays = format!("{},{}", delta1.display(), delta2.display());
+    let mut connector = create_connector(&ConnectorArgs::new(
+        base.to_str(),
+        Args::new().insert("overlay", &overlays),
+        None,
+    ))
+    .unwrap();
+    let mut buff = vec![0u8; 0x3000];
+    connector
+        .phys_view()
+        .read_raw_into(0x1000.into(), &mut buff)
+        .unwrap();
+    let mut added = [0u8; 0x10];
+    connector
+        .phys_view()
+        .read_raw_into(0x8ff0.into(), &mut added)
+        .unwrap();
+    let missing = create_connector(&ConnectorArgs::new(
+        base.to_str(),
+        Args::new().insert("overlay", "missing.lime"),
+        None,
+    ));
+
+    for path in [base, delta1, delta2] {
+        fs::remove_file(path).unwrap();
+    }
+    assert_eq!(buff[..0x1000], [0x11; 0x1000]);
+    assert_eq!(buff[0x1000..0x1800], [0xd1; 0x800]);
+    assert_eq!(buff[0x1800..0x2000], [0xd2; 0x800]);
+    assert_eq!(buff[0x2000..], [0x11; 0x1000]);
+    assert_eq!(added, [0xd2; 0x10]);
+    assert!(missing.is_err());
+}
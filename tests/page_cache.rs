@@ -0,0 +1,115 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, PhysicalAddress, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::thread;
+use std::time::Duration;
+
+/// With `cache_size` set, pages read once are served from memory afterwards.
+#[test]
+fn pages_are_served_from_the_cache() {
+    let lime_path = tmp_path("page_cache.lime");
+    write_lime(&lime_path, &[(0x1000, &[0x11; 0x1000])]);
+    let open = |extra_args: Args| {
+        create_connector(&ConnectorArgs::new(lime_path.to_str(), extra_args, None)).unwrap()
+    };
+    let mut cached = open(Args::new().insert("cache_size", "10kb"));
+    let mut direct = open(Args::new());
+
+    let mut buff = [0u8; 2];
+    cached
+        .phys_read_into(PhysicalAddress::from(0x1000), &mut buff)
+        .unwrap();
+
+    let mut file = OpenOptions::new().write(true).open(&lime_path).unwrap();
+    file.seek(SeekFrom::Start(0x20)).unwrap();
+    file.write_all(&[0xff; 2]).unwrap();
+    drop(file);
+
+    let mut from_cache = [0u8; 2];
+    cached
+        .phys_read_into(PhysicalAddress::from(0x1000), &mut from_cache)
+        .unwrap();
+    let mut from_file = [0u8; 2];
+    direct
+        .phys_read_into(PhysicalAddress::from(0x1000), &mut from_file)
+        .unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(buff, [0x11, 0x11]);
+    assert_eq!(from_cache, [0x11, 0x11]);
+    assert_eq!(from_file, [0xff, 0xff]);
+}
+
+/// `cached=true` alone puts the default cache in front of the dump.
+#[test]
+fn cached_enables_the_default_cache() {
+    let lime_path = tmp_path("page_cache_default.lime");
+    write_lime(&lime_path, &[(0x1000, &[0x22; 0x1000])]);
+    let mut cached = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("cached", "true"),
+        None,
+    ))
+    .unwrap();
+
+    let mut buff = [0u8; 2];
+    cached
+        .phys_read_into(PhysicalAddress::from(0x1000), &mut buff)
+        .unwrap();
+
+    let mut file = OpenOptions::new().write(true).open(&lime_path).unwrap();
+    file.seek(SeekFrom::Start(0x20)).unwrap();
+    file.write_all(&[0xff; 2]).unwrap();
+    drop(file);
+
+    let mut from_cache = [0u8; 2];
+    cached
+        .phys_read_into(PhysicalAddress::from(0x1000), &mut from_cache)
+        .unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(buff, [0x22, 0x22]);
+    assert_eq!(from_cache, [0x22, 0x22]);
+}
+
+/// With `cache_time` set, pages are read again from the dump once they expired.
+#[test]
+fn pages_expire_after_cache_time() {
+    let lime_path = tmp_path("page_cache_time.lime");
+    write_lime(&lime_path, &[(0x1000, &[0x33; 0x1000])]);
+    let mut cached = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new()
+            .insert("cached", "true")
+            .insert("cache_time", "1"),
+        None,
+    ))
+    .unwrap();
+
+    let mut buff = [0u8; 2];
+    cached
+        .phys_read_into(PhysicalAddress::from(0x1000), &mut buff)
+        .unwrap();
+
+    let mut file = OpenOptions::new().write(true).open(&lime_path).unwrap();
+    file.seek(SeekFrom::Start(0x20)).unwrap();
+    file.write_all(&[0xff; 2]).unwrap();
+    drop(file);
+    thread::sleep(Duration::from_millis(50));
+
+    let mut expired = [0u8; 2];
+    cached
+        .phys_read_into(PhysicalAddress::from(0x1000), &mut expired)
+        .unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(buff, [0x33, 0x33]);
+    assert_eq!(expired, [0xff, 0xff]);
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs};
+use memflow_lime::create_connector;
+use std::fs;
+
+/// Zero, constant and high entropy pages are told apart, partially mapped pages counting
+/// once.
+#[test]
+fn pages_are_classified() {
+    let lime_path = tmp_path("page_stats.lime");
+    let mut data = vec![0u8; 0x1000];
+    data.extend_from_slice(&[0xcc; 0x1000]);
+    // Every byte value repeated, 8 bits of entropy per byte.
+    data.extend((0..0x2000).map(|i| (i * 0x9d % 0x100) as u8));
+    write_lime(&lime_path, &[(0x1000, &data), (0x10800, &[0x11; 0x800])]);
+
+    let mut mem =
+        create_connector(&ConnectorArgs::new(lime_path.to_str(), Args::new(), None)).unwrap();
+    let stats = mem.page_stats(0x1000).unwrap();
+    let invalid = mem.page_stats(0).is_err();
+
+    fs::remove_file(&lime_path).unwrap();
+    assert_eq!(stats.pages, 5);
+    assert_eq!(stats.zero_pages, 1);
+    assert_eq!(stats.constant_pages, 2);
+    assert_eq!(stats.histogram, [3, 0, 0, 0, 0, 0, 0, 2]);
+    assert_eq!(stats.high_entropy, [(0x3000, 0x4fff)]);
+    assert_eq!(stats.populated_ratio(), 0.4);
+    assert!(invalid);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, PhysicalAddress, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+
+/// The parallel scan maps the same ranges as the serial one, even when the data of a range
+/// contains a header magic number.
+#[test]
+fn parallel_scan_matches_serial_scan() {
+    let lime_path = tmp_path("parallel_scan.lime");
+    let mut decoy = vec![0x77u8; 0x2000];
+    decoy[0x100..0x104].copy_from_slice(b"EMiL");
+    write_lime(
+        &lime_path,
+        &[
+            (0x1000, &decoy),
+            (0x8000, &[0x88; 0x300]),
+            (0x20000, &[0x99; 0x10]),
+        ],
+    );
+
+    let mut serial =
+        create_connector(&ConnectorArgs::new(lime_path.to_str(), Args::new(), None)).unwrap();
+    let mut parallel = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("scan", "parallel"),
+        None,
+    ))
+    .unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    for addr in [0x1100, 0x2fff, 0x8000, 0x82ff, 0x2000f] {
+        let mut expected = [0u8; 1];
+        let mut actual = [0u8; 1];
+        serial
+            .phys_read_into(PhysicalAddress::from(addr), &mut expected)
+            .unwrap();
+        parallel
+            .phys_read_into(PhysicalAddress::from(addr), &mut actual)
+            .unwrap();
+        assert_eq!(actual, expected);
+    }
+    assert_eq!(
+        parallel.metadata().max_address,
+        serial.metadata().max_address
+    );
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{header_bytes, tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, PhysicalAddress, PhysicalMemory};
+use memflow_lime::{create_connector, read_layout, LimeReserved, ParseMode};
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+
+fn connector_args(lime_path: &std::path::Path, mode: &str) -> ConnectorArgs {
+    ConnectorArgs::new(lime_path.to_str(), Args::new().insert("mode", mode), None)
+}
+
+/// Non-zero reserved bytes and a short final range are refused in strict mode only.
+#[test]
+fn lenient_mode_tolerates_deviations() {
+    let lime_path = tmp_path("parse_mode.lime");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x100]), (0x2000, &[0x22; 0x100])],
+    );
+    let mut file = OpenOptions::new().write(true).open(&lime_path).unwrap();
+    file.seek(SeekFrom::Start(24)).unwrap();
+    file.write_all(&[0xff; 8]).unwrap();
+    file.set_len(2 * 0x20 + 0x180).unwrap();
+    drop(file);
+
+    let strict = create_connector(&connector_args(&lime_path, "strict"));
+    let mut lenient = create_connector(&connector_args(&lime_path, "lenient")).unwrap();
+    let mut buff = [0u8; 2];
+    lenient
+        .phys_read_into(PhysicalAddress::from(0x207f), &mut buff)
+        .unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert!(strict.is_err());
+    assert_eq!(buff, [0x22, 0]);
+}
+
+/// Headers with a version unknown to the connector are mapped in strict mode only when
+/// `allow_unknown_version` is set.
+#[test]
+fn unknown_versions_are_mapped_on_request() {
+    let lime_path = tmp_path("unknown_version.lime");
+    write_lime(&lime_path, &[(0x1000, &[0x33; 0x100])]);
+    let mut file = OpenOptions::new().write(true).open(&lime_path).unwrap();
+    file.seek(SeekFrom::Start(4)).unwrap();
+    file.write_all(&3u32.to_le_bytes()).unwrap();
+    drop(file);
+
+    let refused = create_connector(&connector_args(&lime_path, "strict"));
+    let mut allowed = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("allow_unknown_version", "true"),
+        None,
+    ))
+    .unwrap();
+    let mut buff = [0u8; 2];
+    allowed
+        .phys_read_into(PhysicalAddress::from(0x1000), &mut buff)
+        .unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert!(refused.is_err());
+    assert_eq!(buff, [0x33, 0x33]);
+}
+
+/// Reserved bytes stashed by patched modules are reported with the segment they belong to.
+#[test]
+fn reserved_bytes_are_exposed() {
+    let lime_path = tmp_path("reserved.lime");
+    let mut dump = header_bytes(1, 0x1000, 0x10ff, [0; 8]);
+    dump.extend_from_slice(&[0x11; 0x100]);
+    dump.extend_from_slice(&header_bytes(1, 0x2000, 0x20ff, *b"vendor01"));
+    dump.extend_from_slice(&[0x22; 0x100]);
+    fs::write(&lime_path, dump).unwrap();
+
+    let strict = read_layout(&lime_path, ParseMode::Strict);
+    let lenient = read_layout(&lime_path, ParseMode::Lenient).unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert!(strict.is_err());
+    assert_eq!(lenient.segments.len(), 2);
+    assert_eq!(
+        lenient.reserved,
+        [LimeReserved {
+            segment: 1,
+            reserved: *b"vendor01",
+        }]
+    );
+}
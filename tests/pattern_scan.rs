@@ -0,0 +1,38 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{umem, Args, ConnectorArgs};
+use memflow_lime::create_connector;
+use std::fs;
+
+/// Matches are reported in file order, including the ones straddling two reads or two
+/// contiguous ranges, and the gaps are skipped.
+#[test]
+fn pattern_is_found_in_mapped_ranges() {
+    let lime_path = tmp_path("pattern_scan.lime");
+    let mut high = vec![0u8; 0x100100];
+    high[0x10..0x14].copy_from_slice(&[0x4c, 0x69, 0x4d, 0x45]);
+    // Straddles the first and the second read of the range.
+    high[0xffffe..0x100002].copy_from_slice(&[0x4c, 0x69, 0x00, 0x45]);
+    let mut low = vec![0u8; 0x1000];
+    low[0xffe..].copy_from_slice(&[0x4c, 0x69]);
+    let mut next = vec![0u8; 0x1000];
+    next[..2].copy_from_slice(&[0xff, 0x45]);
+    write_lime(
+        &lime_path,
+        &[(0x100000, &high), (0x1000, &low), (0x2000, &next)],
+    );
+
+    let mut mem =
+        create_connector(&ConnectorArgs::new(lime_path.to_str(), Args::new(), None)).unwrap();
+    let matches = mem
+        .scan("4c 69 ?? 45")
+        .unwrap()
+        .map(|addr| addr.to_umem())
+        .collect::<Vec<umem>>();
+    let invalid = mem.scan("4c 6").is_err();
+
+    fs::remove_file(&lime_path).unwrap();
+    assert_eq!(matches, [0x100010, 0x1ffffe, 0x1ffe]);
+    assert!(invalid);
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs};
+use memflow_lime::{convert_to_raw_with_progress, create_connector_with_progress, sha256_digest};
+use std::fs;
+
+/// Opening reports the hashing and the scan, each phase ending at the size of the file.
+#[test]
+fn open_reports_progress() {
+    let lime_path = tmp_path("progress_open.lime");
+    let sidecar_path = tmp_path("progress_open.lime.sha256");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x100]), (0x2000, &[0x22; 0x100])],
+    );
+    let digest = sha256_digest(&lime_path).unwrap();
+    let hex = digest
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    fs::write(&sidecar_path, hex).unwrap();
+
+    let mut reports = Vec::new();
+    let args = ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("verify", "auto"),
+        None,
+    );
+    let connector =
+        create_connector_with_progress(&args, |done, total| reports.push((done, total)));
+
+    fs::remove_file(&lime_path).unwrap();
+    fs::remove_file(&sidecar_path).unwrap();
+
+    assert!(connector.is_ok());
+    let file_len = 2 * 0x120;
+    assert!(reports
+        .iter()
+        .all(|&(done, total)| total == file_len && done <= total));
+    assert_eq!(
+        reports
+            .iter()
+            .filter(|&&(done, _)| done == file_len)
+            .count(),
+        3
+    );
+    assert!(reports.contains(&(0x120, file_len)));
+}
+
+/// The conversion reports the bytes of memory copied.
+#[test]
+fn conversion_reports_progress() {
+    let lime_path = tmp_path("progress_convert.lime");
+    let raw_path = tmp_path("progress_convert.raw");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x100]), (0x2000, &[0x22; 0x80])],
+    );
+
+    let mut last = (0, 0);
+    convert_to_raw_with_progress(&lime_path, &raw_path, |done, total| last = (done, total))
+        .unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+    fs::remove_file(&raw_path).unwrap();
+
+    assert_eq!(last, (0x180, 0x180));
+}
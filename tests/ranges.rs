@@ -0,0 +1,46 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+
+/// Only the parts of the ranges within the windows are mapped.
+#[test]
+fn only_windows_are_mapped() {
+    let lime_path = tmp_path("ranges.lime");
+    write_lime(
+        &lime_path,
+        &[
+            (0x1000, &[0x11; 0x3000]),
+            (0x10000, &[0x22; 0x1000]),
+            (0x100000, &[0x33; 0x2000]),
+        ],
+    );
+
+    let mut mem = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("ranges", "0x2000-0x2fff,0x101000-"),
+        None,
+    ))
+    .unwrap();
+    let ranges = mem
+        .mem_map()
+        .iter()
+        .map(|mapping| (mapping.base().to_umem(), mapping.output().1))
+        .collect::<Vec<_>>();
+    let mut kept = [0u8; 0x10];
+    mem.phys_view()
+        .read_raw_into(0x101ff0.into(), &mut kept)
+        .unwrap();
+    let skipped = mem.phys_view().read_raw_into(0x1000.into(), &mut [0; 0x10]);
+    let outside = mem
+        .phys_view()
+        .read_raw_into(0x10000.into(), &mut [0; 0x10]);
+
+    fs::remove_file(&lime_path).unwrap();
+    assert_eq!(ranges, [(0x2000, 0x1000), (0x101000, 0x1000)]);
+    assert_eq!(kept, [0x33; 0x10]);
+    assert!(skipped.is_err());
+    assert!(outside.is_err());
+}
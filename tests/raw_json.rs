@@ -0,0 +1,112 @@
+#![cfg(feature = "rawjson")]
+
+mod common;
+
+use common::tmp_path;
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+
+/// Runs of a padded raw image are mapped at the offsets equal to their address, runs of a
+/// packed one at the offsets listed.
+#[test]
+fn raw_images_are_mapped_from_run_lists() {
+    let padded_json = tmp_path("padded.raw.json");
+    let padded_raw = tmp_path("padded.raw");
+    let packed_json = tmp_path("packed.json");
+    let packed_raw = tmp_path("packed.bin");
+    let mut padded = vec![0u8; 0x5000];
+    padded[0x1000..0x2000].fill(0x11);
+    padded[0x4000..0x5000].fill(0x44);
+    fs::write(&padded_raw, padded).unwrap();
+    fs::write(
+        &padded_json,
+        r#"{"runs": [{"start": "0x1000", "length": 4096}, {"start": 16384, "length": 4096}]}"#,
+    )
+    .unwrap();
+    fs::write(&packed_raw, [[0x11; 0x1000], [0x44; 0x1000]].concat()).unwrap();
+    fs::write(
+        &packed_json,
+        r#"{"image": "memflow_lime_packed.bin", "Runs": [
+            {"BaseAddress": "0x1000", "Size": "0x1000", "FileOffset": 0},
+            {"BaseAddress": "0x100000", "Size": "0x1000", "FileOffset": "0x1000"}
+        ]}"#,
+    )
+    .unwrap();
+
+    let read = |json: &std::path::Path, addr: u64| {
+        let mut connector =
+            create_connector(&ConnectorArgs::new(json.to_str(), Args::new(), None)).unwrap();
+        let mut buff = [0u8; 0x10];
+        let gap = connector
+            .phys_view()
+            .read_raw_into(0x3000.into(), &mut buff)
+            .is_err();
+        connector
+            .phys_view()
+            .read_raw_into(addr.into(), &mut buff)
+            .unwrap();
+        (buff, gap)
+    };
+    let padded = read(&padded_json, 0x4ff0);
+    let packed = read(&packed_json, 0x100ff0);
+    fs::write(
+        &packed_json,
+        r#"{"image": "memflow_lime_packed.bin", "runs": [{"start": 0, "length": 8193}]}"#,
+    )
+    .unwrap();
+    let past_end = create_connector(&ConnectorArgs::new(packed_json.to_str(), Args::new(), None));
+
+    for path in [padded_json, padded_raw, packed_json, packed_raw] {
+        fs::remove_file(path).unwrap();
+    }
+    assert_eq!(padded, ([0x44; 0x10], true));
+    assert_eq!(packed, ([0x44; 0x10], true));
+    assert!(past_end.is_err());
+}
+
+/// A `pmemsave` image is mapped from the RAM regions of its e820-style memory map, relative
+/// to the address the save started at.
+#[test]
+fn pmemsave_images_are_mapped_from_memory_maps() {
+    let map = tmp_path("guest.pmem.json");
+    let image = tmp_path("guest.pmem");
+    let mut guest = vec![0u8; 0x4000];
+    guest[..0x1000].fill(0x11);
+    guest[0x1000..0x2000].fill(0xee);
+    guest[0x2000..].fill(0x33);
+    fs::write(&image, guest).unwrap();
+    fs::write(
+        &map,
+        r#"{"image_base": "0x10000", "regions": [
+            {"addr": "0x10000", "end": "0x10fff", "type": "usable"},
+            {"addr": "0x11000", "end": "0x11fff", "type": "reserved"},
+            {"addr": "0x12000", "size": "0x2000", "type": 1}
+        ]}"#,
+    )
+    .unwrap();
+
+    let mut connector =
+        create_connector(&ConnectorArgs::new(map.to_str(), Args::new(), None)).unwrap();
+    let mut low = [0u8; 0x10];
+    connector
+        .phys_view()
+        .read_raw_into(0x10ff0.into(), &mut low)
+        .unwrap();
+    let mut high = [0u8; 0x10];
+    connector
+        .phys_view()
+        .read_raw_into(0x13ff0.into(), &mut high)
+        .unwrap();
+    let reserved = connector
+        .phys_view()
+        .read_raw_into(0x11000.into(), &mut [0u8; 0x10])
+        .is_err();
+
+    fs::remove_file(map).unwrap();
+    fs::remove_file(image).unwrap();
+
+    assert_eq!(low, [0x11; 0x10]);
+    assert_eq!(high, [0x33; 0x10]);
+    assert!(reserved);
+}
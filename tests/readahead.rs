@@ -0,0 +1,48 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, PhysicalAddress, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+
+/// Sequential reads across ranges return the same data with and without `readahead`.
+#[test]
+fn sequential_reads_match_direct_reads() {
+    let lime_path = tmp_path("readahead.lime");
+    let first = (0..0x3000).map(|i| i as u8).collect::<Vec<_>>();
+    let second = (0..0x3000).map(|i| (i / 3) as u8).collect::<Vec<_>>();
+    write_lime(&lime_path, &[(0x1000, &first), (0x4000, &second)]);
+    let open = |extra_args: Args| {
+        create_connector(&ConnectorArgs::new(lime_path.to_str(), extra_args, None)).unwrap()
+    };
+    let mut prefetched = open(Args::new().insert("readahead", "2k"));
+    let mut direct = open(Args::new());
+
+    let mut reads = Vec::new();
+    for addr in (0x1000..0x7000).step_by(0x100) {
+        let mut ahead = [0u8; 0x100];
+        let mut plain = [0u8; 0x100];
+        prefetched
+            .phys_read_into(PhysicalAddress::from(addr), &mut ahead)
+            .unwrap();
+        direct
+            .phys_read_into(PhysicalAddress::from(addr), &mut plain)
+            .unwrap();
+        reads.push((addr, ahead, plain));
+    }
+
+    let lazy = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new()
+            .insert("parse", "lazy")
+            .insert("readahead", "8M"),
+        None,
+    ));
+
+    fs::remove_file(&lime_path).unwrap();
+
+    for (addr, ahead, plain) in reads {
+        assert_eq!(ahead, plain, "read at {:#x}", addr);
+    }
+    assert!(lazy.is_err());
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+
+/// The ranges are mapped at their rebased addresses, only the ones in a window moving.
+#[test]
+fn ranges_are_rebased() {
+    let lime_path = tmp_path("rebase.lime");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x1000]), (0x10000, &[0x22; 0x2000])],
+    );
+
+    let mut mem = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("rebase", "0x11000-0x11fff:+0x80000000"),
+        None,
+    ))
+    .unwrap();
+    let ranges = mem
+        .mem_map()
+        .iter()
+        .map(|mapping| (mapping.base().to_umem(), mapping.output().1))
+        .collect::<Vec<_>>();
+    let mut moved = [0u8; 0x10];
+    mem.phys_view()
+        .read_raw_into(0x8001_1000u64.into(), &mut moved)
+        .unwrap();
+    let vacated = mem
+        .phys_view()
+        .read_raw_into(0x11000.into(), &mut [0; 0x10]);
+
+    let colliding = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("rebase", "0x10000-0x11fff:-0xf000"),
+        None,
+    ));
+
+    fs::remove_file(&lime_path).unwrap();
+    assert_eq!(
+        ranges,
+        [(0x1000, 0x1000), (0x10000, 0x1000), (0x8001_1000, 0x1000)]
+    );
+    assert_eq!(moved, [0x22; 0x10]);
+    assert!(vacated.is_err());
+    assert!(colliding.is_err());
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use common::{header_bytes, tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalAddress, PhysicalMemory};
+use memflow_lime::{create_connector, recover_layout, LimeSkipped, ParseMode};
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+
+/// Write a dump of three ranges whose second header is corrupt, the data of the second range
+/// holding a magic number with a bogus header.
+fn write_damaged(path: &std::path::Path) {
+    let mut decoy = vec![0x22; 0x1000];
+    decoy[0x100..0x120].copy_from_slice(&header_bytes(9, 0x0, 0xfff, [0; 8]));
+    write_lime(
+        path,
+        &[
+            (0x1000, &[0x11; 0x1000]),
+            (0x2000, &decoy),
+            (0x3000, &[0x33; 0x1000]),
+        ],
+    );
+    let mut file = OpenOptions::new().write(true).open(path).unwrap();
+    file.seek(SeekFrom::Start(0x1020)).unwrap();
+    file.write_all(&[0xde, 0xad, 0xbe, 0xef]).unwrap();
+}
+
+/// With `recover=true` the ranges following a corrupt header are mapped again.
+#[test]
+fn corrupt_header_is_skipped() {
+    let lime_path = tmp_path("recover.lime");
+    write_damaged(&lime_path);
+    let open = |extra_args: Args| {
+        create_connector(&ConnectorArgs::new(lime_path.to_str(), extra_args, None))
+    };
+    let refused = open(Args::new()).is_err();
+    let mut mem = open(Args::new().insert("recover", "true")).unwrap();
+    let layout = recover_layout(&lime_path, ParseMode::Strict).unwrap();
+
+    let mut first = [0u8; 2];
+    mem.phys_read_into(PhysicalAddress::from(0x1000), &mut first)
+        .unwrap();
+    let mut last = [0u8; 2];
+    mem.phys_read_into(PhysicalAddress::from(0x3000), &mut last)
+        .unwrap();
+    let mut lost = [0u8; 2];
+    let lost_read = mem.phys_view().read_raw_into(0x2000.into(), &mut lost);
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert!(refused);
+    assert_eq!(first, [0x11, 0x11]);
+    assert_eq!(last, [0x33, 0x33]);
+    assert!(lost_read.is_err());
+    assert_eq!(
+        layout.skipped,
+        vec![LimeSkipped {
+            offset: 0x1020,
+            len: 0x1020,
+        }]
+    );
+    assert_eq!(layout.segments.len(), 2);
+    assert_eq!(layout.anomalies.len(), 1);
+}
+
+/// A corrupt last header skips the rest of the file.
+#[test]
+fn corrupt_last_header_skips_to_the_end() {
+    let lime_path = tmp_path("recover_last.lime");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x1000]), (0x2000, &[0x22; 0x1000])],
+    );
+    let mut file = OpenOptions::new().write(true).open(&lime_path).unwrap();
+    file.seek(SeekFrom::Start(0x1020)).unwrap();
+    file.write_all(&[0; 4]).unwrap();
+    drop(file);
+
+    let layout = recover_layout(&lime_path, ParseMode::Strict).unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(layout.segments.len(), 1);
+    assert_eq!(
+        layout.skipped,
+        vec![LimeSkipped {
+            offset: 0x1020,
+            len: 0x1020,
+        }]
+    );
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::{header_bytes, tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+fn open(lime_path: &std::path::Path, reload: bool) -> memflow_lime::LimeMemory {
+    create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("reload", if reload { "true" } else { "false" }),
+        None,
+    ))
+    .unwrap()
+}
+
+/// With `reload=true`, ranges appended to the dump after it was opened are read, including
+/// the rest of a range that was cut short.
+#[test]
+fn appended_ranges_are_picked_up() {
+    let lime_path = tmp_path("reload.lime");
+    write_lime(&lime_path, &[(0x1000, &[0x11; 0x1000])]);
+    let mut file = OpenOptions::new().append(true).open(&lime_path).unwrap();
+    file.write_all(&header_bytes(1, 0x3000, 0x3fff, [0; 8]))
+        .unwrap();
+    file.write_all(&[0x33; 0x800]).unwrap();
+
+    let mut reloading = open(&lime_path, true);
+    let mut fixed = open(&lime_path, false);
+    let mut buff = [0u8; 0x10];
+    reloading
+        .phys_view()
+        .read_raw_into(0x1000.into(), &mut buff)
+        .unwrap();
+    assert_eq!(buff, [0x11; 0x10]);
+    assert!(reloading
+        .phys_view()
+        .read_raw_into(0x3f00.into(), &mut buff)
+        .is_err());
+
+    file.write_all(&[0x33; 0x800]).unwrap();
+    file.write_all(&header_bytes(1, 0x5000, 0x5fff, [0; 8]))
+        .unwrap();
+    file.write_all(&[0x55; 0x1000]).unwrap();
+    drop(file);
+
+    for (addr, value) in [(0x3f00_u64, 0x33), (0x5000, 0x55)] {
+        reloading
+            .phys_view()
+            .read_raw_into(addr.into(), &mut buff)
+            .unwrap();
+        assert_eq!(buff, [value; 0x10]);
+        assert!(fixed
+            .phys_view()
+            .read_raw_into(addr.into(), &mut buff)
+            .is_err());
+    }
+
+    fs::remove_file(lime_path).unwrap();
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::{header_bytes, tmp_path, write_lime};
+use memflow_lime::{report, LimeGap, ParseMode};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+/// The report lists the segments, the gaps between them and the problems met while parsing.
+#[test]
+fn report_summarizes_the_dump() {
+    let lime_path = tmp_path("report.lime");
+    write_lime(
+        &lime_path,
+        &[
+            (0x3000, &[0x33; 0x1000]),
+            (0x1000, &[0x11; 0x1000]),
+            (0x3800, &[0x38; 0x1000]),
+        ],
+    );
+    let mut file = OpenOptions::new().append(true).open(&lime_path).unwrap();
+    file.write_all(&header_bytes(1, 0x8000, 0x8fff, [1; 8]))
+        .unwrap();
+    file.write_all(&[0x80; 0x800]).unwrap();
+    drop(file);
+
+    let strict = report(&lime_path, ParseMode::Strict);
+    let report = report(&lime_path, ParseMode::Lenient).unwrap();
+    fs::remove_file(&lime_path).unwrap();
+
+    assert!(strict.is_err());
+    assert_eq!(report.file_size, 0x3800 + 4 * 0x20);
+    assert_eq!(report.segments.len(), 4);
+    assert_eq!(report.segments[1].s_addr, 0x1000);
+    assert_eq!(
+        report.gaps,
+        [
+            LimeGap {
+                s_addr: 0x2000,
+                e_addr: 0x2fff
+            },
+            LimeGap {
+                s_addr: 0x4800,
+                e_addr: 0x7fff
+            }
+        ]
+    );
+    assert_eq!(report.total_ram, 0x1000 + 0x1800 + 0x800);
+    assert_eq!(report.truncation.unwrap().s_addr, 0x8800);
+    assert_eq!(report.digest, None);
+    assert_eq!(report.anomalies.len(), 3, "{:?}", report.anomalies);
+    assert!(report.anomalies[2].contains("overlap"));
+
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["total_ram"], 0x3000);
+        assert_eq!(json["gaps"][0]["s_addr"], 0x2000);
+        assert_eq!(json["segments"][0]["file_offset"], 0x20);
+        assert_eq!(json["anomalies"].as_array().unwrap().len(), 3);
+    }
+}
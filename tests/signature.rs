@@ -0,0 +1,70 @@
+#![cfg(feature = "signature")]
+
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs};
+use memflow_lime::{create_connector, verify_signature};
+use std::fs;
+
+const PUBKEY: &str = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+/// Prehashed signature of the four bytes `test`
+const SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1556193335\tfile:test
+y/rUw2y8/hOUYjZU71eHp/Wo1KZ40fGy2VJEDl34XMJM+TX48Ss/17u3IvIfbVR1FkZZSNCisQbuQY+bHwhEBg==
+";
+
+#[test]
+fn signature_is_verified() {
+    let signed_path = tmp_path("signature_signed");
+    let signature_path = tmp_path("signature_signed.minisig");
+    let pubkey_path = tmp_path("signature.pub");
+    fs::write(&signed_path, b"test").unwrap();
+    fs::write(&signature_path, SIGNATURE).unwrap();
+    fs::write(
+        &pubkey_path,
+        format!("untrusted comment: minisign public key\n{}\n", PUBKEY),
+    )
+    .unwrap();
+
+    let with_base64 = verify_signature(&signed_path, &signature_path, PUBKEY);
+    let with_file = verify_signature(&signed_path, &signature_path, pubkey_path.to_str().unwrap());
+    fs::write(&signed_path, b"tesT").unwrap();
+    let tampered = verify_signature(&signed_path, &signature_path, PUBKEY);
+
+    fs::remove_file(&signed_path).unwrap();
+    fs::remove_file(&signature_path).unwrap();
+    fs::remove_file(&pubkey_path).unwrap();
+
+    assert!(with_base64.is_ok());
+    assert!(with_file.is_ok());
+    assert!(tampered.is_err());
+}
+
+#[test]
+fn mismatching_signature_is_refused_in_strict_mode() {
+    let lime_path = tmp_path("signature.lime");
+    let signature_path = tmp_path("signature.lime.minisig");
+    write_lime(&lime_path, &[(0x1000, &[0xaa; 0x100])]);
+    fs::write(&signature_path, SIGNATURE).unwrap();
+
+    let open = |mode: &str| {
+        create_connector(&ConnectorArgs::new(
+            lime_path.to_str(),
+            Args::new()
+                .insert("signature", "auto")
+                .insert("pubkey", PUBKEY)
+                .insert("mode", mode),
+            None,
+        ))
+    };
+    let strict = open("strict");
+    let lenient = open("lenient");
+
+    fs::remove_file(&lime_path).unwrap();
+    fs::remove_file(&signature_path).unwrap();
+
+    assert!(strict.is_err());
+    assert!(lenient.is_ok());
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+
+/// A dump split in parts cutting through a header and a range reads like the whole dump,
+/// whether the parts are listed or matched by a pattern.
+#[test]
+fn split_dump_reads_like_whole_dump() {
+    let dir = tmp_path("split");
+    fs::create_dir_all(&dir).unwrap();
+    let whole_path = dir.join("dump.lime");
+    let data = (0..0x3000u32).map(|i| i as u8).collect::<Vec<_>>();
+    write_lime(
+        &whole_path,
+        &[(0x1000, &data[..0x1000]), (0x4000, &data[0x1000..])],
+    );
+    let whole = fs::read(&whole_path).unwrap();
+    for (index, part) in [&whole[..0x1010], &whole[0x1010..0x2800], &whole[0x2800..]]
+        .into_iter()
+        .enumerate()
+    {
+        fs::write(dir.join(format!("dump.lime.{:03}", index)), part).unwrap();
+    }
+
+    let pattern = dir.join("dump.lime.*").to_str().unwrap().to_owned();
+    let list = (0..3)
+        .map(|index| {
+            let path = dir.join(format!("dump.lime.{:03}", index));
+            path.to_str().unwrap().to_owned()
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut connectors = [
+        (whole_path.to_str().unwrap(), Args::new()),
+        (pattern.as_str(), Args::new()),
+        (list.as_str(), Args::new()),
+        (pattern.as_str(), Args::new().insert("parse", "lazy")),
+    ]
+    .map(|(target, args)| create_connector(&ConnectorArgs::new(Some(target), args, None)).unwrap());
+    let refused = create_connector(&ConnectorArgs::new(
+        Some(&pattern),
+        Args::new().insert("verify", "auto"),
+        None,
+    ));
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    for connector in &mut connectors {
+        let (mut a, mut b) = ([0u8; 0x20], [0u8; 0x100]);
+        connector
+            .phys_view()
+            .read_raw_into(0x1ff0.into(), &mut a[..0x10])
+            .unwrap();
+        connector
+            .phys_view()
+            .read_raw_into(0x57c0.into(), &mut b)
+            .unwrap();
+        assert_eq!(&a[..0x10], &data[0xff0..0x1000]);
+        assert_eq!(&b[..], &data[0x27c0..0x28c0]);
+        assert_eq!(connector.metadata().max_address, 0x5fff.into());
+    }
+    assert!(refused.is_err());
+}
+
+/// A file whose name holds a comma is opened whole rather than as a list of parts.
+#[test]
+fn comma_in_file_name_is_no_list() {
+    let lime_path = tmp_path("split_a,b.lime");
+    write_lime(&lime_path, &[(0x1000, &[7; 0x100])]);
+
+    let mut connector =
+        create_connector(&ConnectorArgs::new(lime_path.to_str(), Args::new(), None)).unwrap();
+    let mut buff = [0u8; 0x10];
+    connector
+        .phys_view()
+        .read_raw_into(0x1000.into(), &mut buff)
+        .unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(buff, [7; 0x10]);
+}
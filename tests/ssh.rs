@@ -0,0 +1,105 @@
+#![cfg(all(unix, feature = "ssh"))]
+
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::{create_connector, create_connector_with_progress};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+/// SSH client running the remote command locally, logging it to `log`.
+fn write_fake_ssh(path: &Path, log: &Path) {
+    let script = format!(
+        "#!/bin/sh\nfor cmd; do :; done\necho \"$cmd\" >> '{}'\nexec sh -c \"$cmd\"\n",
+        log.display()
+    );
+    fs::write(path, script).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+/// A remote dump is spooled with progress, resumed from an interrupted acquisition and
+/// opened from the spool file, which is then reused without connecting.
+#[test]
+fn remote_dumps_are_spooled_and_resumed() {
+    let dir = tmp_path("ssh");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let (remote, client, log) = (
+        dir.join("remote.lime"),
+        dir.join("fake-ssh"),
+        dir.join("ssh.log"),
+    );
+    let spool = dir.join("spool").join("dump.lime");
+    write_lime(
+        &remote,
+        &[(0x1000, &[0x11; 0x1000]), (0x2000, &[0x22; 0x100])],
+    );
+    write_fake_ssh(&client, &log);
+    let len = fs::metadata(&remote).unwrap().len();
+    fs::create_dir_all(spool.parent().unwrap()).unwrap();
+    fs::write(
+        dir.join("spool").join("dump.lime.part"),
+        &fs::read(&remote).unwrap()[..0x800],
+    )
+    .unwrap();
+
+    let args = ConnectorArgs::new(
+        Some(&format!("ssh://ir@localhost:2222{}", remote.display())),
+        Args::new()
+            .insert("ssh_bin", client.to_str().unwrap())
+            .insert("spool", spool.to_str().unwrap()),
+        None,
+    );
+    let mut reports = Vec::new();
+    let mut connector =
+        create_connector_with_progress(&args, |done, total| reports.push((done, total))).unwrap();
+    let mut data = [0u8; 4];
+    connector
+        .phys_view()
+        .read_raw_into(0x1ffe.into(), &mut data)
+        .unwrap();
+    let reused = create_connector(&args).is_ok();
+    let commands = fs::read_to_string(&log).unwrap();
+    let spooled = fs::read(&spool).unwrap() == fs::read(&remote).unwrap();
+
+    let failing = ConnectorArgs::new(
+        Some("ssh://localhost"),
+        Args::new()
+            .insert("ssh_bin", client.to_str().unwrap())
+            .insert("ssh_cmd", "head -c 16 /dev/zero; exit 3")
+            .insert("spool", dir.join("failed.lime").to_str().unwrap()),
+        None,
+    );
+    let failed = create_connector(&failing).is_err();
+    let kept = fs::metadata(dir.join("failed.lime.part")).map(|m| m.len());
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(data, [0x11, 0x11, 0x22, 0x22]);
+    assert!(spooled);
+    assert!(reused);
+    assert_eq!(commands.lines().count(), 1);
+    assert!(commands.contains("tail -c +2049"));
+    assert_eq!(reports.first(), Some(&(0x800, len)));
+    assert!(reports.contains(&(len, len)));
+    assert!(failed);
+    assert_eq!(kept.unwrap(), 16);
+}
+
+/// Arguments of remote acquisitions need an `ssh://` target.
+#[test]
+fn ssh_arguments_need_an_ssh_target() {
+    let args = ConnectorArgs::new(
+        Some("dump.lime"),
+        Args::new().insert("ssh_cmd", "cat /dev/crash"),
+        None,
+    );
+    assert!(create_connector(&args).is_err());
+    let both = ConnectorArgs::new(
+        Some("ssh://host/dump.lime"),
+        Args::new().insert("ssh_cmd", "cat /dev/crash"),
+        None,
+    );
+    assert!(create_connector(&both).is_err());
+}
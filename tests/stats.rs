@@ -0,0 +1,56 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalAddress, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+
+/// Reads, file IO, page cache lookups and gap fills are counted, clones sharing the counters.
+#[test]
+fn io_is_counted() {
+    let lime_path = tmp_path("stats.lime");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x1000]), (0x3000, &[0x33; 0x1000])],
+    );
+    let mut connector = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new()
+            .insert("cache_size", "10kb")
+            .insert("fill_gaps", "zero"),
+        None,
+    ))
+    .unwrap();
+    let stats = connector.stats();
+
+    let mut buff = [0u8; 0x10];
+    for addr in [0x1000, 0x1008, 0x2000] {
+        connector
+            .phys_read_into(PhysicalAddress::from(addr), &mut buff)
+            .unwrap();
+    }
+    let (read_ops, bytes_read) = (stats.read_ops(), stats.bytes_read());
+    let (file_read_ops, file_bytes_read) = (stats.file_read_ops(), stats.file_bytes_read());
+    let (cache_hits, cache_misses) = (stats.cache_hits(), stats.cache_misses());
+    let gap_fills = stats.gap_fills();
+
+    stats.reset();
+    let mut clone = connector.clone();
+    let mut view = clone.phys_view();
+    let (mut a, mut b) = ([0u8; 0x10], [0u8; 0x10]);
+    let mut batcher = view.batcher();
+    batcher
+        .read_raw_into(0x3000.into(), &mut a)
+        .read_raw_into(0x3010.into(), &mut b);
+    batcher.commit_rw().unwrap();
+    drop(batcher);
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!((read_ops, bytes_read), (3, 0x30));
+    assert_eq!((file_read_ops, file_bytes_read), (1, 0x1000));
+    assert_eq!((cache_hits, cache_misses), (1, 2));
+    assert_eq!(gap_fills, 1);
+    assert_eq!(stats.read_ops(), 2);
+    assert_eq!(stats.cache_misses(), 1);
+}
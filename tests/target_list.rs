@@ -0,0 +1,32 @@
+use memflow_lime::{list_dumps, target_list, EVIDENCE_DIR_ENV};
+use std::fs;
+
+/// Only the `.lime` and `.lime.gz` files of the evidence directory are listed, sorted.
+#[test]
+fn dumps_of_the_evidence_directory_are_listed() {
+    let dir = std::env::temp_dir().join("memflow_lime_evidence");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("nested.lime")).unwrap();
+    for name in ["b.lime", "a.LIME.gz", "notes.txt", "c.lime.sha256"] {
+        fs::write(dir.join(name), b"").unwrap();
+    }
+
+    let dumps = list_dumps(&dir).unwrap();
+    std::env::set_var(EVIDENCE_DIR_ENV, &dir);
+    let targets = target_list().unwrap();
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(dumps, vec![dir.join("a.LIME.gz"), dir.join("b.lime")]);
+    assert_eq!(
+        targets
+            .iter()
+            .map(|target| target.name.to_string())
+            .collect::<Vec<_>>(),
+        vec![
+            dir.join("a.LIME.gz").to_string_lossy(),
+            dir.join("b.lime").to_string_lossy()
+        ]
+    );
+    assert!(list_dumps(dir.join("missing")).is_err());
+}
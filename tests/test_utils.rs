@@ -0,0 +1,54 @@
+#![cfg(feature = "test-utils")]
+
+mod common;
+
+use common::tmp_path;
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::test_utils::LimeFixture;
+use memflow_lime::{create_connector, read_layout, ParseMode};
+use std::fs;
+
+/// Fixtures with gaps, adjacent ranges, big-endian headers and truncation are read back as
+/// built.
+#[test]
+fn fixtures_are_valid_dumps() {
+    let lime_path = tmp_path("fixture.lime");
+    let corrupt_path = tmp_path("fixture_corrupt.lime");
+    LimeFixture::new()
+        .range(0x1000, &[0x11; 0x1000])
+        .range(0x2000, &[0x22; 0x1000])
+        .filled(0x8000, 0x1000, 0x88)
+        .truncate(0x800)
+        .write_to(&lime_path)
+        .unwrap();
+    LimeFixture::new()
+        .big_endian()
+        .filled(0x1000, 0x10, 0x11)
+        .header(2, 0x2000, 0x200f, [0; 8])
+        .bytes(&[0x22; 0x10])
+        .write_to(&corrupt_path)
+        .unwrap();
+
+    let layout = read_layout(&lime_path, ParseMode::Strict).unwrap();
+    let mut connector =
+        create_connector(&ConnectorArgs::new(lime_path.to_str(), Args::new(), None)).unwrap();
+    let mut buff = [0u8; 0x20];
+    connector
+        .phys_view()
+        .read_raw_into(0x1ff0.into(), &mut buff)
+        .unwrap();
+    let corrupt = [ParseMode::Strict, ParseMode::Lenient].map(|mode| {
+        read_layout(&corrupt_path, mode)
+            .map(|layout| layout.segments.len())
+            .ok()
+    });
+
+    fs::remove_file(lime_path).unwrap();
+    fs::remove_file(corrupt_path).unwrap();
+    assert_eq!(layout.segments.len(), 3);
+    assert_eq!(layout.segments[2].e_addr, 0x87ff);
+    assert!(layout.truncation.is_some());
+    assert_eq!(buff[..0x10], [0x11; 0x10]);
+    assert_eq!(buff[0x10..], [0x22; 0x10]);
+    assert_eq!(corrupt, [None, Some(2)]);
+}
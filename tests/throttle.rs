@@ -0,0 +1,40 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::v1::*;
+use memflow_lime::create_connector;
+use std::fs;
+use std::time::{Duration, Instant};
+
+#[test]
+fn reads_are_slowed_down() {
+    let lime_path = tmp_path("throttle.lime");
+    write_lime(&lime_path, &[(0x1000, &[0x11; 0x4000])]);
+    let open = |extra_args: Args| {
+        create_connector(&ConnectorArgs::new(lime_path.to_str(), extra_args, None)).unwrap()
+    };
+
+    let mut latency = open(Args::new().insert("throttle", "50ms"));
+    let mut bandwidth = open(Args::new().insert("bandwidth", "100kBps"));
+    let mut buff = [0u8; 0x2800];
+
+    let start = Instant::now();
+    latency
+        .phys_view()
+        .read_raw_into(0x1000.into(), &mut buff[..0x10])
+        .unwrap();
+    let latency_elapsed = start.elapsed();
+
+    let start = Instant::now();
+    bandwidth
+        .phys_view()
+        .read_raw_into(0x1000.into(), &mut buff)
+        .unwrap();
+    let bandwidth_elapsed = start.elapsed();
+
+    fs::remove_file(&lime_path).unwrap();
+    assert!(latency_elapsed >= Duration::from_millis(50));
+    // 10240 bytes at 100 kB/s
+    assert!(bandwidth_elapsed >= Duration::from_micros(102_400));
+    assert_eq!(buff, [0x11; 0x2800]);
+}
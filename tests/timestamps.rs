@@ -0,0 +1,110 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow_lime::{
+    default_timestamps_sidecar, read_timed_segments, LimeSegment, LimeTimestamps, LimeWriter,
+};
+use std::fs;
+use std::io::Cursor;
+use std::time::{Duration, SystemTime};
+
+/// The writer records when each section was acquired, and the segment enumeration returns the
+/// times stored next to the dump.
+#[test]
+fn sections_carry_their_acquisition_times() {
+    let before = SystemTime::now();
+    let mut writer = LimeWriter::new(Vec::new())
+        .with_segment_size(0x1000)
+        .unwrap()
+        .with_timestamps();
+    writer.write_range(0x1000, &[0x11; 0x800]).unwrap();
+    writer
+        .write_range_from(0x8000, 0x2000, &mut Cursor::new(vec![0x22; 0x2000]))
+        .unwrap();
+    let times = writer.timestamps().unwrap().clone();
+    let lime = writer.finish().unwrap();
+    let after = SystemTime::now();
+
+    let lime_path = tmp_path("timestamps.lime");
+    let sidecar = default_timestamps_sidecar(&lime_path);
+    fs::write(&lime_path, &lime).unwrap();
+    let untimed = read_timed_segments(&lime_path).unwrap();
+    times.write(&sidecar).unwrap();
+    let reread = LimeTimestamps::read(&sidecar).unwrap();
+    let timed = read_timed_segments(&lime_path).unwrap();
+    fs::remove_file(&lime_path).unwrap();
+    fs::remove_file(&sidecar).unwrap();
+
+    let segment = |s_addr, e_addr, file_offset| LimeSegment {
+        s_addr,
+        e_addr,
+        file_offset,
+    };
+    let segments = [
+        segment(0x1000, 0x17ff, 0x20),
+        segment(0x8000, 0x8fff, 0x840),
+        segment(0x9000, 0x9fff, 0x1860),
+    ];
+    assert_eq!(
+        times
+            .sections
+            .iter()
+            .map(|time| segment(time.s_addr, time.e_addr, time.file_offset))
+            .collect::<Vec<_>>(),
+        segments
+    );
+    for pair in times.sections.windows(2) {
+        assert!(pair[0].started <= pair[0].finished);
+        assert!(pair[0].finished <= pair[1].started);
+    }
+    assert!(times.sections[0].started >= before);
+    assert!(times.sections[2].finished <= after);
+    assert!(times.smear().unwrap() <= after.duration_since(before).unwrap());
+
+    // The sidecar keeps the times to the nanosecond.
+    assert_eq!(reread, times);
+    assert!(untimed.iter().all(|(_, time)| time.is_none()));
+    assert_eq!(
+        timed,
+        segments
+            .into_iter()
+            .zip(times.sections.iter().copied().map(Some))
+            .collect::<Vec<_>>()
+    );
+}
+
+/// Writers not asked for the times record none, and sections missing from the sidecar have
+/// no time.
+#[test]
+fn times_are_opt_in() {
+    let mut writer = LimeWriter::new(Vec::new());
+    writer.write_range(0x1000, &[1; 0x100]).unwrap();
+    assert!(writer.timestamps().is_none());
+
+    let lime_path = tmp_path("timestamps_partial.lime");
+    let sidecar = default_timestamps_sidecar(&lime_path);
+    write_lime(&lime_path, &[(0x1000, &[1; 0x100]), (0x4000, &[2; 0x100])]);
+    fs::write(
+        &sidecar,
+        "# timestamps unix_ns\n0x4000-0x40ff  0x140  1000000000  1500000000\n",
+    )
+    .unwrap();
+    let timed = read_timed_segments(&lime_path).unwrap();
+    fs::write(
+        &sidecar,
+        "# timestamps unix_ns\n0x4000-0x40ff  0x140  2  1\n",
+    )
+    .unwrap();
+    let backwards = read_timed_segments(&lime_path);
+    fs::remove_file(&lime_path).unwrap();
+    fs::remove_file(&sidecar).unwrap();
+
+    assert!(timed[0].1.is_none());
+    let time = timed[1].1.unwrap();
+    assert_eq!(time.duration(), Duration::from_millis(500));
+    assert_eq!(
+        time.started,
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1)
+    );
+    assert!(backwards.is_err());
+}
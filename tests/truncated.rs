@@ -0,0 +1,44 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{ConnectorArgs, PhysicalAddress, PhysicalMemory};
+use memflow_lime::{create_connector, read_layout, LimeTruncation, ParseMode};
+use std::fs::{self, OpenOptions};
+
+/// A dump cut in the middle of its last range is mapped up to the last byte present.
+#[test]
+fn truncated_dump_is_partially_mapped() {
+    let lime_path = tmp_path("truncated.lime");
+    write_lime(&lime_path, &[(0x1000, &[1; 0x100]), (0x4000, &[2; 0x100])]);
+    OpenOptions::new()
+        .write(true)
+        .open(&lime_path)
+        .unwrap()
+        .set_len(2 * 0x20 + 0x140)
+        .unwrap();
+
+    let layout = read_layout(&lime_path, ParseMode::Strict).unwrap();
+    let args = ConnectorArgs::new(lime_path.to_str(), Default::default(), None);
+    let mut con = create_connector(&args).unwrap();
+    let mut buff = [0xffu8; 2];
+    con.phys_read_into(PhysicalAddress::from(0x403f), &mut buff)
+        .unwrap();
+
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(
+        layout.truncation,
+        Some(LimeTruncation {
+            s_addr: 0x4040,
+            e_addr: 0x40ff,
+        })
+    );
+    assert_eq!(layout.segments[1].e_addr, 0x403f);
+    assert_eq!(buff, [2, 0]);
+}
+
+#[test]
+fn complete_dump_is_not_truncated() {
+    let layout = read_layout("./tests/deb-x86_64-slice.lime", ParseMode::Strict).unwrap();
+    assert_eq!(layout.truncation, None);
+}
@@ -0,0 +1,31 @@
+use memflow::prelude::{
+    ConnectorArgs, Error, ErrorKind, ErrorOrigin, PhysicalAddress, PhysicalMemory,
+};
+use memflow_lime::create_connector;
+
+fn connector_args(target: &str) -> ConnectorArgs {
+    ConnectorArgs::new(Some(target), Default::default(), None)
+}
+
+/// `file://` targets open the dump at their path, other schemes are refused.
+#[test]
+fn file_uris_are_opened() {
+    let lime_path = std::fs::canonicalize("./tests/deb-x86_64-slice.lime").unwrap();
+    let uri = format!("file://{}", lime_path.display()).replace(' ', "%20");
+
+    let mut from_uri = create_connector(&connector_args(&uri)).unwrap();
+    let mut from_path = create_connector(&connector_args(lime_path.to_str().unwrap())).unwrap();
+    let (mut a, mut b) = ([0u8; 16], [0u8; 16]);
+    from_uri
+        .phys_read_into(PhysicalAddress::from(0x1000), &mut a)
+        .unwrap();
+    from_path
+        .phys_read_into(PhysicalAddress::from(0x1000), &mut b)
+        .unwrap();
+
+    assert_eq!(a, b);
+    assert_eq!(
+        create_connector(&connector_args("http://example.com/dump.lime")).err(),
+        Some(Error(ErrorOrigin::Connector, ErrorKind::NotSupported))
+    );
+}
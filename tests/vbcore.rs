@@ -0,0 +1,119 @@
+#![cfg(feature = "vbcore")]
+
+mod common;
+
+use common::tmp_path;
+use memflow::prelude::{Args, ConnectorArgs, ErrorKind, MemoryView, PhysicalMemory};
+use memflow_lime::{create_connector, vbox_core_info, VBoxCoreRange};
+use std::fs;
+
+/// Note padded to 8 bytes like VirtualBox writes them.
+fn note(name: &[u8], n_type: u32, desc: &[u8]) -> Vec<u8> {
+    let mut note = Vec::new();
+    note.extend_from_slice(&(name.len() as u32 + 1).to_le_bytes());
+    note.extend_from_slice(&(desc.len() as u32).to_le_bytes());
+    note.extend_from_slice(&n_type.to_le_bytes());
+    note.extend_from_slice(name);
+    note.push(0);
+    note.resize(note.len().next_multiple_of(8), 0);
+    note.extend_from_slice(desc);
+    note.resize(note.len().next_multiple_of(8), 0);
+    note
+}
+
+/// Core of a 2-CPU guest written by VirtualBox 7.0.12r159484, with 0x2000 bytes of RAM at 0,
+/// an MMIO range without data and 0x1000 bytes of RAM at 0x100000.
+fn write_vbcore(path: &std::path::Path, with_descriptor: bool) {
+    let mut notes = Vec::new();
+    if with_descriptor {
+        let mut descriptor = Vec::new();
+        for field in [0xc01a_c0de_u32, 0x1_0006, 0x18, 0x0700_000c, 159_484, 2] {
+            descriptor.extend_from_slice(&field.to_le_bytes());
+        }
+        notes.extend(note(b"VBCORE", 0xb00, &descriptor));
+    }
+    notes.extend(note(b"VBCPU", 0xb01, &[0xcc; 0x20]));
+    notes.extend(note(b"VBCPU", 0xb01, &[0xcc; 0x20]));
+
+    // (p_type, offset, paddr, filesz, memsz)
+    let phdrs = [
+        (4u32, 0x1000u64, 0u64, notes.len() as u64, 0u64),
+        (1, 0x2000, 0x0, 0x2000, 0x2000),
+        (1, 0x4000, 0xe000_0000, 0, 0x10_0000),
+        (1, 0x4000, 0x10_0000, 0x1000, 0x1000),
+    ];
+    let mut file = vec![0u8; 0x40];
+    file[..4].copy_from_slice(b"\x7fELF");
+    file[4] = 2;
+    file[5] = 1;
+    file[6] = 1;
+    file[0x10..0x12].copy_from_slice(&4u16.to_le_bytes());
+    file[0x20..0x28].copy_from_slice(&0x40u64.to_le_bytes());
+    file[0x36..0x38].copy_from_slice(&0x38u16.to_le_bytes());
+    file[0x38..0x3a].copy_from_slice(&(phdrs.len() as u16).to_le_bytes());
+    for (p_type, offset, paddr, filesz, memsz) in phdrs {
+        let mut phdr = [0u8; 0x38];
+        phdr[..4].copy_from_slice(&p_type.to_le_bytes());
+        phdr[8..0x10].copy_from_slice(&offset.to_le_bytes());
+        phdr[0x10..0x18].copy_from_slice(&paddr.to_le_bytes());
+        phdr[0x18..0x20].copy_from_slice(&paddr.to_le_bytes());
+        phdr[0x20..0x28].copy_from_slice(&filesz.to_le_bytes());
+        phdr[0x28..0x30].copy_from_slice(&memsz.to_le_bytes());
+        file.extend_from_slice(&phdr);
+    }
+    file.resize(0x1000, 0);
+    file.extend(notes);
+    file.resize(0x2000, 0);
+    file.extend([0x11; 0x2000]);
+    file.extend([0x33; 0x1000]);
+    fs::write(path, file).unwrap();
+}
+
+/// The guest-physical ranges holding data are mapped at their address, the MMIO ranges are
+/// listed but left unmapped.
+#[test]
+fn guest_ram_is_mapped() {
+    let path = tmp_path("vbcore.elf");
+    write_vbcore(&path, true);
+
+    let core = vbox_core_info(&path).unwrap();
+    let vbcore = || Args::new().insert("format", "vbcore");
+    let mut connector =
+        create_connector(&ConnectorArgs::new(path.to_str(), Args::new(), None)).unwrap();
+    let (mut low, mut high) = ([0u8; 4], [0u8; 4]);
+    connector
+        .phys_view()
+        .read_raw_into(0x1ffc.into(), &mut low)
+        .unwrap();
+    connector
+        .phys_view()
+        .read_raw_into(0x10_0000.into(), &mut high)
+        .unwrap();
+    let mmio = connector
+        .phys_view()
+        .read_raw_into(0xe000_0000u64.into(), &mut [0u8; 4]);
+    let real_size = connector.metadata().real_size;
+    write_vbcore(&path, false);
+    let not_vbox = create_connector(&ConnectorArgs::new(path.to_str(), vbcore(), None));
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(core.vbox_version, (7, 0, 12));
+    assert_eq!(core.vbox_revision, 159_484);
+    assert_eq!(core.format_version, 0x1_0006);
+    assert_eq!(core.cpus, 2);
+    assert_eq!(
+        core.ranges[1],
+        VBoxCoreRange {
+            phys_addr: 0xe000_0000,
+            file_offset: 0x4000,
+            size: 0x10_0000,
+            file_size: 0,
+        }
+    );
+    assert_eq!(core.ranges.len(), 3);
+    assert_eq!(low, [0x11; 4]);
+    assert_eq!(high, [0x33; 4]);
+    assert!(mmio.is_err());
+    assert_eq!(real_size, 0x3000);
+    assert_eq!(not_vbox.err().unwrap().1, ErrorKind::NotSupported);
+}
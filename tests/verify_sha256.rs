@@ -0,0 +1,36 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs};
+use memflow_lime::{create_connector, default_sha256_sidecar, sha256_digest};
+use std::fs;
+
+#[test]
+fn verify_auto_sidecar() {
+    let lime_path = tmp_path("verify_auto.lime");
+    write_lime(&lime_path, &[(0x1000, &[0xaa; 0x100])]);
+    let digest = sha256_digest(&lime_path).unwrap();
+    let hex = digest
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    let sidecar = default_sha256_sidecar(&lime_path);
+
+    let args = ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("verify", "auto"),
+        None,
+    );
+
+    fs::write(&sidecar, format!("{}  verify_auto.lime\n", hex)).unwrap();
+    let matching = create_connector(&args);
+
+    fs::write(&sidecar, "0".repeat(64)).unwrap();
+    let mismatching = create_connector(&args);
+
+    fs::remove_file(&lime_path).unwrap();
+    fs::remove_file(&sidecar).unwrap();
+
+    assert!(matching.is_ok());
+    assert!(mismatching.is_err());
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow_lime::{volatility_layer, ParseMode, VolatilitySegment};
+use std::fs;
+
+/// The layer holds the segments Volatility3 builds, with their data offsets in the file.
+#[test]
+fn layer_matches_volatility_segments() {
+    let lime_path = tmp_path("volatility.lime");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x1000]), (0x10000, &[0x22; 0x2000])],
+    );
+
+    let layer = volatility_layer(&lime_path, ParseMode::Strict).unwrap();
+    let url = format!("file://{}", lime_path.canonicalize().unwrap().display());
+
+    fs::remove_file(&lime_path).unwrap();
+    assert_eq!(
+        layer.segments,
+        [
+            VolatilitySegment {
+                offset: 0x1000,
+                file_offset: 0x20,
+                length: 0x1000,
+                mapped_length: 0x1000
+            },
+            VolatilitySegment {
+                offset: 0x10000,
+                file_offset: 0x1040,
+                length: 0x2000,
+                mapped_length: 0x2000
+            }
+        ]
+    );
+    assert_eq!(layer.location, url);
+    assert_eq!(layer.as_volatility_args(), ["--single-location", &url]);
+    assert_eq!(
+        layer.to_json(),
+        format!(
+            "{{\n  \"class\": \"volatility3.framework.layers.lime.LimeLayer\",\n  \"location\": \
+             \"{}\",\n  \"segments\": [\n    [4096, 32, 4096, 4096],\n    [65536, 4160, 8192, \
+             8192]\n  ]\n}}\n",
+            url
+        )
+    );
+}
+
+/// Dumps with descending ranges are refused like Volatility3 does.
+#[test]
+fn descending_ranges_are_refused() {
+    let lime_path = tmp_path("volatility_descending.lime");
+    write_lime(
+        &lime_path,
+        &[(0x10000, &[0x22; 0x1000]), (0x1000, &[0x11; 0x1000])],
+    );
+
+    let layer = volatility_layer(&lime_path, ParseMode::Strict);
+
+    fs::remove_file(&lime_path).unwrap();
+    assert!(layer.is_err());
+}
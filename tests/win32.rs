@@ -0,0 +1,15 @@
+#![cfg(feature = "win32")]
+
+use memflow::prelude::ErrorKind;
+use memflow_lime::open_win32;
+
+/// Dumps without a Windows kernel are refused by the OS layer, missing dumps by the
+/// connector.
+#[test]
+fn linux_dump_has_no_windows_kernel() {
+    assert!(open_win32("tests/deb-x86_64-slice.lime").is_err());
+    assert_eq!(
+        open_win32("tests/missing.lime").err().unwrap().1,
+        ErrorKind::UnableToReadFile
+    );
+}
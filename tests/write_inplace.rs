@@ -0,0 +1,60 @@
+mod common;
+
+use common::{tmp_path, write_lime};
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::create_connector;
+use std::fs;
+
+/// Writes are refused by default and modify the dump itself with `write=inplace`.
+#[test]
+fn writes_modify_the_dump_in_place() {
+    let lime_path = tmp_path("write_inplace.lime");
+    write_lime(
+        &lime_path,
+        &[(0x1000, &[0x11; 0x1000]), (0x3000, &[0x33; 0x1000])],
+    );
+    let args = |write: Option<&str>| {
+        let args = match write {
+            Some(write) => Args::new().insert("write", write),
+            None => Args::new(),
+        };
+        ConnectorArgs::new(lime_path.to_str(), args, None)
+    };
+
+    let mut read_only = create_connector(&args(None)).unwrap();
+    assert!(read_only.metadata().readonly);
+    assert!(read_only
+        .phys_view()
+        .write_raw(0x1000.into(), b"secret")
+        .is_err());
+    assert!(create_connector(&args(Some("yes"))).is_err());
+
+    let mut writable = create_connector(&args(Some("inplace"))).unwrap();
+    assert!(!writable.metadata().readonly);
+    writable
+        .phys_view()
+        .write_raw(0x3ffc.into(), b"XXXX")
+        .unwrap();
+    writable
+        .phys_view()
+        .write_raw(0x1000.into(), &[0; 0x10])
+        .unwrap();
+    assert!(writable
+        .phys_view()
+        .write_raw(0x2000.into(), b"gap")
+        .is_err());
+
+    let mut buff = [0u8; 8];
+    writable
+        .phys_view()
+        .read_raw_into(0x3ff8.into(), &mut buff)
+        .unwrap();
+    let lime = fs::read(&lime_path).unwrap();
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(&buff, b"\x33\x33\x33\x33XXXX");
+    assert_eq!(lime.len(), 0x2040);
+    assert_eq!(lime[0x20..0x30], [0; 0x10]);
+    assert_eq!(lime[0x30], 0x11);
+    assert_eq!(&lime[0x203c..], b"XXXX");
+}
@@ -0,0 +1,278 @@
+mod common;
+
+use common::tmp_path;
+use memflow::prelude::{Args, ConnectorArgs, MemoryView, PhysicalMemory};
+use memflow_lime::{create_connector, read_segments, LimeAppender, LimeSegment, LimeWriter};
+use std::fs::{self, File};
+use std::io::{self, IoSlice, Write};
+
+/// Zero pages are split out of the ranges written, the rest reading back unchanged.
+#[test]
+fn zero_pages_are_skipped() {
+    let mut data = vec![0u8; 0x5000];
+    data[0x10..0x20].fill(0x11);
+    data[0x3000..0x4000].fill(0x33);
+    data[0x4fff] = 0x44;
+
+    let mut plain = LimeWriter::new(Vec::new());
+    plain.write_range(0x100000, &data).unwrap();
+    assert_eq!(plain.ranges_written(), 1);
+    assert_eq!(plain.finish().unwrap().len(), 0x20 + 0x5000);
+
+    let mut writer = LimeWriter::new(Vec::new())
+        .with_zero_skipping(0x1000)
+        .unwrap();
+    writer.write_range(0x100000, &data).unwrap();
+    writer.write_range(0x200800, &[0; 0x1000]).unwrap();
+    writer.write_range(0x300000, &[]).unwrap();
+    assert_eq!(writer.ranges_written(), 2);
+    assert_eq!(writer.bytes_written(), 0x3000);
+    assert_eq!(writer.bytes_skipped(), 0x3000);
+    let lime = writer.finish().unwrap();
+    assert!(LimeWriter::new(Vec::new()).with_zero_skipping(0).is_err());
+
+    let lime_path = tmp_path("writer.lime");
+    fs::write(&lime_path, lime).unwrap();
+    let segments = read_segments(&lime_path).unwrap();
+    let mut connector = create_connector(&ConnectorArgs::new(
+        lime_path.to_str(),
+        Args::new().insert("fill_gaps", "zero"),
+        None,
+    ))
+    .unwrap();
+    let mut read = vec![0u8; data.len()];
+    connector
+        .phys_view()
+        .read_raw_into(0x100000.into(), &mut read)
+        .unwrap();
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(
+        segments,
+        [
+            LimeSegment {
+                s_addr: 0x100000,
+                e_addr: 0x100fff,
+                file_offset: 0x20,
+            },
+            LimeSegment {
+                s_addr: 0x103000,
+                e_addr: 0x104fff,
+                file_offset: 0x1040,
+            },
+        ]
+    );
+    assert_eq!(read, data);
+}
+
+/// An interrupted dump is cut after its last complete range and continued from the address
+/// following it.
+#[test]
+fn interrupted_dump_is_resumed() {
+    let lime_path = tmp_path("writer_resume.lime");
+    let mut writer = LimeWriter::new(File::create(&lime_path).unwrap());
+    writer.write_range(0x1000, &[1; 0x1000]).unwrap();
+    writer.write_range(0x2000, &[2; 0x1000]).unwrap();
+    writer.write_range(0x3000, &[3; 0x1000]).unwrap();
+    writer.finish().unwrap();
+    // Cut in the middle of the data of the last range.
+    File::options()
+        .write(true)
+        .open(&lime_path)
+        .unwrap()
+        .set_len(3 * 0x20 + 0x2800)
+        .unwrap();
+
+    let mut writer = LimeWriter::resume(&lime_path).unwrap();
+    let next_address = writer.next_address();
+    let ranges_written = writer.ranges_written();
+    writer.write_range(0x3000, &[3; 0x1000]).unwrap();
+    writer.finish().unwrap();
+    let resumed = fs::read(&lime_path).unwrap();
+
+    // Cut in the middle of the header of the last range.
+    File::options()
+        .write(true)
+        .open(&lime_path)
+        .unwrap()
+        .set_len(2 * 0x20 + 0x2000 + 0x10)
+        .unwrap();
+    let cut_header = LimeWriter::resume(&lime_path).unwrap().next_address();
+    fs::write(&lime_path, []).unwrap();
+    let empty = LimeWriter::resume(&lime_path).unwrap().next_address();
+
+    fs::remove_file(&lime_path).unwrap();
+    let mut expected = LimeWriter::new(Vec::new());
+    for (i, s_addr) in [0x1000, 0x2000, 0x3000].into_iter().enumerate() {
+        expected
+            .write_range(s_addr, &[i as u8 + 1; 0x1000])
+            .unwrap();
+    }
+    assert_eq!(next_address, Some(0x3000));
+    assert_eq!(ranges_written, 2);
+    assert_eq!(resumed, expected.finish().unwrap());
+    assert_eq!(cut_header, Some(0x3000));
+    assert_eq!(empty, None);
+}
+
+/// Ranges accumulate in the file across openings, overlapping ones being refused.
+#[test]
+fn ranges_are_appended() {
+    let lime_path = tmp_path("writer_append.lime");
+    let _ = fs::remove_file(&lime_path);
+
+    let mut appender = LimeAppender::open(&lime_path).unwrap();
+    appender.append(0x1000, &[1; 0x1000]).unwrap();
+    appender.append(0x8000, &[3; 0x1000]).unwrap();
+    let overlap = appender.append(0x1800, &[0; 0x1000]).is_err();
+    drop(appender);
+
+    let mut appender = LimeAppender::open(&lime_path).unwrap();
+    let reopened = appender.ranges().to_vec();
+    appender.append(0x2000, &[2; 0x1000]).unwrap();
+    let ranges = appender.ranges().to_vec();
+    let bytes_written = appender.bytes_written();
+    drop(appender);
+    let segments = read_segments(&lime_path).unwrap();
+
+    File::options()
+        .write(true)
+        .open(&lime_path)
+        .unwrap()
+        .set_len(0x30)
+        .unwrap();
+    let truncated = LimeAppender::open(&lime_path).is_err();
+
+    fs::remove_file(&lime_path).unwrap();
+    assert!(overlap);
+    assert_eq!(reopened, [(0x1000, 0x1fff), (0x8000, 0x8fff)]);
+    assert_eq!(
+        ranges,
+        [(0x1000, 0x1fff), (0x2000, 0x2fff), (0x8000, 0x8fff)]
+    );
+    assert_eq!(bytes_written, 0x3000);
+    assert_eq!(
+        segments
+            .iter()
+            .map(|segment| segment.s_addr)
+            .collect::<Vec<_>>(),
+        [0x1000, 0x8000, 0x2000]
+    );
+    assert!(truncated);
+}
+
+/// Writer counting the calls it gets
+#[derive(Default)]
+struct CountingWriter {
+    out: Vec<u8>,
+    calls: usize,
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.calls += 1;
+        self.out.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.calls += 1;
+        self.out.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Ranges contiguous in physical memory are merged into one section written at once,
+/// whatever their order, and overlapping ranges are refused.
+#[test]
+fn batched_ranges_are_coalesced() {
+    let mut writer = LimeWriter::new(CountingWriter::default());
+    writer
+        .write_ranges([
+            (0x2000, &[2u8; 0x1000][..]),
+            (0x8000, &[8; 0x800][..]),
+            (0x1000, &[1; 0x1000][..]),
+            (0x3000, &[]),
+            (0x3000, &[3; 0x10][..]),
+        ])
+        .unwrap();
+    assert_eq!(writer.ranges_written(), 2);
+    assert_eq!(writer.next_address(), Some(0x8800));
+    let batched = writer.finish().unwrap();
+
+    let mut expected = LimeWriter::new(Vec::new());
+    let mut low = vec![1u8; 0x1000];
+    low.extend([2; 0x1000]);
+    low.extend([3; 0x10]);
+    expected.write_range(0x1000, &low).unwrap();
+    expected.write_range(0x8000, &[8; 0x800]).unwrap();
+    let overlap = LimeWriter::new(Vec::new())
+        .write_ranges([(0x1000, &[0u8; 0x10][..]), (0x100f, &[0; 0x10][..])])
+        .is_err();
+
+    assert_eq!(batched.calls, 2);
+    assert_eq!(batched.out, expected.finish().unwrap());
+    assert!(overlap);
+}
+
+/// Ranges are split at the multiples of the segment size, whether written from a buffer, a
+/// batch or a reader copied in chunks.
+#[test]
+fn ranges_are_split_into_segments() {
+    let data: Vec<u8> = (0..0x3800u32).map(|i| (i % 251) as u8).collect();
+
+    let mut buffered = LimeWriter::new(Vec::new())
+        .with_segment_size(0x1000)
+        .unwrap();
+    buffered.write_range(0x10800, &data).unwrap();
+    assert_eq!(buffered.ranges_written(), 4);
+    let buffered = buffered.finish().unwrap();
+
+    let mut batched = LimeWriter::new(Vec::new())
+        .with_segment_size(0x1000)
+        .unwrap();
+    batched
+        .write_ranges([(0x10800, &data[..0x900]), (0x11100, &data[0x900..])])
+        .unwrap();
+    let batched = batched.finish().unwrap();
+
+    let mut streamed = LimeWriter::new(Vec::new())
+        .with_segment_size(0x1000)
+        .unwrap()
+        .with_chunk_size(0x300)
+        .unwrap();
+    streamed
+        .write_range_from(0x10800, data.len() as u64, &mut &data[..])
+        .unwrap();
+    assert_eq!(streamed.bytes_written(), data.len() as u64);
+    assert_eq!(streamed.next_address(), Some(0x14000));
+    let streamed = streamed.finish().unwrap();
+    let short = LimeWriter::new(Vec::new())
+        .write_range_from(0x20000, 0x10, &mut &[0u8; 4][..])
+        .is_err();
+
+    let lime_path = tmp_path("writer_segments.lime");
+    fs::write(&lime_path, &streamed).unwrap();
+    let segments = read_segments(&lime_path).unwrap();
+    fs::remove_file(&lime_path).unwrap();
+
+    assert_eq!(
+        segments
+            .iter()
+            .map(|segment| (segment.s_addr, segment.e_addr))
+            .collect::<Vec<_>>(),
+        [
+            (0x10800, 0x10fff),
+            (0x11000, 0x11fff),
+            (0x12000, 0x12fff),
+            (0x13000, 0x13fff),
+        ]
+    );
+    assert_eq!(batched, buffered);
+    assert_eq!(streamed, buffered);
+    assert!(short);
+    assert!(LimeWriter::new(Vec::new()).with_segment_size(0).is_err());
+    assert!(LimeWriter::new(Vec::new()).with_chunk_size(0).is_err());
+}